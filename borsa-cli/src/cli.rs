@@ -0,0 +1,110 @@
+//! Command-line argument definitions.
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::output::Format;
+
+/// Query market data through the borsa router.
+#[derive(Debug, Parser)]
+#[command(name = "borsa", version, about)]
+pub struct Cli {
+    /// Configuration file (JSON, or TOML by extension) with connectors, middleware and router
+    /// settings.
+    #[arg(long, short, global = true, env = "BORSA_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Use the offline mock connector instead of the configured connectors.
+    #[arg(long, global = true)]
+    pub mock: bool,
+
+    /// Output format.
+    #[arg(long, short, global = true, value_enum, default_value_t = Format::Table)]
+    pub format: Format,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Fetch a quote for one symbol.
+    Quote {
+        symbol: String,
+        #[command(flatten)]
+        kind: KindArg,
+    },
+    /// Fetch quotes for several symbols.
+    Quotes {
+        #[arg(required = true)]
+        symbols: Vec<String>,
+        #[command(flatten)]
+        kind: KindArg,
+    },
+    /// Fetch historical candles for one symbol.
+    History {
+        symbol: String,
+        #[command(flatten)]
+        kind: KindArg,
+        #[command(flatten)]
+        window: WindowArgs,
+    },
+    /// Search for instruments.
+    Search {
+        query: String,
+        /// Restrict results to an asset kind (e.g. `equity`).
+        #[arg(long)]
+        kind: Option<String>,
+        /// Maximum number of results.
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Fetch the aggregated info snapshot for one symbol.
+    Info {
+        symbol: String,
+        #[command(flatten)]
+        kind: KindArg,
+    },
+    /// Fetch the option chain for one symbol.
+    OptionChain {
+        symbol: String,
+        #[command(flatten)]
+        kind: KindArg,
+        /// Expiration date (`YYYY-MM-DD` or unix seconds); nearest expiration when omitted.
+        #[arg(long)]
+        expiration: Option<String>,
+    },
+    /// Download history for several symbols.
+    Download {
+        #[arg(required = true)]
+        symbols: Vec<String>,
+        #[command(flatten)]
+        kind: KindArg,
+        #[command(flatten)]
+        window: WindowArgs,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct KindArg {
+    /// Asset kind of the symbols (default: equity).
+    #[arg(long = "kind")]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct WindowArgs {
+    /// Candle interval code (default: daily).
+    #[arg(long)]
+    pub interval: Option<String>,
+    /// Lookback range code (default: six months); ignored when `--start` is given.
+    #[arg(long)]
+    pub range: Option<String>,
+    /// Period start (`YYYY-MM-DD` or unix seconds).
+    #[arg(long, requires = "end")]
+    pub start: Option<String>,
+    /// Period end (`YYYY-MM-DD` or unix seconds).
+    #[arg(long, requires = "start")]
+    pub end: Option<String>,
+}
@@ -0,0 +1,238 @@
+//! Execution of subcommands against a configured `Borsa`.
+
+use borsa::Borsa;
+use borsa_core::{
+    AssetKind, BorsaError, HistoryRequest, Instrument, Interval, Range, SearchRequest,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::cli::{Command, KindArg, WindowArgs};
+use crate::output::{Output, field_rows, row, symbol_label};
+
+/// Run one subcommand and collect its output.
+///
+/// # Errors
+/// Returns argument validation errors and any error surfaced by the router.
+pub async fn run(borsa: &Borsa, command: Command) -> Result<Output, BorsaError> {
+    match command {
+        Command::Quote { symbol, kind } => {
+            let inst = instrument(&symbol, &kind)?;
+            let quote = borsa.quote(&inst).await?;
+            let payload = to_value(&quote)?;
+            Ok(Output {
+                rows: vec![row(Some(&symbol_label(&inst)), &payload)],
+                payload,
+                warnings: vec![],
+            })
+        }
+        Command::Quotes { symbols, kind } => {
+            let insts = instruments(&symbols, &kind)?;
+            let (quotes, failures) = borsa.quotes(&insts).await?;
+            let mut rows = Vec::with_capacity(quotes.len());
+            for q in &quotes {
+                rows.push(row(Some(&symbol_label(&q.instrument)), &to_value(q)?));
+            }
+            Ok(Output {
+                payload: to_value(&quotes)?,
+                rows,
+                warnings: failures
+                    .iter()
+                    .map(|(inst, e)| format!("{}: {e}", symbol_label(inst)))
+                    .collect(),
+            })
+        }
+        Command::History {
+            symbol,
+            kind,
+            window,
+        } => {
+            let inst = instrument(&symbol, &kind)?;
+            let response = borsa.history(&inst, history_request(&window)?).await?;
+            let payload = to_value(&response)?;
+            Ok(Output {
+                rows: candle_rows(None, &payload),
+                payload,
+                warnings: vec![],
+            })
+        }
+        Command::Search { query, kind, limit } => {
+            let mut builder = SearchRequest::builder(query);
+            if let Some(kind) = kind {
+                builder = builder.kind(parse_code("asset kind", &kind)?);
+            }
+            if let Some(limit) = limit {
+                builder = builder.limit(limit);
+            }
+            let request = builder
+                .build()
+                .map_err(|e| BorsaError::InvalidArg(format!("invalid search: {e}")))?;
+            let report = borsa.search(request).await?;
+            let mut rows = Vec::new();
+            for result in report.response.iter().flat_map(|r| &r.results) {
+                rows.push(row(
+                    Some(&symbol_label(&result.instrument)),
+                    &to_value(result)?,
+                ));
+            }
+            Ok(Output {
+                payload: to_value(&report)?,
+                rows,
+                warnings: report.warnings.iter().map(ToString::to_string).collect(),
+            })
+        }
+        Command::Info { symbol, kind } => {
+            let inst = instrument(&symbol, &kind)?;
+            let report = borsa.info(&inst).await?;
+            let info = to_value(&report.info)?;
+            Ok(Output {
+                payload: to_value(&report)?,
+                rows: field_rows(&info),
+                warnings: report.warnings.iter().map(ToString::to_string).collect(),
+            })
+        }
+        Command::OptionChain {
+            symbol,
+            kind,
+            expiration,
+        } => {
+            let inst = instrument(&symbol, &kind)?;
+            let date = expiration.as_deref().map(parse_timestamp).transpose()?;
+            let chain = borsa
+                .option_chain(&inst, date.map(|d| d.timestamp()))
+                .await?;
+            let payload = to_value(&chain)?;
+            Ok(Output {
+                rows: option_rows(&payload),
+                payload,
+                warnings: vec![],
+            })
+        }
+        Command::Download {
+            symbols,
+            kind,
+            window,
+        } => {
+            let insts = instruments(&symbols, &kind)?;
+            let mut builder = borsa
+                .download()
+                .instruments(&insts)?
+                .interval(interval(&window)?);
+            builder = match period(&window)? {
+                Some((start, end)) => builder.period(start.timestamp(), end.timestamp()),
+                None => builder.range(range(&window)?),
+            };
+            let report = builder.run().await?;
+            let mut rows = Vec::new();
+            for entry in report.response.iter().flat_map(|r| &r.entries) {
+                let label = symbol_label(&entry.instrument);
+                rows.extend(candle_rows(Some(&label), &to_value(&entry.history)?));
+            }
+            Ok(Output {
+                payload: to_value(&report)?,
+                rows,
+                warnings: report.warnings.iter().map(ToString::to_string).collect(),
+            })
+        }
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, BorsaError> {
+    serde_json::to_value(value).map_err(|e| BorsaError::Other(format!("serialize output: {e}")))
+}
+
+/// Parse an enum from its serialized code, tolerating case differences.
+fn parse_code<T: DeserializeOwned>(what: &str, s: &str) -> Result<T, BorsaError> {
+    [s.to_string(), s.to_lowercase(), s.to_uppercase()]
+        .into_iter()
+        .find_map(|candidate| serde_json::from_value(Value::String(candidate)).ok())
+        .ok_or_else(|| BorsaError::InvalidArg(format!("invalid {what} `{s}`")))
+}
+
+fn asset_kind(arg: &KindArg) -> Result<AssetKind, BorsaError> {
+    arg.kind
+        .as_deref()
+        .map_or(Ok(AssetKind::Equity), |k| parse_code("asset kind", k))
+}
+
+fn instrument(symbol: &str, kind: &KindArg) -> Result<Instrument, BorsaError> {
+    Instrument::from_symbol(symbol, asset_kind(kind)?)
+        .map_err(|e| BorsaError::InvalidArg(format!("invalid symbol `{symbol}`: {e}")))
+}
+
+fn instruments(symbols: &[String], kind: &KindArg) -> Result<Vec<Instrument>, BorsaError> {
+    symbols.iter().map(|s| instrument(s, kind)).collect()
+}
+
+fn interval(window: &WindowArgs) -> Result<Interval, BorsaError> {
+    window
+        .interval
+        .as_deref()
+        .map_or(Ok(Interval::D1), |i| parse_code("interval", i))
+}
+
+fn range(window: &WindowArgs) -> Result<Range, BorsaError> {
+    window
+        .range
+        .as_deref()
+        .map_or(Ok(Range::M6), |r| parse_code("range", r))
+}
+
+fn period(window: &WindowArgs) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>, BorsaError> {
+    match (&window.start, &window.end) {
+        (Some(start), Some(end)) => Ok(Some((parse_timestamp(start)?, parse_timestamp(end)?))),
+        _ => Ok(None),
+    }
+}
+
+fn history_request(window: &WindowArgs) -> Result<HistoryRequest, BorsaError> {
+    let interval = interval(window)?;
+    let req = match period(window)? {
+        Some((start, end)) => HistoryRequest::try_from_period(start, end, interval)?,
+        None => HistoryRequest::try_from_range(range(window)?, interval)?,
+    };
+    Ok(req)
+}
+
+/// Accept `YYYY-MM-DD` (midnight UTC) or unix seconds.
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, BorsaError> {
+    if let Ok(secs) = s.parse::<i64>() {
+        return DateTime::from_timestamp(secs, 0)
+            .ok_or_else(|| BorsaError::InvalidArg(format!("invalid timestamp `{s}`")));
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+        .ok_or_else(|| BorsaError::InvalidArg(format!("invalid date `{s}` (expected YYYY-MM-DD)")))
+}
+
+fn candle_rows(symbol: Option<&str>, history: &Value) -> Vec<Map<String, Value>> {
+    history
+        .get("candles")
+        .and_then(Value::as_array)
+        .map(|candles| candles.iter().map(|c| row(symbol, c)).collect())
+        .unwrap_or_default()
+}
+
+/// One row per contract; each array of contracts in the chain (calls, puts) becomes a `side`.
+fn option_rows(chain: &Value) -> Vec<Map<String, Value>> {
+    let Some(map) = chain.as_object() else {
+        return vec![];
+    };
+    let mut rows = Vec::new();
+    for (side, contracts) in map {
+        let Some(contracts) = contracts.as_array() else {
+            continue;
+        };
+        for contract in contracts {
+            let mut r = Map::new();
+            r.insert("side".into(), Value::String(side.clone()));
+            r.extend(row(None, contract));
+            rows.push(r);
+        }
+    }
+    rows
+}
@@ -0,0 +1,86 @@
+//! Configuration file loading and connector assembly.
+//!
+//! The file uses the same [`BorsaSetup`] schema as [`Borsa::from_config`], in JSON or TOML
+//! (chosen by extension):
+//!
+//! ```json
+//! {
+//!   "connectors": [
+//!     { "name": "borsa-yfinance", "middleware": { "layers": [
+//!       { "name": "CachingMiddleware", "config": { "default_ttl_ms": 60000 } }
+//!     ] } },
+//!     { "name": "borsa-mock" }
+//!   ],
+//!   "borsa": { "prefer_adjusted_history": true }
+//! }
+//! ```
+//!
+//! Connectors are looked up by name in a registry holding `borsa-mock` and, with the
+//! `yfinance` feature, `borsa-yfinance`.
+
+use std::path::Path;
+
+use borsa::{Borsa, ConfigFormat, ConnectorRegistry, parse_setup};
+use borsa_core::{BorsaError, BorsaSetup, ConnectorSpec};
+use borsa_types::MiddlewareStack;
+
+/// Registry name of the offline connector.
+const MOCK: &str = "borsa-mock";
+
+/// Read and parse a configuration file.
+///
+/// # Errors
+/// Returns an error if the file cannot be read, or `InvalidConfig` naming the offending value.
+pub fn load(path: &Path) -> Result<BorsaSetup, BorsaError> {
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        BorsaError::InvalidArg(format!("cannot read config {}: {e}", path.display()))
+    })?;
+    parse_setup(&text, ConfigFormat::from_path(path))
+}
+
+/// Build the orchestrator from an optional setup.
+///
+/// Without a setup, or with an empty connector list, the CLI uses `borsa-yfinance` (or
+/// `borsa-mock` when built without the `yfinance` feature). `force_mock` replaces the
+/// configured connectors with `borsa-mock`, keeping the first connector's middleware.
+///
+/// # Errors
+/// Returns `InvalidConfig` if a connector is not registered in this build, a middleware
+/// stack is invalid, or the orchestrator rejects the configuration.
+pub fn build(setup: Option<BorsaSetup>, force_mock: bool) -> Result<Borsa, BorsaError> {
+    let mut setup = setup.unwrap_or_else(|| BorsaSetup {
+        connectors: Vec::new(),
+        borsa: borsa_core::BorsaConfig::default(),
+        routing_policy: None,
+    });
+    if setup.connectors.is_empty() {
+        let name = if force_mock || !cfg!(feature = "yfinance") {
+            MOCK
+        } else {
+            "borsa-yfinance"
+        };
+        setup
+            .connectors
+            .push(spec(name, MiddlewareStack::default()));
+    } else if force_mock {
+        let middleware = setup.connectors.swap_remove(0).middleware;
+        setup.connectors = vec![spec(MOCK, middleware)];
+    }
+    Borsa::from_setup(&setup, &registry())
+}
+
+fn spec(name: &str, middleware: MiddlewareStack) -> ConnectorSpec {
+    ConnectorSpec {
+        name: name.to_string(),
+        config: serde_json::Value::Null,
+        middleware,
+    }
+}
+
+fn registry() -> ConnectorRegistry {
+    let mut registry = ConnectorRegistry::new();
+    borsa_mock::MockConnector::register(&mut registry);
+    #[cfg(feature = "yfinance")]
+    borsa_yfinance::YfConnector::register(&mut registry);
+    registry
+}
@@ -0,0 +1,54 @@
+//! `borsa` command-line interface.
+//!
+//! Maps subcommands onto the `Borsa` router (`quote`, `quotes`, `history`, `search`, `info`,
+//! `option_chain`, `download`) and prints results as a table, JSON or CSV. Connectors,
+//! middleware and router settings come from a JSON or TOML configuration file in the
+//! `Borsa::from_config` format; `--mock` runs fully offline against `borsa-mock`.
+
+mod cli;
+mod commands;
+mod config;
+mod output;
+
+use std::io::Write as _;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use crate::cli::Cli;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let setup = match cli.config.as_deref().map(config::load).transpose() {
+        Ok(setup) => setup,
+        Err(e) => return fail(&e),
+    };
+    let borsa = match config::build(setup, cli.mock) {
+        Ok(b) => b,
+        Err(e) => return fail(&e),
+    };
+
+    match commands::run(&borsa, cli.command).await {
+        Ok(output) => {
+            for warning in &output.warnings {
+                eprintln!("warning: {warning}");
+            }
+            let mut stdout = std::io::stdout().lock();
+            if stdout
+                .write_all(output.render(cli.format).as_bytes())
+                .is_err()
+            {
+                return ExitCode::FAILURE;
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => fail(&e),
+    }
+}
+
+fn fail(err: &dyn std::fmt::Display) -> ExitCode {
+    eprintln!("error: {err}");
+    ExitCode::FAILURE
+}
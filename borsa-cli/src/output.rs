@@ -0,0 +1,196 @@
+//! Rendering of command results as a table, JSON or CSV.
+
+use std::fmt::Write as _;
+
+use borsa_core::{IdentifierScheme, Instrument};
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+
+/// Output format selected with `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Format {
+    /// Aligned, human-readable columns.
+    #[default]
+    Table,
+    /// The full response payload as pretty-printed JSON.
+    Json,
+    /// One header line followed by one line per row.
+    Csv,
+}
+
+/// A command result: the full payload for JSON and flat rows for table/CSV.
+pub struct Output {
+    pub payload: Value,
+    pub rows: Vec<Map<String, Value>>,
+    /// Non-fatal problems (e.g. per-symbol failures), printed to stderr.
+    pub warnings: Vec<String>,
+}
+
+impl Output {
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Json => {
+                let mut s = serde_json::to_string_pretty(&self.payload).unwrap_or_default();
+                s.push('\n');
+                s
+            }
+            Format::Table => render_table(&self.rows),
+            Format::Csv => render_csv(&self.rows),
+        }
+    }
+}
+
+/// Display label for an instrument: the ticker for securities, the unique key otherwise.
+pub fn symbol_label(inst: &Instrument) -> String {
+    match inst.id() {
+        IdentifierScheme::Security(sec) => sec.symbol.as_str().to_string(),
+        IdentifierScheme::Prediction(_) => inst.id().unique_key().into_owned(),
+    }
+}
+
+/// Flatten a serialized record into one row, replacing a nested `instrument` by `symbol`.
+pub fn row(symbol: Option<&str>, value: &Value) -> Map<String, Value> {
+    let mut out = Map::new();
+    if let Some(symbol) = symbol {
+        out.insert("symbol".into(), Value::String(symbol.to_string()));
+    }
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                if key == "instrument" && symbol.is_some() {
+                    continue;
+                }
+                flatten_into(&mut out, key, v);
+            }
+        }
+        other => flatten_into(&mut out, "value", other),
+    }
+    out
+}
+
+/// Rows of `field`/`value` pairs, for single records with many fields.
+pub fn field_rows(value: &Value) -> Vec<Map<String, Value>> {
+    let mut flat = Map::new();
+    if let Value::Object(map) = value {
+        for (key, v) in map {
+            flatten_into(&mut flat, key, v);
+        }
+    }
+    flat.into_iter()
+        .filter(|(_, v)| !v.is_null())
+        .map(|(field, value)| {
+            let mut r = Map::new();
+            r.insert("field".into(), Value::String(field));
+            r.insert("value".into(), value);
+            r
+        })
+        .collect()
+}
+
+fn flatten_into(out: &mut Map<String, Value>, prefix: &str, value: &Value) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, v) in map {
+                flatten_into(out, &format!("{prefix}.{key}"), v);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other.clone());
+        }
+    }
+}
+
+fn columns(rows: &[Map<String, Value>]) -> Vec<String> {
+    let mut cols: Vec<String> = Vec::new();
+    for r in rows {
+        for key in r.keys() {
+            if !cols.contains(key) {
+                cols.push(key.clone());
+            }
+        }
+    }
+    // Identity columns first; the rest keep their first-seen order.
+    for lead in ["symbol", "ts", "field"].iter().rev() {
+        if let Some(pos) = cols.iter().position(|c| c == lead) {
+            let c = cols.remove(pos);
+            cols.insert(0, c);
+        }
+    }
+    cols
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn render_table(rows: &[Map<String, Value>]) -> String {
+    if rows.is_empty() {
+        return "(no rows)\n".to_string();
+    }
+    let cols = columns(rows);
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|r| cols.iter().map(|c| cell(r.get(c))).collect())
+        .collect();
+    let widths: Vec<usize> = cols
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            cells
+                .iter()
+                .map(|r| r[i].chars().count())
+                .chain(std::iter::once(c.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = String::new();
+    let line = |out: &mut String, values: &[String]| {
+        let padded: Vec<String> = values
+            .iter()
+            .zip(&widths)
+            .map(|(v, w)| format!("{v:<w$}"))
+            .collect();
+        let _ = writeln!(out, "{}", padded.join("  ").trim_end());
+    };
+    line(&mut out, &cols);
+    line(
+        &mut out,
+        &widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>(),
+    );
+    for r in &cells {
+        line(&mut out, r);
+    }
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn render_csv(rows: &[Map<String, Value>]) -> String {
+    let cols = columns(rows);
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{}",
+        cols.iter()
+            .map(|c| csv_field(c))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    for r in rows {
+        let line: Vec<String> = cols.iter().map(|c| csv_field(&cell(r.get(c)))).collect();
+        let _ = writeln!(out, "{}", line.join(","));
+    }
+    out
+}
@@ -0,0 +1,89 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn borsa() -> Command {
+    let mut cmd = Command::cargo_bin("borsa").unwrap();
+    cmd.env_remove("BORSA_CONFIG").arg("--mock");
+    cmd
+}
+
+#[test]
+fn quote_renders_a_table_row() {
+    borsa()
+        .args(["quote", "AAPL"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("symbol"))
+        .stdout(predicate::str::contains("AAPL"));
+}
+
+#[test]
+fn quotes_as_json_is_an_array_in_input_order() {
+    let out = borsa()
+        .args(["--format", "json", "quotes", "MSFT", "AAPL"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let quotes = v.as_array().expect("array of quotes");
+    assert_eq!(quotes.len(), 2);
+    assert!(quotes[0].to_string().contains("MSFT"));
+    assert!(quotes[1].to_string().contains("AAPL"));
+}
+
+#[test]
+fn history_as_csv_has_one_line_per_candle() {
+    let out = borsa()
+        .args(["--format", "csv", "history", "AAPL"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let text = String::from_utf8(out).unwrap();
+    let mut lines = text.lines();
+    let header = lines.next().expect("header line");
+    assert!(header.starts_with("ts,"), "unexpected header: {header}");
+    assert!(lines.count() > 0, "expected candle rows");
+}
+
+#[test]
+fn download_tags_rows_with_their_symbol() {
+    borsa()
+        .args(["--format", "csv", "download", "AAPL", "MSFT"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("symbol,ts,"))
+        .stdout(predicate::str::contains("\nMSFT,"));
+}
+
+#[test]
+fn config_file_is_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(
+        &path,
+        r#"{ "connectors": [{ "name": "borsa-mock" }], "borsa": { "prefer_adjusted_history": true } }"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("borsa")
+        .unwrap()
+        .arg("--config")
+        .arg(&path)
+        .args(["quote", "MSFT"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("MSFT"));
+}
+
+#[test]
+fn invalid_config_fails_with_a_message() {
+    borsa()
+        .args(["--config", "/nonexistent/borsa.json", "quote", "AAPL"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot read config"));
+}
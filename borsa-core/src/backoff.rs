@@ -0,0 +1,33 @@
+//! Backoff helpers shared by stream reconnects and retrying middleware.
+
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::BackoffConfig;
+
+/// Add up to `jitter_percent`% of random jitter on top of `base_ms`.
+#[must_use]
+pub fn jitter_wait(base_ms: u64, jitter_percent: u32) -> u64 {
+    let jitter_range = if jitter_percent == 0 {
+        1
+    } else {
+        std::cmp::max(1, (base_ms.saturating_mul(u64::from(jitter_percent))) / 100)
+    };
+    let mut rng = rand::rng();
+    base_ms + rng.random_range(0..jitter_range)
+}
+
+/// Jittered delay before retry number `attempt` (1-based).
+///
+/// Grows from `min_backoff_ms` by `factor` per attempt and is capped at `max_backoff_ms`
+/// before jitter is applied.
+#[must_use]
+pub fn backoff_delay(cfg: &BackoffConfig, attempt: u32) -> Duration {
+    let growth = u64::from(cfg.factor.max(1)).saturating_pow(attempt.saturating_sub(1));
+    let base = cfg
+        .min_backoff_ms
+        .saturating_mul(growth)
+        .min(cfg.max_backoff_ms.max(cfg.min_backoff_ms));
+    Duration::from_millis(jitter_wait(base, u32::from(cfg.jitter_percent)))
+}
@@ -41,6 +41,26 @@ pub trait HistoryProvider: Send + Sync {
     fn supported_history_intervals(&self, kind: AssetKind) -> &'static [Interval];
 }
 
+/// Return a `&'static` copy of `intervals` for connectors that discover their intervals at
+/// runtime, as [`HistoryProvider::supported_history_intervals`] requires.
+///
+/// Each distinct sequence is leaked once and shared by later calls, so memory grows only with
+/// the number of distinct sequences.
+///
+/// # Panics
+/// Panics if the internal mutex is poisoned.
+#[must_use]
+pub fn intern_intervals(intervals: &[Interval]) -> &'static [Interval] {
+    static INTERNED: std::sync::Mutex<Vec<&'static [Interval]>> = std::sync::Mutex::new(Vec::new());
+    let mut interned = INTERNED.lock().expect("mutex poisoned");
+    if let Some(found) = interned.iter().find(|set| **set == intervals) {
+        return found;
+    }
+    let leaked: &'static [Interval] = Box::leak(intervals.to_vec().into_boxed_slice());
+    interned.push(leaked);
+    leaked
+}
+
 /// Focused role trait for connectors that provide quotes.
 #[async_trait]
 pub trait QuoteProvider: Send + Sync {
@@ -48,6 +68,23 @@ pub trait QuoteProvider: Send + Sync {
     async fn quote(&self, instrument: &Instrument) -> Result<Quote, BorsaError>;
 }
 
+/// Focused role trait for connectors that can fetch quotes for many instruments in one request.
+///
+/// Implementations may omit instruments they could not resolve; the router falls back to
+/// per-instrument [`QuoteProvider::quote`] calls for anything missing from the result.
+#[async_trait]
+pub trait BatchQuoteProvider: Send + Sync {
+    /// Fetch point-in-time quotes for the given instruments in a single upstream call.
+    async fn quotes(&self, instruments: &[Instrument]) -> Result<Vec<Quote>, BorsaError>;
+
+    /// Maximum number of instruments accepted per call. The router chunks larger requests.
+    ///
+    /// Default: 50.
+    fn max_batch_size(&self) -> usize {
+        50
+    }
+}
+
 // Granular role traits
 /// Focused role trait for connectors that provide earnings fundamentals.
 #[async_trait]
@@ -341,6 +378,11 @@ pub trait BorsaConnector: Send + Sync {
         None
     }
 
+    /// If implemented, returns a trait object for batched quote requests.
+    fn as_batch_quote_provider(&self) -> Option<&dyn BatchQuoteProvider> {
+        None
+    }
+
     /// If implemented, returns a trait object for earnings fundamentals.
     fn as_earnings_provider(&self) -> Option<&dyn EarningsProvider> {
         None
@@ -469,6 +511,13 @@ macro_rules! borsa_connector_accessors {
                 None
             }
         }
+        fn as_batch_quote_provider(&self) -> Option<&dyn $crate::connector::BatchQuoteProvider> {
+            if self.$inner.as_batch_quote_provider().is_some() {
+                Some(self as &dyn $crate::connector::BatchQuoteProvider)
+            } else {
+                None
+            }
+        }
         fn as_earnings_provider(&self) -> Option<&dyn $crate::connector::EarningsProvider> {
             if self.$inner.as_earnings_provider().is_some() {
                 Some(self as &dyn $crate::connector::EarningsProvider)
@@ -697,12 +746,10 @@ macro_rules! borsa_delegate_provider_impls {
                     .$inner
                     .as_history_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("history"))?;
-                let ctx = $crate::middleware::CallContext::new($crate::Capability::History);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .history(instrument, req)
+                let ctx = $crate::middleware::CallContext::new($crate::Capability::History)
+                    .with_size($crate::middleware::CallSize::history(&req));
+                $crate::middleware::run_call(self, &ctx, || inner.history(instrument, req.clone()))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
             fn supported_history_intervals(
                 &self,
@@ -727,11 +774,29 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_quote_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("quote"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::Quote);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .quote(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || inner.quote(instrument)).await
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::connector::BatchQuoteProvider for $self_ty {
+            async fn quotes(
+                &self,
+                instruments: &[$crate::Instrument],
+            ) -> Result<Vec<$crate::Quote>, $crate::BorsaError> {
+                let inner = self
+                    .$inner
+                    .as_batch_quote_provider()
+                    .ok_or_else(|| $crate::BorsaError::unsupported("quotes"))?;
+                let ctx = $crate::middleware::CallContext::new($crate::Capability::Quote)
+                    .with_size($crate::middleware::CallSize::batch(instruments.len()));
+                $crate::middleware::run_call(self, &ctx, || inner.quotes(instruments)).await
+            }
+
+            fn max_batch_size(&self) -> usize {
+                self.$inner
+                    .as_batch_quote_provider()
+                    .map_or(50, $crate::connector::BatchQuoteProvider::max_batch_size)
             }
         }
 
@@ -746,11 +811,7 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_earnings_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("earnings"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::Earnings);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .earnings(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || inner.earnings(instrument)).await
             }
         }
 
@@ -766,11 +827,10 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_income_statement_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("income_statement"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::IncomeStatement);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .income_statement(instrument, quarterly)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || {
+                    inner.income_statement(instrument, quarterly)
+                })
+                .await
             }
         }
 
@@ -786,11 +846,10 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_balance_sheet_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("balance_sheet"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::BalanceSheet);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .balance_sheet(instrument, quarterly)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || {
+                    inner.balance_sheet(instrument, quarterly)
+                })
+                .await
             }
         }
 
@@ -806,11 +865,8 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_cashflow_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("cashflow"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::Cashflow);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .cashflow(instrument, quarterly)
+                $crate::middleware::run_call(self, &ctx, || inner.cashflow(instrument, quarterly))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
         }
 
@@ -825,11 +881,7 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_calendar_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("calendar"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::Calendar);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .calendar(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || inner.calendar(instrument)).await
             }
         }
 
@@ -844,11 +896,7 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_recommendations_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("recommendations"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::Recommendations);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .recommendations(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || inner.recommendations(instrument)).await
             }
         }
 
@@ -865,11 +913,10 @@ macro_rules! borsa_delegate_provider_impls {
                 let ctx = $crate::middleware::CallContext::new(
                     $crate::Capability::RecommendationsSummary,
                 );
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .recommendations_summary(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || {
+                    inner.recommendations_summary(instrument)
+                })
+                .await
             }
         }
 
@@ -885,11 +932,8 @@ macro_rules! borsa_delegate_provider_impls {
                     .ok_or_else(|| $crate::BorsaError::unsupported("upgrades_downgrades"))?;
                 let ctx =
                     $crate::middleware::CallContext::new($crate::Capability::UpgradesDowngrades);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .upgrades_downgrades(instrument)
+                $crate::middleware::run_call(self, &ctx, || inner.upgrades_downgrades(instrument))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
         }
 
@@ -905,11 +949,8 @@ macro_rules! borsa_delegate_provider_impls {
                     .ok_or_else(|| $crate::BorsaError::unsupported("analyst_price_target"))?;
                 let ctx =
                     $crate::middleware::CallContext::new($crate::Capability::AnalystPriceTarget);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .analyst_price_target(instrument)
+                $crate::middleware::run_call(self, &ctx, || inner.analyst_price_target(instrument))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
         }
 
@@ -924,11 +965,7 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_major_holders_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("major_holders"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::MajorHolders);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .major_holders(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || inner.major_holders(instrument)).await
             }
         }
 
@@ -944,11 +981,8 @@ macro_rules! borsa_delegate_provider_impls {
                     .ok_or_else(|| $crate::BorsaError::unsupported("institutional_holders"))?;
                 let ctx =
                     $crate::middleware::CallContext::new($crate::Capability::InstitutionalHolders);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .institutional_holders(instrument)
+                $crate::middleware::run_call(self, &ctx, || inner.institutional_holders(instrument))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
         }
 
@@ -964,11 +998,8 @@ macro_rules! borsa_delegate_provider_impls {
                     .ok_or_else(|| $crate::BorsaError::unsupported("mutual_fund_holders"))?;
                 let ctx =
                     $crate::middleware::CallContext::new($crate::Capability::MutualFundHolders);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .mutual_fund_holders(instrument)
+                $crate::middleware::run_call(self, &ctx, || inner.mutual_fund_holders(instrument))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
         }
 
@@ -984,11 +1015,8 @@ macro_rules! borsa_delegate_provider_impls {
                     .ok_or_else(|| $crate::BorsaError::unsupported("insider_transactions"))?;
                 let ctx =
                     $crate::middleware::CallContext::new($crate::Capability::InsiderTransactions);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .insider_transactions(instrument)
+                $crate::middleware::run_call(self, &ctx, || inner.insider_transactions(instrument))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
         }
 
@@ -1003,11 +1031,10 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_insider_roster_holders_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("insider_roster_holders"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::InsiderRoster);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .insider_roster_holders(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || {
+                    inner.insider_roster_holders(instrument)
+                })
+                .await
             }
         }
 
@@ -1026,11 +1053,10 @@ macro_rules! borsa_delegate_provider_impls {
                 let ctx = $crate::middleware::CallContext::new(
                     $crate::Capability::NetSharePurchaseActivity,
                 );
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .net_share_purchase_activity(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || {
+                    inner.net_share_purchase_activity(instrument)
+                })
+                .await
             }
         }
 
@@ -1045,11 +1071,7 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_profile_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("profile"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::Profile);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .profile(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || inner.profile(instrument)).await
             }
         }
 
@@ -1064,11 +1086,7 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_isin_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("isin"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::Isin);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .isin(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || inner.isin(instrument)).await
             }
         }
 
@@ -1083,11 +1101,7 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_search_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("search"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::Search);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .search(req)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || inner.search(req.clone())).await
             }
         }
 
@@ -1102,11 +1116,7 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_esg_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("sustainability"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::Esg);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .sustainability(instrument)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                $crate::middleware::run_call(self, &ctx, || inner.sustainability(instrument)).await
             }
         }
 
@@ -1122,11 +1132,8 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_news_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("news"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::News);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .news(instrument, req)
+                $crate::middleware::run_call(self, &ctx, || inner.news(instrument, req.clone()))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
         }
 
@@ -1142,11 +1149,8 @@ macro_rules! borsa_delegate_provider_impls {
                     .ok_or_else(|| $crate::BorsaError::unsupported("options_expirations"))?;
                 let ctx =
                     $crate::middleware::CallContext::new($crate::Capability::OptionsExpirations);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .options_expirations(instrument)
+                $crate::middleware::run_call(self, &ctx, || inner.options_expirations(instrument))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
         }
 
@@ -1162,11 +1166,8 @@ macro_rules! borsa_delegate_provider_impls {
                     .as_option_chain_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("option_chain"))?;
                 let ctx = $crate::middleware::CallContext::new($crate::Capability::OptionChain);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .option_chain(instrument, date)
+                $crate::middleware::run_call(self, &ctx, || inner.option_chain(instrument, date))
                     .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
             }
         }
 
@@ -1186,12 +1187,9 @@ macro_rules! borsa_delegate_provider_impls {
                     .$inner
                     .as_stream_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("stream_quotes"))?;
-                let ctx = $crate::middleware::CallContext::new($crate::Capability::StreamQuotes);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .stream_quotes(instruments)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                let ctx = $crate::middleware::CallContext::new($crate::Capability::StreamQuotes)
+                    .with_size($crate::middleware::CallSize::batch(instruments.len()));
+                $crate::middleware::run_call(self, &ctx, || inner.stream_quotes(instruments)).await
             }
         }
 
@@ -1212,12 +1210,12 @@ macro_rules! borsa_delegate_provider_impls {
                     .$inner
                     .as_candle_stream_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("stream_candles"))?;
-                let ctx = $crate::middleware::CallContext::new($crate::Capability::StreamCandles);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .stream_candles(instruments, interval)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                let ctx = $crate::middleware::CallContext::new($crate::Capability::StreamCandles)
+                    .with_size($crate::middleware::CallSize::batch(instruments.len()));
+                $crate::middleware::run_call(self, &ctx, || {
+                    inner.stream_candles(instruments, interval)
+                })
+                .await
             }
         }
 
@@ -1237,12 +1235,9 @@ macro_rules! borsa_delegate_provider_impls {
                     .$inner
                     .as_option_stream_provider()
                     .ok_or_else(|| $crate::BorsaError::unsupported("stream_options"))?;
-                let ctx = $crate::middleware::CallContext::new($crate::Capability::StreamOptions);
-                <Self as $crate::Middleware>::pre_call(self, &ctx).await?;
-                inner
-                    .stream_options(instruments)
-                    .await
-                    .map_err(|e| <Self as $crate::Middleware>::map_error(self, e, &ctx))
+                let ctx = $crate::middleware::CallContext::new($crate::Capability::StreamOptions)
+                    .with_size($crate::middleware::CallSize::batch(instruments.len()));
+                $crate::middleware::run_call(self, &ctx, || inner.stream_options(instruments)).await
             }
         }
     };
@@ -23,10 +23,14 @@
 //!
 #![warn(missing_docs)]
 
+/// Jittered exponential backoff helpers.
+pub mod backoff;
 /// Connector capability traits and the primary `BorsaConnector` interface.
 pub mod connector;
 /// Middleware trait implemented by connector wrappers.
 pub mod middleware;
+/// Named connector constructors for building connectors from configuration.
+pub mod registry;
 /// Internal stream utilities used by `StreamHandle` and tests.
 pub mod stream;
 /// Time-series utilities for merging and resampling.
@@ -35,10 +39,20 @@ pub mod types;
 
 pub use connector::BorsaConnector;
 pub use middleware::{
-    CallContext, CallOrigin, Middleware, MiddlewareDescriptor, MiddlewarePosition,
-    ValidationContext,
+    CallContext, CallDeadline, CallOrigin, CallPriority, CallSize, Middleware,
+    MiddlewareDescriptor, MiddlewarePosition, ValidationContext,
+};
+pub use registry::{ConnectorFactory, ConnectorRegistry, global_registry, register_connector};
+pub use timeseries::adjust::{
+    AdjustmentAnchor, AdjustmentFactor, AdjustmentKind, adjust_candles, adjust_history,
+    adjustment_factors, unadjust_candles,
 };
 pub use timeseries::infer::{estimate_step_seconds, is_subdaily};
-pub use timeseries::merge::{dedup_actions, merge_candles_by_priority, merge_history};
-pub use timeseries::resample::{resample_to_daily, resample_to_minutes, resample_to_weekly};
+pub use timeseries::merge::{
+    dedup_actions, merge_candles_by_priority, merge_history, stitch_history,
+};
+pub use timeseries::resample::{
+    interval_bounds, resample_to_daily, resample_to_minutes, resample_to_weekly,
+};
+pub use timeseries::window::{slice_to_request, slice_to_request_at};
 pub use types::*;
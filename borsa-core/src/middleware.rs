@@ -3,18 +3,21 @@
 use std::any::{Any, TypeId};
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::connector::BorsaConnector;
-use crate::{BorsaError, Capability};
+use crate::{BorsaError, Capability, HistoryRequest, Range};
 use async_trait::async_trait;
 use tokio::task_local;
 
 task_local! {
     static CALL_ORIGIN: CallOrigin;
+    static CALL_PRIORITY: CallPriority;
+    static CALL_DEADLINE: tokio::time::Instant;
 }
 
 /// Classification of who initiated a connector call.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CallOrigin {
     /// End-user / public API invocation (default).
     External,
@@ -75,23 +78,134 @@ impl CallOrigin {
     }
 }
 
+/// Scheduling lane of a call, for middleware that queues calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CallPriority {
+    /// Latency-sensitive request waiting on an answer (default).
+    #[default]
+    Interactive,
+    /// Throughput-oriented work such as bulk downloads; yields to interactive calls.
+    Bulk,
+}
+
+impl CallPriority {
+    /// Return the currently scoped priority, defaulting to `Interactive`.
+    #[must_use]
+    pub fn current() -> Self {
+        CALL_PRIORITY.try_with(|p| *p).unwrap_or_default()
+    }
+
+    /// Run a future within a scoped priority.
+    pub async fn scope<Fut, T>(priority: Self, fut: Fut) -> T
+    where
+        Fut: Future<Output = T>,
+    {
+        CALL_PRIORITY.scope(priority, fut).await
+    }
+}
+
+/// Deadline after which the caller abandons the current provider call.
+///
+/// The router scopes each provider call with its `provider_timeout`, so middleware that
+/// waits (e.g. for rate-limit tokens) can give up early instead of being cancelled.
+#[derive(Debug, Clone, Copy)]
+pub struct CallDeadline;
+
+impl CallDeadline {
+    /// Return the currently scoped deadline, if any.
+    #[must_use]
+    pub fn current() -> Option<tokio::time::Instant> {
+        CALL_DEADLINE.try_with(|d| *d).ok()
+    }
+
+    /// Run a future with `deadline`, keeping an earlier deadline already in scope.
+    pub async fn scope<Fut, T>(deadline: tokio::time::Instant, fut: Fut) -> T
+    where
+        Fut: Future<Output = T>,
+    {
+        let deadline = Self::current().map_or(deadline, |outer| outer.min(deadline));
+        CALL_DEADLINE.scope(deadline, fut).await
+    }
+}
+
+/// Volume of a pending provider call, for middleware that prices calls by size.
+#[derive(Debug, Clone, Copy)]
+pub struct CallSize {
+    /// Instruments covered by the call: the batch length for batch quotes and streams, 1
+    /// otherwise.
+    pub instruments: usize,
+    /// Requested history range, for range-based history calls.
+    pub range: Option<Range>,
+    /// Length of the requested history period, for period-based history calls.
+    pub period: Option<Duration>,
+}
+
+impl Default for CallSize {
+    fn default() -> Self {
+        Self {
+            instruments: 1,
+            range: None,
+            period: None,
+        }
+    }
+}
+
+impl CallSize {
+    /// Size of a call covering `instruments` instruments.
+    #[must_use]
+    pub fn batch(instruments: usize) -> Self {
+        Self {
+            instruments,
+            ..Self::default()
+        }
+    }
+
+    /// Size of a history call for `req`.
+    #[must_use]
+    pub fn history(req: &HistoryRequest) -> Self {
+        Self {
+            range: req.range(),
+            period: req.period().map(|(start, end)| {
+                Duration::from_secs(
+                    u64::try_from(end.timestamp().saturating_sub(start.timestamp())).unwrap_or(0),
+                )
+            }),
+            ..Self::default()
+        }
+    }
+}
+
 /// Snapshot of the capability and origin for a pending provider call.
 #[derive(Debug, Clone)]
 pub struct CallContext {
     capability: Capability,
     origin: CallOrigin,
+    priority: CallPriority,
+    deadline: Option<tokio::time::Instant>,
+    size: CallSize,
 }
 
 impl CallContext {
-    /// Create a new context for a capability using the currently scoped origin.
+    /// Create a new context for a capability using the currently scoped origin, priority
+    /// and deadline.
     #[must_use]
     pub fn new(capability: Capability) -> Self {
         Self {
             capability,
             origin: CallOrigin::current(),
+            priority: CallPriority::current(),
+            deadline: CallDeadline::current(),
+            size: CallSize::default(),
         }
     }
 
+    /// Attach the size of the call.
+    #[must_use]
+    pub const fn with_size(mut self, size: CallSize) -> Self {
+        self.size = size;
+        self
+    }
+
     /// Capability being invoked.
     #[must_use]
     pub const fn capability(&self) -> Capability {
@@ -103,6 +217,24 @@ impl CallContext {
     pub const fn origin(&self) -> &CallOrigin {
         &self.origin
     }
+
+    /// Scheduling lane of this call.
+    #[must_use]
+    pub const fn priority(&self) -> CallPriority {
+        self.priority
+    }
+
+    /// Deadline after which the caller abandons this call, if any.
+    #[must_use]
+    pub const fn deadline(&self) -> Option<tokio::time::Instant> {
+        self.deadline
+    }
+
+    /// Size of this call.
+    #[must_use]
+    pub const fn size(&self) -> &CallSize {
+        &self.size
+    }
 }
 
 /// Position requirement for middleware in the stack.
@@ -178,6 +310,20 @@ impl<'a> ValidationContext<'a> {
             .collect()
     }
 
+    /// Check a position requirement against the current middleware's place in the stack.
+    ///
+    /// `OuterThan`/`InnerThan` only constrain the order relative to middleware that is
+    /// actually present, so optional neighbours may be left out.
+    #[must_use]
+    pub fn satisfies(&self, position: MiddlewarePosition) -> bool {
+        match position {
+            MiddlewarePosition::Outermost => self.current_index == 0,
+            MiddlewarePosition::OuterThan(type_id) => !self.has_middleware_outer(type_id),
+            MiddlewarePosition::InnerThan(type_id) => !self.has_middleware_inner(type_id),
+            MiddlewarePosition::Any => true,
+        }
+    }
+
     /// Get the middleware's position in the stack (0 = outermost, n-1 = innermost).
     #[must_use]
     pub const fn current_position(&self) -> usize {
@@ -292,6 +438,99 @@ pub trait Middleware: Send + Sync {
     fn map_error(&self, err: BorsaError, _ctx: &CallContext) -> BorsaError {
         err
     }
+
+    /// Observe a successful result from the inner provider in generated implementations.
+    ///
+    /// Complements [`map_error`](Self::map_error) for middleware whose state reacts to
+    /// recoveries (e.g., closing a circuit breaker).
+    ///
+    /// Default: No-op.
+    fn on_success(&self, _ctx: &CallContext) {}
+
+    /// Observe an inner call that was cancelled after its [`CallDeadline`] passed.
+    ///
+    /// The router drops a provider call when its timeout fires, so neither
+    /// [`on_success`](Self::on_success) nor [`map_error`](Self::map_error) runs for it.
+    /// Calls dropped before the deadline (e.g. the loser of a latency race) are not reported.
+    ///
+    /// Default: No-op.
+    fn on_abandoned(&self, _ctx: &CallContext) {}
+
+    /// Decide whether a failed inner call should be attempted again.
+    ///
+    /// Called with the error of failed attempt number `attempt` (1-based) before
+    /// [`map_error`](Self::map_error). Returning `Some(delay)` sleeps for `delay`, then runs
+    /// [`pre_call`](Self::pre_call) and the inner call again; `None` ends the call with
+    /// this error.
+    ///
+    /// Default: Never retry.
+    fn retry_delay(
+        &self,
+        _err: &BorsaError,
+        _attempt: u32,
+        _ctx: &CallContext,
+    ) -> Option<Duration> {
+        None
+    }
+}
+
+/// Reports an in-flight inner call to `on_abandoned` if it is dropped past its deadline.
+struct AbandonGuard<'a, M: Middleware + ?Sized> {
+    middleware: &'a M,
+    ctx: &'a CallContext,
+}
+
+impl<M: Middleware + ?Sized> Drop for AbandonGuard<'_, M> {
+    fn drop(&mut self) {
+        if self
+            .ctx
+            .deadline()
+            .is_some_and(|deadline| tokio::time::Instant::now() >= deadline)
+        {
+            self.middleware.on_abandoned(self.ctx);
+        }
+    }
+}
+
+/// Drive one inner provider call through a middleware's hooks.
+///
+/// Used by the generated provider implementations: runs `pre_call`, invokes `call`, reports
+/// success via `on_success` and consults `retry_delay` on failure before handing the last
+/// error to `map_error`. An attempt cancelled after the call deadline is reported to
+/// `on_abandoned`.
+///
+/// # Errors
+/// Returns the `pre_call` rejection or the mapped error of the final attempt.
+pub async fn run_call<M, T, F, Fut>(
+    middleware: &M,
+    ctx: &CallContext,
+    mut call: F,
+) -> Result<T, BorsaError>
+where
+    M: Middleware + ?Sized,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, BorsaError>>,
+{
+    let mut attempt = 0u32;
+    loop {
+        middleware.pre_call(ctx).await?;
+        let guard = AbandonGuard { middleware, ctx };
+        let res = call().await;
+        std::mem::forget(guard);
+        match res {
+            Ok(value) => {
+                middleware.on_success(ctx);
+                return Ok(value);
+            }
+            Err(err) => {
+                attempt = attempt.saturating_add(1);
+                match middleware.retry_delay(&err, attempt, ctx) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(middleware.map_error(err, ctx)),
+                }
+            }
+        }
+    }
 }
 
 /// Helper macro for middleware to check dependencies without hardcoding strings.
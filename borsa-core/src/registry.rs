@@ -0,0 +1,108 @@
+//! Connector factory registry used to build connectors from declarative configuration.
+//!
+//! Connector crates expose a `register` function that adds a constructor under the
+//! connector's `name()`. Applications either fill a local [`ConnectorRegistry`] or call
+//! [`register_connector`] to make a factory available to every config loader in the process.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use crate::{BorsaConnector, BorsaError};
+
+/// Constructor invoked with the connector's free-form `config` section.
+pub type ConnectorFactory =
+    Arc<dyn Fn(&serde_json::Value) -> Result<Arc<dyn BorsaConnector>, BorsaError> + Send + Sync>;
+
+/// Named connector constructors.
+#[derive(Clone, Default)]
+pub struct ConnectorRegistry {
+    factories: BTreeMap<String, ConnectorFactory>,
+}
+
+impl std::fmt::Debug for ConnectorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectorRegistry")
+            .field("names", &self.factories.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ConnectorRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) the constructor for `name`.
+    pub fn register<F>(&mut self, name: impl Into<String>, factory: F)
+    where
+        F: Fn(&serde_json::Value) -> Result<Arc<dyn BorsaConnector>, BorsaError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.factories.insert(name.into(), Arc::new(factory));
+    }
+
+    /// Returns true if a constructor is registered under `name`.
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Registered connector names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    /// Construct the connector registered under `name`.
+    ///
+    /// # Errors
+    /// Returns `InvalidArg` if no constructor is registered under `name`, or the error
+    /// returned by the constructor.
+    pub fn build(
+        &self,
+        name: &str,
+        config: &serde_json::Value,
+    ) -> Result<Arc<dyn BorsaConnector>, BorsaError> {
+        let factory = self.factories.get(name).ok_or_else(|| {
+            let known = self.names().collect::<Vec<_>>().join(", ");
+            BorsaError::InvalidArg(format!("unknown connector '{name}' (registered: {known})"))
+        })?;
+        factory(config)
+    }
+
+    /// Copy every constructor of `other` into this registry, replacing duplicates.
+    pub fn extend(&mut self, other: &Self) {
+        for (name, factory) in &other.factories {
+            self.factories.insert(name.clone(), Arc::clone(factory));
+        }
+    }
+}
+
+static GLOBAL: LazyLock<RwLock<ConnectorRegistry>> =
+    LazyLock::new(|| RwLock::new(ConnectorRegistry::new()));
+
+/// Register a constructor in the process-wide registry.
+pub fn register_connector<F>(name: impl Into<String>, factory: F)
+where
+    F: Fn(&serde_json::Value) -> Result<Arc<dyn BorsaConnector>, BorsaError>
+        + Send
+        + Sync
+        + 'static,
+{
+    let mut guard = GLOBAL
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    guard.register(name, factory);
+}
+
+/// Snapshot of the process-wide registry.
+#[must_use]
+pub fn global_registry() -> ConnectorRegistry {
+    GLOBAL
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+}
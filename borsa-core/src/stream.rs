@@ -1,6 +1,97 @@
-use tokio::sync::oneshot;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinHandle;
 
+use crate::{BorsaError, Symbol};
+
+/// Event reported by a running stream alongside its updates.
+///
+/// Providers are identified by their connector name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StreamEvent {
+    /// A provider session started for `symbols`.
+    ProviderStarted {
+        /// Connector name of the provider.
+        provider: &'static str,
+        /// Symbols the session streams.
+        symbols: Vec<Symbol>,
+    },
+    /// A provider failed to start a session for `symbols`; it is retried after backoff.
+    ProviderFailed {
+        /// Connector name of the provider.
+        provider: &'static str,
+        /// Symbols the session would have streamed.
+        symbols: Vec<Symbol>,
+        /// Error returned by the provider.
+        error: BorsaError,
+    },
+    /// A running provider session ended, either on its own or preempted by a
+    /// higher-priority provider.
+    ProviderEnded {
+        /// Connector name of the provider.
+        provider: &'static str,
+        /// Symbols the session streamed.
+        symbols: Vec<Symbol>,
+    },
+    /// A running session stopped delivering updates for `symbols` and is being replaced.
+    Stale {
+        /// Connector name of the provider.
+        provider: &'static str,
+        /// Symbols the session let go stale.
+        symbols: Vec<Symbol>,
+    },
+    /// `symbols` are now streamed by `to` after previously being streamed by `from`.
+    Failover {
+        /// Connector name of the provider that streamed the symbols before.
+        from: &'static str,
+        /// Connector name of the provider now streaming the symbols.
+        to: &'static str,
+        /// Symbols that moved.
+        symbols: Vec<Symbol>,
+    },
+    /// `symbols` were streamed but no running session covers them anymore.
+    CoverageLost {
+        /// Symbols without a running session.
+        symbols: Vec<Symbol>,
+    },
+    /// `symbols` wait for the next start attempt, which happens after `delay`.
+    BackoffScheduled {
+        /// Time until the next start attempt.
+        delay: Duration,
+        /// Symbols waiting for a provider.
+        symbols: Vec<Symbol>,
+    },
+    /// An update older than the last one delivered for `symbol` was discarded by monotonic
+    /// timestamp enforcement.
+    OutOfOrderDropped {
+        /// Connector name of the provider that sent the update.
+        provider: &'static str,
+        /// Symbol of the update.
+        symbol: Symbol,
+        /// Timestamp of the discarded update.
+        ts: DateTime<Utc>,
+    },
+    /// A provider sent an update for `symbol`, which its session was not assigned, and the
+    /// update was discarded.
+    UnassignedDropped {
+        /// Connector name of the provider that sent the update.
+        provider: &'static str,
+        /// Symbol of the update.
+        symbol: Symbol,
+    },
+    /// The output's overflow policy discarded updates for `symbol` because the consumer fell
+    /// behind. `total` counts every update discarded for the symbol since the stream started.
+    UpdatesDropped {
+        /// Symbol of the discarded updates.
+        symbol: Symbol,
+        /// Updates discarded for `symbol` so far.
+        total: u64,
+    },
+}
+
 /// Abstraction over a handle that can be queried for completion and aborted.
 pub trait Abortable {
     /// Abort the underlying task if it is still running.
@@ -61,6 +152,7 @@ where
 pub struct StreamHandle {
     inner: Option<JoinHandle<()>>,
     stop_tx: Option<oneshot::Sender<()>>,
+    events: Option<mpsc::Receiver<StreamEvent>>,
 }
 
 impl StreamHandle {
@@ -76,6 +168,7 @@ impl StreamHandle {
         Self {
             inner: Some(inner),
             stop_tx: Some(stop_tx),
+            events: None,
         }
     }
 
@@ -89,9 +182,25 @@ impl StreamHandle {
         Self {
             inner: Some(inner),
             stop_tx: None,
+            events: None,
         }
     }
 
+    /// Attach a receiver of [`StreamEvent`]s reported by the stream.
+    #[must_use]
+    pub fn with_events(mut self, events: mpsc::Receiver<StreamEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Take the receiver of [`StreamEvent`]s, if the stream reports events.
+    ///
+    /// Returns `None` once taken. Events are best-effort: when the receiver falls behind,
+    /// new events are discarded rather than slowing the stream.
+    pub const fn take_events(&mut self) -> Option<mpsc::Receiver<StreamEvent>> {
+        self.events.take()
+    }
+
     /// Gracefully stop the underlying stream task and await its completion.
     ///
     /// Sends a stop signal if available, then awaits the task. Any errors
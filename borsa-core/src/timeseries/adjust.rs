@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use paft::market::action::Action;
+use paft::market::responses::history::{Candle, HistoryResponse};
+use paft::money::{Currency, Money};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::BorsaError;
+
+/// Decimal places kept in adjusted prices.
+pub const PRICE_SCALE: u32 = 6;
+
+/// Which corporate actions are folded into adjusted prices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AdjustmentKind {
+    /// Splits only: prices stay comparable across splits, dividends are ignored.
+    Splits,
+    /// Splits, dividends and capital gains (total return), like providers' adjusted close.
+    TotalReturn,
+}
+
+/// Which end of the series keeps actual traded prices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AdjustmentAnchor {
+    /// The newest candle keeps its traded prices and earlier ones are scaled (the usual
+    /// "adjusted close").
+    Back,
+    /// The oldest candle keeps its traded prices and later ones are scaled.
+    Forward,
+}
+
+/// Multipliers that turn one raw candle into its adjusted form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdjustmentFactor {
+    /// Multiplier for open, high, low and close.
+    pub price: Decimal,
+    /// Multiplier for volume (splits only).
+    pub volume: Decimal,
+}
+
+impl AdjustmentFactor {
+    const ONE: Self = Self {
+        price: Decimal::ONE,
+        volume: Decimal::ONE,
+    };
+}
+
+/// Actions sharing one ex-date, combined.
+struct Step {
+    date: NaiveDate,
+    // price multiplier of the splits (denominator / numerator)
+    split: Decimal,
+    // volume multiplier of the splits (numerator / denominator)
+    split_volume: Decimal,
+    // dividends and capital gains paid per share, with their currency
+    cash: Option<(Decimal, Currency)>,
+}
+
+fn data_err(what: &str) -> BorsaError {
+    BorsaError::Data(format!("adjust: {what}"))
+}
+
+/// Group `actions` by ex-date, newest first.
+fn steps(actions: &[Action], kind: AdjustmentKind) -> Result<Vec<Step>, BorsaError> {
+    let mut by_date: BTreeMap<NaiveDate, Step> = BTreeMap::new();
+    for action in actions {
+        let (ts, split, cash) = match action {
+            Action::Split {
+                ts,
+                numerator,
+                denominator,
+            } => {
+                if *numerator == 0 || *denominator == 0 {
+                    return Err(data_err(&format!(
+                        "split {numerator}:{denominator} on {}",
+                        ts.date_naive()
+                    )));
+                }
+                (ts, Some((*numerator, *denominator)), None)
+            }
+            Action::Dividend { ts, amount } => (ts, None, Some(amount)),
+            Action::CapitalGain { ts, gain } => (ts, None, Some(gain)),
+        };
+        let date = ts.date_naive();
+        let step = by_date.entry(date).or_insert(Step {
+            date,
+            split: Decimal::ONE,
+            split_volume: Decimal::ONE,
+            cash: None,
+        });
+        if let Some((numerator, denominator)) = split {
+            let (n, d) = (Decimal::from(numerator), Decimal::from(denominator));
+            step.split *= d / n;
+            step.split_volume *= n / d;
+        }
+        if let Some(amount) = cash.filter(|_| kind == AdjustmentKind::TotalReturn) {
+            step.cash = Some(match step.cash.take() {
+                None => (amount.amount(), amount.currency().clone()),
+                Some((sum, currency)) if currency == *amount.currency() => {
+                    (sum + amount.amount(), currency)
+                }
+                Some((_, currency)) => {
+                    return Err(data_err(&format!(
+                        "distributions on {date} in {currency:?} and {:?}",
+                        amount.currency()
+                    )));
+                }
+            });
+        }
+    }
+    Ok(by_date.into_values().rev().collect())
+}
+
+/// Price multiplier of a cash distribution paid from a previous close of `close`.
+fn cash_factor(
+    date: NaiveDate,
+    (cash, cash_currency): &(Decimal, Currency),
+    close: &Money,
+    raw_close: Decimal,
+) -> Result<Decimal, BorsaError> {
+    if cash_currency != close.currency() {
+        return Err(data_err(&format!(
+            "distribution on {date} in {cash_currency:?} but prices in {:?}",
+            close.currency()
+        )));
+    }
+    if raw_close <= *cash {
+        return Err(data_err(&format!(
+            "distribution {cash} on {date} is not below the previous close {raw_close}"
+        )));
+    }
+    Ok(Decimal::ONE - *cash / raw_close)
+}
+
+/// Cumulative back-adjustment factors for candles sorted by time.
+///
+/// `raw_close(i, factor, cash)` returns the raw close of candle `i`, given the factor
+/// accumulated before a distribution of `cash` is folded in.
+fn back_factors(
+    candles: &[Candle],
+    steps: &[Step],
+    raw_close: impl Fn(usize, Decimal, Decimal) -> Decimal,
+) -> Result<Vec<AdjustmentFactor>, BorsaError> {
+    let mut factors = vec![AdjustmentFactor::ONE; candles.len()];
+    let mut current = AdjustmentFactor::ONE;
+    let mut steps = steps.iter().peekable();
+    for i in (0..candles.len()).rev() {
+        let date = candles[i].ts.date_naive();
+        // Every action dated after this candle applies to it.
+        while let Some(step) = steps.next_if(|s| s.date > date) {
+            current.price *= step.split;
+            current.volume *= step.split_volume;
+            if let Some(cash) = &step.cash {
+                let close = raw_close(i, current.price, cash.0);
+                current.price *= cash_factor(step.date, cash, &candles[i].close, close)?;
+            }
+        }
+        factors[i] = current;
+    }
+    Ok(factors)
+}
+
+fn sorted(mut candles: Vec<Candle>) -> Vec<Candle> {
+    candles.sort_by_key(|c| c.ts);
+    candles
+}
+
+/// Adjustment factor of each candle in `candles` (raw prices, sorted by time).
+///
+/// Behavior:
+/// - An action applies to candles dated (UTC calendar date) before its ex-date.
+/// - A split `n:d` multiplies earlier prices by `d/n` and earlier volumes by `n/d`.
+/// - Under [`AdjustmentKind::TotalReturn`], a distribution `D` multiplies earlier prices by
+///   `1 - D / C`, where `C` is the raw close of the last candle before the ex-date; `D` must
+///   be in the same (unsplit) terms as `C`. Distributions sharing an ex-date are summed.
+/// - [`AdjustmentAnchor::Forward`] divides every factor by the first candle's, so the oldest
+///   candle keeps its traded prices.
+///
+/// # Errors
+/// Returns `BorsaError::Data` for splits with a zero side, distributions in another
+/// currency than the prices, or distributions not below the previous close.
+pub fn adjustment_factors(
+    candles: &[Candle],
+    actions: &[Action],
+    kind: AdjustmentKind,
+    anchor: AdjustmentAnchor,
+) -> Result<Vec<AdjustmentFactor>, BorsaError> {
+    let steps = steps(actions, kind)?;
+    let mut factors = back_factors(candles, &steps, |i, _, _| candles[i].close.amount())?;
+    if anchor == AdjustmentAnchor::Forward
+        && let Some(first) = factors.first().copied()
+    {
+        for f in &mut factors {
+            f.price /= first.price;
+            f.volume /= first.volume;
+        }
+    }
+    Ok(factors)
+}
+
+fn scale(m: &Money, factor: Decimal) -> Result<Money, BorsaError> {
+    Money::new(
+        (m.amount() * factor).round_dp(PRICE_SCALE),
+        m.currency().clone(),
+    )
+    .map_err(|e| data_err(&e.to_string()))
+}
+
+fn scale_volume(volume: Option<u64>, factor: Decimal) -> Option<u64> {
+    volume.and_then(|v| (Decimal::from(v) * factor).round().to_u64())
+}
+
+/// Apply `factors` (one per candle) to OHLC and volume.
+fn apply(
+    candles: Vec<Candle>,
+    factors: &[AdjustmentFactor],
+    keep_raw_close: bool,
+) -> Result<Vec<Candle>, BorsaError> {
+    candles
+        .into_iter()
+        .zip(factors)
+        .map(|(c, f)| -> Result<Candle, BorsaError> {
+            Ok(Candle {
+                ts: c.ts,
+                open: scale(&c.open, f.price)?,
+                high: scale(&c.high, f.price)?,
+                low: scale(&c.low, f.price)?,
+                close: scale(&c.close, f.price)?,
+                close_unadj: keep_raw_close.then_some(c.close),
+                volume: scale_volume(c.volume, f.volume),
+            })
+        })
+        .collect()
+}
+
+/// Adjust raw candles for `actions`.
+///
+/// Output candles are sorted by time, with prices rounded to [`PRICE_SCALE`] decimals and
+/// `close_unadj` set to the raw close. See [`adjustment_factors`] for the method.
+///
+/// # Errors
+/// Returns `BorsaError::Data` under the conditions listed on [`adjustment_factors`].
+pub fn adjust_candles(
+    candles: Vec<Candle>,
+    actions: &[Action],
+    kind: AdjustmentKind,
+    anchor: AdjustmentAnchor,
+) -> Result<Vec<Candle>, BorsaError> {
+    let candles = sorted(candles);
+    let factors = adjustment_factors(&candles, actions, kind, anchor)?;
+    apply(candles, &factors, true)
+}
+
+/// Recover raw candles from back-adjusted ones, inverting [`adjust_candles`] with
+/// [`AdjustmentAnchor::Back`].
+///
+/// Use this to bring a provider's adjusted series into the same terms as a raw one before
+/// merging. `kind` must match how the input was adjusted. Output candles are sorted by time
+/// and have no `close_unadj`.
+///
+/// # Errors
+/// Returns `BorsaError::Data` under the conditions listed on [`adjustment_factors`].
+pub fn unadjust_candles(
+    adjusted: Vec<Candle>,
+    actions: &[Action],
+    kind: AdjustmentKind,
+) -> Result<Vec<Candle>, BorsaError> {
+    let candles = sorted(adjusted);
+    let steps = steps(actions, kind)?;
+    // Before a distribution D, adjusted close = (raw close - D) * factor accumulated so far.
+    let factors = back_factors(&candles, &steps, |i, factor, cash| {
+        candles[i].close.amount() / factor + cash
+    })?;
+    let inverse: Vec<AdjustmentFactor> = factors
+        .iter()
+        .map(|f| AdjustmentFactor {
+            price: Decimal::ONE / f.price,
+            volume: Decimal::ONE / f.volume,
+        })
+        .collect();
+    apply(candles, &inverse, false)
+}
+
+/// Adjust a raw history for its own actions.
+///
+/// The result keeps the actions and metadata. `adjusted` is set for
+/// [`AdjustmentKind::TotalReturn`], matching what providers report for adjusted series.
+///
+/// # Errors
+/// Returns `BorsaError::InvalidArg` if `history` is already adjusted, and `BorsaError::Data`
+/// under the conditions listed on [`adjustment_factors`].
+pub fn adjust_history(
+    history: &HistoryResponse,
+    kind: AdjustmentKind,
+    anchor: AdjustmentAnchor,
+) -> Result<HistoryResponse, BorsaError> {
+    if history.adjusted {
+        return Err(BorsaError::InvalidArg(
+            "adjust: history is already adjusted".into(),
+        ));
+    }
+    Ok(HistoryResponse {
+        candles: adjust_candles(history.candles.clone(), &history.actions, kind, anchor)?,
+        actions: history.actions.clone(),
+        adjusted: kind == AdjustmentKind::TotalReturn,
+        meta: history.meta.clone(),
+    })
+}
@@ -26,6 +26,32 @@ use paft::market::responses::history::{Candle, HistoryMeta, HistoryResponse};
 /// consistency is a required invariant for all merged candles and the merge
 /// aborts on the first inconsistency (series-wide check).
 pub fn merge_history<I>(responses: I) -> Result<HistoryResponse, BorsaError>
+where
+    I: IntoIterator<Item = HistoryResponse>,
+{
+    let mut merged = merge_responses(responses)?;
+    // Enforce invariant: merged series do not carry per-candle raw close provenance
+    util::strip_unadjusted(&mut merged.candles);
+    Ok(merged)
+}
+
+/// Stitch windows of one source's series (first is highest priority).
+///
+/// Same as [`merge_history`], except that candles keep their `close_unadj`. Use it to
+/// extend a cached or stored series with fresh windows from the provider that produced
+/// it, where raw closes all come from the same source.
+///
+/// # Errors
+/// Returns `Err(BorsaError::Data)` if mixed currencies are detected, like
+/// [`merge_history`].
+pub fn stitch_history<I>(responses: I) -> Result<HistoryResponse, BorsaError>
+where
+    I: IntoIterator<Item = HistoryResponse>,
+{
+    merge_responses(responses)
+}
+
+fn merge_responses<I>(responses: I) -> Result<HistoryResponse, BorsaError>
 where
     I: IntoIterator<Item = HistoryResponse>,
 {
@@ -90,10 +116,7 @@ where
         meta = fallback_meta;
     }
 
-    let mut candles: Vec<Candle> = candle_map.into_values().collect();
-    // Enforce invariant: merged series do not carry per-candle raw close provenance
-    util::strip_unadjusted(&mut candles);
-
+    let candles: Vec<Candle> = candle_map.into_values().collect();
     let actions = dedup_actions(actions);
 
     let adjusted = match (first_contrib_adjusted, adjusted_all) {
@@ -1,9 +1,13 @@
 //! Time-series utilities shared by connectors and orchestrator.
 //!
 //! Modules include:
+//! - `adjust`: adjust raw candles for splits and distributions, and undo such adjustments
 //! - `infer`: infer interval and detect gaps/continuity
 //! - `merge`: merge multiple provider series respecting priority and adjusted preference
 //! - `resample`: resample candles to requested cadence
+//! - `window`: cut stored series down to a request's period or range
+/// Split and dividend adjustment of raw candles.
+pub mod adjust;
 /// Interval inference and sub-daily detection helpers.
 pub mod infer;
 /// Merge utilities for joining multiple history series.
@@ -12,3 +16,5 @@ pub mod merge;
 pub mod resample;
 /// Shared helpers used by merge/resample and router.
 pub mod util;
+/// Request windows over stored series.
+pub mod window;
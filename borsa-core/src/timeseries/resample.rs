@@ -478,3 +478,40 @@ pub fn resample_to_minutes_with_meta(
     }
     resample_by(candles, move |ts| choose_bucket_minutes(ts, minutes, meta))
 }
+
+/// Bounds `[start, end)` of the `interval` bar that contains `ts`, aligned in `tz`.
+///
+/// - Intraday intervals are aligned to local midnight and last exactly one interval.
+/// - `D1` bars span a local calendar day and `W1` bars a local week starting Monday, so
+///   they can be 23 or 25 hours longer or shorter around DST changes.
+///
+/// Returns `None` for other calendar intervals (e.g. months) and for timestamps that cannot
+/// be represented.
+#[must_use]
+pub fn interval_bounds(
+    ts: DateTime<Utc>,
+    interval: paft::market::requests::history::Interval,
+    tz: chrono_tz::Tz,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    use paft::market::requests::history::Interval;
+
+    let minutes = interval.minutes().or_else(|| {
+        interval
+            .seconds()
+            .and_then(|s| (s % 60 == 0).then_some(s / 60))
+    });
+    if let Some(minutes) = minutes.filter(|m| *m > 0) {
+        let start = bucket_minutes_with_tz(ts, minutes, tz)?;
+        return Some((start, start + chrono::Duration::minutes(minutes)));
+    }
+    let (start, days) = match interval {
+        Interval::D1 => (bucket_day_with_tz(ts, tz)?, 1),
+        Interval::W1 => (bucket_week_monday_with_tz(ts, tz)?, 7),
+        _ => return None,
+    };
+    let next = start
+        .with_timezone(&tz)
+        .date_naive()
+        .checked_add_signed(chrono::Duration::days(days))?;
+    Some((start, local_midnight_utc_for_date(start, next, tz)?))
+}
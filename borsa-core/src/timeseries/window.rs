@@ -0,0 +1,75 @@
+use chrono::{DateTime, Datelike, Months, TimeDelta, TimeZone, Utc};
+use paft::market::action::Action;
+use paft::market::requests::history::{HistoryRequest, Range};
+use paft::market::responses::history::HistoryResponse;
+
+const fn action_ts(action: &Action) -> DateTime<Utc> {
+    match action {
+        Action::Dividend { ts, .. } | Action::Split { ts, .. } | Action::CapitalGain { ts, .. } => {
+            *ts
+        }
+    }
+}
+
+/// Start of `range` counted back from `last`.
+///
+/// Returns `None` for `max` (no lower bound) and for range codes it does not know.
+#[must_use]
+pub fn range_start(range: Range, last: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let code = serde_json::to_value(range)
+        .ok()?
+        .as_str()?
+        .to_ascii_lowercase();
+    if code == "ytd" {
+        return Utc.with_ymd_and_hms(last.year(), 1, 1, 0, 0, 0).single();
+    }
+    let split = code.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = code.split_at(split);
+    let count: u32 = count.parse().ok()?;
+    match unit {
+        "d" => last.checked_sub_signed(TimeDelta::days(count.into())),
+        "wk" => last.checked_sub_signed(TimeDelta::weeks(count.into())),
+        "mo" => last.checked_sub_months(Months::new(count)),
+        "y" => last.checked_sub_months(Months::new(count.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+/// Keep the part of a stored series that `req` asks for.
+///
+/// Behavior:
+/// - A period keeps candles and actions with `start <= ts < end`.
+/// - A range is measured back from now, so a series that has not been updated lately
+///   returns only the part of it that falls inside the range.
+/// - Actions are dropped unless `req.include_actions()` is set.
+#[must_use]
+pub fn slice_to_request(history: HistoryResponse, req: &HistoryRequest) -> HistoryResponse {
+    slice_to_request_at(history, req, Utc::now())
+}
+
+/// Same as [`slice_to_request`], with ranges measured back from `anchor` instead of now.
+///
+/// Useful for snapshots of past data, e.g. measuring from the newest candle of a file.
+#[must_use]
+pub fn slice_to_request_at(
+    mut history: HistoryResponse,
+    req: &HistoryRequest,
+    anchor: DateTime<Utc>,
+) -> HistoryResponse {
+    let window = if let Some((start, end)) = req.period() {
+        Some((start, Some(end)))
+    } else {
+        req.range()
+            .and_then(|range| range_start(range, anchor))
+            .map(|start| (start, None))
+    };
+    if let Some((start, end)) = window {
+        let keep = |ts: DateTime<Utc>| ts >= start && end.is_none_or(|end| ts < end);
+        history.candles.retain(|c| keep(c.ts));
+        history.actions.retain(|a| keep(action_ts(a)));
+    }
+    if !req.include_actions() {
+        history.actions.clear();
+    }
+    history
+}
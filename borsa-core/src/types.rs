@@ -7,10 +7,15 @@ pub use borsa_types::{BorsaError, Capability};
 pub use borsa_types::ConnectorKey;
 pub use borsa_types::routing_policy::Selector;
 pub use borsa_types::{
-    Attribution, BackoffConfig, BorsaConfig, DownloadReport, FetchStrategy, InfoReport,
-    MergeStrategy, Resampling, SearchReport, Span,
+    AdaptiveRankingConfig, Attribution, BackoffConfig, BorsaConfig, DownloadReport, FetchStrategy,
+    InfoReport, MergeStrategy, Resampling, SearchReport, Span,
+};
+pub use borsa_types::{BorsaSetup, ConnectorSpec, MiddlewareLayer, MiddlewareStack};
+pub use borsa_types::{
+    CacheBackend, CacheConfig, CandleSynthesisConfig, CircuitBreakerConfig, EmptyIntervals,
+    HistoryCacheMode, LateTicks, OverflowPolicy, QuotaConfig, QuotaConsumptionStrategy, QuotaState,
+    RateLimitConfig, RetryConfig, RetryPolicy, StalenessConfig, StalenessThresholds, TradingHours,
 };
-pub use borsa_types::{CacheConfig, QuotaConfig, QuotaConsumptionStrategy, QuotaState};
 pub use borsa_types::{Preference, RoutingContext, RoutingPolicy, RoutingPolicyBuilder, ScopeKey};
 
 pub use paft::domain::{
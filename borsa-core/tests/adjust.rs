@@ -0,0 +1,232 @@
+use std::str::FromStr;
+
+use borsa_core::{
+    Action, AdjustmentAnchor, AdjustmentKind, BorsaError, Candle, Currency, HistoryResponse,
+    IsoCurrency, Money, adjust_candles, adjust_history, adjustment_factors, unadjust_candles,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+
+fn dec(s: &str) -> Decimal {
+    Decimal::from_str(s).unwrap()
+}
+
+fn usd(s: &str) -> Money {
+    Money::new(dec(s), Currency::Iso(IsoCurrency::USD)).unwrap()
+}
+
+fn at(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+    // Daily bars are stamped at the US open, after the 00:00 UTC action timestamps.
+    Utc.with_ymd_and_hms(y, m, d, 13, 30, 0).unwrap()
+}
+
+fn bar(y: i32, m: u32, d: u32, close: &str, volume: u64) -> Candle {
+    Candle {
+        ts: at(y, m, d),
+        open: usd(close),
+        high: usd(close),
+        low: usd(close),
+        close: usd(close),
+        close_unadj: None,
+        volume: Some(volume),
+    }
+}
+
+fn split(y: i32, m: u32, d: u32, numerator: u32, denominator: u32) -> Action {
+    Action::Split {
+        ts: Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap(),
+        numerator,
+        denominator,
+    }
+}
+
+fn dividend(y: i32, m: u32, d: u32, amount: &str) -> Action {
+    Action::Dividend {
+        ts: Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap(),
+        amount: usd(amount),
+    }
+}
+
+fn closes(candles: &[Candle]) -> Vec<Decimal> {
+    candles.iter().map(|c| c.close.amount()).collect()
+}
+
+/// AAPL around its 4-for-1 split on 2020-08-31.
+fn aapl_2020() -> Vec<Candle> {
+    vec![
+        bar(2020, 8, 27, "500.04", 38_888_100),
+        bar(2020, 8, 28, "499.23", 46_907_500),
+        bar(2020, 8, 31, "129.04", 225_702_700),
+        bar(2020, 9, 1, "134.18", 151_948_100),
+    ]
+}
+
+#[test]
+fn back_adjusts_aapl_four_for_one_split() {
+    let out = adjust_candles(
+        aapl_2020(),
+        &[split(2020, 8, 31, 4, 1)],
+        AdjustmentKind::Splits,
+        AdjustmentAnchor::Back,
+    )
+    .unwrap();
+    assert_eq!(
+        closes(&out),
+        [dec("125.01"), dec("124.8075"), dec("129.04"), dec("134.18")]
+    );
+    assert_eq!(out[1].volume, Some(187_630_000));
+    assert_eq!(out[2].volume, Some(225_702_700));
+    assert_eq!(out[1].close_unadj, Some(usd("499.23")));
+}
+
+#[test]
+fn forward_adjusts_aapl_four_for_one_split() {
+    let out = adjust_candles(
+        aapl_2020(),
+        &[split(2020, 8, 31, 4, 1)],
+        AdjustmentKind::Splits,
+        AdjustmentAnchor::Forward,
+    )
+    .unwrap();
+    assert_eq!(
+        closes(&out),
+        [dec("500.04"), dec("499.23"), dec("516.16"), dec("536.72")]
+    );
+    assert_eq!(out[3].volume, Some(37_987_025));
+}
+
+#[test]
+fn compounds_tsla_five_and_three_for_one_splits() {
+    let candles = vec![
+        bar(2020, 8, 28, "2213.40", 20_081_200),
+        bar(2020, 8, 31, "498.32", 118_374_400),
+        bar(2022, 8, 24, "891.29", 33_000_000),
+        bar(2022, 8, 25, "296.07", 53_230_000),
+    ];
+    let actions = [split(2020, 8, 31, 5, 1), split(2022, 8, 25, 3, 1)];
+    let out = adjust_candles(
+        candles,
+        &actions,
+        AdjustmentKind::Splits,
+        AdjustmentAnchor::Back,
+    )
+    .unwrap();
+    assert_eq!(out[0].close.amount(), dec("147.56"));
+    assert_eq!(out[1].close.amount(), dec("166.106667"));
+    assert_eq!(out[3].close.amount(), dec("296.07"));
+    assert_eq!(out[0].volume, Some(301_218_000));
+}
+
+#[test]
+fn nvda_ten_for_one_split_factor() {
+    let candles = vec![
+        bar(2024, 6, 7, "1208.88", 41_238_600),
+        bar(2024, 6, 10, "121.79", 314_162_700),
+    ];
+    let factors = adjustment_factors(
+        &candles,
+        &[split(2024, 6, 10, 10, 1)],
+        AdjustmentKind::Splits,
+        AdjustmentAnchor::Back,
+    )
+    .unwrap();
+    assert_eq!(factors[0].price, dec("0.1"));
+    assert_eq!(factors[0].volume, dec("10"));
+    assert_eq!(factors[1].price, Decimal::ONE);
+}
+
+#[test]
+fn total_return_folds_in_dividends_against_the_previous_close() {
+    // AAPL paid $0.82 (pre-split) with ex-date 2020-08-07.
+    let candles = vec![
+        bar(2020, 8, 5, "440.25", 30_498_000),
+        bar(2020, 8, 6, "455.61", 50_607_200),
+        bar(2020, 8, 7, "444.45", 49_511_400),
+    ];
+    let actions = [dividend(2020, 8, 7, "0.82")];
+
+    let splits_only = adjust_candles(
+        candles.clone(),
+        &actions,
+        AdjustmentKind::Splits,
+        AdjustmentAnchor::Back,
+    )
+    .unwrap();
+    assert_eq!(closes(&splits_only), closes(&candles));
+
+    let out = adjust_candles(
+        candles,
+        &actions,
+        AdjustmentKind::TotalReturn,
+        AdjustmentAnchor::Back,
+    )
+    .unwrap();
+    let factor = Decimal::ONE - dec("0.82") / dec("455.61");
+    assert_eq!(out[1].close.amount(), dec("454.79"));
+    assert_eq!(out[0].close.amount(), (dec("440.25") * factor).round_dp(6));
+    assert_eq!(out[2].close.amount(), dec("444.45"));
+    // Dividends do not change volume.
+    assert_eq!(out[0].volume, Some(30_498_000));
+}
+
+#[test]
+fn unadjust_inverts_back_adjustment() {
+    let candles = vec![
+        bar(2020, 8, 5, "440.25", 30_498_000),
+        bar(2020, 8, 6, "455.61", 50_607_200),
+        bar(2020, 8, 7, "444.45", 49_511_400),
+        bar(2020, 8, 28, "499.23", 46_907_500),
+        bar(2020, 8, 31, "129.04", 225_702_700),
+    ];
+    let actions = [dividend(2020, 8, 7, "0.82"), split(2020, 8, 31, 4, 1)];
+    let adjusted = adjust_candles(
+        candles.clone(),
+        &actions,
+        AdjustmentKind::TotalReturn,
+        AdjustmentAnchor::Back,
+    )
+    .unwrap();
+    let raw = unadjust_candles(adjusted, &actions, AdjustmentKind::TotalReturn).unwrap();
+    for (got, want) in raw.iter().zip(&candles) {
+        let diff = (got.close.amount() - want.close.amount()).abs();
+        assert!(
+            diff < dec("0.0001"),
+            "{} vs {}",
+            got.close.amount(),
+            want.close.amount()
+        );
+        assert_eq!(got.volume, want.volume);
+        assert_eq!(got.close_unadj, None);
+    }
+}
+
+#[test]
+fn adjust_history_rejects_adjusted_input_and_bad_actions() {
+    let mut history = HistoryResponse {
+        candles: aapl_2020(),
+        actions: vec![split(2020, 8, 31, 4, 1)],
+        adjusted: false,
+        meta: None,
+    };
+    let out = adjust_history(
+        &history,
+        AdjustmentKind::TotalReturn,
+        AdjustmentAnchor::Back,
+    )
+    .unwrap();
+    assert!(out.adjusted);
+    assert_eq!(out.actions, history.actions);
+
+    history.adjusted = true;
+    let err = adjust_history(&history, AdjustmentKind::Splits, AdjustmentAnchor::Back).unwrap_err();
+    assert!(matches!(err, BorsaError::InvalidArg(_)), "got {err:?}");
+
+    let err = adjust_candles(
+        aapl_2020(),
+        &[dividend(2020, 8, 31, "600")],
+        AdjustmentKind::TotalReturn,
+        AdjustmentAnchor::Back,
+    )
+    .unwrap_err();
+    assert!(matches!(err, BorsaError::Data(_)), "got {err:?}");
+}
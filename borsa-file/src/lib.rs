@@ -0,0 +1,579 @@
+//! Offline connector serving market data from a directory of files.
+//!
+//! [`FileConnector`] reads one subdirectory per symbol under its root:
+//!
+//! ```text
+//! <root>/
+//!   AAPL/
+//!     history_1d.parquet             candles for one interval (.parquet, .csv or .json)
+//!     history_1h.csv
+//!     profile.json                   Profile
+//!     earnings.json                  Earnings
+//!     income_statement_annual.json   Vec<IncomeStatementRow> (and ..._quarterly.json)
+//!     balance_sheet_annual.json      Vec<BalanceSheetRow> (and ..._quarterly.json)
+//!     cashflow_annual.json           Vec<CashflowRow> (and ..._quarterly.json)
+//!     calendar.json                  Calendar
+//! ```
+//!
+//! History file names end in an interval code (`1d`, `1h`, `5m`, ...), and the connector
+//! offers exactly the intervals that have a file for at least one symbol. A JSON history file
+//! holds a whole serialized `HistoryResponse`, including actions and metadata; CSV and Parquet
+//! history files hold one candle per row with the columns `ts`, `open`, `high`, `low`,
+//! `close` and optionally `close_unadj`, `volume` and `currency`. Other JSON files hold the
+//! serialized `borsa-core` type named above. When one file exists in several formats,
+//! Parquet is preferred over CSV, and CSV over JSON.
+//!
+//! Range requests count back from the newest candle of the file rather than from now, so a
+//! snapshot of past data answers them like it did when it was taken.
+//!
+//! Quotes are the last close (and the close before it) of the symbol's daily history, or of
+//! its first history file when it has no daily one.
+//!
+//! The directory is indexed when the connector is created. Call [`FileConnector::rescan`],
+//! or start [`FileConnector::watch`], to pick up files added later. Files are read on the
+//! blocking thread pool. Parsed history files are kept in memory until their modification
+//! time or size changes, so rewriting one takes effect on the next call; write new files
+//! under another name (e.g. with a `.tmp` suffix) and rename them into place so no call
+//! reads half a file.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use borsa_core::connector::{
+    BalanceSheetProvider, BorsaConnector, CalendarProvider, CashflowProvider, EarningsProvider,
+    HistoryProvider, IncomeStatementProvider, ProfileProvider, QuoteProvider, intern_intervals,
+};
+use borsa_core::{
+    AssetKind, BalanceSheetRow, BorsaError, Calendar, CashflowRow, ConnectorRegistry, Currency,
+    Earnings, HistoryRequest, HistoryResponse, IncomeStatementRow, Instrument, Interval,
+    IsoCurrency, Profile, Quote,
+};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+
+mod table;
+
+const NAME: &str = "borsa-file";
+const HISTORY_PREFIX: &str = "history_";
+/// Recognized extensions, most preferred first.
+const EXTENSIONS: [&str; 3] = ["parquet", "csv", "json"];
+
+/// Files found for one symbol.
+#[derive(Clone, Default)]
+struct SymbolFiles {
+    history: Vec<(Interval, PathBuf)>,
+    // file stem -> path, for everything that is not history
+    documents: HashMap<String, PathBuf>,
+}
+
+struct Index {
+    symbols: HashMap<String, SymbolFiles>,
+    intervals: &'static [Interval],
+}
+
+/// A parsed history file and the modification time and size it was parsed at.
+struct CachedHistory {
+    stamp: (SystemTime, u64),
+    history: Arc<HistoryResponse>,
+}
+
+type HistoryCache = Arc<Mutex<HashMap<PathBuf, CachedHistory>>>;
+
+/// Connector serving history, quotes, profiles and fundamentals from local files.
+///
+/// See the [crate documentation](crate) for the expected directory layout.
+pub struct FileConnector {
+    root: PathBuf,
+    currency: Currency,
+    index: RwLock<Index>,
+    histories: HistoryCache,
+}
+
+fn io_error(path: &Path, err: &std::io::Error) -> BorsaError {
+    BorsaError::Other(format!("{NAME} {}: {err}", path.display()))
+}
+
+fn extension_rank(path: &Path) -> Option<usize> {
+    let ext = path.extension()?.to_str()?;
+    EXTENSIONS.iter().position(|e| e.eq_ignore_ascii_case(ext))
+}
+
+/// Interval for a file-name code, accepted in any case like the CLI's `--interval`.
+fn parse_interval(code: &str) -> Option<Interval> {
+    [code.to_string(), code.to_lowercase(), code.to_uppercase()]
+        .into_iter()
+        .find_map(|candidate| serde_json::from_value(serde_json::Value::String(candidate)).ok())
+}
+
+fn scan_symbol(dir: &Path) -> Result<SymbolFiles, BorsaError> {
+    // stem -> (extension rank, path)
+    let mut best: HashMap<String, (usize, PathBuf)> = HashMap::new();
+    for entry in fs::read_dir(dir).map_err(|e| io_error(dir, &e))? {
+        let path = entry.map_err(|e| io_error(dir, &e))?.path();
+        let Some(rank) = extension_rank(&path) else {
+            continue;
+        };
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if best.get(stem).is_none_or(|(r, _)| rank < *r) {
+            best.insert(stem.to_string(), (rank, path));
+        }
+    }
+
+    let mut files = SymbolFiles::default();
+    let mut stems: Vec<_> = best.into_iter().collect();
+    stems.sort_by(|a, b| a.0.cmp(&b.0));
+    for (stem, (_, path)) in stems {
+        match stem.strip_prefix(HISTORY_PREFIX).and_then(parse_interval) {
+            Some(interval) if !files.history.iter().any(|(i, _)| *i == interval) => {
+                files.history.push((interval, path));
+            }
+            Some(_) => {}
+            None => {
+                files.documents.insert(stem, path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn scan(root: &Path) -> Result<Index, BorsaError> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(root).map_err(|e| io_error(root, &e))? {
+        let path = entry.map_err(|e| io_error(root, &e))?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            names.push((name.to_string(), path));
+        }
+    }
+    names.sort();
+
+    let mut symbols = HashMap::with_capacity(names.len());
+    let mut intervals = Vec::new();
+    for (name, dir) in names {
+        let files = scan_symbol(&dir)?;
+        for (interval, _) in &files.history {
+            if !intervals.contains(interval) {
+                intervals.push(*interval);
+            }
+        }
+        symbols.insert(name, files);
+    }
+    Ok(Index {
+        symbols,
+        intervals: intern_intervals(&intervals),
+    })
+}
+
+fn require_security_symbol_str(inst: &Instrument) -> Result<&str, BorsaError> {
+    match inst.id() {
+        borsa_core::IdentifierScheme::Security(sec) => Ok(sec.symbol.as_str()),
+        borsa_core::IdentifierScheme::Prediction(_) => Err(BorsaError::unsupported(
+            "instrument scheme (borsa-file/security-only)",
+        )),
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, BorsaError> {
+    let raw = fs::read(path).map_err(|e| io_error(path, &e))?;
+    serde_json::from_slice(&raw)
+        .map_err(|e| BorsaError::Data(format!("{NAME} {}: {e}", path.display())))
+}
+
+/// Run file IO and parsing on the blocking thread pool.
+async fn blocking<T, F>(f: F) -> Result<T, BorsaError>
+where
+    F: FnOnce() -> Result<T, BorsaError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| BorsaError::Other(format!("{NAME} read task failed: {e}")))?
+}
+
+fn parse_history(path: &Path, currency: &Currency) -> Result<HistoryResponse, BorsaError> {
+    match extension_rank(path).map(|rank| EXTENSIONS[rank]) {
+        Some("json") => read_json(path),
+        parquet_or_csv => Ok(HistoryResponse {
+            candles: table::read_candles(path, parquet_or_csv == Some("parquet"), currency)?,
+            actions: vec![],
+            adjusted: false,
+            meta: None,
+        }),
+    }
+}
+
+/// Parse the history file at `path`, or reuse the cached parse while the file is unchanged.
+fn cached_history(
+    cache: &HistoryCache,
+    path: &Path,
+    currency: &Currency,
+) -> Result<Arc<HistoryResponse>, BorsaError> {
+    let meta = fs::metadata(path).map_err(|e| io_error(path, &e))?;
+    let modified = meta.modified().map_err(|e| io_error(path, &e))?;
+    let stamp = (modified, meta.len());
+    if let Some(hit) = cache.lock().expect("lock poisoned").get(path)
+        && hit.stamp == stamp
+    {
+        return Ok(Arc::clone(&hit.history));
+    }
+    let history = Arc::new(parse_history(path, currency)?);
+    cache.lock().expect("lock poisoned").insert(
+        path.to_path_buf(),
+        CachedHistory {
+            stamp,
+            history: Arc::clone(&history),
+        },
+    );
+    Ok(history)
+}
+
+impl FileConnector {
+    /// Index the symbol directories under `root`.
+    ///
+    /// Prices in CSV and Parquet files without a `currency` column are in USD; see
+    /// [`with_currency`](Self::with_currency).
+    ///
+    /// # Errors
+    /// Returns an error if `root` or one of its subdirectories cannot be read.
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, BorsaError> {
+        let root = root.into();
+        let index = scan(&root)?;
+        Ok(Self {
+            root,
+            currency: Currency::Iso(IsoCurrency::USD),
+            index: RwLock::new(index),
+            histories: Arc::default(),
+        })
+    }
+
+    /// Price CSV and Parquet rows without a `currency` value in `currency`.
+    #[must_use]
+    pub fn with_currency(mut self, currency: Currency) -> Self {
+        self.currency = currency;
+        self
+    }
+
+    /// Directory this connector serves.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Index the directory again, picking up added and removed files.
+    ///
+    /// Cached histories of files that are no longer indexed are dropped. On error the
+    /// previous index is kept.
+    ///
+    /// # Errors
+    /// Returns an error if the root or one of its subdirectories cannot be read.
+    ///
+    /// # Panics
+    /// Panics if the internal lock is poisoned.
+    pub fn rescan(&self) -> Result<(), BorsaError> {
+        let index = scan(&self.root)?;
+        let indexed: HashSet<&PathBuf> = index
+            .symbols
+            .values()
+            .flat_map(|files| files.history.iter().map(|(_, path)| path))
+            .collect();
+        self.histories
+            .lock()
+            .expect("lock poisoned")
+            .retain(|path, _| indexed.contains(path));
+        *self.index.write().expect("lock poisoned") = index;
+        Ok(())
+    }
+
+    /// Rescan the directory every `every` on the current Tokio runtime.
+    ///
+    /// The task holds only a weak reference and ends once the connector is dropped; abort the
+    /// returned handle to stop it earlier. A failed scan keeps the previous index and is
+    /// retried on the next tick.
+    ///
+    /// # Panics
+    /// Panics if called outside a Tokio runtime.
+    pub fn watch(self: &Arc<Self>, every: Duration) -> tokio::task::JoinHandle<()> {
+        let connector = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(every);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick fires immediately; the directory was just indexed.
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let Some(connector) = connector.upgrade() else {
+                    break;
+                };
+                let _ = tokio::task::spawn_blocking(move || connector.rescan()).await;
+            }
+        })
+    }
+
+    /// Add a `borsa-file` constructor to `registry`.
+    ///
+    /// Options: `root` (required directory), `currency` (code for rows without one,
+    /// default `USD`) and `watch_ms` (rescan period; no watching when absent).
+    pub fn register(registry: &mut ConnectorRegistry) {
+        registry.register(NAME, Self::from_config);
+    }
+
+    /// Add a `borsa-file` constructor to the process-wide registry.
+    pub fn register_global() {
+        borsa_core::register_connector(NAME, Self::from_config);
+    }
+
+    fn from_config(config: &serde_json::Value) -> Result<Arc<dyn BorsaConnector>, BorsaError> {
+        let invalid = |what: &str| BorsaError::InvalidArg(format!("{NAME}: {what}"));
+        let map = config
+            .as_object()
+            .ok_or_else(|| invalid("options must include `root`"))?;
+        if let Some(key) = map
+            .keys()
+            .find(|k| !matches!(k.as_str(), "root" | "currency" | "watch_ms"))
+        {
+            return Err(invalid(&format!("unknown option `{key}`")));
+        }
+        let root = map
+            .get("root")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| invalid("`root` must be a directory path"))?;
+        let mut connector = Self::new(root)?;
+        if let Some(code) = map.get("currency") {
+            let code = code
+                .as_str()
+                .ok_or_else(|| invalid("`currency` must be a currency code"))?;
+            let currency = Currency::from_str(code)
+                .map_err(|e| invalid(&format!("currency `{code}`: {e}")))?;
+            connector = connector.with_currency(currency);
+        }
+        let connector = Arc::new(connector);
+        if let Some(ms) = map.get("watch_ms") {
+            let ms = ms
+                .as_u64()
+                .filter(|ms| *ms > 0)
+                .ok_or_else(|| invalid("`watch_ms` must be a positive integer"))?;
+            if tokio::runtime::Handle::try_current().is_err() {
+                return Err(invalid("`watch_ms` requires a running Tokio runtime"));
+            }
+            connector.watch(Duration::from_millis(ms));
+        }
+        Ok(connector)
+    }
+
+    fn files(&self, instrument: &Instrument) -> Result<SymbolFiles, BorsaError> {
+        let symbol = require_security_symbol_str(instrument)?;
+        self.index
+            .read()
+            .expect("lock poisoned")
+            .symbols
+            .get(symbol)
+            .cloned()
+            .ok_or_else(|| BorsaError::not_found(format!("{NAME} files for {symbol}")))
+    }
+
+    async fn document<T: DeserializeOwned + Send + 'static>(
+        &self,
+        instrument: &Instrument,
+        stem: &str,
+    ) -> Result<T, BorsaError> {
+        let files = self.files(instrument)?;
+        let path = files.documents.get(stem).cloned().ok_or_else(|| {
+            BorsaError::not_found(format!(
+                "{NAME} {stem} for {}",
+                instrument.id().unique_key()
+            ))
+        })?;
+        blocking(move || read_json(&path)).await
+    }
+
+    async fn statement<T: DeserializeOwned + Send + 'static>(
+        &self,
+        instrument: &Instrument,
+        name: &str,
+        quarterly: bool,
+    ) -> Result<T, BorsaError> {
+        let period = if quarterly { "quarterly" } else { "annual" };
+        self.document(instrument, &format!("{name}_{period}")).await
+    }
+
+    async fn read_history(&self, path: &Path) -> Result<Arc<HistoryResponse>, BorsaError> {
+        let cache = Arc::clone(&self.histories);
+        let path = path.to_path_buf();
+        let currency = self.currency.clone();
+        blocking(move || cached_history(&cache, &path, &currency)).await
+    }
+}
+
+#[async_trait]
+impl BorsaConnector for FileConnector {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+    fn vendor(&self) -> &'static str {
+        "File"
+    }
+
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+
+    fn as_quote_provider(&self) -> Option<&dyn QuoteProvider> {
+        Some(self as &dyn QuoteProvider)
+    }
+    fn as_history_provider(&self) -> Option<&dyn HistoryProvider> {
+        Some(self as &dyn HistoryProvider)
+    }
+    fn as_profile_provider(&self) -> Option<&dyn ProfileProvider> {
+        Some(self as &dyn ProfileProvider)
+    }
+    fn as_earnings_provider(&self) -> Option<&dyn EarningsProvider> {
+        Some(self as &dyn EarningsProvider)
+    }
+    fn as_income_statement_provider(&self) -> Option<&dyn IncomeStatementProvider> {
+        Some(self as &dyn IncomeStatementProvider)
+    }
+    fn as_balance_sheet_provider(&self) -> Option<&dyn BalanceSheetProvider> {
+        Some(self as &dyn BalanceSheetProvider)
+    }
+    fn as_cashflow_provider(&self) -> Option<&dyn CashflowProvider> {
+        Some(self as &dyn CashflowProvider)
+    }
+    fn as_calendar_provider(&self) -> Option<&dyn CalendarProvider> {
+        Some(self as &dyn CalendarProvider)
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for FileConnector {
+    async fn quote(&self, instrument: &Instrument) -> Result<Quote, BorsaError> {
+        let files = self.files(instrument)?;
+        let (interval, path) = files
+            .history
+            .iter()
+            .find(|(i, _)| *i == Interval::D1)
+            .or_else(|| files.history.first())
+            .ok_or_else(|| {
+                BorsaError::not_found(format!(
+                    "{NAME} history for {}",
+                    instrument.id().unique_key()
+                ))
+            })?;
+        let history = self.read_history(path).await?;
+        let candles = &history.candles;
+        let last = candles.last().ok_or_else(|| {
+            BorsaError::not_found(format!(
+                "{NAME} candles for {}",
+                instrument.id().unique_key()
+            ))
+        })?;
+        let previous = candles.len().checked_sub(2).map(|i| &candles[i]);
+        Ok(Quote {
+            instrument: instrument.clone(),
+            shortname: None,
+            price: Some(last.close.clone()),
+            previous_close: previous.map(|c| c.close.clone()),
+            exchange: None,
+            market_state: None,
+            day_volume: if *interval == Interval::D1 {
+                last.volume
+            } else {
+                None
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl HistoryProvider for FileConnector {
+    async fn history(
+        &self,
+        instrument: &Instrument,
+        req: HistoryRequest,
+    ) -> Result<HistoryResponse, BorsaError> {
+        let files = self.files(instrument)?;
+        let path = files
+            .history
+            .iter()
+            .find(|(i, _)| *i == req.interval())
+            .map(|(_, path)| path)
+            .ok_or_else(|| {
+                BorsaError::not_found(format!(
+                    "{NAME} {:?} history for {}",
+                    req.interval(),
+                    instrument.id().unique_key()
+                ))
+            })?;
+        let history = Arc::unwrap_or_clone(self.read_history(path).await?);
+        // Files are snapshots; ranges count back from their newest candle.
+        let anchor = history.candles.last().map_or_else(Utc::now, |c| c.ts);
+        Ok(borsa_core::slice_to_request_at(history, &req, anchor))
+    }
+
+    fn supported_history_intervals(&self, _kind: AssetKind) -> &'static [Interval] {
+        self.index.read().expect("lock poisoned").intervals
+    }
+}
+
+#[async_trait]
+impl ProfileProvider for FileConnector {
+    async fn profile(&self, instrument: &Instrument) -> Result<Profile, BorsaError> {
+        self.document(instrument, "profile").await
+    }
+}
+
+#[async_trait]
+impl EarningsProvider for FileConnector {
+    async fn earnings(&self, instrument: &Instrument) -> Result<Earnings, BorsaError> {
+        self.document(instrument, "earnings").await
+    }
+}
+
+#[async_trait]
+impl IncomeStatementProvider for FileConnector {
+    async fn income_statement(
+        &self,
+        instrument: &Instrument,
+        quarterly: bool,
+    ) -> Result<Vec<IncomeStatementRow>, BorsaError> {
+        self.statement(instrument, "income_statement", quarterly)
+            .await
+    }
+}
+
+#[async_trait]
+impl BalanceSheetProvider for FileConnector {
+    async fn balance_sheet(
+        &self,
+        instrument: &Instrument,
+        quarterly: bool,
+    ) -> Result<Vec<BalanceSheetRow>, BorsaError> {
+        self.statement(instrument, "balance_sheet", quarterly).await
+    }
+}
+
+#[async_trait]
+impl CashflowProvider for FileConnector {
+    async fn cashflow(
+        &self,
+        instrument: &Instrument,
+        quarterly: bool,
+    ) -> Result<Vec<CashflowRow>, BorsaError> {
+        self.statement(instrument, "cashflow", quarterly).await
+    }
+}
+
+#[async_trait]
+impl CalendarProvider for FileConnector {
+    async fn calendar(&self, instrument: &Instrument) -> Result<Calendar, BorsaError> {
+        self.document(instrument, "calendar").await
+    }
+}
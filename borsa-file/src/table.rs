@@ -0,0 +1,149 @@
+//! Candle tables stored as CSV or Parquet.
+//!
+//! A table has one row per candle. `ts`, `open`, `high`, `low` and `close` are required;
+//! `close_unadj`, `volume` and `currency` are optional. Column order does not matter and other
+//! columns are ignored. `ts` is seconds since the Unix epoch, an RFC 3339 timestamp, a
+//! `YYYY-MM-DD HH:MM:SS` UTC timestamp or a `YYYY-MM-DD` date. Prices in CSV files are read as
+//! text, so they keep their exact decimal digits.
+
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+
+use borsa_core::{BorsaError, Candle, Currency, Decimal, Money};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use polars::prelude::*;
+
+fn table_err(path: &Path, e: impl std::fmt::Display) -> BorsaError {
+    BorsaError::Other(format!("borsa-file {}: {e}", path.display()))
+}
+
+fn data_err(path: &Path, row: usize, what: &str) -> BorsaError {
+    BorsaError::Data(format!("borsa-file {} row {row}: {what}", path.display()))
+}
+
+/// Read the candles of a CSV (or, with `parquet`, a Parquet) table, sorted by time.
+///
+/// Rows without a `currency` value are priced in `currency`.
+pub(crate) fn read_candles(
+    path: &Path,
+    parquet: bool,
+    currency: &Currency,
+) -> Result<Vec<Candle>, BorsaError> {
+    let frame = if parquet {
+        let file = File::open(path).map_err(|e| table_err(path, e))?;
+        ParquetReader::new(file).finish()
+    } else {
+        // A zero-row inference window reads every column as text.
+        CsvReadOptions::default()
+            .with_has_header(true)
+            .with_infer_schema_length(Some(0))
+            .try_into_reader_with_file_path(Some(path.to_path_buf()))
+            .and_then(SerReader::finish)
+    }
+    .map_err(|e| table_err(path, e))?;
+
+    let column = |name: &str| text(&frame, name).map_err(|e| table_err(path, e));
+    let required = |name: &str| {
+        column(name)?.ok_or_else(|| table_err(path, format!("missing column `{name}`")))
+    };
+    let ts = required("ts")?;
+    let open = required("open")?;
+    let high = required("high")?;
+    let low = required("low")?;
+    let close = required("close")?;
+    let close_unadj = column("close_unadj")?;
+    let volume = column("volume")?;
+    let currencies = column("currency")?;
+
+    let mut candles = (0..frame.height())
+        .map(|i| -> Result<Candle, BorsaError> {
+            let currency = match currencies.as_ref().and_then(|c| c[i].as_deref()) {
+                Some(code) => Currency::from_str(code)
+                    .map_err(|e| data_err(path, i, &format!("currency '{code}': {e}")))?,
+                None => currency.clone(),
+            };
+            let money = |col: &[Option<String>], name: &str| {
+                col[i]
+                    .as_deref()
+                    .map(|raw| {
+                        let amount = Decimal::from_str(raw.trim())
+                            .map_err(|e| data_err(path, i, &format!("{name} '{raw}': {e}")))?;
+                        Money::new(amount, currency.clone())
+                            .map_err(|e| data_err(path, i, &e.to_string()))
+                    })
+                    .transpose()
+            };
+            let price = |col: &[Option<String>], name: &str| {
+                money(col, name)?.ok_or_else(|| data_err(path, i, &format!("missing {name}")))
+            };
+            let raw_ts = ts[i]
+                .as_deref()
+                .ok_or_else(|| data_err(path, i, "missing ts"))?;
+            Ok(Candle {
+                ts: parse_ts(raw_ts).ok_or_else(|| data_err(path, i, &format!("ts '{raw_ts}'")))?,
+                open: price(&open, "open")?,
+                high: price(&high, "high")?,
+                low: price(&low, "low")?,
+                close: price(&close, "close")?,
+                close_unadj: match &close_unadj {
+                    Some(col) => money(col, "close_unadj")?,
+                    None => None,
+                },
+                volume: volume
+                    .as_ref()
+                    .and_then(|col| col[i].as_deref())
+                    .map(|raw| {
+                        parse_volume(raw)
+                            .ok_or_else(|| data_err(path, i, &format!("volume '{raw}'")))
+                    })
+                    .transpose()?,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    candles.sort_by_key(|c| c.ts);
+    Ok(candles)
+}
+
+/// Values of column `name` as text, or `None` when the table has no such column.
+fn text(frame: &DataFrame, name: &str) -> PolarsResult<Option<Vec<Option<String>>>> {
+    let Ok(column) = frame.column(name) else {
+        return Ok(None);
+    };
+    let column = column.cast(&DataType::String)?;
+    Ok(Some(
+        column
+            .str()?
+            .into_iter()
+            .map(|v| v.map(str::to_owned))
+            .collect(),
+    ))
+}
+
+fn parse_ts(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if let Ok(secs) = raw.parse::<i64>() {
+        return DateTime::from_timestamp(secs, 0);
+    }
+    if let Ok(ts) = DateTime::parse_from_rfc3339(raw) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    if let Ok(ts) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%.f") {
+        return Some(ts.and_utc());
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)
+        .map(|ts| ts.and_utc())
+}
+
+/// Volumes stored as floats (`1000.0`) are accepted when they are whole numbers.
+fn parse_volume(raw: &str) -> Option<u64> {
+    let raw = raw.trim();
+    let whole = match raw.split_once('.') {
+        Some((int, frac)) if frac.bytes().all(|b| b == b'0') => int,
+        Some(_) => return None,
+        None => raw,
+    };
+    whole.parse().ok()
+}
@@ -0,0 +1,251 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use borsa_core::{
+    AssetKind, BorsaConnector, BorsaError, Calendar, ConnectorRegistry, Currency, HistoryRequest,
+    Instrument, Interval, IsoCurrency, Money, Range,
+};
+use borsa_file::FileConnector;
+use chrono::TimeZone;
+
+fn write(root: &Path, symbol: &str, file: &str, contents: &str) {
+    let dir = root.join(symbol);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(file), contents).unwrap();
+}
+
+/// Daily candles for 2023-01-01 through 2023-01-`days`, closing at `100 + day + 0.25`.
+fn daily_csv(days: u32) -> String {
+    let mut csv = String::from("ts,open,high,low,close,volume\n");
+    for day in 1..=days {
+        let close = 100 + day;
+        writeln!(
+            csv,
+            "2023-01-{day:02},{close},{},{},{close}.25,{}",
+            close + 1,
+            close - 1,
+            day * 1_000
+        )
+        .unwrap();
+    }
+    csv
+}
+
+fn inst(symbol: &str) -> Instrument {
+    Instrument::from_symbol(symbol, AssetKind::Equity).expect("valid symbol")
+}
+
+fn usd(amount: &str) -> Money {
+    Money::from_canonical_str(amount, Currency::Iso(IsoCurrency::USD)).unwrap()
+}
+
+#[tokio::test]
+async fn csv_history_is_cut_to_the_requested_range() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    write(dir, "AAPL", "history_1d.csv", &daily_csv(10));
+    let file = FileConnector::new(dir).unwrap();
+    let hp = file.as_history_provider().unwrap();
+
+    let req = HistoryRequest::try_from_range(Range::D5, Interval::D1).unwrap();
+    let history = hp.history(&inst("AAPL"), req).await.unwrap();
+    // Five days back from the newest candle, inclusive.
+    assert_eq!(history.candles.len(), 6);
+    assert_eq!(
+        history.candles[0].ts,
+        chrono::Utc.with_ymd_and_hms(2023, 1, 5, 0, 0, 0).unwrap()
+    );
+    assert_eq!(history.candles[5].close, usd("110.25"));
+    assert_eq!(history.candles[5].volume, Some(10_000));
+
+    let req = HistoryRequest::try_from_range(Range::Max, Interval::D1).unwrap();
+    let history = hp.history(&inst("AAPL"), req).await.unwrap();
+    assert_eq!(history.candles.len(), 10);
+}
+
+#[tokio::test]
+async fn parsed_histories_are_reused_until_the_file_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    write(dir, "AAPL", "history_1d.csv", &daily_csv(3));
+    let file = FileConnector::new(dir).unwrap();
+    let hp = file.as_history_provider().unwrap();
+    let candles = || async {
+        let req = HistoryRequest::try_from_range(Range::Max, Interval::D1).unwrap();
+        hp.history(&inst("AAPL"), req).await.unwrap().candles.len()
+    };
+    assert_eq!(candles().await, 3);
+
+    // Same size and modification time: the cached parse is served.
+    let path = dir.join("AAPL").join("history_1d.csv");
+    let modified = fs::metadata(&path).unwrap().modified().unwrap();
+    fs::write(&path, "x".repeat(daily_csv(3).len())).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+    assert_eq!(candles().await, 3);
+
+    // A rewrite is picked up on the next call.
+    write(dir, "AAPL", "history_1d.csv", &daily_csv(5));
+    assert_eq!(candles().await, 5);
+}
+
+#[tokio::test]
+async fn quote_is_the_last_daily_close() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    write(dir, "AAPL", "history_1d.csv", &daily_csv(3));
+    let file = FileConnector::new(dir).unwrap();
+
+    let quote = file
+        .as_quote_provider()
+        .unwrap()
+        .quote(&inst("AAPL"))
+        .await
+        .unwrap();
+    assert_eq!(quote.price, Some(usd("103.25")));
+    assert_eq!(quote.previous_close, Some(usd("102.25")));
+    assert_eq!(quote.day_volume, Some(3_000));
+
+    let err = file
+        .as_quote_provider()
+        .unwrap()
+        .quote(&inst("MSFT"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, BorsaError::NotFound { .. }), "got {err:?}");
+}
+
+#[tokio::test]
+async fn intervals_follow_the_files_after_a_rescan() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    write(dir, "AAPL", "history_1d.csv", &daily_csv(2));
+    let file = FileConnector::new(dir).unwrap();
+    let hp = file.as_history_provider().unwrap();
+    assert_eq!(
+        hp.supported_history_intervals(AssetKind::Equity),
+        [Interval::D1]
+    );
+
+    write(dir, "MSFT", "history_1h.csv", &daily_csv(2));
+    assert_eq!(
+        hp.supported_history_intervals(AssetKind::Equity),
+        [Interval::D1]
+    );
+    file.rescan().unwrap();
+    let supported = hp.supported_history_intervals(AssetKind::Equity);
+    assert!(supported.contains(&Interval::D1) && supported.contains(&Interval::I1h));
+
+    let req = HistoryRequest::try_from_range(Range::Max, Interval::I1h).unwrap();
+    let history = hp.history(&inst("MSFT"), req).await.unwrap();
+    assert_eq!(history.candles.len(), 2);
+    let req = HistoryRequest::try_from_range(Range::Max, Interval::I1h).unwrap();
+    let err = hp.history(&inst("AAPL"), req).await.unwrap_err();
+    assert!(matches!(err, BorsaError::NotFound { .. }), "got {err:?}");
+}
+
+#[tokio::test]
+async fn watch_picks_up_new_symbols() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let file = Arc::new(FileConnector::new(dir).unwrap());
+    let watcher = file.watch(Duration::from_millis(20));
+
+    write(dir, "NVDA", "history_1d.csv", &daily_csv(2));
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let quote = file
+        .as_quote_provider()
+        .unwrap()
+        .quote(&inst("NVDA"))
+        .await
+        .unwrap();
+    assert_eq!(quote.price, Some(usd("102.25")));
+    watcher.abort();
+}
+
+#[tokio::test]
+async fn fundamentals_are_read_from_json_documents() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let calendar = Calendar {
+        earnings_dates: vec![chrono::Utc.with_ymd_and_hms(2023, 2, 1, 0, 0, 0).unwrap()],
+        ex_dividend_date: None,
+        dividend_payment_date: None,
+    };
+    write(
+        dir,
+        "AAPL",
+        "calendar.json",
+        &serde_json::to_string(&calendar).unwrap(),
+    );
+    write(dir, "AAPL", "income_statement_quarterly.json", "[]");
+    let file = FileConnector::new(dir).unwrap();
+
+    let got = file
+        .as_calendar_provider()
+        .unwrap()
+        .calendar(&inst("AAPL"))
+        .await
+        .unwrap();
+    assert_eq!(got, calendar);
+    let rows = file
+        .as_income_statement_provider()
+        .unwrap()
+        .income_statement(&inst("AAPL"), true)
+        .await
+        .unwrap();
+    assert!(rows.is_empty());
+    let err = file
+        .as_income_statement_provider()
+        .unwrap()
+        .income_statement(&inst("AAPL"), false)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, BorsaError::NotFound { .. }), "got {err:?}");
+    let err = file
+        .as_profile_provider()
+        .unwrap()
+        .profile(&inst("AAPL"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, BorsaError::NotFound { .. }), "got {err:?}");
+}
+
+#[test]
+fn registry_builds_from_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    write(dir, "AAPL", "history_1d.csv", &daily_csv(2));
+    let mut registry = ConnectorRegistry::new();
+    FileConnector::register(&mut registry);
+
+    let connector = registry
+        .build(
+            "borsa-file",
+            &serde_json::json!({ "root": dir, "currency": "EUR" }),
+        )
+        .unwrap();
+    assert_eq!(connector.name(), "borsa-file");
+
+    let err = registry
+        .build("borsa-file", &serde_json::json!({ "path": dir }))
+        .err()
+        .expect("unknown option");
+    assert!(matches!(err, BorsaError::InvalidArg(_)), "got {err:?}");
+    // Watching needs a runtime to run on.
+    let err = registry
+        .build(
+            "borsa-file",
+            &serde_json::json!({ "root": dir, "watch_ms": 100 }),
+        )
+        .err()
+        .expect("no runtime");
+    assert!(matches!(err, BorsaError::InvalidArg(_)), "got {err:?}");
+}
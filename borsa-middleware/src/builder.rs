@@ -32,7 +32,9 @@
 //! This convention matches [`MiddlewareStack`](borsa_types::MiddlewareStack) where
 //! `layers[0]` is the outermost layer.
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -42,10 +44,288 @@ use borsa_core::{
     middleware::{MiddlewareDescriptor, ValidationContext},
 };
 use borsa_types::{
-    CacheConfig, MiddlewareLayer, MiddlewareStack, QuotaConfig, QuotaConsumptionStrategy,
+    CacheBackend, CacheConfig, CircuitBreakerConfig, HistoryCacheMode, MiddlewareLayer,
+    MiddlewareStack, QuotaConfig, QuotaConsumptionStrategy, RateLimitConfig, RetryConfig,
+    RetryPolicy,
 };
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use serde_json::json;
 
+/// Duration as whole milliseconds, the unit every layer config uses.
+fn millis(d: Duration) -> u64 {
+    u64::try_from(d.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Parse one layer config strictly.
+///
+/// A `null` config stands for an empty one. Missing fields take the middleware's defaults;
+/// unknown fields and values of the wrong type are errors, returned as the path inside the
+/// config (empty for the config itself) and a message.
+fn parse_layer<T: DeserializeOwned>(config: &serde_json::Value) -> Result<T, (String, String)> {
+    let value = if config.is_null() {
+        json!({})
+    } else {
+        config.clone()
+    };
+    serde_path_to_error::deserialize(value).map_err(|e| {
+        let path = e.path().to_string();
+        let path = if path == "." { String::new() } else { path };
+        (path, e.into_inner().to_string())
+    })
+}
+
+/// Parse one layer config leniently, for stacks that may come from another version.
+///
+/// Unknown top-level fields and fields with invalid values are dropped, with a warning, so
+/// they take the middleware's defaults instead of removing the whole layer.
+fn parse_layer_lenient<T: DeserializeOwned + Default>(
+    _name: &str,
+    config: &serde_json::Value,
+) -> T {
+    let mut value = if config.is_object() {
+        config.clone()
+    } else {
+        json!({})
+    };
+    loop {
+        let (path, message) = match parse_layer::<T>(&value) {
+            Ok(parsed) => return parsed,
+            Err(err) => err,
+        };
+        // Unknown fields are reported on the config itself; the name is in the message.
+        let field = path
+            .split(['.', '['])
+            .next()
+            .filter(|f| !f.is_empty())
+            .or_else(|| message.strip_prefix("unknown field `")?.split('`').next())
+            .map(str::to_string);
+        let removed = field
+            .as_deref()
+            .and_then(|f| value.as_object_mut()?.remove(f));
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            target = "borsa::middleware::builder",
+            layer = _name,
+            field = field.as_deref().unwrap_or_default(),
+            %message,
+            "ignoring invalid middleware layer config value"
+        );
+        if removed.is_none() {
+            return T::default();
+        }
+    }
+}
+
+/// `CachingMiddleware` layer config.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CacheLayer {
+    default_ttl_ms: u64,
+    default_max_entries: usize,
+    per_capability_ttl_ms: HashMap<String, u64>,
+    per_capability_max_entries: HashMap<String, usize>,
+    default_negative_ttl_ms: u64,
+    per_capability_negative_ttl_ms: HashMap<String, u64>,
+    default_backend: CacheBackend,
+    per_capability_backend: HashMap<String, CacheBackend>,
+    history_mode: HistoryCacheMode,
+}
+
+impl Default for CacheLayer {
+    fn default() -> Self {
+        let d = CacheConfig::default();
+        Self {
+            default_ttl_ms: d.default_ttl_ms,
+            default_max_entries: d.default_max_entries,
+            per_capability_ttl_ms: d.per_capability_ttl_ms,
+            per_capability_max_entries: d.per_capability_max_entries,
+            default_negative_ttl_ms: d.default_negative_ttl_ms,
+            per_capability_negative_ttl_ms: d.per_capability_negative_ttl_ms,
+            default_backend: d.default_backend,
+            per_capability_backend: d.per_capability_backend,
+            history_mode: d.history_mode,
+        }
+    }
+}
+
+impl From<CacheLayer> for CacheConfig {
+    fn from(l: CacheLayer) -> Self {
+        Self {
+            default_ttl_ms: l.default_ttl_ms,
+            default_max_entries: l.default_max_entries,
+            per_capability_ttl_ms: l.per_capability_ttl_ms,
+            per_capability_max_entries: l.per_capability_max_entries,
+            default_negative_ttl_ms: l.default_negative_ttl_ms,
+            per_capability_negative_ttl_ms: l.per_capability_negative_ttl_ms,
+            default_backend: l.default_backend,
+            per_capability_backend: l.per_capability_backend,
+            history_mode: l.history_mode,
+        }
+    }
+}
+
+/// `QuotaAwareConnector` layer config.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct QuotaLayer {
+    limit: u64,
+    window_ms: u64,
+    strategy: QuotaConsumptionStrategy,
+    weights: HashMap<String, u64>,
+    pool: Option<String>,
+    pool_path: Option<PathBuf>,
+}
+
+impl Default for QuotaLayer {
+    fn default() -> Self {
+        let d = QuotaConfig::default();
+        Self {
+            limit: d.limit,
+            window_ms: millis(d.window),
+            strategy: d.strategy,
+            weights: d.weights,
+            pool: d.pool,
+            pool_path: d.pool_path,
+        }
+    }
+}
+
+impl From<QuotaLayer> for QuotaConfig {
+    fn from(l: QuotaLayer) -> Self {
+        let mut cfg = Self::new(l.limit, Duration::from_millis(l.window_ms), l.strategy);
+        cfg.weights = l.weights;
+        cfg.pool = l.pool;
+        cfg.pool_path = l.pool_path;
+        cfg
+    }
+}
+
+/// `BlacklistConnector` layer config.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BlacklistLayer {
+    default_duration_ms: u64,
+}
+
+impl Default for BlacklistLayer {
+    fn default() -> Self {
+        Self {
+            default_duration_ms: 300_000,
+        }
+    }
+}
+
+/// `CircuitBreakerConnector` layer config.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CircuitBreakerLayer {
+    failure_threshold: u32,
+    failure_rate: Option<f64>,
+    window_ms: u64,
+    min_calls: u32,
+    open_duration_ms: u64,
+    half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerLayer {
+    fn default() -> Self {
+        let d = CircuitBreakerConfig::default();
+        Self {
+            failure_threshold: d.failure_threshold,
+            failure_rate: d.failure_rate,
+            window_ms: millis(d.window),
+            min_calls: d.min_calls,
+            open_duration_ms: millis(d.open_duration),
+            half_open_probes: d.half_open_probes,
+        }
+    }
+}
+
+impl From<CircuitBreakerLayer> for CircuitBreakerConfig {
+    fn from(l: CircuitBreakerLayer) -> Self {
+        Self {
+            failure_threshold: l.failure_threshold,
+            failure_rate: l.failure_rate,
+            window: Duration::from_millis(l.window_ms),
+            min_calls: l.min_calls,
+            open_duration: Duration::from_millis(l.open_duration_ms),
+            half_open_probes: l.half_open_probes,
+        }
+    }
+}
+
+/// `RetryConnector` layer config.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RetryLayer {
+    default: RetryPolicy,
+    per_capability: HashMap<String, RetryPolicy>,
+    max_hint_wait_ms: u64,
+    budget: Option<u32>,
+    budget_window_ms: u64,
+}
+
+impl Default for RetryLayer {
+    fn default() -> Self {
+        let d = RetryConfig::default();
+        Self {
+            default: d.default,
+            per_capability: d.per_capability,
+            max_hint_wait_ms: millis(d.max_hint_wait),
+            budget: d.budget,
+            budget_window_ms: millis(d.budget_window),
+        }
+    }
+}
+
+impl From<RetryLayer> for RetryConfig {
+    fn from(l: RetryLayer) -> Self {
+        Self {
+            default: l.default,
+            per_capability: l.per_capability,
+            max_hint_wait: Duration::from_millis(l.max_hint_wait_ms),
+            budget: l.budget,
+            budget_window: Duration::from_millis(l.budget_window_ms),
+        }
+    }
+}
+
+/// `RateLimiterConnector` layer config.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RateLimitLayer {
+    requests_per_second: f64,
+    burst: u32,
+    max_wait_ms: u64,
+}
+
+impl Default for RateLimitLayer {
+    fn default() -> Self {
+        let d = RateLimitConfig::default();
+        Self {
+            requests_per_second: d.requests_per_second,
+            burst: d.burst,
+            max_wait_ms: millis(d.max_wait),
+        }
+    }
+}
+
+impl From<RateLimitLayer> for RateLimitConfig {
+    fn from(l: RateLimitLayer) -> Self {
+        Self {
+            requests_per_second: l.requests_per_second,
+            burst: l.burst,
+            max_wait: Duration::from_millis(l.max_wait_ms),
+        }
+    }
+}
+
+/// Config of layers without options (`CoalescingConnector`).
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EmptyLayer {}
+
 /// Generic middleware builder for composing a connector with layered wrappers.
 ///
 /// See [module-level documentation](self) for details on middleware ordering.
@@ -73,13 +353,18 @@ impl ConnectorBuilder {
     }
 
     /// Reorder layers to satisfy helper ordering policy:
-    /// Cache (outermost) -> Blacklist -> Quota -> others (stable among themselves).
+    /// Cache (outermost) -> Coalescing -> Blacklist -> Retry -> `CircuitBreaker` -> Quota ->
+    /// `RateLimiter` -> others (stable among themselves).
     fn enforce_ordering(&mut self) {
         self.layers.sort_by_key(|d| match d.name() {
             "CachingMiddleware" => 0,
-            "BlacklistConnector" => 1,
-            "QuotaAwareConnector" => 2,
-            _ => 3,
+            "CoalescingConnector" => 1,
+            "BlacklistConnector" => 2,
+            "RetryConnector" => 3,
+            "CircuitBreakerConnector" => 4,
+            "QuotaAwareConnector" => 5,
+            "RateLimiterConnector" => 6,
+            _ => 7,
         });
     }
 
@@ -107,34 +392,11 @@ impl ConnectorBuilder {
 
     /// Internal: extract existing quota config from layers if present.
     fn existing_quota_config(&self) -> Option<QuotaConfig> {
-        for desc in &self.layers {
-            if desc.name() == "QuotaAwareConnector" {
-                let cfg = desc.middleware().config_json();
-                let defaults = QuotaConfig::default();
-                let limit = cfg
-                    .get("limit")
-                    .and_then(serde_json::Value::as_u64)
-                    .unwrap_or(defaults.limit);
-                let window_ms = cfg
-                    .get("window_ms")
-                    .and_then(serde_json::Value::as_u64)
-                    .unwrap_or_else(|| {
-                        u64::try_from(defaults.window.as_millis()).unwrap_or(u64::MAX)
-                    });
-                let strategy = match cfg.get("strategy").and_then(|v| v.as_str()) {
-                    Some("EvenSpreadHourly") => QuotaConsumptionStrategy::EvenSpreadHourly,
-                    Some("Weighted") => QuotaConsumptionStrategy::Weighted,
-                    Some("Unit") => QuotaConsumptionStrategy::Unit,
-                    _ => defaults.strategy,
-                };
-                return Some(QuotaConfig {
-                    limit,
-                    window: Duration::from_millis(window_ms),
-                    strategy,
-                });
-            }
-        }
-        None
+        self.layers
+            .iter()
+            .find(|desc| desc.name() == "QuotaAwareConnector")
+            .and_then(|desc| parse_layer::<QuotaLayer>(&desc.middleware().config_json()).ok())
+            .map(Into::into)
     }
 
     /// Add or replace quota configuration.
@@ -185,6 +447,96 @@ impl ConnectorBuilder {
         self
     }
 
+    /// Add or replace circuit breaker configuration.
+    ///
+    /// Places the breaker inside blacklist and outside quota, so an open circuit fails fast
+    /// without consuming quota units.
+    ///
+    /// If circuit breaker middleware already exists, it is removed and replaced.
+    #[must_use]
+    pub fn with_circuit_breaker(mut self, cfg: &CircuitBreakerConfig) -> Self {
+        self.layers
+            .retain(|d| d.name() != "CircuitBreakerConnector");
+        self.layers.push(MiddlewareDescriptor::new(
+            crate::circuit_breaker::CircuitBreakerMiddleware::new(*cfg),
+        ));
+        self.enforce_ordering();
+        self
+    }
+
+    /// Remove circuit breaker if present.
+    #[must_use]
+    pub fn without_circuit_breaker(mut self) -> Self {
+        self.layers
+            .retain(|d| d.name() != "CircuitBreakerConnector");
+        self
+    }
+
+    /// Add or replace retry configuration.
+    ///
+    /// Places retry inside cache and outside circuit breaker and quota, so every attempt is
+    /// charged against the quota and open circuits surface their `reset_in_ms` hint.
+    ///
+    /// If retry middleware already exists, it is removed and replaced.
+    #[must_use]
+    pub fn with_retry(mut self, cfg: &RetryConfig) -> Self {
+        self.layers.retain(|d| d.name() != "RetryConnector");
+        self.layers.push(MiddlewareDescriptor::new(
+            crate::retry::RetryMiddleware::new(cfg.clone()),
+        ));
+        self.enforce_ordering();
+        self
+    }
+
+    /// Remove retry if present.
+    #[must_use]
+    pub fn without_retry(mut self) -> Self {
+        self.layers.retain(|d| d.name() != "RetryConnector");
+        self
+    }
+
+    /// Share one in-flight provider call among identical concurrent requests.
+    ///
+    /// Placed just inside the cache so a cache miss stampede collapses into a single call
+    /// that is charged, retried and circuit-checked once.
+    #[must_use]
+    pub fn with_coalescing(mut self) -> Self {
+        self.layers.retain(|d| d.name() != "CoalescingConnector");
+        self.layers.push(MiddlewareDescriptor::new(
+            crate::coalesce::CoalescingMiddleware::new(),
+        ));
+        self.enforce_ordering();
+        self
+    }
+
+    /// Remove coalescing if present.
+    #[must_use]
+    pub fn without_coalescing(mut self) -> Self {
+        self.layers.retain(|d| d.name() != "CoalescingConnector");
+        self
+    }
+
+    /// Add or replace the token-bucket rate limiter.
+    ///
+    /// Placed just inside the quota so only calls that fit the budget wait for a token, and
+    /// inside retry so every attempt is spaced.
+    #[must_use]
+    pub fn with_rate_limit(mut self, cfg: &RateLimitConfig) -> Self {
+        self.layers.retain(|d| d.name() != "RateLimiterConnector");
+        self.layers.push(MiddlewareDescriptor::new(
+            crate::rate_limit::RateLimiterMiddleware::new(*cfg),
+        ));
+        self.enforce_ordering();
+        self
+    }
+
+    /// Remove the rate limiter if present.
+    #[must_use]
+    pub fn without_rate_limit(mut self) -> Self {
+        self.layers.retain(|d| d.name() != "RateLimiterConnector");
+        self
+    }
+
     /// Shortcut: set quota limit only (preserves existing window/strategy if already set).
     #[must_use]
     pub fn quota_limit(self, limit: u64) -> Self {
@@ -238,7 +590,10 @@ impl ConnectorBuilder {
     ///
     /// Reconstructs middleware layers from a serialized [`MiddlewareStack`],
     /// preserving the outermost-first ordering convention. Unknown middleware
-    /// types are silently ignored (forward compatibility).
+    /// types are skipped (forward compatibility). Known layers are kept even when part of
+    /// their config does not parse: unknown or invalid fields are ignored with a warning and
+    /// take the middleware's defaults. Use [`try_from_stack`](Self::try_from_stack) to reject
+    /// such stacks instead.
     ///
     /// This is the inverse of [`to_stack`](Self::to_stack).
     #[must_use]
@@ -247,117 +602,108 @@ impl ConnectorBuilder {
         let layers: Vec<MiddlewareDescriptor> = stack
             .layers
             .iter()
-            .filter_map(Self::descriptor_from_layer)
+            .filter_map(|layer| Self::descriptor_from_layer(layer, false).ok().flatten())
             .collect();
         let mut builder = Self { raw, layers };
         builder.enforce_ordering();
         builder
     }
 
-    fn descriptor_from_layer(layer: &MiddlewareLayer) -> Option<MiddlewareDescriptor> {
-        match layer.name.as_str() {
-            "CachingMiddleware" => {
-                let cfg = Self::parse_cache_config_from(&layer.config);
-                Some(MiddlewareDescriptor::new(
-                    crate::cache::CacheMiddleware::new(cfg),
+    /// Strict variant of [`from_stack`](Self::from_stack) for configuration loading.
+    ///
+    /// The `RawConnector` marker appended by [`to_stack`](Self::to_stack) is skipped; any
+    /// other unknown layer name is rejected. Each layer config is parsed strictly: fields it
+    /// leaves out take the middleware's defaults, but unknown fields and values of the wrong
+    /// type are rejected instead of falling back to a default.
+    ///
+    /// # Errors
+    /// Returns `InvalidConfig` with the path `layers[<index>].name` of the first unknown layer,
+    /// or `layers[<index>].config.<field>` of the first invalid config value.
+    pub fn try_from_stack(
+        raw: Arc<dyn BorsaConnector>,
+        stack: &MiddlewareStack,
+    ) -> Result<Self, BorsaError> {
+        let mut layers: Vec<MiddlewareDescriptor> = Vec::with_capacity(stack.layers.len());
+        for (idx, layer) in stack.layers.iter().enumerate() {
+            if layer.name == "RawConnector" {
+                continue;
+            }
+            let desc = Self::descriptor_from_layer(layer, true).map_err(|(path, message)| {
+                let at = if path.is_empty() {
+                    format!("layers[{idx}].config")
+                } else {
+                    format!("layers[{idx}].config.{path}")
+                };
+                BorsaError::invalid_config(at, message)
+            })?;
+            let desc = desc.ok_or_else(|| {
+                BorsaError::invalid_config(
+                    format!("layers[{idx}].name"),
+                    format!(
+                        "unknown middleware layer '{}' (known: CachingMiddleware, CoalescingConnector, BlacklistConnector, RetryConnector, CircuitBreakerConnector, QuotaAwareConnector, RateLimiterConnector)",
+                        layer.name
+                    ),
+                )
+            })?;
+            layers.push(desc);
+        }
+        let mut builder = Self { raw, layers };
+        builder.enforce_ordering();
+        Ok(builder)
+    }
+
+    /// Typed middleware for a known layer name, or `None` for an unknown one.
+    ///
+    /// With `strict`, errors carry the path inside the layer config and a message (see
+    /// [`parse_layer`]); otherwise the config is parsed with [`parse_layer_lenient`] and
+    /// never fails.
+    fn descriptor_from_layer(
+        layer: &MiddlewareLayer,
+        strict: bool,
+    ) -> Result<Option<MiddlewareDescriptor>, (String, String)> {
+        fn parse<T: DeserializeOwned + Default>(
+            layer: &MiddlewareLayer,
+            strict: bool,
+        ) -> Result<T, (String, String)> {
+            if strict {
+                parse_layer(&layer.config)
+            } else {
+                Ok(parse_layer_lenient(&layer.name, &layer.config))
+            }
+        }
+        let desc = match layer.name.as_str() {
+            "CachingMiddleware" => MiddlewareDescriptor::new(crate::cache::CacheMiddleware::new(
+                parse::<CacheLayer>(layer, strict)?.into(),
+            )),
+            "QuotaAwareConnector" => MiddlewareDescriptor::new(crate::quota::QuotaMiddleware::new(
+                parse::<QuotaLayer>(layer, strict)?.into(),
+            )),
+            "BlacklistConnector" => {
+                let layer = parse::<BlacklistLayer>(layer, strict)?;
+                MiddlewareDescriptor::new(crate::blacklist::BlacklistMiddleware::new(
+                    Duration::from_millis(layer.default_duration_ms),
                 ))
             }
-            "QuotaAwareConnector" => {
-                let cfg = Self::parse_quota_config_from(&layer.config);
-                Some(MiddlewareDescriptor::new(
-                    crate::quota::QuotaMiddleware::new(cfg),
+            "CircuitBreakerConnector" => {
+                MiddlewareDescriptor::new(crate::circuit_breaker::CircuitBreakerMiddleware::new(
+                    parse::<CircuitBreakerLayer>(layer, strict)?.into(),
                 ))
             }
-            "BlacklistConnector" => {
-                let duration = Self::parse_blacklist_duration_from(&layer.config);
-                Some(MiddlewareDescriptor::new(
-                    crate::blacklist::BlacklistMiddleware::new(duration),
+            "CoalescingConnector" => {
+                parse::<EmptyLayer>(layer, strict)?;
+                MiddlewareDescriptor::new(crate::coalesce::CoalescingMiddleware::new())
+            }
+            "RetryConnector" => MiddlewareDescriptor::new(crate::retry::RetryMiddleware::new(
+                parse::<RetryLayer>(layer, strict)?.into(),
+            )),
+            "RateLimiterConnector" => {
+                MiddlewareDescriptor::new(crate::rate_limit::RateLimiterMiddleware::new(
+                    parse::<RateLimitLayer>(layer, strict)?.into(),
                 ))
             }
-            _ => None,
-        }
-    }
-
-    fn parse_cache_config_from(config: &serde_json::Value) -> CacheConfig {
-        let default_ttl_ms = config
-            .get("default_ttl_ms")
-            .and_then(serde_json::Value::as_u64)
-            .unwrap_or(300_000);
-        let default_max_entries = config
-            .get("default_max_entries")
-            .and_then(serde_json::Value::as_u64)
-            .and_then(|n| usize::try_from(n).ok())
-            .unwrap_or(2000);
-        let per_cap_ttl = config
-            .get("per_capability_ttl_ms")
-            .and_then(serde_json::Value::as_object)
-            .cloned()
-            .unwrap_or_default();
-        let per_cap_capacity = config
-            .get("per_capability_max_entries")
-            .and_then(serde_json::Value::as_object)
-            .cloned()
-            .unwrap_or_default();
-        let default_negative_ttl_ms = config
-            .get("default_negative_ttl_ms")
-            .and_then(serde_json::Value::as_u64)
-            .unwrap_or_else(|| CacheConfig::default().default_negative_ttl_ms);
-        let per_cap_negative_ttl = config
-            .get("per_capability_negative_ttl_ms")
-            .and_then(serde_json::Value::as_object)
-            .cloned()
-            .unwrap_or_default();
-
-        CacheConfig {
-            default_ttl_ms,
-            per_capability_ttl_ms: per_cap_ttl
-                .into_iter()
-                .filter_map(|(k, v)| v.as_u64().map(|ms| (k, ms)))
-                .collect(),
-            default_max_entries,
-            per_capability_max_entries: per_cap_capacity
-                .into_iter()
-                .filter_map(|(k, v)| {
-                    v.as_u64()
-                        .and_then(|n| usize::try_from(n).ok())
-                        .map(|n| (k, n))
-                })
-                .collect(),
-            default_negative_ttl_ms,
-            per_capability_negative_ttl_ms: per_cap_negative_ttl
-                .into_iter()
-                .filter_map(|(k, v)| v.as_u64().map(|ms| (k, ms)))
-                .collect(),
-        }
-    }
-
-    fn parse_quota_config_from(config: &serde_json::Value) -> QuotaConfig {
-        let limit = config
-            .get("limit")
-            .and_then(serde_json::Value::as_u64)
-            .unwrap_or(1);
-        let window_ms = config
-            .get("window_ms")
-            .and_then(serde_json::Value::as_u64)
-            .unwrap_or(60_000);
-        let strategy = match config.get("strategy").and_then(|v| v.as_str()) {
-            Some("EvenSpreadHourly") => QuotaConsumptionStrategy::EvenSpreadHourly,
-            Some("Weighted") => QuotaConsumptionStrategy::Weighted,
-            _ => QuotaConsumptionStrategy::Unit,
+            _ => return Ok(None),
         };
-        QuotaConfig {
-            limit,
-            window: Duration::from_millis(window_ms),
-            strategy,
-        }
-    }
-
-    fn parse_blacklist_duration_from(config: &serde_json::Value) -> Duration {
-        let dur_ms = config
-            .get("default_duration_ms")
-            .and_then(serde_json::Value::as_u64)
-            .unwrap_or(300_000);
-        Duration::from_millis(dur_ms)
+        Ok(Some(desc))
     }
 
     /// Validate the middleware stack without building.
@@ -5,28 +5,33 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use borsa_core::connector::{
-    AnalystPriceTargetProvider, BalanceSheetProvider, CalendarProvider, CandleStreamProvider,
-    CashflowProvider, EarningsProvider, EsgProvider, HistoryProvider, IncomeStatementProvider,
-    InsiderRosterHoldersProvider, InsiderTransactionsProvider, InstitutionalHoldersProvider,
-    IsinProvider, MajorHoldersProvider, MutualFundHoldersProvider,
+    AnalystPriceTargetProvider, BalanceSheetProvider, BatchQuoteProvider, CalendarProvider,
+    CandleStreamProvider, CashflowProvider, EarningsProvider, EsgProvider, HistoryProvider,
+    IncomeStatementProvider, InsiderRosterHoldersProvider, InsiderTransactionsProvider,
+    InstitutionalHoldersProvider, IsinProvider, MajorHoldersProvider, MutualFundHoldersProvider,
     NetSharePurchaseActivityProvider, NewsProvider, OptionChainProvider, OptionStreamProvider,
     OptionsExpirationsProvider, ProfileProvider, QuoteProvider, RecommendationsProvider,
     RecommendationsSummaryProvider, SearchProvider, StreamProvider, UpgradesDowngradesProvider,
 };
 use borsa_core::{
-    AssetKind, BalanceSheetRow, BorsaConnector, BorsaError, Calendar, CandleUpdate, CashflowRow,
-    Earnings, EsgScores, HistoryRequest, HistoryResponse, IncomeStatementRow, Instrument, Interval,
-    Isin, NewsArticle, NewsRequest, NewsTab, OptionChain, OptionUpdate, PriceTarget, Profile,
-    Quote, Range, RecommendationRow, RecommendationSummary, SearchRequest, SearchResponse,
-    UpgradeDowngradeRow,
+    Action, AssetKind, BalanceSheetRow, BorsaConnector, BorsaError, Calendar, CandleUpdate,
+    CashflowRow, Earnings, EsgScores, HistoryRequest, HistoryRequestBuilder, HistoryResponse,
+    IncomeStatementRow, Instrument, Interval, Isin, NewsArticle, NewsRequest, NewsTab, OptionChain,
+    OptionUpdate, PriceTarget, Profile, Quote, Range, RecommendationRow, RecommendationSummary,
+    SearchRequest, SearchResponse, UpgradeDowngradeRow,
 };
-use borsa_types::{CacheConfig, Capability};
+use borsa_types::{CacheBackend, CacheConfig, Capability, HistoryCacheMode};
+use chrono::{DateTime, Utc};
 use moka::future::Cache;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::file_store::FileStore;
 #[cfg(feature = "tracing")]
 use tracing::{debug, info, warn};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct HistoryKey {
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub(crate) struct HistoryKey {
     inst: Instrument,
     interval: IntervalKey,
     range: RangeKey,
@@ -40,7 +45,7 @@ impl HistoryKey {
     const AUTO_ADJUST: u8 = 1 << 2;
     const KEEPNA: u8 = 1 << 3;
 
-    fn from_request(inst: &Instrument, req: &HistoryRequest) -> Self {
+    fn flags_of(req: &HistoryRequest) -> u8 {
         let mut flags = 0u8;
         if req.include_prepost() {
             flags |= Self::INCLUDE_PREPOST;
@@ -54,43 +59,154 @@ impl HistoryKey {
         if req.keepna() {
             flags |= Self::KEEPNA;
         }
+        flags
+    }
+
+    pub(crate) fn from_request(inst: &Instrument, req: &HistoryRequest) -> Self {
         Self {
             inst: inst.clone(),
             interval: IntervalKey(req.interval()),
             range: RangeKey(req.range()),
             period: PeriodKey(req.period().map(|(s, e)| (s.timestamp(), e.timestamp()))),
-            flags,
+            flags: Self::flags_of(req),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct BoolByInstrumentKey {
+/// Key of a stitched history series: everything in the request except its time window.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+struct SeriesKey {
     inst: Instrument,
-    flag: bool,
+    interval: IntervalKey,
+    flags: u8,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct OptionChainKey {
-    inst: Instrument,
-    date: Option<i64>,
+impl SeriesKey {
+    fn from_request(inst: &Instrument, req: &HistoryRequest) -> Self {
+        Self {
+            inst: inst.clone(),
+            interval: IntervalKey(req.interval()),
+            flags: HistoryKey::flags_of(req),
+        }
+    }
+}
+
+/// Candles known for the window `[start, end)` (unix seconds).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistorySeries {
+    start: i64,
+    end: i64,
+    response: HistoryResponse,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct SearchKey {
+impl HistorySeries {
+    /// Windows to fetch upstream so that `[start, end)` is covered, and whether the cached
+    /// series can be merged with the result.
+    ///
+    /// A disjoint request replaces the series because a single window cannot represent gaps.
+    /// Only candles followed by a newer one are known to be closed, so any request that
+    /// reaches the last cached candle refetches the tail from it; a bar that was still
+    /// forming when it was cached is never served frozen.
+    fn missing(cached: Option<&Self>, start: i64, end: i64) -> (Vec<(i64, i64)>, bool) {
+        let Some(c) = cached else {
+            return (vec![(start, end)], false);
+        };
+        if start > c.end || end < c.start {
+            return (vec![(start, end)], false);
+        }
+        let mut windows = Vec::new();
+        if start < c.start {
+            windows.push((start, c.start));
+        }
+        let tail_from = c
+            .response
+            .candles
+            .last()
+            .map_or(c.end, |last| last.ts.timestamp().clamp(c.start, c.end));
+        if end > tail_from {
+            windows.push((tail_from, end));
+        }
+        (windows, true)
+    }
+
+    /// The part of the series inside `[start, end)`.
+    fn slice(&self, start: i64, end: i64) -> HistoryResponse {
+        let within = |ts: DateTime<Utc>| (start..end).contains(&ts.timestamp());
+        HistoryResponse {
+            candles: self
+                .response
+                .candles
+                .iter()
+                .filter(|c| within(c.ts))
+                .cloned()
+                .collect(),
+            actions: self
+                .response
+                .actions
+                .iter()
+                .filter(|a| {
+                    let ts = match a {
+                        Action::Dividend { ts, .. }
+                        | Action::Split { ts, .. }
+                        | Action::CapitalGain { ts, .. } => *ts,
+                    };
+                    within(ts)
+                })
+                .cloned()
+                .collect(),
+            adjusted: self.response.adjusted,
+            meta: self.response.meta.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub(crate) struct BoolByInstrumentKey {
+    pub(crate) inst: Instrument,
+    pub(crate) flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub(crate) struct OptionChainKey {
+    pub(crate) inst: Instrument,
+    pub(crate) date: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub(crate) struct SearchKey {
     query: String,
     kind: Option<AssetKind>,
     limit: Option<usize>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct NewsKey {
+impl SearchKey {
+    pub(crate) fn from_request(req: &SearchRequest) -> Self {
+        Self {
+            query: req.query().to_string(),
+            kind: req.kind(),
+            limit: req.limit(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub(crate) struct NewsKey {
     inst: Instrument,
     count: u32,
     tab: NewsTabKey,
 }
 
-#[derive(Clone, Copy)]
+impl NewsKey {
+    pub(crate) fn from_request(inst: &Instrument, req: &NewsRequest) -> Self {
+        Self {
+            inst: inst.clone(),
+            count: req.count,
+            tab: NewsTabKey(req.tab),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize)]
 struct IntervalKey(Interval);
 
 impl std::fmt::Debug for IntervalKey {
@@ -111,7 +227,7 @@ impl std::hash::Hash for IntervalKey {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
 struct RangeKey(Option<Range>);
 
 impl std::fmt::Debug for RangeKey {
@@ -141,7 +257,7 @@ impl std::hash::Hash for RangeKey {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 struct PeriodKey(Option<(i64, i64)>);
 
 impl std::fmt::Debug for PeriodKey {
@@ -150,7 +266,7 @@ impl std::fmt::Debug for PeriodKey {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
 struct NewsTabKey(NewsTab);
 
 impl std::fmt::Debug for NewsTabKey {
@@ -170,11 +286,19 @@ impl std::hash::Hash for NewsTabKey {
     }
 }
 
-type CacheStoreFuture<V> = Pin<Box<dyn Future<Output = Result<V, BorsaError>> + Send>>;
-type CacheLoader<K, V> = Arc<dyn Fn(K) -> CacheStoreFuture<V> + Send + Sync>;
+/// Future returned by a [`CacheLoader`].
+pub type CacheStoreFuture<V> = Pin<Box<dyn Future<Output = Result<V, BorsaError>> + Send>>;
+/// Loader invoked by a [`CacheStore`] on a miss.
+pub type CacheLoader<K, V> = Arc<dyn Fn(K) -> CacheStoreFuture<V> + Send + Sync>;
 
+/// Storage backend for a single per-capability cache used by [`CachingConnector`].
+///
+/// Implementations own expiry: values must stop being returned once the store's TTL elapses.
 #[async_trait]
-trait CacheStore<K, V>: Send + Sync {
+pub trait CacheStore<K, V>: Send + Sync {
+    /// Return the cached value for `key`, or run `loader`, cache a successful result and return it.
+    ///
+    /// Errors from the loader are returned as-is and are not cached.
     async fn get_or_try_put_with(&self, key: K, loader: CacheLoader<K, V>)
     -> Result<V, BorsaError>;
 
@@ -185,7 +309,8 @@ trait CacheStore<K, V>: Send + Sync {
     async fn insert(&self, key: K, value: V);
 }
 
-struct MokaStore<K, V> {
+/// In-memory [`CacheStore`] backed by `moka` with a fixed capacity and TTL.
+pub struct MokaStore<K, V> {
     cache: Cache<K, V>,
     #[cfg(feature = "tracing")]
     ttl: std::time::Duration,
@@ -196,7 +321,9 @@ where
     K: Clone + std::hash::Hash + Eq + Send + Sync + 'static,
     V: Clone + Send + Sync + 'static,
 {
-    fn new(capacity: usize, ttl: Duration) -> Self {
+    /// Create a store holding at most `capacity` entries, each expiring `ttl` after insertion.
+    #[must_use]
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
         let cap = capacity.max(1);
         let cap_u64 = u64::try_from(cap).unwrap_or(u64::MAX);
         let cache = Cache::builder()
@@ -348,6 +475,9 @@ impl borsa_core::Middleware for CacheMiddleware {
             "per_capability_max_entries": self.cfg.per_capability_max_entries,
             "default_negative_ttl_ms": self.cfg.default_negative_ttl_ms,
             "per_capability_negative_ttl_ms": self.cfg.per_capability_negative_ttl_ms,
+            "default_backend": self.cfg.default_backend,
+            "per_capability_backend": self.cfg.per_capability_backend,
+            "history_mode": self.cfg.history_mode,
         })
     }
 }
@@ -359,6 +489,7 @@ struct Stores {
     profile: Option<Arc<dyn CacheStore<Instrument, Profile>>>,
     isin: Option<Arc<dyn CacheStore<Instrument, Option<Isin>>>>,
     history: Option<Arc<dyn CacheStore<HistoryKey, HistoryResponse>>>,
+    history_series: Option<Arc<dyn CacheStore<SeriesKey, HistorySeries>>>,
     earnings: Option<Arc<dyn CacheStore<Instrument, Earnings>>>,
     income_stmt: Option<Arc<dyn CacheStore<BoolByInstrumentKey, Vec<IncomeStatementRow>>>>,
     balance_sheet: Option<Arc<dyn CacheStore<BoolByInstrumentKey, Vec<BalanceSheetRow>>>>,
@@ -417,14 +548,52 @@ pub struct CachingConnector {
 }
 
 impl CachingConnector {
-    fn maybe_store<K, V>(cfg: &CacheConfig, cap: Capability) -> Option<Arc<dyn CacheStore<K, V>>>
+    fn build_store<K, V>(
+        cfg: &CacheConfig,
+        connector: &str,
+        cap: Capability,
+        file_suffix: &str,
+        capacity: usize,
+        ttl: Duration,
+    ) -> Arc<dyn CacheStore<K, V>>
     where
-        K: Clone + std::hash::Hash + Eq + Send + Sync + 'static,
-        V: Clone + Send + Sync + 'static,
+        K: Clone + std::hash::Hash + Eq + Serialize + Send + Sync + 'static,
+        V: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        if let CacheBackend::File { dir } = cfg.backend_for(cap) {
+            let path = dir
+                .join(connector)
+                .join(format!("{}{file_suffix}.jsonl", cap.as_str()));
+            match FileStore::<K, V>::open(&path, capacity, ttl) {
+                Ok(store) => return Arc::new(store),
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    warn!(
+                        target = "borsa::middleware::cache",
+                        event = "file_store_fallback",
+                        capability = %cap,
+                        path = %path.display(),
+                        err = %_err,
+                        "failed to open file-backed cache; using in-memory store"
+                    );
+                }
+            }
+        }
+        Arc::new(MokaStore::<K, V>::new(capacity, ttl))
+    }
+
+    fn maybe_store<K, V>(
+        cfg: &CacheConfig,
+        connector: &str,
+        cap: Capability,
+    ) -> Option<Arc<dyn CacheStore<K, V>>>
+    where
+        K: Clone + std::hash::Hash + Eq + Serialize + Send + Sync + 'static,
+        V: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
     {
         let ttl = cfg.ttl_for(cap)?;
         let capacity = cfg.capacity_for(cap);
-        let store = MokaStore::<K, V>::new(capacity, ttl);
+        let store = Self::build_store(cfg, connector, cap, "", capacity, ttl);
         #[cfg(feature = "tracing")]
         {
             let ttl_ms: u64 = ttl.as_millis().try_into().unwrap_or(u64::MAX);
@@ -437,19 +606,20 @@ impl CachingConnector {
                 "created per-capability store"
             );
         }
-        Some(Arc::new(store))
+        Some(store)
     }
 
     fn maybe_negative_store<K>(
         cfg: &CacheConfig,
+        connector: &str,
         cap: Capability,
     ) -> Option<Arc<dyn CacheStore<K, BorsaError>>>
     where
-        K: Clone + std::hash::Hash + Eq + Send + Sync + 'static,
+        K: Clone + std::hash::Hash + Eq + Serialize + Send + Sync + 'static,
     {
         let ttl = cfg.negative_ttl_for(cap)?;
         let capacity = cfg.capacity_for(cap);
-        let store = MokaStore::<K, BorsaError>::new(capacity, ttl);
+        let store = Self::build_store(cfg, connector, cap, ".neg", capacity, ttl);
         #[cfg(feature = "tracing")]
         {
             let ttl_ms: u64 = ttl.as_millis().try_into().unwrap_or(u64::MAX);
@@ -462,7 +632,22 @@ impl CachingConnector {
                 "created per-capability negative store"
             );
         }
-        Some(Arc::new(store))
+        Some(store)
+    }
+
+    fn maybe_history_series_store(
+        cfg: &CacheConfig,
+        connector: &str,
+    ) -> Option<Arc<dyn CacheStore<SeriesKey, HistorySeries>>> {
+        if cfg.history_mode != HistoryCacheMode::Series {
+            return None;
+        }
+        let cap = Capability::History;
+        let ttl = cfg.ttl_for(cap)?;
+        let capacity = cfg.capacity_for(cap);
+        Some(Self::build_store(
+            cfg, connector, cap, ".series", capacity, ttl,
+        ))
     }
 
     async fn cached_or_load_neg<K, T>(
@@ -504,80 +689,98 @@ impl CachingConnector {
 
     #[must_use]
     pub fn new(inner: Arc<dyn BorsaConnector>, cfg: &CacheConfig) -> Self {
+        let name = inner.name();
         let stores = Stores {
-            quote: Self::maybe_store(cfg, Capability::Quote),
-            profile: Self::maybe_store(cfg, Capability::Profile),
-            isin: Self::maybe_store(cfg, Capability::Isin),
-            history: Self::maybe_store(cfg, Capability::History),
-            earnings: Self::maybe_store(cfg, Capability::Earnings),
-            income_stmt: Self::maybe_store(cfg, Capability::IncomeStatement),
-            balance_sheet: Self::maybe_store(cfg, Capability::BalanceSheet),
-            cashflow: Self::maybe_store(cfg, Capability::Cashflow),
-            calendar: Self::maybe_store(cfg, Capability::Calendar),
-            recommendations: Self::maybe_store(cfg, Capability::Recommendations),
-            recommendations_summary: Self::maybe_store(cfg, Capability::RecommendationsSummary),
-            upgrades_downgrades: Self::maybe_store(cfg, Capability::UpgradesDowngrades),
-            analyst_price_target: Self::maybe_store(cfg, Capability::AnalystPriceTarget),
-            major_holders: Self::maybe_store(cfg, Capability::MajorHolders),
-            institutional_holders: Self::maybe_store(cfg, Capability::InstitutionalHolders),
-            mutual_fund_holders: Self::maybe_store(cfg, Capability::MutualFundHolders),
-            insider_transactions: Self::maybe_store(cfg, Capability::InsiderTransactions),
-            insider_roster: Self::maybe_store(cfg, Capability::InsiderRoster),
+            quote: Self::maybe_store(cfg, name, Capability::Quote),
+            profile: Self::maybe_store(cfg, name, Capability::Profile),
+            isin: Self::maybe_store(cfg, name, Capability::Isin),
+            history: Self::maybe_store(cfg, name, Capability::History),
+            history_series: Self::maybe_history_series_store(cfg, name),
+            earnings: Self::maybe_store(cfg, name, Capability::Earnings),
+            income_stmt: Self::maybe_store(cfg, name, Capability::IncomeStatement),
+            balance_sheet: Self::maybe_store(cfg, name, Capability::BalanceSheet),
+            cashflow: Self::maybe_store(cfg, name, Capability::Cashflow),
+            calendar: Self::maybe_store(cfg, name, Capability::Calendar),
+            recommendations: Self::maybe_store(cfg, name, Capability::Recommendations),
+            recommendations_summary: Self::maybe_store(
+                cfg,
+                name,
+                Capability::RecommendationsSummary,
+            ),
+            upgrades_downgrades: Self::maybe_store(cfg, name, Capability::UpgradesDowngrades),
+            analyst_price_target: Self::maybe_store(cfg, name, Capability::AnalystPriceTarget),
+            major_holders: Self::maybe_store(cfg, name, Capability::MajorHolders),
+            institutional_holders: Self::maybe_store(cfg, name, Capability::InstitutionalHolders),
+            mutual_fund_holders: Self::maybe_store(cfg, name, Capability::MutualFundHolders),
+            insider_transactions: Self::maybe_store(cfg, name, Capability::InsiderTransactions),
+            insider_roster: Self::maybe_store(cfg, name, Capability::InsiderRoster),
             net_share_purchase_activity: Self::maybe_store(
                 cfg,
+                name,
                 Capability::NetSharePurchaseActivity,
             ),
-            esg: Self::maybe_store(cfg, Capability::Esg),
-            news: Self::maybe_store(cfg, Capability::News),
-            options_expirations: Self::maybe_store(cfg, Capability::OptionsExpirations),
-            option_chain: Self::maybe_store(cfg, Capability::OptionChain),
-            search: Self::maybe_store(cfg, Capability::Search),
-
-            quote_neg: Self::maybe_negative_store(cfg, Capability::Quote),
-            profile_neg: Self::maybe_negative_store(cfg, Capability::Profile),
-            isin_neg: Self::maybe_negative_store(cfg, Capability::Isin),
-            history_neg: Self::maybe_negative_store(cfg, Capability::History),
-            earnings_neg: Self::maybe_negative_store(cfg, Capability::Earnings),
-            income_stmt_neg: Self::maybe_negative_store(cfg, Capability::IncomeStatement),
-            balance_sheet_neg: Self::maybe_negative_store(cfg, Capability::BalanceSheet),
-            cashflow_neg: Self::maybe_negative_store(cfg, Capability::Cashflow),
-            calendar_neg: Self::maybe_negative_store(cfg, Capability::Calendar),
-            recommendations_neg: Self::maybe_negative_store(cfg, Capability::Recommendations),
+            esg: Self::maybe_store(cfg, name, Capability::Esg),
+            news: Self::maybe_store(cfg, name, Capability::News),
+            options_expirations: Self::maybe_store(cfg, name, Capability::OptionsExpirations),
+            option_chain: Self::maybe_store(cfg, name, Capability::OptionChain),
+            search: Self::maybe_store(cfg, name, Capability::Search),
+
+            quote_neg: Self::maybe_negative_store(cfg, name, Capability::Quote),
+            profile_neg: Self::maybe_negative_store(cfg, name, Capability::Profile),
+            isin_neg: Self::maybe_negative_store(cfg, name, Capability::Isin),
+            history_neg: Self::maybe_negative_store(cfg, name, Capability::History),
+            earnings_neg: Self::maybe_negative_store(cfg, name, Capability::Earnings),
+            income_stmt_neg: Self::maybe_negative_store(cfg, name, Capability::IncomeStatement),
+            balance_sheet_neg: Self::maybe_negative_store(cfg, name, Capability::BalanceSheet),
+            cashflow_neg: Self::maybe_negative_store(cfg, name, Capability::Cashflow),
+            calendar_neg: Self::maybe_negative_store(cfg, name, Capability::Calendar),
+            recommendations_neg: Self::maybe_negative_store(cfg, name, Capability::Recommendations),
             recommendations_summary_neg: Self::maybe_negative_store(
                 cfg,
+                name,
                 Capability::RecommendationsSummary,
             ),
             upgrades_downgrades_neg: Self::maybe_negative_store(
                 cfg,
+                name,
                 Capability::UpgradesDowngrades,
             ),
             analyst_price_target_neg: Self::maybe_negative_store(
                 cfg,
+                name,
                 Capability::AnalystPriceTarget,
             ),
-            major_holders_neg: Self::maybe_negative_store(cfg, Capability::MajorHolders),
+            major_holders_neg: Self::maybe_negative_store(cfg, name, Capability::MajorHolders),
             institutional_holders_neg: Self::maybe_negative_store(
                 cfg,
+                name,
                 Capability::InstitutionalHolders,
             ),
-            mutual_fund_holders_neg: Self::maybe_negative_store(cfg, Capability::MutualFundHolders),
+            mutual_fund_holders_neg: Self::maybe_negative_store(
+                cfg,
+                name,
+                Capability::MutualFundHolders,
+            ),
             insider_transactions_neg: Self::maybe_negative_store(
                 cfg,
+                name,
                 Capability::InsiderTransactions,
             ),
-            insider_roster_neg: Self::maybe_negative_store(cfg, Capability::InsiderRoster),
+            insider_roster_neg: Self::maybe_negative_store(cfg, name, Capability::InsiderRoster),
             net_share_purchase_activity_neg: Self::maybe_negative_store(
                 cfg,
+                name,
                 Capability::NetSharePurchaseActivity,
             ),
-            esg_neg: Self::maybe_negative_store(cfg, Capability::Esg),
-            news_neg: Self::maybe_negative_store(cfg, Capability::News),
+            esg_neg: Self::maybe_negative_store(cfg, name, Capability::Esg),
+            news_neg: Self::maybe_negative_store(cfg, name, Capability::News),
             options_expirations_neg: Self::maybe_negative_store(
                 cfg,
+                name,
                 Capability::OptionsExpirations,
             ),
-            option_chain_neg: Self::maybe_negative_store(cfg, Capability::OptionChain),
-            search_neg: Self::maybe_negative_store(cfg, Capability::Search),
+            option_chain_neg: Self::maybe_negative_store(cfg, name, Capability::OptionChain),
+            search_neg: Self::maybe_negative_store(cfg, name, Capability::Search),
         };
         Self { inner, stores }
     }
@@ -628,6 +831,55 @@ impl QuoteProvider for CachingConnector {
     }
 }
 
+#[async_trait]
+impl BatchQuoteProvider for CachingConnector {
+    async fn quotes(&self, instruments: &[Instrument]) -> Result<Vec<Quote>, BorsaError> {
+        let provider = self
+            .inner
+            .as_batch_quote_provider()
+            .ok_or_else(|| BorsaError::unsupported("quotes"))?;
+
+        // Serve what the per-instrument quote store already holds; only misses go upstream.
+        // Negative entries are omitted so the router's per-instrument fallback surfaces them.
+        let mut out: Vec<Quote> = Vec::with_capacity(instruments.len());
+        let mut misses: Vec<Instrument> = Vec::new();
+        for inst in instruments {
+            if let Some(neg_store) = self.stores.quote_neg.as_ref()
+                && neg_store.get_if_present(inst).await.is_some()
+            {
+                continue;
+            }
+            match self.stores.quote.as_ref() {
+                Some(store) => match store.get_if_present(inst).await {
+                    Some(q) => out.push(q),
+                    None => misses.push(inst.clone()),
+                },
+                None => misses.push(inst.clone()),
+            }
+        }
+        if misses.is_empty() {
+            return Ok(out);
+        }
+
+        let fetched = provider.quotes(&misses).await?;
+        if let Some(store) = self.stores.quote.as_ref() {
+            for q in &fetched {
+                if misses.contains(&q.instrument) {
+                    store.insert(q.instrument.clone(), q.clone()).await;
+                }
+            }
+        }
+        out.extend(fetched);
+        Ok(out)
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.inner
+            .as_batch_quote_provider()
+            .map_or(50, BatchQuoteProvider::max_batch_size)
+    }
+}
+
 #[async_trait]
 impl ProfileProvider for CachingConnector {
     async fn profile(&self, instrument: &Instrument) -> Result<Profile, BorsaError> {
@@ -686,6 +938,86 @@ impl IsinProvider for CachingConnector {
     }
 }
 
+impl CachingConnector {
+    /// The window a series-mode request covers, in unix seconds.
+    ///
+    /// Ranges are measured back from now. `None` for ranges without a lower bound (`max`),
+    /// which are cached per request instead.
+    fn series_window(req: &HistoryRequest) -> Option<(i64, i64)> {
+        if let Some((start, end)) = req.period() {
+            return Some((start.timestamp(), end.timestamp()));
+        }
+        let now = Utc::now();
+        let start = borsa_core::timeseries::window::range_start(req.range()?, now)?;
+        // Periods are end-exclusive; include a bar that starts in the current second.
+        Some((start.timestamp(), now.timestamp() + 1))
+    }
+
+    /// Serve a request from the stitched series, fetching only the uncovered head/tail.
+    async fn history_from_series(
+        &self,
+        series: &Arc<dyn CacheStore<SeriesKey, HistorySeries>>,
+        instrument: &Instrument,
+        req: &HistoryRequest,
+        (start, end): (i64, i64),
+    ) -> Result<HistoryResponse, BorsaError> {
+        let key = SeriesKey::from_request(instrument, req);
+        let cached = series.get_if_present(&key).await;
+        let (windows, keep_cached) = HistorySeries::missing(cached.as_ref(), start, end);
+        if windows.is_empty()
+            && let Some(c) = cached.as_ref()
+        {
+            return Ok(c.slice(start, end));
+        }
+
+        let provider = self
+            .inner
+            .as_history_provider()
+            .ok_or_else(|| BorsaError::unsupported("history"))?;
+        let mut fetched: Vec<HistoryResponse> = Vec::with_capacity(windows.len());
+        for &(from, to) in &windows {
+            let from_dt = DateTime::<Utc>::from_timestamp(from, 0).ok_or_else(|| {
+                BorsaError::InvalidArg(format!("invalid start timestamp: {from}"))
+            })?;
+            let to_dt = DateTime::<Utc>::from_timestamp(to, 0)
+                .ok_or_else(|| BorsaError::InvalidArg(format!("invalid end timestamp: {to}")))?;
+            let window_req = HistoryRequestBuilder::default()
+                .period(from_dt, to_dt)
+                .interval(req.interval())
+                .include_prepost(req.include_prepost())
+                .include_actions(req.include_actions())
+                .auto_adjust(req.auto_adjust())
+                .keepna(req.keepna())
+                .build()?;
+            fetched.push(provider.history(instrument, window_req).await?);
+        }
+
+        #[cfg(feature = "tracing")]
+        debug!(
+            target = "borsa::middleware::cache",
+            event = "history_series_fetch",
+            windows = windows.len(),
+            stitched = keep_cached,
+            "fetching uncovered history windows"
+        );
+
+        // Fresh windows come first so they win over cached candles at the seams.
+        let (window_start, window_end, previous) = match cached {
+            Some(c) if keep_cached => (c.start.min(start), c.end.max(end), Some(c.response)),
+            _ => (start, end, None),
+        };
+        let merged = borsa_core::stitch_history(fetched.into_iter().chain(previous))?;
+        let updated = HistorySeries {
+            start: window_start,
+            end: window_end,
+            response: merged,
+        };
+        let out = updated.slice(start, end);
+        series.insert(key, updated).await;
+        Ok(out)
+    }
+}
+
 #[async_trait]
 impl HistoryProvider for CachingConnector {
     async fn history(
@@ -694,6 +1026,27 @@ impl HistoryProvider for CachingConnector {
         req: HistoryRequest,
     ) -> Result<HistoryResponse, BorsaError> {
         let key = HistoryKey::from_request(instrument, &req);
+
+        if let Some(series) = self.stores.history_series.as_ref()
+            && let Some(window) = Self::series_window(&req)
+        {
+            if let Some(neg_store) = self.stores.history_neg.as_ref()
+                && let Some(err) = neg_store.get_if_present(&key).await
+            {
+                return Err(err);
+            }
+            let res = self
+                .history_from_series(series, instrument, &req, window)
+                .await;
+            if let Err(e) = &res
+                && e.is_permanent()
+                && let Some(neg_store) = self.stores.history_neg.as_ref()
+            {
+                neg_store.insert(key, e.clone()).await;
+            }
+            return res;
+        }
+
         let inner = Arc::clone(&self.inner);
         let instrument_clone = instrument.clone();
         let req_clone = req.clone();
@@ -1258,11 +1611,7 @@ impl NewsProvider for CachingConnector {
         instrument: &Instrument,
         req: NewsRequest,
     ) -> Result<Vec<NewsArticle>, BorsaError> {
-        let key = NewsKey {
-            inst: instrument.clone(),
-            count: req.count,
-            tab: NewsTabKey(req.tab),
-        };
+        let key = NewsKey::from_request(instrument, &req);
         let inner = Arc::clone(&self.inner);
         let instrument_clone = instrument.clone();
         let req_clone = req;
@@ -1419,11 +1768,7 @@ impl OptionChainProvider for CachingConnector {
 #[async_trait]
 impl SearchProvider for CachingConnector {
     async fn search(&self, req: SearchRequest) -> Result<SearchResponse, BorsaError> {
-        let key = SearchKey {
-            query: req.query().to_string(),
-            kind: req.kind(),
-            limit: req.limit(),
-        };
+        let key = SearchKey::from_request(&req);
         let inner = Arc::clone(&self.inner);
         let req_clone = req.clone();
         let loader: CacheLoader<SearchKey, SearchResponse> = Arc::new(move |_key| {
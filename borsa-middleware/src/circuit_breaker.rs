@@ -0,0 +1,410 @@
+//! Circuit breaker middleware that fast-fails a capability after repeated provider failures.
+//!
+//! Each capability has its own circuit. A closed circuit passes calls through and counts
+//! failures; once it opens, calls fail immediately with [`BorsaError::CircuitOpen`] until
+//! `open_duration` elapses. The circuit then turns half-open and admits up to
+//! `half_open_probes` probe calls: a successful probe closes it, a failed probe reopens it.
+//! A probe rejected by a local quota or blacklist layer gives its slot back. A call the
+//! router cancels at its timeout counts as a failure.
+//!
+//! Internal orchestrator calls flagged via [`CallOrigin::Internal`](borsa_core::CallOrigin)
+//! bypass the breaker entirely, neither blocked nor counted.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use borsa_core::connector::BorsaConnector;
+use borsa_core::{BorsaError, CallContext, CallOrigin, Capability, Middleware};
+use borsa_types::CircuitBreakerConfig;
+#[cfg(feature = "tracing")]
+use tracing::{debug, info, warn};
+
+#[derive(Debug, Clone, Copy)]
+enum Phase {
+    Closed,
+    Open { until: Instant },
+    HalfOpen { since: Instant, in_flight: u32 },
+}
+
+#[derive(Debug)]
+struct Breaker {
+    phase: Phase,
+    consecutive: u32,
+    // (time, failed) outcomes inside the failure-rate window
+    outcomes: VecDeque<(Instant, bool)>,
+}
+
+impl Default for Breaker {
+    fn default() -> Self {
+        Self {
+            phase: Phase::Closed,
+            consecutive: 0,
+            outcomes: VecDeque::new(),
+        }
+    }
+}
+
+impl Breaker {
+    fn reset(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.consecutive = 0;
+        self.outcomes.clear();
+    }
+
+    fn prune(&mut self, now: Instant, window: Duration) {
+        while self
+            .outcomes
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) > window)
+        {
+            self.outcomes.pop_front();
+        }
+    }
+}
+
+fn remaining_ms(until: Instant, now: Instant) -> u64 {
+    let ms: u64 = until
+        .saturating_duration_since(now)
+        .as_millis()
+        .try_into()
+        .unwrap_or(u64::MAX);
+    ms.max(1)
+}
+
+/// Middleware that opens a per-capability circuit after repeated provider failures.
+pub struct CircuitBreakerConnector {
+    inner: Arc<dyn BorsaConnector>,
+    cfg: CircuitBreakerConfig,
+    state: Mutex<HashMap<Capability, Breaker>>,
+}
+
+impl CircuitBreakerConnector {
+    pub fn new(inner: Arc<dyn BorsaConnector>, cfg: CircuitBreakerConfig) -> Self {
+        #[cfg(feature = "tracing")]
+        {
+            info!(
+                target = "borsa::middleware::circuit_breaker",
+                event = "init",
+                failure_threshold = cfg.failure_threshold,
+                failure_rate = ?cfg.failure_rate,
+                open_duration_ms = u64::try_from(cfg.open_duration.as_millis()).unwrap_or(u64::MAX),
+                half_open_probes = cfg.half_open_probes,
+                "initialized circuit breaker middleware"
+            );
+        }
+        Self {
+            inner,
+            cfg,
+            state: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns true if calls for `capability` are currently rejected without reaching
+    /// the provider (open circuit, or half-open with every probe slot taken).
+    #[must_use]
+    pub fn is_open(&self, capability: Capability) -> bool {
+        let guard = self.state.lock().expect("mutex poisoned");
+        let now = Instant::now();
+        match guard.get(&capability).map(|b| b.phase) {
+            Some(Phase::Open { until }) => now < until,
+            Some(Phase::HalfOpen { since, in_flight }) => {
+                in_flight >= self.cfg.half_open_probes.max(1)
+                    && now.saturating_duration_since(since) < self.cfg.open_duration
+            }
+            Some(Phase::Closed) | None => false,
+        }
+    }
+
+    fn admit(&self, capability: Capability) -> Result<(), BorsaError> {
+        let mut guard = self.state.lock().expect("mutex poisoned");
+        let breaker = guard.entry(capability).or_default();
+        let now = Instant::now();
+        let probes = self.cfg.half_open_probes.max(1);
+        let reset_in_ms = match breaker.phase {
+            Phase::Closed => return Ok(()),
+            Phase::Open { until } if now < until => remaining_ms(until, now),
+            Phase::Open { .. } => {
+                breaker.phase = Phase::HalfOpen {
+                    since: now,
+                    in_flight: 1,
+                };
+                return Ok(());
+            }
+            Phase::HalfOpen { since, in_flight } => {
+                let stale_at = since + self.cfg.open_duration;
+                if now >= stale_at {
+                    // Probes dropped without a deadline never report back.
+                    breaker.phase = Phase::HalfOpen {
+                        since: now,
+                        in_flight: 1,
+                    };
+                    return Ok(());
+                }
+                if in_flight < probes {
+                    breaker.phase = Phase::HalfOpen {
+                        since,
+                        in_flight: in_flight + 1,
+                    };
+                    return Ok(());
+                }
+                remaining_ms(stale_at, now)
+            }
+        };
+        Err(BorsaError::CircuitOpen {
+            capability: capability.to_string(),
+            reset_in_ms,
+        })
+    }
+
+    fn record_success(&self, capability: Capability) {
+        let mut guard = self.state.lock().expect("mutex poisoned");
+        let breaker = guard.entry(capability).or_default();
+        match breaker.phase {
+            Phase::HalfOpen { .. } => {
+                breaker.reset(Phase::Closed);
+                #[cfg(feature = "tracing")]
+                info!(
+                    target = "borsa::middleware::circuit_breaker",
+                    event = "closed",
+                    capability = %capability,
+                    "circuit closed after successful probe"
+                );
+            }
+            Phase::Closed => {
+                let now = Instant::now();
+                breaker.consecutive = 0;
+                if self.cfg.failure_rate.is_some() {
+                    breaker.outcomes.push_back((now, false));
+                    breaker.prune(now, self.cfg.window);
+                }
+            }
+            Phase::Open { .. } => {}
+        }
+    }
+
+    /// Release the slot of a half-open probe that ended without saying anything about the
+    /// provider's health.
+    fn record_neutral(&self, capability: Capability) {
+        let mut guard = self.state.lock().expect("mutex poisoned");
+        if let Some(breaker) = guard.get_mut(&capability)
+            && let Phase::HalfOpen { since, in_flight } = breaker.phase
+        {
+            breaker.phase = Phase::HalfOpen {
+                since,
+                in_flight: in_flight.saturating_sub(1),
+            };
+        }
+    }
+
+    fn record_failure(&self, capability: Capability) {
+        let mut guard = self.state.lock().expect("mutex poisoned");
+        let breaker = guard.entry(capability).or_default();
+        let now = Instant::now();
+        let trip = match breaker.phase {
+            Phase::HalfOpen { .. } => true,
+            Phase::Closed => {
+                breaker.consecutive = breaker.consecutive.saturating_add(1);
+                let by_count = self.cfg.failure_threshold > 0
+                    && breaker.consecutive >= self.cfg.failure_threshold;
+                let by_rate = self.cfg.failure_rate.is_some_and(|rate| {
+                    breaker.outcomes.push_back((now, true));
+                    breaker.prune(now, self.cfg.window);
+                    let calls = breaker.outcomes.len();
+                    let failed = breaker.outcomes.iter().filter(|(_, f)| *f).count();
+                    #[allow(clippy::cast_precision_loss)]
+                    let share = failed as f64 / calls as f64;
+                    calls >= self.cfg.min_calls as usize && share >= rate
+                });
+                by_count || by_rate
+            }
+            Phase::Open { .. } => false,
+        };
+        if trip {
+            breaker.reset(Phase::Open {
+                until: now + self.cfg.open_duration,
+            });
+            #[cfg(feature = "tracing")]
+            warn!(
+                target = "borsa::middleware::circuit_breaker",
+                event = "opened",
+                capability = %capability,
+                open_duration_ms =
+                    u64::try_from(self.cfg.open_duration.as_millis()).unwrap_or(u64::MAX),
+                "circuit opened after provider failures"
+            );
+        }
+    }
+}
+
+/// Non-permanent errors count against the circuit, except rejections raised by local
+/// quota/blacklist layers, which say nothing about the provider's health.
+fn counts_as_failure(err: &BorsaError) -> bool {
+    match err {
+        BorsaError::Connector { error, .. } => counts_as_failure(error),
+        BorsaError::QuotaExceeded { .. }
+        | BorsaError::TemporarilyBlacklisted { .. }
+        | BorsaError::CircuitOpen { .. } => false,
+        other => !other.is_permanent(),
+    }
+}
+
+fn config_json(cfg: &CircuitBreakerConfig) -> serde_json::Value {
+    serde_json::json!({
+        "failure_threshold": cfg.failure_threshold,
+        "failure_rate": cfg.failure_rate,
+        "window_ms": cfg.window.as_millis(),
+        "min_calls": cfg.min_calls,
+        "open_duration_ms": cfg.open_duration.as_millis(),
+        "half_open_probes": cfg.half_open_probes,
+    })
+}
+
+/// Middleware config for constructing a [`CircuitBreakerConnector`].
+pub struct CircuitBreakerMiddleware {
+    pub config: CircuitBreakerConfig,
+}
+
+impl CircuitBreakerMiddleware {
+    #[must_use]
+    pub const fn new(config: CircuitBreakerConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Middleware for CircuitBreakerMiddleware {
+    fn apply(self: Box<Self>, inner: Arc<dyn BorsaConnector>) -> Arc<dyn BorsaConnector> {
+        #[cfg(feature = "tracing")]
+        {
+            info!(
+                target = "borsa::middleware::circuit_breaker",
+                event = "apply",
+                failure_threshold = self.config.failure_threshold,
+                "applying circuit breaker middleware"
+            );
+        }
+        Arc::new(CircuitBreakerConnector::new(inner, self.config))
+    }
+
+    fn name(&self) -> &'static str {
+        "CircuitBreakerConnector"
+    }
+
+    fn config_json(&self) -> serde_json::Value {
+        config_json(&self.config)
+    }
+
+    fn validate(&self, _ctx: &borsa_core::middleware::ValidationContext) -> Result<(), BorsaError> {
+        if let Some(rate) = self.config.failure_rate
+            && !(rate > 0.0 && rate <= 1.0)
+        {
+            return Err(BorsaError::InvalidMiddlewareStack {
+                message: "circuit breaker failure_rate must be in (0, 1]".into(),
+            });
+        }
+        if self.config.failure_threshold == 0 && self.config.failure_rate.is_none() {
+            return Err(BorsaError::InvalidMiddlewareStack {
+                message: "circuit breaker needs a failure_threshold or a failure_rate".into(),
+            });
+        }
+        if self.config.open_duration.is_zero() {
+            return Err(BorsaError::InvalidMiddlewareStack {
+                message: "circuit breaker open_duration must be non-zero".into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[borsa_macros::delegate_connector(inner)]
+#[borsa_macros::delegate_all_providers(inner)]
+impl CircuitBreakerConnector {}
+
+#[async_trait]
+impl Middleware for CircuitBreakerConnector {
+    fn apply(self: Box<Self>, _inner: Arc<dyn BorsaConnector>) -> Arc<dyn BorsaConnector> {
+        unreachable!("CircuitBreakerConnector is already applied")
+    }
+
+    fn name(&self) -> &'static str {
+        "CircuitBreakerConnector"
+    }
+
+    fn config_json(&self) -> serde_json::Value {
+        config_json(&self.cfg)
+    }
+
+    async fn pre_call(&self, ctx: &CallContext) -> Result<(), BorsaError> {
+        if matches!(ctx.origin(), CallOrigin::Internal { .. }) {
+            return Ok(());
+        }
+        #[cfg(feature = "tracing")]
+        debug!(
+            target = "borsa::middleware::circuit_breaker",
+            event = "pre_call",
+            capability = %ctx.capability(),
+            origin = ?ctx.origin(),
+            "circuit breaker pre-call check"
+        );
+        let res = self.admit(ctx.capability());
+        #[cfg(feature = "tracing")]
+        {
+            if let Err(BorsaError::CircuitOpen { reset_in_ms, .. }) = &res {
+                warn!(
+                    target = "borsa::middleware::circuit_breaker",
+                    event = "blocked",
+                    capability = %ctx.capability(),
+                    reset_in_ms = *reset_in_ms,
+                    "blocked by open circuit"
+                );
+            }
+        }
+        res
+    }
+
+    fn map_error(&self, err: BorsaError, ctx: &CallContext) -> BorsaError {
+        if matches!(ctx.origin(), CallOrigin::Internal { .. }) {
+            return err;
+        }
+        #[cfg(feature = "tracing")]
+        debug!(
+            target = "borsa::middleware::circuit_breaker",
+            event = "map_error",
+            capability = %ctx.capability(),
+            origin = ?ctx.origin(),
+            %err,
+            "circuit breaker observing provider error"
+        );
+        if counts_as_failure(&err) {
+            self.record_failure(ctx.capability());
+        } else if err.is_permanent() {
+            // Not found, unsupported and bad input prove the provider answered.
+            self.record_success(ctx.capability());
+        } else {
+            self.record_neutral(ctx.capability());
+        }
+        err
+    }
+
+    fn on_success(&self, ctx: &CallContext) {
+        if matches!(ctx.origin(), CallOrigin::Internal { .. }) {
+            return;
+        }
+        self.record_success(ctx.capability());
+    }
+
+    fn on_abandoned(&self, ctx: &CallContext) {
+        if matches!(ctx.origin(), CallOrigin::Internal { .. }) {
+            return;
+        }
+        #[cfg(feature = "tracing")]
+        debug!(
+            target = "borsa::middleware::circuit_breaker",
+            event = "abandoned",
+            capability = %ctx.capability(),
+            "provider call cancelled at its deadline"
+        );
+        self.record_failure(ctx.capability());
+    }
+}
@@ -0,0 +1,526 @@
+//! Request coalescing (single-flight) for identical in-flight provider calls.
+//!
+//! Concurrent callers asking for the same `(capability, arguments)` with the same
+//! [`CallOrigin`] share one call to the inner connector and all receive its result, success
+//! or error. Keeping origins apart means an external request never rides on an internal
+//! call that skipped quota and circuit-breaker accounting. Nothing is retained once
+//! the call completes, so unlike caching this never serves stale data; the next request
+//! after completion goes upstream again. Keys reuse the cache key types, so two calls
+//! coalesce exactly when the cache would treat them as the same entry.
+//!
+//! Batch quotes and streaming subscriptions pass through unchanged.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use borsa_core::connector::{
+    AnalystPriceTargetProvider, BalanceSheetProvider, BatchQuoteProvider, CalendarProvider,
+    CandleStreamProvider, CashflowProvider, EarningsProvider, EsgProvider, HistoryProvider,
+    IncomeStatementProvider, InsiderRosterHoldersProvider, InsiderTransactionsProvider,
+    InstitutionalHoldersProvider, IsinProvider, MajorHoldersProvider, MutualFundHoldersProvider,
+    NetSharePurchaseActivityProvider, NewsProvider, OptionChainProvider, OptionStreamProvider,
+    OptionsExpirationsProvider, ProfileProvider, QuoteProvider, RecommendationsProvider,
+    RecommendationsSummaryProvider, SearchProvider, StreamProvider, UpgradesDowngradesProvider,
+};
+use borsa_core::{
+    AssetKind, BalanceSheetRow, BorsaConnector, BorsaError, Calendar, CallOrigin, CandleUpdate,
+    CashflowRow, Earnings, EsgScores, HistoryRequest, HistoryResponse, IncomeStatementRow,
+    InsiderRosterHolder, InsiderTransaction, InstitutionalHolder, Instrument, Interval, Isin,
+    MajorHolder, NetSharePurchaseActivity, NewsArticle, NewsRequest, OptionChain, OptionUpdate,
+    PriceTarget, Profile, Quote, QuoteUpdate, RecommendationRow, RecommendationSummary,
+    SearchRequest, SearchResponse, UpgradeDowngradeRow,
+};
+use tokio::sync::OnceCell;
+#[cfg(feature = "tracing")]
+use tracing::{debug, info};
+
+use crate::cache::{BoolByInstrumentKey, HistoryKey, NewsKey, OptionChainKey, SearchKey};
+
+type Slot<V> = Arc<OnceCell<Result<V, BorsaError>>>;
+
+/// In-flight calls of one capability, keyed by call origin and request arguments.
+struct Flights<K, V> {
+    inflight: Mutex<HashMap<(CallOrigin, K), Slot<V>>>,
+}
+
+impl<K, V> Flights<K, V>
+where
+    K: Clone + Hash + Eq,
+    V: Clone,
+{
+    fn new() -> Self {
+        Self {
+            inflight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `call` unless an identical call from the same origin is already in flight, then
+    /// share its result.
+    ///
+    /// If the caller driving the call is cancelled, one of the waiters takes over with its
+    /// own `call`.
+    async fn run<F, Fut>(&self, key: K, call: F) -> Result<V, BorsaError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, BorsaError>>,
+    {
+        let flight = self.join((CallOrigin::current(), key));
+        flight.slot().get_or_init(call).await.clone()
+    }
+
+    fn join(&self, key: (CallOrigin, K)) -> Flight<'_, K, V> {
+        let mut guard = self.inflight.lock().expect("mutex poisoned");
+        let slot = Arc::clone(guard.entry(key.clone()).or_default());
+        #[cfg(feature = "tracing")]
+        if Arc::strong_count(&slot) > 2 {
+            debug!(
+                target = "borsa::middleware::coalesce",
+                event = "join",
+                waiters = Arc::strong_count(&slot) - 1,
+                "joined in-flight call"
+            );
+        }
+        Flight {
+            flights: self,
+            key,
+            slot: Some(slot),
+        }
+    }
+}
+
+/// A caller's membership in one in-flight call.
+///
+/// Dropping the last membership, or any membership once the result is known, removes the
+/// entry so completed calls are never served again. Reference counts only change under the
+/// map lock, which keeps the "last member" check exact.
+struct Flight<'a, K: Hash + Eq, V> {
+    flights: &'a Flights<K, V>,
+    key: (CallOrigin, K),
+    slot: Option<Slot<V>>,
+}
+
+impl<K: Hash + Eq, V> Flight<'_, K, V> {
+    fn slot(&self) -> &OnceCell<Result<V, BorsaError>> {
+        self.slot.as_ref().expect("slot present until drop")
+    }
+}
+
+impl<K: Hash + Eq, V> Drop for Flight<'_, K, V> {
+    fn drop(&mut self) {
+        let mut guard = self.flights.inflight.lock().expect("mutex poisoned");
+        let Some(slot) = self.slot.take() else {
+            return;
+        };
+        let done = slot.initialized();
+        // A finished flight may already have been replaced by a newer one for the same key.
+        let remove = guard.get(&self.key).is_some_and(|current| {
+            Arc::ptr_eq(current, &slot) && (done || Arc::strong_count(current) == 2)
+        });
+        drop(slot);
+        if remove {
+            guard.remove(&self.key);
+        }
+    }
+}
+
+struct AllFlights {
+    quote: Flights<Instrument, Quote>,
+    profile: Flights<Instrument, Profile>,
+    isin: Flights<Instrument, Option<Isin>>,
+    history: Flights<HistoryKey, HistoryResponse>,
+    earnings: Flights<Instrument, Earnings>,
+    income_statement: Flights<BoolByInstrumentKey, Vec<IncomeStatementRow>>,
+    balance_sheet: Flights<BoolByInstrumentKey, Vec<BalanceSheetRow>>,
+    cashflow: Flights<BoolByInstrumentKey, Vec<CashflowRow>>,
+    calendar: Flights<Instrument, Calendar>,
+    recommendations: Flights<Instrument, Vec<RecommendationRow>>,
+    recommendations_summary: Flights<Instrument, RecommendationSummary>,
+    upgrades_downgrades: Flights<Instrument, Vec<UpgradeDowngradeRow>>,
+    analyst_price_target: Flights<Instrument, PriceTarget>,
+    major_holders: Flights<Instrument, Vec<MajorHolder>>,
+    institutional_holders: Flights<Instrument, Vec<InstitutionalHolder>>,
+    mutual_fund_holders: Flights<Instrument, Vec<InstitutionalHolder>>,
+    insider_transactions: Flights<Instrument, Vec<InsiderTransaction>>,
+    insider_roster_holders: Flights<Instrument, Vec<InsiderRosterHolder>>,
+    net_share_purchase_activity: Flights<Instrument, Option<NetSharePurchaseActivity>>,
+    sustainability: Flights<Instrument, EsgScores>,
+    news: Flights<NewsKey, Vec<NewsArticle>>,
+    options_expirations: Flights<Instrument, Vec<i64>>,
+    option_chain: Flights<OptionChainKey, OptionChain>,
+    search: Flights<SearchKey, SearchResponse>,
+}
+
+impl AllFlights {
+    fn new() -> Self {
+        Self {
+            quote: Flights::new(),
+            profile: Flights::new(),
+            isin: Flights::new(),
+            history: Flights::new(),
+            earnings: Flights::new(),
+            income_statement: Flights::new(),
+            balance_sheet: Flights::new(),
+            cashflow: Flights::new(),
+            calendar: Flights::new(),
+            recommendations: Flights::new(),
+            recommendations_summary: Flights::new(),
+            upgrades_downgrades: Flights::new(),
+            analyst_price_target: Flights::new(),
+            major_holders: Flights::new(),
+            institutional_holders: Flights::new(),
+            mutual_fund_holders: Flights::new(),
+            insider_transactions: Flights::new(),
+            insider_roster_holders: Flights::new(),
+            net_share_purchase_activity: Flights::new(),
+            sustainability: Flights::new(),
+            news: Flights::new(),
+            options_expirations: Flights::new(),
+            option_chain: Flights::new(),
+            search: Flights::new(),
+        }
+    }
+}
+
+/// Middleware that shares one in-flight provider call among identical concurrent requests.
+pub struct CoalescingConnector {
+    inner: Arc<dyn BorsaConnector>,
+    flights: AllFlights,
+}
+
+impl CoalescingConnector {
+    #[must_use]
+    pub fn new(inner: Arc<dyn BorsaConnector>) -> Self {
+        #[cfg(feature = "tracing")]
+        info!(
+            target = "borsa::middleware::coalesce",
+            event = "init",
+            connector = inner.name(),
+            "initialized coalescing middleware"
+        );
+        Self {
+            inner,
+            flights: AllFlights::new(),
+        }
+    }
+}
+
+/// Declarative wrapper that applies request coalescing when building a connector stack.
+#[derive(Debug, Default)]
+pub struct CoalescingMiddleware;
+
+impl CoalescingMiddleware {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl borsa_core::Middleware for CoalescingMiddleware {
+    fn apply(self: Box<Self>, inner: Arc<dyn BorsaConnector>) -> Arc<dyn BorsaConnector> {
+        Arc::new(CoalescingConnector::new(inner))
+    }
+
+    fn name(&self) -> &'static str {
+        "CoalescingConnector"
+    }
+
+    fn config_json(&self) -> serde_json::Value {
+        serde_json::json!({})
+    }
+}
+
+#[borsa_macros::delegate_connector(inner)]
+impl CoalescingConnector {}
+
+#[async_trait]
+impl borsa_core::Middleware for CoalescingConnector {
+    fn apply(self: Box<Self>, _inner: Arc<dyn BorsaConnector>) -> Arc<dyn BorsaConnector> {
+        unreachable!("CoalescingConnector is already applied")
+    }
+    fn name(&self) -> &'static str {
+        "CoalescingConnector"
+    }
+    fn config_json(&self) -> serde_json::Value {
+        serde_json::json!({})
+    }
+}
+
+/// Coalesce a provider method whose only argument is the instrument.
+macro_rules! coalesce_by_instrument {
+    ($trait_:ident, $as_provider:ident, $method:ident -> $out:ty) => {
+        #[async_trait]
+        impl $trait_ for CoalescingConnector {
+            async fn $method(&self, instrument: &Instrument) -> Result<$out, BorsaError> {
+                let provider = self
+                    .inner
+                    .$as_provider()
+                    .ok_or_else(|| BorsaError::unsupported(stringify!($method)))?;
+                self.flights
+                    .$method
+                    .run(instrument.clone(), || provider.$method(instrument))
+                    .await
+            }
+        }
+    };
+}
+
+/// Coalesce a statement method keyed by instrument and the `quarterly` flag.
+macro_rules! coalesce_statement {
+    ($trait_:ident, $as_provider:ident, $method:ident -> $out:ty) => {
+        #[async_trait]
+        impl $trait_ for CoalescingConnector {
+            async fn $method(
+                &self,
+                instrument: &Instrument,
+                quarterly: bool,
+            ) -> Result<$out, BorsaError> {
+                let provider = self
+                    .inner
+                    .$as_provider()
+                    .ok_or_else(|| BorsaError::unsupported(stringify!($method)))?;
+                let key = BoolByInstrumentKey {
+                    inst: instrument.clone(),
+                    flag: quarterly,
+                };
+                self.flights
+                    .$method
+                    .run(key, || provider.$method(instrument, quarterly))
+                    .await
+            }
+        }
+    };
+}
+
+coalesce_by_instrument!(QuoteProvider, as_quote_provider, quote -> Quote);
+coalesce_by_instrument!(ProfileProvider, as_profile_provider, profile -> Profile);
+coalesce_by_instrument!(IsinProvider, as_isin_provider, isin -> Option<Isin>);
+coalesce_by_instrument!(EarningsProvider, as_earnings_provider, earnings -> Earnings);
+coalesce_by_instrument!(CalendarProvider, as_calendar_provider, calendar -> Calendar);
+coalesce_by_instrument!(
+    RecommendationsProvider,
+    as_recommendations_provider,
+    recommendations -> Vec<RecommendationRow>
+);
+coalesce_by_instrument!(
+    RecommendationsSummaryProvider,
+    as_recommendations_summary_provider,
+    recommendations_summary -> RecommendationSummary
+);
+coalesce_by_instrument!(
+    UpgradesDowngradesProvider,
+    as_upgrades_downgrades_provider,
+    upgrades_downgrades -> Vec<UpgradeDowngradeRow>
+);
+coalesce_by_instrument!(
+    AnalystPriceTargetProvider,
+    as_analyst_price_target_provider,
+    analyst_price_target -> PriceTarget
+);
+coalesce_by_instrument!(
+    MajorHoldersProvider,
+    as_major_holders_provider,
+    major_holders -> Vec<MajorHolder>
+);
+coalesce_by_instrument!(
+    InstitutionalHoldersProvider,
+    as_institutional_holders_provider,
+    institutional_holders -> Vec<InstitutionalHolder>
+);
+coalesce_by_instrument!(
+    MutualFundHoldersProvider,
+    as_mutual_fund_holders_provider,
+    mutual_fund_holders -> Vec<InstitutionalHolder>
+);
+coalesce_by_instrument!(
+    InsiderTransactionsProvider,
+    as_insider_transactions_provider,
+    insider_transactions -> Vec<InsiderTransaction>
+);
+coalesce_by_instrument!(
+    InsiderRosterHoldersProvider,
+    as_insider_roster_holders_provider,
+    insider_roster_holders -> Vec<InsiderRosterHolder>
+);
+coalesce_by_instrument!(
+    NetSharePurchaseActivityProvider,
+    as_net_share_purchase_activity_provider,
+    net_share_purchase_activity -> Option<NetSharePurchaseActivity>
+);
+coalesce_by_instrument!(EsgProvider, as_esg_provider, sustainability -> EsgScores);
+coalesce_by_instrument!(
+    OptionsExpirationsProvider,
+    as_options_expirations_provider,
+    options_expirations -> Vec<i64>
+);
+coalesce_statement!(
+    IncomeStatementProvider,
+    as_income_statement_provider,
+    income_statement -> Vec<IncomeStatementRow>
+);
+coalesce_statement!(
+    BalanceSheetProvider,
+    as_balance_sheet_provider,
+    balance_sheet -> Vec<BalanceSheetRow>
+);
+coalesce_statement!(CashflowProvider, as_cashflow_provider, cashflow -> Vec<CashflowRow>);
+
+#[async_trait]
+impl HistoryProvider for CoalescingConnector {
+    async fn history(
+        &self,
+        instrument: &Instrument,
+        req: HistoryRequest,
+    ) -> Result<HistoryResponse, BorsaError> {
+        let provider = self
+            .inner
+            .as_history_provider()
+            .ok_or_else(|| BorsaError::unsupported("history"))?;
+        let key = HistoryKey::from_request(instrument, &req);
+        self.flights
+            .history
+            .run(key, || provider.history(instrument, req))
+            .await
+    }
+
+    fn supported_history_intervals(&self, kind: AssetKind) -> &'static [Interval] {
+        if let Some(inner) = self.inner.as_history_provider() {
+            inner.supported_history_intervals(kind)
+        } else {
+            &[]
+        }
+    }
+}
+
+#[async_trait]
+impl NewsProvider for CoalescingConnector {
+    async fn news(
+        &self,
+        instrument: &Instrument,
+        req: NewsRequest,
+    ) -> Result<Vec<NewsArticle>, BorsaError> {
+        let provider = self
+            .inner
+            .as_news_provider()
+            .ok_or_else(|| BorsaError::unsupported("news"))?;
+        let key = NewsKey::from_request(instrument, &req);
+        self.flights
+            .news
+            .run(key, || provider.news(instrument, req))
+            .await
+    }
+}
+
+#[async_trait]
+impl OptionChainProvider for CoalescingConnector {
+    async fn option_chain(
+        &self,
+        instrument: &Instrument,
+        date: Option<i64>,
+    ) -> Result<OptionChain, BorsaError> {
+        let provider = self
+            .inner
+            .as_option_chain_provider()
+            .ok_or_else(|| BorsaError::unsupported("option_chain"))?;
+        let key = OptionChainKey {
+            inst: instrument.clone(),
+            date,
+        };
+        self.flights
+            .option_chain
+            .run(key, || provider.option_chain(instrument, date))
+            .await
+    }
+}
+
+#[async_trait]
+impl SearchProvider for CoalescingConnector {
+    async fn search(&self, req: SearchRequest) -> Result<SearchResponse, BorsaError> {
+        let provider = self
+            .inner
+            .as_search_provider()
+            .ok_or_else(|| BorsaError::unsupported("search"))?;
+        let key = SearchKey::from_request(&req);
+        self.flights.search.run(key, || provider.search(req)).await
+    }
+}
+
+#[async_trait]
+impl BatchQuoteProvider for CoalescingConnector {
+    async fn quotes(&self, instruments: &[Instrument]) -> Result<Vec<Quote>, BorsaError> {
+        let provider = self
+            .inner
+            .as_batch_quote_provider()
+            .ok_or_else(|| BorsaError::unsupported("quotes"))?;
+        provider.quotes(instruments).await
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.inner
+            .as_batch_quote_provider()
+            .map_or(50, BatchQuoteProvider::max_batch_size)
+    }
+}
+
+#[async_trait]
+impl StreamProvider for CoalescingConnector {
+    async fn stream_quotes(
+        &self,
+        instruments: &[Instrument],
+    ) -> Result<
+        (
+            borsa_core::stream::StreamHandle,
+            tokio::sync::mpsc::Receiver<QuoteUpdate>,
+        ),
+        BorsaError,
+    > {
+        let inner = self
+            .inner
+            .as_stream_provider()
+            .ok_or_else(|| BorsaError::unsupported("stream_quotes"))?;
+        inner.stream_quotes(instruments).await
+    }
+}
+
+#[async_trait]
+impl CandleStreamProvider for CoalescingConnector {
+    async fn stream_candles(
+        &self,
+        instruments: &[Instrument],
+        interval: Interval,
+    ) -> Result<
+        (
+            borsa_core::stream::StreamHandle,
+            tokio::sync::mpsc::Receiver<CandleUpdate>,
+        ),
+        BorsaError,
+    > {
+        let inner = self
+            .inner
+            .as_candle_stream_provider()
+            .ok_or_else(|| BorsaError::unsupported("stream_candles"))?;
+        inner.stream_candles(instruments, interval).await
+    }
+}
+
+#[async_trait]
+impl OptionStreamProvider for CoalescingConnector {
+    async fn stream_options(
+        &self,
+        instruments: &[Instrument],
+    ) -> Result<
+        (
+            borsa_core::stream::StreamHandle,
+            tokio::sync::mpsc::Receiver<OptionUpdate>,
+        ),
+        BorsaError,
+    > {
+        let inner = self
+            .inner
+            .as_option_stream_provider()
+            .ok_or_else(|| BorsaError::unsupported("stream_options"))?;
+        inner.stream_options(instruments).await
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use borsa_core::BorsaError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{CacheLoader, CacheStore};
+
+/// File-backed [`CacheStore`] that survives process restarts.
+///
+/// Entries are kept in memory and mirrored to a JSON-lines file: each insert appends one record
+/// with the serialized key, the value and its absolute expiry time. The file is compacted when
+/// opened and again after every `capacity` appends, so its size stays proportional to
+/// `capacity`. Keys are matched by their JSON encoding.
+///
+/// File writes run on the blocking thread pool, in the order the inserts were made, and an
+/// insert completes once its record is written. Eviction follows an expiry-ordered index, so
+/// inserts stay cheap at large capacities.
+///
+/// A store file must have a single writer; sharing one path between processes is not supported.
+/// Unlike [`MokaStore`](crate::MokaStore), concurrent misses for the same key are not coalesced.
+pub struct FileStore<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    state: Mutex<State<V>>,
+    disk: Arc<Disk>,
+    _key: PhantomData<fn(K)>,
+}
+
+struct State<V> {
+    entries: HashMap<String, Entry<V>>,
+    /// Keys ordered by expiry, then by insertion, for eviction.
+    by_expiry: BTreeMap<(u64, u64), String>,
+    /// Insertion counter breaking expiry ties in `by_expiry`.
+    seq: u64,
+    /// Records appended since the last compaction.
+    appended: usize,
+}
+
+struct Entry<V> {
+    value: V,
+    expires_at_ms: u64,
+    seq: u64,
+}
+
+/// The store file and the writes still to be applied to it.
+struct Disk {
+    path: PathBuf,
+    /// Filled under the state lock, so writes are queued in insertion order.
+    pending: Mutex<VecDeque<DiskWrite>>,
+    /// Held while writing, so one writer drains the queue at a time.
+    io: Mutex<()>,
+}
+
+enum DiskWrite {
+    Append(String),
+    Rewrite(Vec<String>),
+}
+
+#[derive(Serialize)]
+struct RecordRef<'a, V> {
+    key: &'a str,
+    expires_at_ms: u64,
+    value: &'a V,
+}
+
+#[derive(Deserialize)]
+struct Record<V> {
+    key: String,
+    expires_at_ms: u64,
+    value: V,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+fn io_error(path: &Path, err: &std::io::Error) -> BorsaError {
+    BorsaError::Other(format!("cache file {}: {err}", path.display()))
+}
+
+fn record_line<V: Serialize>(key: &str, expires_at_ms: u64, value: &V) -> Option<String> {
+    serde_json::to_string(&RecordRef {
+        key,
+        expires_at_ms,
+        value,
+    })
+    .ok()
+}
+
+impl<V> State<V> {
+    fn insert(&mut self, key: String, value: V, expires_at_ms: u64) {
+        self.seq += 1;
+        let seq = self.seq;
+        self.by_expiry.insert((expires_at_ms, seq), key.clone());
+        let old = self.entries.insert(
+            key,
+            Entry {
+                value,
+                expires_at_ms,
+                seq,
+            },
+        );
+        if let Some(old) = old {
+            self.by_expiry.remove(&(old.expires_at_ms, old.seq));
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(old) = self.entries.remove(key) {
+            self.by_expiry.remove(&(old.expires_at_ms, old.seq));
+        }
+    }
+
+    /// Drop expired entries, then the earliest-expiring ones until `capacity` remain.
+    fn evict(&mut self, capacity: usize, now: u64) {
+        while let Some(entry) = self.by_expiry.first_entry() {
+            let (expires_at_ms, _) = *entry.key();
+            if expires_at_ms > now && self.entries.len() <= capacity {
+                break;
+            }
+            let key = entry.remove();
+            self.entries.remove(&key);
+        }
+    }
+
+    fn lines(&self) -> Vec<String>
+    where
+        V: Serialize,
+    {
+        self.by_expiry
+            .values()
+            .filter_map(|key| {
+                let entry = self.entries.get(key)?;
+                record_line(key, entry.expires_at_ms, &entry.value)
+            })
+            .collect()
+    }
+}
+
+impl Disk {
+    fn rewrite(&self, lines: &[String]) -> Result<(), BorsaError> {
+        let tmp = self.path.with_extension("jsonl.tmp");
+        let file = File::create(&tmp).map_err(|e| io_error(&tmp, &e))?;
+        let mut writer = BufWriter::new(file);
+        for line in lines {
+            writeln!(writer, "{line}").map_err(|e| io_error(&tmp, &e))?;
+        }
+        writer.flush().map_err(|e| io_error(&tmp, &e))?;
+        drop(writer);
+        fs::rename(&tmp, &self.path).map_err(|e| io_error(&self.path, &e))
+    }
+
+    fn append(&self, line: &str) -> Result<(), BorsaError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| io_error(&self.path, &e))?;
+        writeln!(file, "{line}").map_err(|e| io_error(&self.path, &e))
+    }
+
+    /// Apply every queued write in order. Blocking; runs on the blocking pool.
+    fn drain(&self) {
+        let _io = self.io.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            let next = self
+                .pending
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .pop_front();
+            // Persistence is best-effort: the in-memory entry still serves this process.
+            let _ = match next {
+                Some(DiskWrite::Append(line)) => self.append(&line),
+                Some(DiskWrite::Rewrite(lines)) => self.rewrite(&lines),
+                None => break,
+            };
+        }
+    }
+}
+
+impl<K, V> FileStore<K, V>
+where
+    K: Serialize + Send + Sync + 'static,
+    V: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// Open (or create) a store at `path` holding at most `capacity` live entries, each expiring
+    /// `ttl` after insertion.
+    ///
+    /// Records that fail to parse or have expired are dropped while loading.
+    ///
+    /// # Errors
+    /// Returns an error if the parent directory cannot be created or the file cannot be read
+    /// or rewritten.
+    pub fn open(
+        path: impl Into<PathBuf>,
+        capacity: usize,
+        ttl: Duration,
+    ) -> Result<Self, BorsaError> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error(&path, &e))?;
+        }
+
+        let mut state = State {
+            entries: HashMap::new(),
+            by_expiry: BTreeMap::new(),
+            seq: 0,
+            appended: 0,
+        };
+        let now = now_ms();
+        match File::open(&path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = line.map_err(|e| io_error(&path, &e))?;
+                    let Ok(record) = serde_json::from_str::<Record<V>>(&line) else {
+                        continue;
+                    };
+                    if record.expires_at_ms <= now {
+                        state.remove(&record.key);
+                        continue;
+                    }
+                    state.insert(record.key, record.value, record.expires_at_ms);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(io_error(&path, &e)),
+        }
+
+        let capacity = capacity.max(1);
+        state.evict(capacity, now);
+        let disk = Disk {
+            path,
+            pending: Mutex::new(VecDeque::new()),
+            io: Mutex::new(()),
+        };
+        disk.rewrite(&state.lines())?;
+
+        Ok(Self {
+            capacity,
+            ttl,
+            state: Mutex::new(state),
+            disk: Arc::new(disk),
+            _key: PhantomData,
+        })
+    }
+
+    fn encode_key(key: &K) -> Option<String> {
+        serde_json::to_string(key).ok()
+    }
+
+    fn lookup(&self, key: &str) -> Option<V> {
+        let mut state = self.state.lock().ok()?;
+        let entry = state.entries.get(key)?;
+        if entry.expires_at_ms > now_ms() {
+            return Some(entry.value.clone());
+        }
+        state.remove(key);
+        None
+    }
+
+    async fn store(&self, key: String, value: V) {
+        let now = now_ms();
+        let expires_at_ms =
+            now.saturating_add(u64::try_from(self.ttl.as_millis()).unwrap_or(u64::MAX));
+        let Some(line) = record_line(&key, expires_at_ms, &value) else {
+            return;
+        };
+        {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            state.insert(key, value, expires_at_ms);
+            state.evict(self.capacity, now);
+
+            let write = if state.appended >= self.capacity.max(state.entries.len()) {
+                state.appended = 0;
+                DiskWrite::Rewrite(state.lines())
+            } else {
+                state.appended += 1;
+                DiskWrite::Append(line)
+            };
+            self.disk
+                .pending
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push_back(write);
+        }
+
+        let disk = Arc::clone(&self.disk);
+        let _ = tokio::task::spawn_blocking(move || disk.drain()).await;
+    }
+}
+
+#[async_trait]
+impl<K, V> CacheStore<K, V> for FileStore<K, V>
+where
+    K: Serialize + Send + Sync + 'static,
+    V: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get_or_try_put_with(
+        &self,
+        key: K,
+        loader: CacheLoader<K, V>,
+    ) -> Result<V, BorsaError> {
+        let Some(encoded) = Self::encode_key(&key) else {
+            return loader(key).await;
+        };
+        if let Some(v) = self.lookup(&encoded) {
+            return Ok(v);
+        }
+        let value = loader(key).await?;
+        self.store(encoded, value.clone()).await;
+        Ok(value)
+    }
+
+    async fn get_if_present(&self, key: &K) -> Option<V> {
+        let encoded = Self::encode_key(key)?;
+        self.lookup(&encoded)
+    }
+
+    async fn insert(&self, key: K, value: V) {
+        if let Some(encoded) = Self::encode_key(&key) {
+            self.store(encoded, value).await;
+        }
+    }
+}
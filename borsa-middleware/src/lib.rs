@@ -6,9 +6,25 @@
 mod blacklist;
 mod builder;
 mod cache;
+mod circuit_breaker;
+mod coalesce;
+mod file_store;
 mod quota;
+mod quota_pool;
+mod rate_limit;
+mod retry;
 
 pub use crate::blacklist::{BlacklistConnector, BlacklistMiddleware};
 pub use crate::builder::ConnectorBuilder;
-pub use crate::cache::{CacheMiddleware, CachingConnector};
-pub use crate::quota::{QuotaAwareConnector, QuotaMiddleware};
+pub use crate::cache::{
+    CacheLoader, CacheMiddleware, CacheStore, CacheStoreFuture, CachingConnector, MokaStore,
+};
+pub use crate::circuit_breaker::{CircuitBreakerConnector, CircuitBreakerMiddleware};
+pub use crate::coalesce::{CoalescingConnector, CoalescingMiddleware};
+pub use crate::file_store::FileStore;
+pub use crate::quota::{QuotaAwareConnector, QuotaMiddleware, QuotaWeigher};
+pub use crate::quota_pool::{
+    FileQuotaPool, MemoryQuotaPool, QuotaPool, quota_pool, register_quota_pool,
+};
+pub use crate::rate_limit::{RateLimiterConnector, RateLimiterMiddleware};
+pub use crate::retry::{RetryConnector, RetryMiddleware};
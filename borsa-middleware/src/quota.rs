@@ -2,88 +2,78 @@
 //!
 //! Calls executed under [`CallOrigin::Internal`](borsa_core::CallOrigin) bypass quota
 //! accounting so that orchestrator fan-outs do not consume end-user budget.
+//!
+//! Under [`QuotaConsumptionStrategy::Weighted`] each call is charged its capability's weight
+//! from [`QuotaConfig::weights`], optionally rescaled by a [`QuotaWeigher`] that can look at
+//! the call's [`CallSize`](borsa_core::CallSize) (batch length, history range or period).
+//!
+//! The window state lives in a [`QuotaPool`](crate::QuotaPool), which several quota
+//! middlewares (and, with a file pool, several processes) can share by name.
 
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use borsa_core::connector::BorsaConnector;
 use borsa_core::{BorsaError, CallContext, CallOrigin, Middleware};
-use borsa_types::{QuotaConfig, QuotaConsumptionStrategy};
+use borsa_types::{QuotaConfig, QuotaConsumptionStrategy, QuotaState};
 #[cfg(feature = "tracing")]
 use tracing::{debug, info, warn};
 
+use crate::quota_pool::{QuotaPool, check_pool_budget, check_pool_path, resolve_pool};
+
+/// Hook computing the units charged for one call under the weighted strategy.
+///
+/// Receives the call context and the static weight configured for its capability.
+pub type QuotaWeigher = Arc<dyn Fn(&CallContext, u64) -> u64 + Send + Sync>;
+
 /// Wrapper that enforces quotas.
 pub struct QuotaAwareConnector {
     inner: Arc<dyn BorsaConnector>,
-    _config: QuotaConfig,
-    runtime: Mutex<QuotaRuntime>,
-}
-
-struct QuotaRuntime {
-    // Daily (window) tracking
-    limit: u64,
-    calls_made_in_window: u64,
-    last_reset: Instant,
-    window: Duration,
-
-    // Hourly-spread tracking
-    allowed_per_slice: u64,   // per-hour when strategy == EvenSpreadHourly
-    slice_duration: Duration, // 1h slices
-    calls_made_in_slice: u64,
-    slice_start: Instant,
-    strategy: QuotaConsumptionStrategy,
+    config: QuotaConfig,
+    weigher: Option<QuotaWeigher>,
+    pool: Arc<dyn QuotaPool>,
 }
 
 impl QuotaAwareConnector {
     /// Create a new quota-aware wrapper around an existing connector.
+    ///
+    /// Charges the shared pool named by `config.pool`, or a private in-memory pool.
     pub fn new(inner: Arc<dyn BorsaConnector>, config: QuotaConfig) -> Self {
-        let window = config.window;
-        let limit = config.limit;
-        // Compute hourly-spread slice parameters
-        let strategy = config.strategy;
-        let (allowed_per_slice, slice_duration) = match strategy {
-            QuotaConsumptionStrategy::EvenSpreadHourly => {
-                // Divide the configured window into 24 slices; for a 24h window this is 1h slices.
-                let slices = 24u64;
-                let per_slice = std::cmp::max(1, limit / slices);
-                // Compute slice duration in milliseconds to handle small windows deterministically in tests.
-                let window_ms = u128::from(u64::try_from(window.as_millis()).unwrap_or(u64::MAX));
-                let raw_slice_ms = std::cmp::max(1u128, window_ms / u128::from(slices));
-                let slice_ms = u64::try_from(raw_slice_ms).unwrap_or(u64::MAX);
-                (per_slice, Duration::from_millis(slice_ms))
-            }
-            _ => (0, Duration::from_secs(0)),
-        };
-
-        let s = Self {
-            inner,
-            _config: config,
-            runtime: Mutex::new(QuotaRuntime {
-                limit,
-                calls_made_in_window: 0,
-                last_reset: Instant::now(),
-                window,
+        let pool = resolve_pool(&config);
+        Self::with_pool(inner, config, pool)
+    }
 
-                allowed_per_slice,
-                slice_duration,
-                calls_made_in_slice: 0,
-                slice_start: Instant::now(),
-                strategy,
-            }),
-        };
+    /// Create a quota-aware wrapper that charges `pool`, ignoring `config.pool`.
+    pub fn with_pool(
+        inner: Arc<dyn BorsaConnector>,
+        config: QuotaConfig,
+        pool: Arc<dyn QuotaPool>,
+    ) -> Self {
         #[cfg(feature = "tracing")]
         {
             info!(
                 target = "borsa::middleware::quota",
                 event = "init",
-                limit = limit,
-                window_ms = u64::try_from(window.as_millis()).unwrap_or(u64::MAX),
-                strategy = ?strategy,
+                limit = config.limit,
+                window_ms = u64::try_from(config.window.as_millis()).unwrap_or(u64::MAX),
+                strategy = ?config.strategy,
+                pool = ?config.pool,
                 "initialized quota middleware"
             );
         }
-        s
+        Self {
+            inner,
+            config,
+            weigher: None,
+            pool,
+        }
+    }
+
+    /// Rescale per-call weights with `weigher` under the weighted strategy.
+    #[must_use]
+    pub fn with_weigher(mut self, weigher: QuotaWeigher) -> Self {
+        self.weigher = Some(weigher);
+        self
     }
 
     /// Access the inner connector.
@@ -91,8 +81,31 @@ impl QuotaAwareConnector {
         &self.inner
     }
 
+    /// Units charged for a call: its weight under the weighted strategy, one otherwise.
+    fn units_for(&self, ctx: &CallContext) -> u64 {
+        if !matches!(self.config.strategy, QuotaConsumptionStrategy::Weighted) {
+            return 1;
+        }
+        let weight = self.config.weight_for(ctx.capability().as_str());
+        self.weigher
+            .as_ref()
+            .map_or(weight, |weigher| weigher(ctx, weight))
+    }
+
     /// Check whether a call should be allowed under the configured quota strategy.
     ///
+    /// Charges one unit; see [`try_consume`](Self::try_consume) for weighted calls.
+    ///
+    /// # Errors
+    /// See [`try_consume`](Self::try_consume).
+    pub fn should_allow_call(&self) -> Result<(), BorsaError> {
+        self.try_consume(1)
+    }
+
+    /// Charge `units` against the quota if the configured strategy allows it.
+    ///
+    /// A call is admitted only when all of its units fit in the remaining window budget.
+    ///
     /// # Errors
     /// Returns `BorsaError::QuotaExceeded` when the per-slice (for
     /// `EvenSpreadHourly`) or the overall window budget is exhausted. When the
@@ -100,112 +113,54 @@ impl QuotaAwareConnector {
     /// units, `remaining` will be greater than zero and `reset_in_ms` reflects
     /// the time until the next slice boundary.
     ///
-    /// # Panics
-    /// Panics if the internal mutex is poisoned.
-    pub fn should_allow_call(&self) -> Result<(), BorsaError> {
-        let mut rt = self.runtime.lock().expect("mutex poisoned");
-        let now = Instant::now();
-
-        // Reset window if elapsed
-        let elapsed = now.duration_since(rt.last_reset);
-        if elapsed >= rt.window {
-            rt.calls_made_in_window = 0;
-            // Align last_reset to the beginning of the current window by calculating
-            // how many complete windows have passed and advancing by that amount.
-            // This ensures windows remain aligned to regular boundaries even with gaps in usage.
-            let windows_passed = elapsed.as_nanos() / rt.window.as_nanos();
-            let boundary_offset = Duration::from_nanos(
-                (windows_passed * rt.window.as_nanos())
-                    .try_into()
-                    .unwrap_or(u64::MAX),
-            );
-            rt.last_reset += boundary_offset;
-        }
-
-        // Optional hourly-spread slice handling
-        if matches!(rt.strategy, QuotaConsumptionStrategy::EvenSpreadHourly) {
-            let elapsed = now.duration_since(rt.slice_start);
-            if elapsed >= rt.slice_duration {
-                rt.calls_made_in_slice = 0;
-                // Align slice_start to the beginning of the current slice by calculating
-                // how many complete slices have passed and advancing by that amount.
-                // This ensures slices remain aligned to regular boundaries even with gaps in usage.
-                let slices_passed = elapsed.as_nanos() / rt.slice_duration.as_nanos();
-                let boundary_offset = Duration::from_nanos(
-                    (slices_passed * rt.slice_duration.as_nanos())
-                        .try_into()
-                        .unwrap_or(u64::MAX),
-                );
-                rt.slice_start += boundary_offset;
-            }
+    /// Other errors come from a pool whose storage failed (e.g. an unwritable pool file).
+    pub fn try_consume(&self, units: u64) -> Result<(), BorsaError> {
+        let res = self.pool.try_consume(&self.config, units);
+        self.report(units, res)
+    }
 
-            // If slice is exhausted but daily window still has room, block temporarily
-            if rt.calls_made_in_slice >= rt.allowed_per_slice && rt.calls_made_in_window < rt.limit
-            {
-                let elapsed_in_slice = now.duration_since(rt.slice_start);
-                let reset_in_ms: u64 = rt
-                    .slice_duration
-                    .saturating_sub(elapsed_in_slice)
-                    .as_millis()
-                    .try_into()
-                    .unwrap_or(u64::MAX);
-                let remaining_units = rt.limit.saturating_sub(rt.calls_made_in_window);
-                #[cfg(feature = "tracing")]
-                warn!(
-                    target = "borsa::middleware::quota",
-                    event = "blocked_slice",
-                    remaining = remaining_units,
-                    reset_in_ms = reset_in_ms,
-                    "temporarily blocked by slice policy"
-                );
-                return Err(BorsaError::QuotaExceeded {
-                    remaining: remaining_units,
-                    reset_in_ms,
-                });
-            }
+    /// Charge `units` from async code, moving pools that block (e.g. on a file lock) onto
+    /// the blocking thread pool.
+    async fn consume_async(&self, units: u64) -> Result<(), BorsaError> {
+        if !self.pool.blocks() {
+            return self.try_consume(units);
         }
+        let pool = Arc::clone(&self.pool);
+        let config = self.config.clone();
+        let res = tokio::task::spawn_blocking(move || pool.try_consume(&config, units))
+            .await
+            .map_err(|e| BorsaError::Other(format!("quota pool task failed: {e}")))?;
+        self.report(units, res)
+    }
 
-        // Allow under overall window
-        if rt.calls_made_in_window < rt.limit {
-            rt.calls_made_in_window += 1;
-            if matches!(rt.strategy, QuotaConsumptionStrategy::EvenSpreadHourly) {
-                rt.calls_made_in_slice += 1;
-            }
-            #[cfg(feature = "tracing")]
-            debug!(
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn report(&self, units: u64, res: Result<(), BorsaError>) -> Result<(), BorsaError> {
+        #[cfg(feature = "tracing")]
+        match &res {
+            Ok(()) => debug!(
                 target = "borsa::middleware::quota",
                 event = "allow",
-                made_in_window = rt.calls_made_in_window,
-                limit = rt.limit,
-                strategy = ?rt.strategy,
+                units = units,
+                strategy = ?self.config.strategy,
                 "quota allows call"
-            );
-            return Ok(());
+            ),
+            Err(err) => warn!(
+                target = "borsa::middleware::quota",
+                event = "blocked",
+                units = units,
+                %err,
+                "blocked by quota"
+            ),
         }
+        res
+    }
 
-        // Window exceeded
-        let elapsed = now.duration_since(rt.last_reset);
-        let remaining_ms = rt
-            .window
-            .saturating_sub(elapsed)
-            .as_millis()
-            .try_into()
-            .unwrap_or(u64::MAX);
-        let remaining_units = rt.limit.saturating_sub(rt.calls_made_in_window);
-        let err = BorsaError::QuotaExceeded {
-            remaining: remaining_units,
-            reset_in_ms: remaining_ms,
-        };
-        drop(rt);
-        #[cfg(feature = "tracing")]
-        warn!(
-            target = "borsa::middleware::quota",
-            event = "blocked_window",
-            remaining = remaining_units,
-            reset_in_ms = remaining_ms,
-            "blocked by window policy"
-        );
-        Err(err)
+    /// Snapshot of the budget this connector charges.
+    ///
+    /// # Errors
+    /// Returns an error when the pool's storage fails.
+    pub fn state(&self) -> Result<QuotaState, BorsaError> {
+        self.pool.state(&self.config)
     }
 
     fn translate_provider_error(err: BorsaError) -> BorsaError {
@@ -227,12 +182,26 @@ impl QuotaAwareConnector {
 /// Middleware config for constructing a [`QuotaAwareConnector`].
 pub struct QuotaMiddleware {
     pub config: QuotaConfig,
+    weigher: Option<QuotaWeigher>,
 }
 
 impl QuotaMiddleware {
     #[must_use]
     pub const fn new(config: QuotaConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            weigher: None,
+        }
+    }
+
+    /// Rescale per-call weights with `weigher` under the weighted strategy.
+    ///
+    /// The hook is code and is not part of [`config_json`](Middleware::config_json), so it
+    /// must be attached again after rebuilding a stack from its serialized form.
+    #[must_use]
+    pub fn with_weigher(mut self, weigher: QuotaWeigher) -> Self {
+        self.weigher = Some(weigher);
+        self
     }
 }
 
@@ -249,7 +218,11 @@ impl Middleware for QuotaMiddleware {
                 "applying quota middleware"
             );
         }
-        Arc::new(QuotaAwareConnector::new(inner, self.config))
+        let connector = QuotaAwareConnector::new(inner, self.config);
+        Arc::new(match self.weigher {
+            Some(weigher) => connector.with_weigher(weigher),
+            None => connector,
+        })
     }
 
     fn name(&self) -> &'static str {
@@ -266,13 +239,36 @@ impl Middleware for QuotaMiddleware {
             "limit": self.config.limit,
             "window_ms": self.config.window.as_millis(),
             "strategy": strategy,
+            "weights": self.config.weights,
+            "pool": self.config.pool,
+            "pool_path": self.config.pool_path,
         })
     }
 
     fn validate(&self, _ctx: &borsa_core::middleware::ValidationContext) -> Result<(), BorsaError> {
         // Optional: QuotaAware middleware works best with Blacklisting outermost (to handle quota errors)
-        // but this is not strictly required. Validation is intentionally permissive to avoid breaking
+        // but this is not strictly required. Placement is intentionally unchecked to avoid breaking
         // existing usage patterns and allow flexible composition.
+        if self.config.pool_path.is_some() && self.config.pool.is_none() {
+            return Err(BorsaError::InvalidMiddlewareStack {
+                message: "quota pool_path requires a pool name".into(),
+            });
+        }
+        check_pool_path(&self.config)?;
+        check_pool_budget(&self.config)?;
+        if let Some((capability, weight)) = self
+            .config
+            .weights
+            .iter()
+            .find(|(_, weight)| **weight > self.config.limit)
+        {
+            return Err(BorsaError::InvalidMiddlewareStack {
+                message: format!(
+                    "quota weight {weight} for '{capability}' exceeds the limit {}; such calls could never run",
+                    self.config.limit
+                ),
+            });
+        }
         Ok(())
     }
 }
@@ -303,7 +299,7 @@ impl Middleware for QuotaAwareConnector {
             origin = ?ctx.origin(),
             "quota pre-call check"
         );
-        self.should_allow_call()
+        self.consume_async(self.units_for(ctx)).await
     }
 
     fn map_error(&self, err: BorsaError, ctx: &CallContext) -> BorsaError {
@@ -0,0 +1,391 @@
+//! Quota accounting shared between quota middlewares.
+//!
+//! A [`QuotaPool`] owns the window state that [`QuotaAwareConnector`](crate::QuotaAwareConnector)
+//! charges calls against. Every quota middleware gets a private [`MemoryQuotaPool`] unless its
+//! [`QuotaConfig::pool`] names a shared one: middlewares naming the same pool share one budget,
+//! e.g. two connector instances that use the same vendor API key.
+//!
+//! Named pools are looked up in a process-wide registry. A pool registered up front with
+//! [`register_quota_pool`] is used as is; otherwise the first middleware naming it creates a
+//! [`FileQuotaPool`] when [`QuotaConfig::pool_path`] is set and a [`MemoryQuotaPool`]
+//! otherwise. A file pool lets separate processes on one host share accounting and keeps the
+//! window state across restarts. Building a quota middleware whose `pool_path`, limit, window
+//! or strategy does not match the pool already known under its name fails validation.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use borsa_core::BorsaError;
+use borsa_types::{QuotaConfig, QuotaConsumptionStrategy, QuotaState};
+use serde::{Deserialize, Serialize};
+
+/// Shared quota accounting charged by one or more quota middlewares.
+pub trait QuotaPool: Send + Sync {
+    /// Charge `units` against the budget described by `cfg` if they fit.
+    ///
+    /// # Errors
+    /// Returns `QuotaExceeded` when the budget (or, for `EvenSpreadHourly`, the current
+    /// slice) is exhausted. Backends may return other errors when their storage fails.
+    fn try_consume(&self, cfg: &QuotaConfig, units: u64) -> Result<(), BorsaError>;
+
+    /// Snapshot of the budget described by `cfg`.
+    ///
+    /// # Errors
+    /// Backends may return an error when their storage fails.
+    fn state(&self, cfg: &QuotaConfig) -> Result<QuotaState, BorsaError>;
+
+    /// Whether charges block the thread (e.g. on file IO), so async callers should run them
+    /// on the blocking thread pool.
+    fn blocks(&self) -> bool {
+        false
+    }
+}
+
+/// Window state of a quota budget, in wall-clock milliseconds so it can be persisted.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct QuotaLedger {
+    window_start_ms: u64,
+    used: u64,
+    slice_start_ms: u64,
+    used_in_slice: u64,
+}
+
+fn duration_ms(d: Duration) -> u64 {
+    u64::try_from(d.as_millis()).unwrap_or(u64::MAX).max(1)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// Advance `start` by whole `period`s so boundaries stay aligned despite gaps in usage.
+/// Returns true when at least one boundary was crossed.
+const fn align(start: &mut u64, now: u64, period: u64) -> bool {
+    let elapsed = now.saturating_sub(*start);
+    if elapsed < period {
+        return false;
+    }
+    *start += elapsed / period * period;
+    true
+}
+
+impl QuotaLedger {
+    // Evenly spread budgets are divided into 24 slices; for a 24h window these are hours.
+    const SLICES: u64 = 24;
+
+    fn roll(&mut self, cfg: &QuotaConfig, now: u64) {
+        if self.window_start_ms == 0 {
+            self.window_start_ms = now;
+            self.slice_start_ms = now;
+        }
+        if align(&mut self.window_start_ms, now, duration_ms(cfg.window)) {
+            self.used = 0;
+        }
+        if matches!(cfg.strategy, QuotaConsumptionStrategy::EvenSpreadHourly)
+            && align(&mut self.slice_start_ms, now, Self::slice_ms(cfg))
+        {
+            self.used_in_slice = 0;
+        }
+    }
+
+    fn slice_ms(cfg: &QuotaConfig) -> u64 {
+        (duration_ms(cfg.window) / Self::SLICES).max(1)
+    }
+
+    fn window_reset_in_ms(&self, cfg: &QuotaConfig, now: u64) -> u64 {
+        duration_ms(cfg.window).saturating_sub(now.saturating_sub(self.window_start_ms))
+    }
+
+    fn consume(&mut self, cfg: &QuotaConfig, now: u64, units: u64) -> Result<(), BorsaError> {
+        self.roll(cfg, now);
+        let remaining = cfg.limit.saturating_sub(self.used);
+        let spread = matches!(cfg.strategy, QuotaConsumptionStrategy::EvenSpreadHourly);
+
+        // If the call does not fit the slice but does fit the window, block temporarily. A
+        // call larger than a whole slice is admitted into an empty slice.
+        let slice_cap = (cfg.limit / Self::SLICES).max(1);
+        if spread
+            && self.used_in_slice > 0
+            && self.used_in_slice.saturating_add(units) > slice_cap
+            && units <= remaining
+        {
+            let reset_in_ms =
+                Self::slice_ms(cfg).saturating_sub(now.saturating_sub(self.slice_start_ms));
+            return Err(BorsaError::QuotaExceeded {
+                remaining,
+                reset_in_ms,
+            });
+        }
+
+        if units <= remaining {
+            self.used += units;
+            if spread {
+                self.used_in_slice += units;
+            }
+            return Ok(());
+        }
+
+        Err(BorsaError::QuotaExceeded {
+            remaining,
+            reset_in_ms: self.window_reset_in_ms(cfg, now),
+        })
+    }
+
+    fn state(&mut self, cfg: &QuotaConfig, now: u64) -> QuotaState {
+        self.roll(cfg, now);
+        QuotaState {
+            limit: cfg.limit,
+            remaining: cfg.limit.saturating_sub(self.used),
+            reset_in: Duration::from_millis(self.window_reset_in_ms(cfg, now)),
+        }
+    }
+}
+
+/// In-process quota pool. State is lost when the process exits.
+#[derive(Debug, Default)]
+pub struct MemoryQuotaPool {
+    ledger: Mutex<QuotaLedger>,
+}
+
+impl MemoryQuotaPool {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl QuotaPool for MemoryQuotaPool {
+    fn try_consume(&self, cfg: &QuotaConfig, units: u64) -> Result<(), BorsaError> {
+        self.ledger
+            .lock()
+            .expect("mutex poisoned")
+            .consume(cfg, now_ms(), units)
+    }
+
+    fn state(&self, cfg: &QuotaConfig) -> Result<QuotaState, BorsaError> {
+        Ok(self
+            .ledger
+            .lock()
+            .expect("mutex poisoned")
+            .state(cfg, now_ms()))
+    }
+}
+
+/// Quota pool persisted to a JSON file guarded by an exclusive file lock.
+///
+/// Every charge locks the file, reads the ledger, updates it and writes it back, so processes
+/// on one host that open the same path share one budget, and the window survives restarts.
+/// The lock is only held for that read-modify-write. The file and its parent directory are
+/// created on first use.
+#[derive(Debug)]
+pub struct FileQuotaPool {
+    path: PathBuf,
+    // serializes threads of this process before they contend for the file lock
+    local: Mutex<()>,
+}
+
+fn io_error(path: &Path, err: &std::io::Error) -> BorsaError {
+    BorsaError::Other(format!("quota pool file {}: {err}", path.display()))
+}
+
+impl FileQuotaPool {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            local: Mutex::new(()),
+        }
+    }
+
+    /// Path of the backing file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn with_ledger<T>(&self, f: impl FnOnce(&mut QuotaLedger) -> T) -> Result<T, BorsaError> {
+        let _local = self.local.lock().expect("mutex poisoned");
+        let path = &self.path;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| io_error(path, &e))?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| io_error(path, &e))?;
+        file.lock().map_err(|e| io_error(path, &e))?;
+
+        let mut raw = String::new();
+        file.read_to_string(&mut raw)
+            .map_err(|e| io_error(path, &e))?;
+        // A new (empty) file starts a fresh window. A corrupt one is an error: silently
+        // resetting it would hand out a whole new budget.
+        let mut ledger: QuotaLedger = if raw.trim().is_empty() {
+            QuotaLedger::default()
+        } else {
+            serde_json::from_str(&raw).map_err(|e| {
+                BorsaError::Other(format!(
+                    "quota pool file {} is corrupt: {e}",
+                    path.display()
+                ))
+            })?
+        };
+        let out = f(&mut ledger);
+
+        let json = serde_json::to_string(&ledger)
+            .map_err(|e| BorsaError::Other(format!("quota pool serialize: {e}")))?;
+        Self::overwrite(&mut file, &json).map_err(|e| io_error(path, &e))?;
+        file.unlock().map_err(|e| io_error(path, &e))?;
+        Ok(out)
+    }
+
+    fn overwrite(file: &mut File, contents: &str) -> std::io::Result<()> {
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_data()
+    }
+}
+
+impl QuotaPool for FileQuotaPool {
+    fn try_consume(&self, cfg: &QuotaConfig, units: u64) -> Result<(), BorsaError> {
+        self.with_ledger(|ledger| ledger.consume(cfg, now_ms(), units))?
+    }
+
+    fn state(&self, cfg: &QuotaConfig) -> Result<QuotaState, BorsaError> {
+        self.with_ledger(|ledger| ledger.state(cfg, now_ms()))
+    }
+
+    fn blocks(&self) -> bool {
+        true
+    }
+}
+
+/// A named pool, the file backing it (`None` for memory and registered pools) and the config
+/// of the first middleware charging it.
+struct NamedPool {
+    pool: Arc<dyn QuotaPool>,
+    path: Option<PathBuf>,
+    config: Option<QuotaConfig>,
+}
+
+static POOLS: LazyLock<Mutex<HashMap<String, NamedPool>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Register (or replace) the process-wide quota pool named `name`.
+///
+/// Quota middlewares created afterwards whose [`QuotaConfig::pool`] is `name` charge this pool.
+///
+/// # Panics
+/// Panics if the registry mutex is poisoned.
+pub fn register_quota_pool(name: impl Into<String>, pool: Arc<dyn QuotaPool>) {
+    POOLS.lock().expect("mutex poisoned").insert(
+        name.into(),
+        NamedPool {
+            pool,
+            path: None,
+            config: None,
+        },
+    );
+}
+
+/// The process-wide quota pool named `name`, if one is registered or was created by a quota
+/// middleware.
+///
+/// # Panics
+/// Panics if the registry mutex is poisoned.
+#[must_use]
+pub fn quota_pool(name: &str) -> Option<Arc<dyn QuotaPool>> {
+    POOLS
+        .lock()
+        .expect("mutex poisoned")
+        .get(name)
+        .map(|named| Arc::clone(&named.pool))
+}
+
+/// Reject a `pool_path` that differs from the backing of the pool already known under
+/// `cfg.pool`, which would otherwise be ignored.
+pub(crate) fn check_pool_path(cfg: &QuotaConfig) -> Result<(), BorsaError> {
+    let (Some(name), Some(path)) = (&cfg.pool, &cfg.pool_path) else {
+        return Ok(());
+    };
+    let pools = POOLS.lock().expect("mutex poisoned");
+    match pools.get(name) {
+        Some(named) if named.path.as_ref() != Some(path) => {
+            let existing = named.path.as_ref().map_or_else(
+                || "is not file-backed".to_string(),
+                |p| format!("uses {}", p.display()),
+            );
+            Err(BorsaError::InvalidMiddlewareStack {
+                message: format!(
+                    "quota pool '{name}' {existing}, not pool_path {}",
+                    path.display()
+                ),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Reject a limit, window or strategy that differs from the budget the pool known under
+/// `cfg.pool` is charged with, since its middlewares share one window state.
+pub(crate) fn check_pool_budget(cfg: &QuotaConfig) -> Result<(), BorsaError> {
+    let Some(name) = &cfg.pool else {
+        return Ok(());
+    };
+    let pools = POOLS.lock().expect("mutex poisoned");
+    match pools.get(name).and_then(|named| named.config.as_ref()) {
+        Some(existing)
+            if (existing.limit, existing.window, existing.strategy)
+                != (cfg.limit, cfg.window, cfg.strategy) =>
+        {
+            Err(BorsaError::InvalidMiddlewareStack {
+                message: format!(
+                    "quota pool '{name}' allows {} per {:?} ({:?}), not {} per {:?} ({:?})",
+                    existing.limit,
+                    existing.window,
+                    existing.strategy,
+                    cfg.limit,
+                    cfg.window,
+                    cfg.strategy
+                ),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Pool that a quota middleware with `cfg` charges: the named shared pool (created on first
+/// use) or a private in-memory pool.
+pub(crate) fn resolve_pool(cfg: &QuotaConfig) -> Arc<dyn QuotaPool> {
+    let Some(name) = &cfg.pool else {
+        return Arc::new(MemoryQuotaPool::new());
+    };
+    let mut pools = POOLS.lock().expect("mutex poisoned");
+    let named = pools
+        .entry(name.clone())
+        .or_insert_with(|| match &cfg.pool_path {
+            Some(path) => NamedPool {
+                pool: Arc::new(FileQuotaPool::new(path.clone())),
+                path: Some(path.clone()),
+                config: None,
+            },
+            None => NamedPool {
+                pool: Arc::new(MemoryQuotaPool::new()),
+                path: None,
+                config: None,
+            },
+        });
+    named.config.get_or_insert_with(|| cfg.clone());
+    Arc::clone(&named.pool)
+}
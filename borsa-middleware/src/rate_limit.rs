@@ -0,0 +1,324 @@
+//! Token-bucket rate limiter that queues calls instead of failing them.
+//!
+//! Tokens refill at [`RateLimitConfig::requests_per_second`] up to
+//! [`RateLimitConfig::burst`]. A call that finds no token waits in line: calls in the
+//! [`CallPriority::Interactive`] lane are served before [`CallPriority::Bulk`] ones, and each
+//! lane is FIFO. A call is rejected with `QuotaExceeded` (whose `reset_in_ms` is the expected
+//! wait) when its turn would come after `max_wait`, or after the router's provider deadline
+//! from [`CallContext::deadline`], so the router can fall back instead of timing out.
+//!
+//! Unlike quota accounting, internal fan-out calls are limited too: they reach the same
+//! upstream API.
+
+use std::any::TypeId;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use borsa_core::connector::BorsaConnector;
+use borsa_core::middleware::{MiddlewarePosition, ValidationContext};
+use borsa_core::{BorsaError, CallContext, CallPriority, Middleware};
+use borsa_types::RateLimitConfig;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+#[cfg(feature = "tracing")]
+use tracing::{debug, info};
+
+use crate::retry::RetryMiddleware;
+
+const LANES: usize = 2;
+
+const fn lane_of(priority: CallPriority) -> usize {
+    match priority {
+        CallPriority::Interactive => 0,
+        CallPriority::Bulk => 1,
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    // waiting tickets per lane, interactive first
+    lanes: [VecDeque<u64>; LANES],
+    next_ticket: u64,
+}
+
+impl Bucket {
+    fn refill(&mut self, cfg: &RateLimitConfig, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = elapsed
+            .mul_add(cfg.requests_per_second, self.tokens)
+            .min(f64::from(cfg.burst));
+        self.refilled_at = now;
+    }
+
+    /// Calls served before a newcomer (or the ticket `id`) in `lane`.
+    fn ahead(&self, lane: usize, id: Option<u64>) -> usize {
+        let own = &self.lanes[lane];
+        let in_lane = id.map_or(own.len(), |id| {
+            own.iter().position(|t| *t == id).unwrap_or(own.len())
+        });
+        self.lanes[..lane].iter().map(VecDeque::len).sum::<usize>() + in_lane
+    }
+
+    /// Expected wait until `ahead` earlier calls and this one have a token each.
+    fn wait_for(&self, cfg: &RateLimitConfig, ahead: usize) -> Duration {
+        #[allow(clippy::cast_precision_loss)]
+        let missing = (ahead as f64 + 1.0 - self.tokens).max(0.0);
+        Duration::from_secs_f64(missing / cfg.requests_per_second)
+    }
+
+    fn remove(&mut self, lane: usize, id: u64) {
+        self.lanes[lane].retain(|t| *t != id);
+    }
+}
+
+/// Middleware that spaces calls to its inner connector with a token bucket.
+pub struct RateLimiterConnector {
+    inner: Arc<dyn BorsaConnector>,
+    cfg: RateLimitConfig,
+    bucket: Mutex<Bucket>,
+    // woken whenever a waiter leaves the line
+    moved: Notify,
+}
+
+/// A queued call; leaves the line when dropped (acquired, rejected or cancelled).
+struct Ticket<'a> {
+    limiter: &'a RateLimiterConnector,
+    lane: usize,
+    id: u64,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        self.limiter
+            .bucket
+            .lock()
+            .expect("mutex poisoned")
+            .remove(self.lane, self.id);
+        self.limiter.moved.notify_waiters();
+    }
+}
+
+fn rejected(wait: Duration) -> BorsaError {
+    BorsaError::QuotaExceeded {
+        remaining: 0,
+        reset_in_ms: u64::try_from(wait.as_millis()).unwrap_or(u64::MAX),
+    }
+}
+
+/// Reject configurations under which the bucket would never refill.
+fn check_config(cfg: &RateLimitConfig) -> Result<(), BorsaError> {
+    if !(cfg.requests_per_second.is_finite() && cfg.requests_per_second > 0.0) {
+        return Err(BorsaError::InvalidMiddlewareStack {
+            message: "rate limit requests_per_second must be positive".into(),
+        });
+    }
+    if cfg.burst == 0 {
+        return Err(BorsaError::InvalidMiddlewareStack {
+            message: "rate limit burst must be at least 1".into(),
+        });
+    }
+    Ok(())
+}
+
+impl RateLimiterConnector {
+    /// Wrap `inner` with a token bucket described by `cfg`.
+    ///
+    /// # Errors
+    /// Returns `InvalidMiddlewareStack` unless `requests_per_second` is a positive finite
+    /// number and `burst` is at least 1.
+    pub fn new(inner: Arc<dyn BorsaConnector>, cfg: RateLimitConfig) -> Result<Self, BorsaError> {
+        check_config(&cfg)?;
+        #[cfg(feature = "tracing")]
+        {
+            info!(
+                target = "borsa::middleware::rate_limit",
+                event = "init",
+                requests_per_second = cfg.requests_per_second,
+                burst = cfg.burst,
+                "initialized rate limiter middleware"
+            );
+        }
+        Ok(Self {
+            inner,
+            cfg,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(cfg.burst),
+                refilled_at: Instant::now(),
+                lanes: [VecDeque::new(), VecDeque::new()],
+                next_ticket: 0,
+            }),
+            moved: Notify::new(),
+        })
+    }
+
+    /// Calls currently waiting for a token, across lanes.
+    ///
+    /// # Panics
+    /// Panics if the internal mutex is poisoned.
+    #[must_use]
+    pub fn queue_depth(&self) -> usize {
+        let bucket = self.bucket.lock().expect("mutex poisoned");
+        bucket.lanes.iter().map(VecDeque::len).sum()
+    }
+
+    /// Calls currently waiting for a token in the lane of `priority`.
+    ///
+    /// # Panics
+    /// Panics if the internal mutex is poisoned.
+    #[must_use]
+    pub fn queue_depth_for(&self, priority: CallPriority) -> usize {
+        self.bucket.lock().expect("mutex poisoned").lanes[lane_of(priority)].len()
+    }
+
+    /// Take a token, waiting in line for it when allowed.
+    ///
+    /// # Errors
+    /// Returns `QuotaExceeded` when the call's turn would come after `max_wait` or after the
+    /// call's deadline.
+    pub async fn acquire(
+        &self,
+        priority: CallPriority,
+        deadline: Option<Instant>,
+    ) -> Result<(), BorsaError> {
+        let lane = lane_of(priority);
+        let started = Instant::now();
+        let give_up = deadline.map_or(started + self.cfg.max_wait, |d| {
+            d.min(started + self.cfg.max_wait)
+        });
+
+        let (ticket, mut wait) = {
+            let mut bucket = self.bucket.lock().expect("mutex poisoned");
+            bucket.refill(&self.cfg, started);
+            let ahead = bucket.ahead(lane, None);
+            if ahead == 0 && bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                return Ok(());
+            }
+            let wait = bucket.wait_for(&self.cfg, ahead);
+            if started + wait > give_up {
+                return Err(rejected(wait));
+            }
+            let id = bucket.next_ticket;
+            bucket.next_ticket += 1;
+            bucket.lanes[lane].push_back(id);
+            #[cfg(feature = "tracing")]
+            debug!(
+                target = "borsa::middleware::rate_limit",
+                event = "queued",
+                lane = lane,
+                ahead = ahead,
+                wait_ms = u64::try_from(wait.as_millis()).unwrap_or(u64::MAX),
+                "waiting for rate limit token"
+            );
+            let ticket = Ticket {
+                limiter: self,
+                lane,
+                id,
+            };
+            (ticket, wait)
+        };
+
+        loop {
+            let now = Instant::now();
+            let sleep = wait
+                .max(Duration::from_millis(1))
+                .min(give_up.saturating_duration_since(now));
+            // Wake early when someone ahead leaves the line.
+            let _ = tokio::time::timeout(sleep, self.moved.notified()).await;
+
+            let now = Instant::now();
+            let mut bucket = self.bucket.lock().expect("mutex poisoned");
+            bucket.refill(&self.cfg, now);
+            let ahead = bucket.ahead(lane, Some(ticket.id));
+            if ahead == 0 && bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                drop(bucket);
+                drop(ticket);
+                return Ok(());
+            }
+            wait = bucket.wait_for(&self.cfg, ahead);
+            if now + wait > give_up {
+                drop(bucket);
+                drop(ticket);
+                return Err(rejected(wait));
+            }
+        }
+    }
+}
+
+fn config_json(cfg: &RateLimitConfig) -> serde_json::Value {
+    serde_json::json!({
+        "requests_per_second": cfg.requests_per_second,
+        "burst": cfg.burst,
+        "max_wait_ms": cfg.max_wait.as_millis(),
+    })
+}
+
+/// Middleware config for constructing a [`RateLimiterConnector`].
+pub struct RateLimiterMiddleware {
+    pub config: RateLimitConfig,
+}
+
+impl RateLimiterMiddleware {
+    #[must_use]
+    pub const fn new(config: RateLimitConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Middleware for RateLimiterMiddleware {
+    fn apply(self: Box<Self>, inner: Arc<dyn BorsaConnector>) -> Arc<dyn BorsaConnector> {
+        Arc::new(
+            RateLimiterConnector::new(inner, self.config)
+                .expect("rate limit config is checked by validate"),
+        )
+    }
+
+    fn name(&self) -> &'static str {
+        "RateLimiterConnector"
+    }
+
+    fn config_json(&self) -> serde_json::Value {
+        config_json(&self.config)
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), BorsaError> {
+        // Retries must queue for a token too.
+        if !ctx.satisfies(MiddlewarePosition::InnerThan(
+            TypeId::of::<RetryMiddleware>(),
+        )) {
+            return Err(BorsaError::InvalidMiddlewareStack {
+                message: "RateLimiterConnector must sit inside RetryConnector".into(),
+            });
+        }
+        check_config(&self.config)
+    }
+}
+
+#[borsa_macros::delegate_connector(inner)]
+#[borsa_macros::delegate_all_providers(inner)]
+impl RateLimiterConnector {}
+
+#[async_trait]
+impl Middleware for RateLimiterConnector {
+    fn apply(self: Box<Self>, _inner: Arc<dyn BorsaConnector>) -> Arc<dyn BorsaConnector> {
+        unreachable!("RateLimiterConnector is already applied")
+    }
+
+    fn name(&self) -> &'static str {
+        "RateLimiterConnector"
+    }
+
+    fn config_json(&self) -> serde_json::Value {
+        config_json(&self.cfg)
+    }
+
+    async fn pre_call(&self, ctx: &CallContext) -> Result<(), BorsaError> {
+        self.acquire(ctx.priority(), ctx.deadline()).await
+    }
+}
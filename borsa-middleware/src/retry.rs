@@ -0,0 +1,238 @@
+//! Retry middleware that re-attempts transient failures against the same connector.
+//!
+//! Errors classified `Transient` by [`BorsaError::retry_class`] (and optionally `Unknown`)
+//! are retried with jittered exponential backoff. Errors that carry a `reset_in_ms` hint
+//! (`QuotaExceeded`, `TemporarilyBlacklisted`, `CircuitOpen`) wait for that hint instead,
+//! or are returned immediately when the hint exceeds `max_hint_wait`. A retry whose delay
+//! would run past the call's deadline ([`CallContext::deadline`]) is not attempted: the
+//! error is returned so the router can fall back.
+//!
+//! Internal orchestrator calls flagged via [`CallOrigin::Internal`](borsa_core::CallOrigin)
+//! are never retried so compositional fan-outs do not stall their parent request.
+
+use std::any::TypeId;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use borsa_core::backoff::backoff_delay;
+use borsa_core::connector::BorsaConnector;
+use borsa_core::middleware::{MiddlewarePosition, ValidationContext};
+use borsa_core::{BorsaError, CallContext, CallOrigin, Middleware};
+use borsa_types::{RetryConfig, RetryPolicy};
+#[cfg(feature = "tracing")]
+use tracing::{debug, info};
+
+use crate::cache::CacheMiddleware;
+use crate::quota::QuotaMiddleware;
+
+/// Middleware that retries failed calls against its inner connector.
+pub struct RetryConnector {
+    inner: Arc<dyn BorsaConnector>,
+    cfg: RetryConfig,
+    // start times of retries counted against `cfg.budget`
+    spent: Mutex<VecDeque<Instant>>,
+}
+
+impl RetryConnector {
+    pub fn new(inner: Arc<dyn BorsaConnector>, cfg: RetryConfig) -> Self {
+        #[cfg(feature = "tracing")]
+        {
+            info!(
+                target = "borsa::middleware::retry",
+                event = "init",
+                max_attempts = cfg.default.max_attempts,
+                budget = ?cfg.budget,
+                "initialized retry middleware"
+            );
+        }
+        Self {
+            inner,
+            cfg,
+            spent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Take one retry from the shared budget; false when the budget is exhausted.
+    fn take_budget(&self) -> bool {
+        let Some(limit) = self.cfg.budget else {
+            return true;
+        };
+        let mut guard = self.spent.lock().expect("mutex poisoned");
+        let now = Instant::now();
+        while guard
+            .front()
+            .is_some_and(|at| now.saturating_duration_since(*at) >= self.cfg.budget_window)
+        {
+            guard.pop_front();
+        }
+        if guard.len() >= limit as usize {
+            return false;
+        }
+        guard.push_back(now);
+        true
+    }
+
+    fn delay_for(&self, policy: &RetryPolicy, err: &BorsaError, attempt: u32) -> Option<Duration> {
+        if attempt >= policy.max_attempts {
+            return None;
+        }
+        if let Some(ms) = reset_hint_ms(err) {
+            let wait = Duration::from_millis(ms);
+            return (wait <= self.cfg.max_hint_wait).then_some(wait);
+        }
+        let retriable = err.is_transient() || (policy.retry_unknown && !err.is_permanent());
+        retriable.then(|| backoff_delay(&policy.backoff, attempt))
+    }
+}
+
+/// `reset_in_ms` carried by local gating errors, looking through connector tags.
+fn reset_hint_ms(err: &BorsaError) -> Option<u64> {
+    match err {
+        BorsaError::Connector { error, .. } => reset_hint_ms(error),
+        BorsaError::QuotaExceeded { reset_in_ms, .. }
+        | BorsaError::TemporarilyBlacklisted { reset_in_ms }
+        | BorsaError::CircuitOpen { reset_in_ms, .. } => Some(*reset_in_ms),
+        _ => None,
+    }
+}
+
+fn config_json(cfg: &RetryConfig) -> serde_json::Value {
+    serde_json::json!({
+        "default": cfg.default,
+        "per_capability": cfg.per_capability,
+        "max_hint_wait_ms": cfg.max_hint_wait.as_millis(),
+        "budget": cfg.budget,
+        "budget_window_ms": cfg.budget_window.as_millis(),
+    })
+}
+
+/// Middleware config for constructing a [`RetryConnector`].
+pub struct RetryMiddleware {
+    pub config: RetryConfig,
+}
+
+impl RetryMiddleware {
+    #[must_use]
+    pub const fn new(config: RetryConfig) -> Self {
+        Self { config }
+    }
+
+    /// Retries must sit inside the cache (so cached answers never retry) and outside the
+    /// quota (so every attempt is charged).
+    fn positions() -> [MiddlewarePosition; 2] {
+        [
+            MiddlewarePosition::InnerThan(TypeId::of::<CacheMiddleware>()),
+            MiddlewarePosition::OuterThan(TypeId::of::<QuotaMiddleware>()),
+        ]
+    }
+}
+
+impl Middleware for RetryMiddleware {
+    fn apply(self: Box<Self>, inner: Arc<dyn BorsaConnector>) -> Arc<dyn BorsaConnector> {
+        #[cfg(feature = "tracing")]
+        {
+            info!(
+                target = "borsa::middleware::retry",
+                event = "apply",
+                max_attempts = self.config.default.max_attempts,
+                "applying retry middleware"
+            );
+        }
+        Arc::new(RetryConnector::new(inner, self.config))
+    }
+
+    fn name(&self) -> &'static str {
+        "RetryConnector"
+    }
+
+    fn config_json(&self) -> serde_json::Value {
+        config_json(&self.config)
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), BorsaError> {
+        if Self::positions().into_iter().any(|p| !ctx.satisfies(p)) {
+            return Err(BorsaError::InvalidMiddlewareStack {
+                message: "RetryConnector must sit inside CachingMiddleware and outside QuotaAwareConnector".into(),
+            });
+        }
+        let policies =
+            std::iter::once(&self.config.default).chain(self.config.per_capability.values());
+        for policy in policies {
+            if policy.max_attempts == 0 {
+                return Err(BorsaError::InvalidMiddlewareStack {
+                    message: "retry max_attempts must be at least 1".into(),
+                });
+            }
+            if policy.backoff.jitter_percent > 100 {
+                return Err(BorsaError::InvalidMiddlewareStack {
+                    message: "retry backoff jitter_percent must be in [0, 100]".into(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[borsa_macros::delegate_connector(inner)]
+#[borsa_macros::delegate_all_providers(inner)]
+impl RetryConnector {}
+
+#[async_trait]
+impl Middleware for RetryConnector {
+    fn apply(self: Box<Self>, _inner: Arc<dyn BorsaConnector>) -> Arc<dyn BorsaConnector> {
+        unreachable!("RetryConnector is already applied")
+    }
+
+    fn name(&self) -> &'static str {
+        "RetryConnector"
+    }
+
+    fn config_json(&self) -> serde_json::Value {
+        config_json(&self.cfg)
+    }
+
+    fn retry_delay(&self, err: &BorsaError, attempt: u32, ctx: &CallContext) -> Option<Duration> {
+        if matches!(ctx.origin(), CallOrigin::Internal { .. }) {
+            return None;
+        }
+        let policy = self.cfg.policy_for(ctx.capability().as_str());
+        let delay = self.delay_for(policy, err, attempt)?;
+        if ctx
+            .deadline()
+            .is_some_and(|deadline| tokio::time::Instant::now() + delay >= deadline)
+        {
+            #[cfg(feature = "tracing")]
+            debug!(
+                target = "borsa::middleware::retry",
+                event = "past_deadline",
+                capability = %ctx.capability(),
+                delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+                "retry would end after the call deadline"
+            );
+            return None;
+        }
+        if !self.take_budget() {
+            #[cfg(feature = "tracing")]
+            debug!(
+                target = "borsa::middleware::retry",
+                event = "budget_exhausted",
+                capability = %ctx.capability(),
+                "retry budget exhausted"
+            );
+            return None;
+        }
+        #[cfg(feature = "tracing")]
+        debug!(
+            target = "borsa::middleware::retry",
+            event = "retry",
+            capability = %ctx.capability(),
+            attempt = attempt,
+            delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+            %err,
+            "retrying provider call"
+        );
+        Some(delay)
+    }
+}
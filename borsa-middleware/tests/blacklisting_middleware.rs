@@ -12,11 +12,7 @@ fn make_quota_wrapper(
     strategy: QuotaConsumptionStrategy,
 ) -> Arc<QuotaAwareConnector> {
     let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
-    let cfg = QuotaConfig {
-        limit,
-        window: Duration::from_millis(window_ms),
-        strategy,
-    };
+    let cfg = QuotaConfig::new(limit, Duration::from_millis(window_ms), strategy);
     Arc::new(QuotaAwareConnector::new(inner, cfg))
 }
 
@@ -84,11 +80,11 @@ async fn temporary_quota_spread_reports_quota_exhaustion() {
 #[tokio::test]
 async fn rate_limit_triggers_blacklist() {
     let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
-    let cfg = QuotaConfig {
-        limit: 10,
-        window: Duration::from_millis(1000),
-        strategy: QuotaConsumptionStrategy::Unit,
-    };
+    let cfg = QuotaConfig::new(
+        10,
+        Duration::from_millis(1000),
+        QuotaConsumptionStrategy::Unit,
+    );
     let quota = Arc::new(QuotaAwareConnector::new(inner, cfg));
     let wrapped: Arc<dyn BorsaConnector> = Arc::new(BlacklistConnector::new(
         quota as Arc<dyn BorsaConnector>,
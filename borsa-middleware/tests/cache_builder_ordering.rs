@@ -5,12 +5,12 @@ use borsa_middleware::ConnectorBuilder;
 use borsa_mock::MockConnector;
 use borsa_types::{CacheConfig, QuotaConfig, QuotaConsumptionStrategy};
 
-const fn default_quota() -> QuotaConfig {
-    QuotaConfig {
-        limit: 10,
-        window: std::time::Duration::from_secs(60),
-        strategy: QuotaConsumptionStrategy::Unit,
-    }
+fn default_quota() -> QuotaConfig {
+    QuotaConfig::new(
+        10,
+        std::time::Duration::from_secs(60),
+        QuotaConsumptionStrategy::Unit,
+    )
 }
 
 #[tokio::test]
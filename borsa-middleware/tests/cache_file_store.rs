@@ -0,0 +1,166 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use borsa_core::{
+    AssetKind, BorsaConnector, BorsaError, Instrument,
+    connector::{ProfileProvider, QuoteProvider},
+};
+use borsa_middleware::ConnectorBuilder;
+use borsa_mock::MockConnector;
+use borsa_types::{CacheBackend, CacheConfig};
+
+struct CountingConnector {
+    inner: Arc<dyn BorsaConnector>,
+    quotes: Arc<AtomicUsize>,
+    profiles: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl BorsaConnector for CountingConnector {
+    fn name(&self) -> &'static str {
+        "counting"
+    }
+    fn vendor(&self) -> &'static str {
+        "test"
+    }
+    fn supports_kind(&self, _k: AssetKind) -> bool {
+        true
+    }
+    fn as_quote_provider(&self) -> Option<&dyn QuoteProvider> {
+        Some(self as &dyn QuoteProvider)
+    }
+    fn as_profile_provider(&self) -> Option<&dyn ProfileProvider> {
+        Some(self as &dyn ProfileProvider)
+    }
+}
+
+#[async_trait::async_trait]
+impl QuoteProvider for CountingConnector {
+    async fn quote(&self, instrument: &Instrument) -> Result<borsa_core::Quote, BorsaError> {
+        self.quotes.fetch_add(1, Ordering::SeqCst);
+        self.inner
+            .as_quote_provider()
+            .unwrap()
+            .quote(instrument)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ProfileProvider for CountingConnector {
+    async fn profile(&self, _instrument: &Instrument) -> Result<borsa_core::Profile, BorsaError> {
+        self.profiles.fetch_add(1, Ordering::SeqCst);
+        Err(BorsaError::not_found("profile"))
+    }
+}
+
+fn wrapped(
+    cfg: &CacheConfig,
+    quotes: &Arc<AtomicUsize>,
+    profiles: &Arc<AtomicUsize>,
+) -> Arc<dyn BorsaConnector> {
+    let raw: Arc<dyn BorsaConnector> = Arc::new(CountingConnector {
+        inner: Arc::new(MockConnector::new()),
+        quotes: Arc::clone(quotes),
+        profiles: Arc::clone(profiles),
+    });
+    ConnectorBuilder::new(raw).with_cache(cfg).build().unwrap()
+}
+
+#[tokio::test]
+async fn file_backend_survives_a_new_connector_instance() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cfg = CacheConfig::default();
+    cfg.per_capability_ttl_ms.insert("quote".into(), 60_000);
+    cfg.default_negative_ttl_ms = 60_000;
+    cfg.default_backend = CacheBackend::File {
+        dir: dir.path().to_path_buf(),
+    };
+
+    let quotes = Arc::new(AtomicUsize::new(0));
+    let profiles = Arc::new(AtomicUsize::new(0));
+    let inst = Instrument::from_symbol("AAPL", AssetKind::Equity).unwrap();
+
+    let first = wrapped(&cfg, &quotes, &profiles);
+    let q1 = first
+        .as_quote_provider()
+        .unwrap()
+        .quote(&inst)
+        .await
+        .unwrap();
+    assert!(matches!(
+        first.as_profile_provider().unwrap().profile(&inst).await,
+        Err(BorsaError::NotFound { .. })
+    ));
+    drop(first);
+
+    // A fresh stack over the same directory simulates a process restart.
+    let second = wrapped(&cfg, &quotes, &profiles);
+    let q2 = second
+        .as_quote_provider()
+        .unwrap()
+        .quote(&inst)
+        .await
+        .unwrap();
+    assert!(matches!(
+        second.as_profile_provider().unwrap().profile(&inst).await,
+        Err(BorsaError::NotFound { .. })
+    ));
+
+    assert_eq!(q1, q2);
+    assert_eq!(quotes.load(Ordering::SeqCst), 1, "quote served from disk");
+    assert_eq!(
+        profiles.load(Ordering::SeqCst),
+        1,
+        "negative entry served from disk"
+    );
+}
+
+#[tokio::test]
+async fn backend_can_be_selected_per_capability() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cfg = CacheConfig::default();
+    cfg.per_capability_ttl_ms.insert("quote".into(), 60_000);
+    cfg.per_capability_backend.insert(
+        "quote".into(),
+        CacheBackend::File {
+            dir: dir.path().to_path_buf(),
+        },
+    );
+
+    let quotes = Arc::new(AtomicUsize::new(0));
+    let profiles = Arc::new(AtomicUsize::new(0));
+    let _conn = wrapped(&cfg, &quotes, &profiles);
+
+    assert!(dir.path().join("counting").join("quote.jsonl").exists());
+    assert!(!dir.path().join("counting").join("profile.jsonl").exists());
+}
+
+#[tokio::test]
+async fn oldest_entries_are_evicted_and_stay_evicted_after_reopen() {
+    use borsa_middleware::{CacheStore, FileStore};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("store.jsonl");
+    let ttl = std::time::Duration::from_secs(60);
+
+    let store = FileStore::<String, u32>::open(&path, 2, ttl).unwrap();
+    for (i, key) in ["a", "b", "c"].into_iter().enumerate() {
+        store
+            .insert(key.to_string(), u32::try_from(i).unwrap())
+            .await;
+    }
+    // Re-inserting moves a key to the back of the eviction order.
+    store.insert("b".to_string(), 10).await;
+    store.insert("d".to_string(), 3).await;
+    assert_eq!(store.get_if_present(&"a".to_string()).await, None);
+    assert_eq!(store.get_if_present(&"c".to_string()).await, None);
+    drop(store);
+
+    let reopened = FileStore::<String, u32>::open(&path, 2, ttl).unwrap();
+    assert_eq!(reopened.get_if_present(&"b".to_string()).await, Some(10));
+    assert_eq!(reopened.get_if_present(&"d".to_string()).await, Some(3));
+    assert_eq!(reopened.get_if_present(&"c".to_string()).await, None);
+}
@@ -0,0 +1,198 @@
+use std::sync::{Arc, Mutex};
+
+use borsa_core::{
+    AssetKind, BorsaConnector, BorsaError, Candle, HistoryCacheMode, HistoryRequest,
+    HistoryResponse, Instrument, Interval, Range, connector::HistoryProvider,
+};
+use borsa_middleware::ConnectorBuilder;
+use borsa_types::CacheConfig;
+use chrono::TimeZone;
+
+const DAY: i64 = 86_400;
+
+/// Returns one daily candle per day inside the requested period and records every window.
+struct DailyBars {
+    windows: Arc<Mutex<Vec<(i64, i64)>>>,
+}
+
+#[async_trait::async_trait]
+impl BorsaConnector for DailyBars {
+    fn name(&self) -> &'static str {
+        "daily-bars"
+    }
+    fn vendor(&self) -> &'static str {
+        "test"
+    }
+    fn supports_kind(&self, _k: AssetKind) -> bool {
+        true
+    }
+    fn as_history_provider(&self) -> Option<&dyn HistoryProvider> {
+        Some(self as &dyn HistoryProvider)
+    }
+}
+
+#[async_trait::async_trait]
+impl HistoryProvider for DailyBars {
+    async fn history(
+        &self,
+        _instrument: &Instrument,
+        req: HistoryRequest,
+    ) -> Result<HistoryResponse, BorsaError> {
+        let (start, end) = req.period().expect("period request");
+        let (start, end) = (start.timestamp(), end.timestamp());
+        self.windows.lock().unwrap().push((start, end));
+
+        let first = (start + DAY - 1) / DAY * DAY;
+        let candles = (first..end)
+            .step_by(usize::try_from(DAY).unwrap())
+            .map(candle)
+            .collect();
+        Ok(HistoryResponse {
+            candles,
+            actions: vec![],
+            adjusted: false,
+            meta: None,
+        })
+    }
+
+    fn supported_history_intervals(&self, _kind: AssetKind) -> &'static [Interval] {
+        &[Interval::D1]
+    }
+}
+
+fn candle(ts: i64) -> Candle {
+    let price = borsa_core::Money::from_canonical_str(
+        "1.0",
+        borsa_core::Currency::Iso(borsa_core::IsoCurrency::USD),
+    )
+    .unwrap();
+    Candle {
+        ts: chrono::Utc.timestamp_opt(ts, 0).unwrap(),
+        open: price.clone(),
+        high: price.clone(),
+        low: price.clone(),
+        close: price.clone(),
+        close_unadj: Some(price),
+        volume: None,
+    }
+}
+
+fn period(start_day: i64, end_day: i64) -> HistoryRequest {
+    HistoryRequest::try_from_period(
+        chrono::Utc.timestamp_opt(start_day * DAY, 0).unwrap(),
+        chrono::Utc.timestamp_opt(end_day * DAY, 0).unwrap(),
+        Interval::D1,
+    )
+    .unwrap()
+}
+
+fn series_connector(windows: &Arc<Mutex<Vec<(i64, i64)>>>) -> Arc<dyn BorsaConnector> {
+    let raw: Arc<dyn BorsaConnector> = Arc::new(DailyBars {
+        windows: Arc::clone(windows),
+    });
+    let mut cfg = CacheConfig::default();
+    cfg.history_mode = HistoryCacheMode::Series;
+    ConnectorBuilder::new(raw).with_cache(&cfg).build().unwrap()
+}
+
+#[tokio::test]
+async fn series_mode_fetches_only_the_missing_head_and_tail() {
+    let windows = Arc::new(Mutex::new(Vec::new()));
+    let wrapped = series_connector(&windows);
+    let h = wrapped.as_history_provider().unwrap();
+    let inst = Instrument::from_symbol("AAPL", AssetKind::Equity).unwrap();
+
+    let first = h.history(&inst, period(100, 130)).await.unwrap();
+    assert_eq!(first.candles.len(), 30);
+
+    let wider = h.history(&inst, period(90, 140)).await.unwrap();
+    assert_eq!(wider.candles.len(), 50);
+    assert!(wider.candles.windows(2).all(|w| w[0].ts < w[1].ts));
+
+    let recorded = windows.lock().unwrap().clone();
+    assert_eq!(
+        recorded,
+        vec![
+            (100 * DAY, 130 * DAY),
+            (90 * DAY, 100 * DAY),
+            // The tail is refetched from the last cached bar so it can be refreshed.
+            (129 * DAY, 140 * DAY),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn series_mode_serves_contained_windows_without_upstream_calls() {
+    let windows = Arc::new(Mutex::new(Vec::new()));
+    let wrapped = series_connector(&windows);
+    let h = wrapped.as_history_provider().unwrap();
+    let inst = Instrument::from_symbol("AAPL", AssetKind::Equity).unwrap();
+
+    let _ = h.history(&inst, period(100, 130)).await.unwrap();
+    let inner = h.history(&inst, period(110, 120)).await.unwrap();
+
+    assert_eq!(inner.candles.len(), 10);
+    assert_eq!(inner.candles[0].ts.timestamp(), 110 * DAY);
+    assert_eq!(windows.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn series_mode_replaces_disjoint_windows() {
+    let windows = Arc::new(Mutex::new(Vec::new()));
+    let wrapped = series_connector(&windows);
+    let h = wrapped.as_history_provider().unwrap();
+    let inst = Instrument::from_symbol("AAPL", AssetKind::Equity).unwrap();
+
+    let _ = h.history(&inst, period(100, 110)).await.unwrap();
+    let later = h.history(&inst, period(200, 210)).await.unwrap();
+    assert_eq!(later.candles.len(), 10);
+
+    // The earlier window was dropped, so asking for it again goes upstream.
+    let _ = h.history(&inst, period(100, 110)).await.unwrap();
+    assert_eq!(windows.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn series_mode_always_refetches_the_last_bar() {
+    let windows = Arc::new(Mutex::new(Vec::new()));
+    let wrapped = series_connector(&windows);
+    let h = wrapped.as_history_provider().unwrap();
+    let inst = Instrument::from_symbol("AAPL", AssetKind::Equity).unwrap();
+
+    let _ = h.history(&inst, period(100, 130)).await.unwrap();
+    let again = h.history(&inst, period(100, 130)).await.unwrap();
+
+    assert_eq!(again.candles.len(), 30);
+    assert!(
+        again.candles.iter().all(|c| c.close_unadj.is_some()),
+        "stitching keeps unadjusted closes"
+    );
+    assert_eq!(
+        windows.lock().unwrap().clone(),
+        vec![(100 * DAY, 130 * DAY), (129 * DAY, 130 * DAY)]
+    );
+}
+
+#[tokio::test]
+async fn series_mode_resolves_ranges_to_windows_ending_now() {
+    let windows = Arc::new(Mutex::new(Vec::new()));
+    let wrapped = series_connector(&windows);
+    let h = wrapped.as_history_provider().unwrap();
+    let inst = Instrument::from_symbol("AAPL", AssetKind::Equity).unwrap();
+
+    let short = HistoryRequest::try_from_range(Range::M1, Interval::D1).unwrap();
+    let long = HistoryRequest::try_from_range(Range::M3, Interval::D1).unwrap();
+    let _ = h.history(&inst, short).await.unwrap();
+    let wider = h.history(&inst, long).await.unwrap();
+    assert!(wider.candles.windows(2).all(|w| w[0].ts < w[1].ts));
+
+    let recorded = windows.lock().unwrap().clone();
+    assert_eq!(recorded.len(), 3, "full month, then only head and tail");
+    let (month_start, month_end) = recorded[0];
+    let (head_start, head_end) = recorded[1];
+    let (tail_start, tail_end) = recorded[2];
+    assert!(head_start < month_start);
+    assert_eq!(head_end, month_start);
+    assert!(tail_start >= month_start && tail_start < month_end);
+    assert!(tail_end >= month_end);
+}
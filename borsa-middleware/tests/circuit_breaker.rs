@@ -0,0 +1,238 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use borsa_core::{AssetKind, BorsaConnector, BorsaError, CallOrigin, Capability, Instrument};
+use borsa_middleware::{CircuitBreakerConnector, ConnectorBuilder};
+use borsa_mock::MockConnector;
+use borsa_types::{CircuitBreakerConfig, QuotaConfig, QuotaConsumptionStrategy};
+
+fn breaker(cfg: CircuitBreakerConfig) -> Arc<CircuitBreakerConnector> {
+    let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    Arc::new(CircuitBreakerConnector::new(inner, cfg))
+}
+
+fn inst(symbol: &str) -> Instrument {
+    Instrument::from_symbol(symbol, AssetKind::Equity).expect("valid symbol")
+}
+
+fn cfg(threshold: u32, open_ms: u64) -> CircuitBreakerConfig {
+    CircuitBreakerConfig {
+        failure_threshold: threshold,
+        open_duration: Duration::from_millis(open_ms),
+        ..CircuitBreakerConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn opens_after_consecutive_failures_and_fails_fast() {
+    let cb = breaker(cfg(3, 60_000));
+    let qp = cb.as_quote_provider().expect("quote capability present");
+
+    for _ in 0..3 {
+        let err = qp.quote(&inst("FAIL")).await.expect_err("forced failure");
+        assert!(matches!(err, BorsaError::Connector { .. }));
+    }
+    assert!(cb.is_open(Capability::Quote));
+
+    // Healthy symbols are rejected too: the circuit guards the capability, not the symbol.
+    let err = qp.quote(&inst("AAPL")).await.expect_err("circuit open");
+    assert!(matches!(
+        err,
+        BorsaError::CircuitOpen { reset_in_ms, .. } if reset_in_ms > 0
+    ));
+    assert!(err.is_transient());
+
+    // Other capabilities keep their own circuit.
+    assert!(!cb.is_open(Capability::History));
+}
+
+#[tokio::test]
+async fn success_resets_the_consecutive_count() {
+    let cb = breaker(cfg(2, 60_000));
+    let qp = cb.as_quote_provider().expect("quote capability present");
+
+    let _ = qp.quote(&inst("FAIL")).await.expect_err("forced failure");
+    let _ = qp.quote(&inst("AAPL")).await.expect("ok");
+    let _ = qp.quote(&inst("FAIL")).await.expect_err("forced failure");
+    assert!(!cb.is_open(Capability::Quote));
+}
+
+#[tokio::test]
+async fn permanent_errors_do_not_count() {
+    let cb = breaker(cfg(1, 60_000));
+    let hp = cb
+        .as_history_provider()
+        .expect("history capability present");
+    let req =
+        borsa_core::HistoryRequest::try_from_range(borsa_core::Range::D1, borsa_core::Interval::D1)
+            .expect("valid request");
+
+    // Unknown symbols yield NotFound, which proves the provider answered.
+    let _ = hp.history(&inst("ZZZZ"), req).await;
+    assert!(!cb.is_open(Capability::History));
+}
+
+#[tokio::test]
+async fn failure_rate_opens_the_circuit() {
+    let cb = breaker(CircuitBreakerConfig {
+        failure_threshold: 0,
+        failure_rate: Some(0.5),
+        window: Duration::from_secs(60),
+        min_calls: 4,
+        ..CircuitBreakerConfig::default()
+    });
+    let qp = cb.as_quote_provider().expect("quote capability present");
+
+    for symbol in ["FAIL", "AAPL", "FAIL"] {
+        let _ = qp.quote(&inst(symbol)).await;
+    }
+    assert!(!cb.is_open(Capability::Quote), "below min_calls");
+    let _ = qp.quote(&inst("AAPL")).await;
+    assert!(!cb.is_open(Capability::Quote), "success does not trip");
+    let _ = qp.quote(&inst("FAIL")).await;
+    assert!(cb.is_open(Capability::Quote), "3 of 5 calls failed");
+}
+
+#[tokio::test]
+async fn half_open_probe_closes_on_success_and_reopens_on_failure() {
+    let cb = breaker(cfg(1, 50));
+    let qp = cb.as_quote_provider().expect("quote capability present");
+
+    let _ = qp.quote(&inst("FAIL")).await.expect_err("forced failure");
+    assert!(cb.is_open(Capability::Quote));
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let _ = qp.quote(&inst("FAIL")).await.expect_err("failed probe");
+    let err = qp.quote(&inst("AAPL")).await.expect_err("reopened");
+    assert!(matches!(err, BorsaError::CircuitOpen { .. }));
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let _ = qp.quote(&inst("AAPL")).await.expect("successful probe");
+    assert!(!cb.is_open(Capability::Quote));
+    let _ = qp.quote(&inst("AAPL")).await.expect("closed again");
+}
+
+#[tokio::test]
+async fn probe_rejected_by_quota_releases_its_slot() {
+    let raw: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let quota = QuotaConfig::new(
+        1,
+        Duration::from_secs(3_600),
+        QuotaConsumptionStrategy::Unit,
+    );
+    let wrapped = ConnectorBuilder::new(raw)
+        .with_quota(&quota)
+        .with_circuit_breaker(&cfg(1, 50))
+        .build()
+        .expect("valid stack");
+    let qp = wrapped
+        .as_quote_provider()
+        .expect("quote capability present");
+
+    let _ = qp.quote(&inst("FAIL")).await.expect_err("forced failure");
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    // The quota says nothing about the provider, so neither probe holds the only slot.
+    for _ in 0..2 {
+        let err = qp.quote(&inst("AAPL")).await.expect_err("quota spent");
+        assert!(matches!(err, BorsaError::QuotaExceeded { .. }));
+    }
+}
+
+#[tokio::test]
+async fn internal_calls_bypass_the_breaker() {
+    let cb = breaker(cfg(1, 60_000));
+    let qp = cb.as_quote_provider().expect("quote capability present");
+    let internal = CallOrigin::internal(Capability::Quote, "test");
+
+    // Internal failures are not counted.
+    let _ = CallOrigin::scope(internal.clone(), qp.quote(&inst("FAIL"))).await;
+    assert!(!cb.is_open(Capability::Quote));
+
+    // Internal calls pass through an open circuit.
+    let _ = qp.quote(&inst("FAIL")).await.expect_err("forced failure");
+    assert!(cb.is_open(Capability::Quote));
+    let _ = CallOrigin::scope(internal, qp.quote(&inst("AAPL")))
+        .await
+        .expect("internal call bypasses open circuit");
+}
+
+#[test]
+fn stack_roundtrip_preserves_config_and_ordering() {
+    let raw: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let cb_cfg = CircuitBreakerConfig {
+        failure_threshold: 7,
+        failure_rate: Some(0.25),
+        window: Duration::from_secs(120),
+        min_calls: 20,
+        open_duration: Duration::from_secs(45),
+        half_open_probes: 2,
+    };
+    let builder = ConnectorBuilder::new(Arc::clone(&raw))
+        .with_quota(&QuotaConfig::default())
+        .with_circuit_breaker(&cb_cfg)
+        .with_blacklist(Duration::from_secs(300));
+    let stack = builder.to_stack();
+    let names: Vec<_> = stack.layers.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "BlacklistConnector",
+            "CircuitBreakerConnector",
+            "QuotaAwareConnector",
+            "RawConnector"
+        ]
+    );
+
+    let rebuilt = ConnectorBuilder::try_from_stack(raw, &stack).expect("known layers");
+    assert_eq!(
+        serde_json::to_value(rebuilt.to_stack()).unwrap(),
+        serde_json::to_value(&stack).unwrap()
+    );
+    assert_eq!(stack.layers[1].config["open_duration_ms"], 45_000);
+    assert_eq!(stack.layers[1].config["failure_rate"], 0.25);
+    rebuilt.build().expect("valid stack");
+}
+
+#[test]
+fn invalid_failure_rate_is_rejected() {
+    let raw: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let err = ConnectorBuilder::new(raw)
+        .with_circuit_breaker(&CircuitBreakerConfig {
+            failure_rate: Some(1.5),
+            ..CircuitBreakerConfig::default()
+        })
+        .build()
+        .err()
+        .expect("invalid config");
+    assert!(matches!(err, BorsaError::InvalidMiddlewareStack { .. }));
+}
+
+#[tokio::test]
+async fn calls_cancelled_at_their_deadline_count_as_failures() {
+    let cb = breaker(cfg(2, 60_000));
+    let qp = cb.as_quote_provider().expect("quote capability present");
+
+    // What the router does with a hung provider: scope a deadline, then drop the call there.
+    for _ in 0..2 {
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(20);
+        let call = borsa_core::CallDeadline::scope(deadline, qp.quote(&inst("TIMEOUT")));
+        assert!(tokio::time::timeout_at(deadline, call).await.is_err());
+    }
+    assert!(cb.is_open(Capability::Quote));
+}
+
+#[tokio::test]
+async fn calls_dropped_before_their_deadline_are_not_counted() {
+    let cb = breaker(cfg(1, 60_000));
+    let qp = cb.as_quote_provider().expect("quote capability present");
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+    let call = borsa_core::CallDeadline::scope(deadline, qp.quote(&inst("TIMEOUT")));
+    assert!(
+        tokio::time::timeout(Duration::from_millis(20), call)
+            .await
+            .is_err()
+    );
+    assert!(!cb.is_open(Capability::Quote));
+}
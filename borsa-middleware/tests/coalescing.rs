@@ -0,0 +1,183 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use borsa_core::{
+    AssetKind, BorsaConnector, BorsaError, CallOrigin, Instrument, Profile,
+    connector::ProfileProvider,
+};
+use borsa_middleware::{CoalescingConnector, ConnectorBuilder};
+use borsa_types::{CacheConfig, QuotaConfig, RetryConfig};
+
+/// Profile connector that counts calls and answers slowly enough for callers to overlap.
+struct SlowConnector {
+    calls: AtomicUsize,
+}
+
+impl SlowConnector {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            calls: AtomicUsize::new(0),
+        })
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait::async_trait]
+impl BorsaConnector for SlowConnector {
+    fn name(&self) -> &'static str {
+        "slow"
+    }
+    fn vendor(&self) -> &'static str {
+        "test"
+    }
+    fn supports_kind(&self, _k: AssetKind) -> bool {
+        true
+    }
+    fn as_profile_provider(&self) -> Option<&dyn ProfileProvider> {
+        Some(self as &dyn ProfileProvider)
+    }
+}
+
+#[async_trait::async_trait]
+impl ProfileProvider for SlowConnector {
+    async fn profile(&self, instrument: &Instrument) -> Result<Profile, BorsaError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Err(BorsaError::not_found(format!(
+            "profile for {}",
+            instrument.id().unique_key()
+        )))
+    }
+}
+
+fn inst(symbol: &str) -> Instrument {
+    Instrument::from_symbol(symbol, AssetKind::Equity).expect("valid symbol")
+}
+
+#[tokio::test]
+async fn concurrent_identical_calls_share_one_provider_call() {
+    let raw = SlowConnector::new();
+    let wrapped = Arc::new(CoalescingConnector::new(raw.clone()));
+
+    let tasks: Vec<_> = (0..10)
+        .map(|_| {
+            let wrapped = Arc::clone(&wrapped);
+            tokio::spawn(async move {
+                wrapped
+                    .as_profile_provider()
+                    .unwrap()
+                    .profile(&inst("AAPL"))
+                    .await
+            })
+        })
+        .collect();
+    for task in tasks {
+        let err = task.await.unwrap().expect_err("shared error");
+        assert!(matches!(err, BorsaError::NotFound { .. }));
+    }
+    assert_eq!(raw.calls(), 1);
+}
+
+#[tokio::test]
+async fn nothing_is_retained_after_completion() {
+    let raw = SlowConnector::new();
+    let wrapped = CoalescingConnector::new(raw.clone());
+    let p = wrapped.as_profile_provider().unwrap();
+
+    let _ = p.profile(&inst("AAPL")).await;
+    let _ = p.profile(&inst("AAPL")).await;
+    assert_eq!(raw.calls(), 2);
+}
+
+#[tokio::test]
+async fn different_arguments_are_not_merged() {
+    let raw = SlowConnector::new();
+    let wrapped = CoalescingConnector::new(raw.clone());
+    let p = wrapped.as_profile_provider().unwrap();
+
+    let (a, b) = tokio::join!(p.profile(&inst("AAPL")), p.profile(&inst("MSFT")));
+    assert!(a.unwrap_err().to_string().contains("AAPL"));
+    assert!(b.unwrap_err().to_string().contains("MSFT"));
+    assert_eq!(raw.calls(), 2);
+}
+
+#[tokio::test]
+async fn different_origins_are_not_merged() {
+    let raw = SlowConnector::new();
+    let wrapped = CoalescingConnector::new(raw.clone());
+    let p = wrapped.as_profile_provider().unwrap();
+
+    let internal = CallOrigin::scope(
+        CallOrigin::internal(None, "info.collect_base"),
+        p.profile(&inst("AAPL")),
+    );
+    let _ = tokio::join!(internal, p.profile(&inst("AAPL")));
+    assert_eq!(raw.calls(), 2);
+}
+
+#[tokio::test]
+async fn cancelled_leader_hands_over_to_a_waiter() {
+    let raw = SlowConnector::new();
+    let wrapped = Arc::new(CoalescingConnector::new(raw.clone()));
+
+    let leader = {
+        let wrapped = Arc::clone(&wrapped);
+        tokio::spawn(async move {
+            wrapped
+                .as_profile_provider()
+                .unwrap()
+                .profile(&inst("AAPL"))
+                .await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let follower = {
+        let wrapped = Arc::clone(&wrapped);
+        tokio::spawn(async move {
+            wrapped
+                .as_profile_provider()
+                .unwrap()
+                .profile(&inst("AAPL"))
+                .await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    leader.abort();
+
+    let err = follower.await.unwrap().expect_err("follower completes");
+    assert!(matches!(err, BorsaError::NotFound { .. }));
+    assert_eq!(raw.calls(), 2);
+}
+
+#[test]
+fn builder_places_coalescing_inside_cache_and_roundtrips() {
+    let raw: Arc<dyn BorsaConnector> = SlowConnector::new();
+    let builder = ConnectorBuilder::new(Arc::clone(&raw))
+        .with_quota(&QuotaConfig::default())
+        .with_retry(&RetryConfig::default())
+        .with_coalescing()
+        .with_cache(&CacheConfig::default());
+    let stack = builder.to_stack();
+    let names: Vec<_> = stack.layers.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "CachingMiddleware",
+            "CoalescingConnector",
+            "RetryConnector",
+            "QuotaAwareConnector",
+            "RawConnector"
+        ]
+    );
+
+    let rebuilt = ConnectorBuilder::try_from_stack(raw, &stack).expect("known layers");
+    assert_eq!(
+        serde_json::to_value(rebuilt.to_stack()).unwrap(),
+        serde_json::to_value(&stack).unwrap()
+    );
+    rebuilt.build().expect("valid stack");
+}
@@ -8,21 +8,21 @@ use borsa_types::{QuotaConfig, QuotaConsumptionStrategy};
 
 fn make_wrapper(limit: u64, window_ms: u64) -> Arc<QuotaAwareConnector> {
     let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
-    let cfg = QuotaConfig {
+    let cfg = QuotaConfig::new(
         limit,
-        window: Duration::from_millis(window_ms),
-        strategy: QuotaConsumptionStrategy::Unit,
-    };
+        Duration::from_millis(window_ms),
+        QuotaConsumptionStrategy::Unit,
+    );
     Arc::new(QuotaAwareConnector::new(inner, cfg))
 }
 
 fn make_wrapper_spread(limit: u64, window_ms: u64) -> Arc<QuotaAwareConnector> {
     let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
-    let cfg = QuotaConfig {
+    let cfg = QuotaConfig::new(
         limit,
-        window: Duration::from_millis(window_ms),
-        strategy: QuotaConsumptionStrategy::EvenSpreadHourly,
-    };
+        Duration::from_millis(window_ms),
+        QuotaConsumptionStrategy::EvenSpreadHourly,
+    );
     Arc::new(QuotaAwareConnector::new(inner, cfg))
 }
 
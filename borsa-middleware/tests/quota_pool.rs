@@ -0,0 +1,225 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use borsa_core::{BorsaConnector, BorsaError};
+use borsa_middleware::{
+    ConnectorBuilder, FileQuotaPool, MemoryQuotaPool, QuotaAwareConnector, QuotaPool, quota_pool,
+    register_quota_pool,
+};
+use borsa_mock::MockConnector;
+use borsa_types::{QuotaConfig, QuotaConsumptionStrategy};
+
+fn mock() -> Arc<dyn BorsaConnector> {
+    Arc::new(MockConnector::new())
+}
+
+fn cfg(limit: u64, pool: Option<&str>) -> QuotaConfig {
+    let cfg = QuotaConfig::new(
+        limit,
+        Duration::from_secs(3_600),
+        QuotaConsumptionStrategy::Unit,
+    );
+    match pool {
+        Some(name) => cfg.with_pool(name),
+        None => cfg,
+    }
+}
+
+/// A pool file path in a fresh directory, removed when the returned guard drops.
+fn temp_file() -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pool.json");
+    (dir, path)
+}
+
+fn exceeded(res: Result<(), BorsaError>) -> bool {
+    matches!(res, Err(BorsaError::QuotaExceeded { .. }))
+}
+
+#[test]
+fn connectors_naming_one_pool_share_its_budget() {
+    let a = QuotaAwareConnector::new(mock(), cfg(3, Some("shared-memory")));
+    let b = QuotaAwareConnector::new(mock(), cfg(3, Some("shared-memory")));
+    a.should_allow_call().unwrap();
+    b.should_allow_call().unwrap();
+    a.should_allow_call().unwrap();
+    assert!(exceeded(b.should_allow_call()));
+    assert_eq!(a.state().unwrap().remaining, 0);
+    assert!(quota_pool("shared-memory").is_some());
+}
+
+#[test]
+fn unnamed_quotas_stay_private() {
+    let a = QuotaAwareConnector::new(mock(), cfg(1, None));
+    let b = QuotaAwareConnector::new(mock(), cfg(1, None));
+    a.should_allow_call().unwrap();
+    b.should_allow_call().unwrap();
+    assert!(exceeded(a.should_allow_call()));
+}
+
+#[test]
+fn registered_pool_is_used() {
+    let pool: Arc<dyn QuotaPool> = Arc::new(MemoryQuotaPool::new());
+    register_quota_pool("registered", Arc::clone(&pool));
+    let a = QuotaAwareConnector::new(mock(), cfg(2, Some("registered")));
+    a.should_allow_call().unwrap();
+    assert_eq!(pool.state(&cfg(2, None)).unwrap().remaining, 1);
+}
+
+#[test]
+fn file_pool_is_shared_between_handles_and_survives_restarts() {
+    let (_dir, path) = temp_file();
+    // Two handles on one file stand in for two processes.
+    let first: Arc<dyn QuotaPool> = Arc::new(FileQuotaPool::new(&path));
+    let second: Arc<dyn QuotaPool> = Arc::new(FileQuotaPool::new(&path));
+    let a = QuotaAwareConnector::with_pool(mock(), cfg(3, None), first);
+    let b = QuotaAwareConnector::with_pool(mock(), cfg(3, None), second);
+    a.should_allow_call().unwrap();
+    b.should_allow_call().unwrap();
+    drop((a, b));
+
+    // A restarted process picks up the same window.
+    let restarted =
+        QuotaAwareConnector::with_pool(mock(), cfg(3, None), Arc::new(FileQuotaPool::new(&path)));
+    restarted.should_allow_call().unwrap();
+    assert!(exceeded(restarted.should_allow_call()));
+    let state = restarted.state().unwrap();
+    assert_eq!(state.remaining, 0);
+    assert!(state.reset_in > Duration::ZERO);
+}
+
+#[test]
+fn pool_path_creates_a_file_pool() {
+    let (_dir, path) = temp_file();
+    let config = cfg(5, Some("configured-file")).with_pool_path(&path);
+    let wrapped = QuotaAwareConnector::new(mock(), config);
+    wrapped.should_allow_call().unwrap();
+    assert!(path.exists());
+}
+
+#[test]
+fn pool_settings_roundtrip_through_stack() {
+    let raw = mock();
+    let (_dir, path) = temp_file();
+    let config = cfg(5, Some("roundtrip")).with_pool_path(path);
+    let stack = ConnectorBuilder::new(Arc::clone(&raw))
+        .with_quota(&config)
+        .to_stack();
+    assert_eq!(stack.layers[0].config["pool"], "roundtrip");
+
+    let rebuilt = ConnectorBuilder::try_from_stack(raw, &stack).expect("known layers");
+    assert_eq!(
+        serde_json::to_value(rebuilt.to_stack()).unwrap(),
+        serde_json::to_value(&stack).unwrap()
+    );
+}
+
+#[test]
+fn pool_path_without_name_is_rejected() {
+    let (_dir, path) = temp_file();
+    let config = cfg(5, None).with_pool_path(path);
+    let err = ConnectorBuilder::new(mock())
+        .with_quota(&config)
+        .build()
+        .err()
+        .expect("pool_path needs a pool name");
+    assert!(matches!(err, BorsaError::InvalidMiddlewareStack { .. }));
+}
+
+#[test]
+fn mismatched_pool_path_is_rejected() {
+    let (_first_dir, first_path) = temp_file();
+    let first = cfg(5, Some("mismatch")).with_pool_path(first_path);
+    ConnectorBuilder::new(mock())
+        .with_quota(&first)
+        .build()
+        .expect("first quota creates the pool");
+
+    let (_second_dir, second_path) = temp_file();
+    let second = cfg(5, Some("mismatch")).with_pool_path(second_path);
+    let err = ConnectorBuilder::new(mock())
+        .with_quota(&second)
+        .build()
+        .err()
+        .expect("pool already uses another file");
+    assert!(matches!(err, BorsaError::InvalidMiddlewareStack { .. }));
+}
+
+#[test]
+fn mismatched_pool_budget_is_rejected() {
+    ConnectorBuilder::new(mock())
+        .with_quota(&cfg(5, Some("budget-mismatch")))
+        .build()
+        .expect("first quota creates the pool");
+    ConnectorBuilder::new(mock())
+        .with_quota(&cfg(5, Some("budget-mismatch")))
+        .build()
+        .expect("same budget shares the pool");
+
+    let err = ConnectorBuilder::new(mock())
+        .with_quota(&cfg(10, Some("budget-mismatch")))
+        .build()
+        .err()
+        .expect("pool already has another limit");
+    assert!(matches!(err, BorsaError::InvalidMiddlewareStack { .. }));
+
+    let hourly_spread = QuotaConfig::new(
+        5,
+        Duration::from_secs(3_600),
+        QuotaConsumptionStrategy::EvenSpreadHourly,
+    )
+    .with_pool("budget-mismatch");
+    let err = ConnectorBuilder::new(mock())
+        .with_quota(&hourly_spread)
+        .build()
+        .err()
+        .expect("pool already has another strategy");
+    assert!(matches!(err, BorsaError::InvalidMiddlewareStack { .. }));
+}
+
+#[test]
+fn corrupt_pool_file_is_an_error() {
+    let (_dir, path) = temp_file();
+    std::fs::write(&path, "not a ledger").unwrap();
+
+    let pool = FileQuotaPool::new(&path);
+    let err = pool
+        .try_consume(&cfg(5, None), 1)
+        .expect_err("corrupt ledger");
+    assert!(matches!(err, BorsaError::Other(_)));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a ledger");
+}
+
+#[test]
+fn spread_slices_count_the_units_of_the_call() {
+    let pool = MemoryQuotaPool::new();
+    // 48 units per day spread over 24 slices: two units per slice.
+    let spread = QuotaConfig::new(
+        48,
+        Duration::from_secs(24 * 60 * 60),
+        QuotaConsumptionStrategy::EvenSpreadHourly,
+    );
+    pool.try_consume(&spread, 1).unwrap();
+    assert!(exceeded(pool.try_consume(&spread, 2)));
+    pool.try_consume(&spread, 1).unwrap();
+
+    // A call larger than a slice still runs, alone, in an empty slice.
+    let fresh = MemoryQuotaPool::new();
+    fresh.try_consume(&spread, 5).unwrap();
+    assert!(exceeded(fresh.try_consume(&spread, 1)));
+}
+
+#[tokio::test]
+async fn file_pool_charges_from_async_calls() {
+    let (_dir, path) = temp_file();
+    let wrapped =
+        QuotaAwareConnector::with_pool(mock(), cfg(1, None), Arc::new(FileQuotaPool::new(&path)));
+    let qp = wrapped.as_quote_provider().unwrap();
+    let inst = borsa_core::Instrument::from_symbol("AAPL", borsa_core::AssetKind::Equity).unwrap();
+    qp.quote(&inst).await.unwrap();
+    assert!(matches!(
+        qp.quote(&inst).await,
+        Err(BorsaError::QuotaExceeded { .. })
+    ));
+}
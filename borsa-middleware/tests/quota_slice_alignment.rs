@@ -15,11 +15,11 @@ use std::time::Duration;
 fn quota_spread_maintains_slice_boundary_alignment() {
     // Configure quota: 24 slices over 2400ms = 100ms per slice, 1 call per slice
     let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
-    let config = QuotaConfig {
-        limit: 24,
-        window: Duration::from_millis(2400),
-        strategy: QuotaConsumptionStrategy::EvenSpreadHourly,
-    };
+    let config = QuotaConfig::new(
+        24,
+        Duration::from_millis(2400),
+        QuotaConsumptionStrategy::EvenSpreadHourly,
+    );
     let wrapper = Arc::new(QuotaAwareConnector::new(inner, config));
 
     // First call establishes initial slice boundary at T=0
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use borsa_core::{AssetKind, BorsaConnector, BorsaError, HistoryRequest, Instrument, Interval};
+use borsa_middleware::{ConnectorBuilder, QuotaAwareConnector, QuotaMiddleware};
+use borsa_mock::MockConnector;
+use borsa_types::{MiddlewareLayer, MiddlewareStack, QuotaConfig, QuotaConsumptionStrategy};
+
+fn weighted(limit: u64, weights: &[(&str, u64)]) -> QuotaConfig {
+    weights.iter().fold(
+        QuotaConfig::new(
+            limit,
+            Duration::from_secs(60),
+            QuotaConsumptionStrategy::Weighted,
+        ),
+        |cfg, (capability, units)| cfg.with_weight(*capability, *units),
+    )
+}
+
+fn inst(symbol: &str) -> Instrument {
+    Instrument::from_symbol(symbol, AssetKind::Equity).expect("valid symbol")
+}
+
+fn history_req() -> HistoryRequest {
+    HistoryRequest::try_from_range(borsa_core::Range::D5, Interval::D1).expect("valid request")
+}
+
+fn is_quota_exceeded<T>(res: &Result<T, BorsaError>) -> bool {
+    matches!(res, Err(BorsaError::QuotaExceeded { .. }))
+}
+
+#[tokio::test]
+async fn capabilities_are_charged_their_weight() {
+    let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let wrapped = QuotaAwareConnector::new(inner, weighted(12, &[("history", 5)]));
+    let hp = wrapped.as_history_provider().unwrap();
+    let qp = wrapped.as_quote_provider().unwrap();
+
+    hp.history(&inst("AAPL"), history_req()).await.unwrap();
+    hp.history(&inst("AAPL"), history_req()).await.unwrap();
+    // 10 of 12 units used: another history call does not fit, quotes still do.
+    let res = hp.history(&inst("AAPL"), history_req()).await;
+    assert!(is_quota_exceeded(&res), "got {res:?}");
+    qp.quote(&inst("AAPL")).await.unwrap();
+    qp.quote(&inst("AAPL")).await.unwrap();
+    assert!(is_quota_exceeded(&qp.quote(&inst("AAPL")).await));
+}
+
+#[tokio::test]
+async fn unit_strategy_ignores_weights() {
+    let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let mut cfg = weighted(3, &[("history", 3)]);
+    cfg.strategy = QuotaConsumptionStrategy::Unit;
+    let wrapped = QuotaAwareConnector::new(inner, cfg);
+    let hp = wrapped.as_history_provider().unwrap();
+    for _ in 0..3 {
+        hp.history(&inst("AAPL"), history_req()).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn weigher_scales_batch_quotes_by_size() {
+    let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let wrapped =
+        QuotaAwareConnector::new(inner, weighted(5, &[])).with_weigher(Arc::new(|ctx, weight| {
+            weight * ctx.size().instruments as u64
+        }));
+    let bp = wrapped.as_batch_quote_provider().unwrap();
+
+    let batch = [inst("AAPL"), inst("MSFT"), inst("GOOG")];
+    bp.quotes(&batch).await.unwrap();
+    // 3 of 5 units used.
+    assert!(is_quota_exceeded(&bp.quotes(&batch).await));
+    bp.quotes(&batch[..2]).await.unwrap();
+}
+
+#[tokio::test]
+async fn weigher_sees_history_period() {
+    let inner: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let wrapped = QuotaAwareConnector::new(inner, weighted(100, &[])).with_weigher(Arc::new(
+        |ctx, weight| {
+            let days = ctx.size().period.map_or(1, |p| p.as_secs() / 86_400);
+            weight * days.max(1)
+        },
+    ));
+    let hp = wrapped.as_history_provider().unwrap();
+    let end = chrono::Utc::now();
+    let req = HistoryRequest::try_from_period(end - chrono::Duration::days(60), end, Interval::D1)
+        .expect("valid request");
+
+    let _ = hp.history(&inst("AAPL"), req.clone()).await;
+    assert!(is_quota_exceeded(&hp.history(&inst("AAPL"), req).await));
+}
+
+#[test]
+fn weights_roundtrip_through_stack() {
+    let raw: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let cfg = weighted(100, &[("history", 5), ("quote", 1)]);
+    let stack = ConnectorBuilder::new(Arc::clone(&raw))
+        .with_quota(&cfg)
+        .to_stack();
+    assert_eq!(stack.layers[0].config["weights"]["history"], 5);
+
+    let rebuilt = ConnectorBuilder::from_stack(raw, &stack);
+    assert_eq!(
+        serde_json::to_value(rebuilt.to_stack()).unwrap(),
+        serde_json::to_value(&stack).unwrap()
+    );
+    // Shortcuts preserve the weight table.
+    let limited = rebuilt.quota_limit(50).to_stack();
+    assert_eq!(limited.layers[0].config["weights"]["history"], 5);
+}
+
+#[test]
+fn stored_quota_layer_with_stale_fields_keeps_its_budget() {
+    let raw: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let mut stack = MiddlewareStack::new();
+    stack.push_inner(MiddlewareLayer::new(
+        "QuotaAwareConnector",
+        serde_json::json!({
+            "limit": 7,
+            "window_ms": 60_000,
+            "strategy": "Weighted",
+            "weights": { "history": 2 },
+            "renamed_field": true,
+            "pool": 5,
+        }),
+    ));
+
+    let rebuilt = ConnectorBuilder::from_stack(Arc::clone(&raw), &stack).to_stack();
+    assert_eq!(rebuilt.layers[0].name, "QuotaAwareConnector");
+    assert_eq!(rebuilt.layers[0].config["limit"], 7);
+    assert_eq!(rebuilt.layers[0].config["weights"]["history"], 2);
+    assert!(rebuilt.layers[0].config["pool"].is_null());
+
+    let err = ConnectorBuilder::try_from_stack(raw, &stack)
+        .err()
+        .expect("strict parsing rejects the stale field");
+    assert!(matches!(err, BorsaError::InvalidConfig { .. }));
+}
+
+#[test]
+fn weight_above_limit_is_rejected() {
+    let raw: Arc<dyn BorsaConnector> = Arc::new(MockConnector::new());
+    let err = ConnectorBuilder::new(raw)
+        .layer(QuotaMiddleware::new(weighted(10, &[("history", 11)])))
+        .build()
+        .err()
+        .expect("weight exceeds limit");
+    assert!(matches!(err, BorsaError::InvalidMiddlewareStack { .. }));
+}
@@ -0,0 +1,188 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use borsa_core::{AssetKind, BorsaConnector, BorsaError, CallDeadline, CallPriority, Instrument};
+use borsa_middleware::{ConnectorBuilder, RateLimiterConnector, RateLimiterMiddleware};
+use borsa_mock::MockConnector;
+use borsa_types::{QuotaConfig, RateLimitConfig, RetryConfig};
+use tokio::time::Instant;
+
+fn mock() -> Arc<dyn BorsaConnector> {
+    Arc::new(MockConnector::new())
+}
+
+fn limiter(requests_per_second: f64, burst: u32, max_wait: Duration) -> Arc<RateLimiterConnector> {
+    Arc::new(
+        RateLimiterConnector::new(
+            mock(),
+            RateLimitConfig {
+                requests_per_second,
+                burst,
+                max_wait,
+            },
+        )
+        .expect("valid rate limit"),
+    )
+}
+
+fn inst(symbol: &str) -> Instrument {
+    Instrument::from_symbol(symbol, AssetKind::Equity).expect("valid symbol")
+}
+
+#[tokio::test]
+async fn burst_passes_then_calls_wait_for_tokens() {
+    let rl = limiter(20.0, 2, Duration::from_secs(1));
+    let started = Instant::now();
+    rl.acquire(CallPriority::Interactive, None).await.unwrap();
+    rl.acquire(CallPriority::Interactive, None).await.unwrap();
+    assert!(started.elapsed() < Duration::from_millis(20));
+
+    // The third call waits ~50ms for a refill instead of failing.
+    let qp = rl.as_quote_provider().unwrap();
+    qp.quote(&inst("AAPL")).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(40));
+}
+
+#[tokio::test]
+async fn rejects_when_wait_exceeds_max_wait() {
+    let rl = limiter(1.0, 1, Duration::from_millis(100));
+    rl.acquire(CallPriority::Interactive, None).await.unwrap();
+    let err = rl
+        .acquire(CallPriority::Interactive, None)
+        .await
+        .unwrap_err();
+    match err {
+        BorsaError::QuotaExceeded {
+            remaining,
+            reset_in_ms,
+        } => {
+            assert_eq!(remaining, 0);
+            assert!(reset_in_ms > 100, "expected wait hint, got {reset_in_ms}");
+        }
+        other => panic!("expected QuotaExceeded, got {other:?}"),
+    }
+    assert_eq!(rl.queue_depth(), 0);
+}
+
+#[tokio::test]
+async fn rejects_when_wait_exceeds_provider_deadline() {
+    let rl = limiter(1.0, 1, Duration::from_secs(30));
+    let qp = rl.as_quote_provider().unwrap();
+    qp.quote(&inst("AAPL")).await.unwrap();
+
+    let started = Instant::now();
+    let deadline = started + Duration::from_millis(100);
+    let res = CallDeadline::scope(deadline, qp.quote(&inst("AAPL"))).await;
+    assert!(
+        matches!(res, Err(BorsaError::QuotaExceeded { .. })),
+        "got {res:?}"
+    );
+    // Rejected up front rather than after waiting out the deadline.
+    assert!(started.elapsed() < Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn interactive_calls_jump_ahead_of_bulk() {
+    let rl = limiter(10.0, 1, Duration::from_secs(5));
+    rl.acquire(CallPriority::Interactive, None).await.unwrap();
+
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let spawn = |priority: CallPriority| {
+        let rl = Arc::clone(&rl);
+        let order = Arc::clone(&order);
+        tokio::spawn(async move {
+            rl.acquire(priority, None).await.unwrap();
+            order.lock().unwrap().push(priority);
+        })
+    };
+    let bulk = spawn(CallPriority::Bulk);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let interactive = spawn(CallPriority::Interactive);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(rl.queue_depth(), 2);
+    assert_eq!(rl.queue_depth_for(CallPriority::Bulk), 1);
+    assert_eq!(rl.queue_depth_for(CallPriority::Interactive), 1);
+
+    interactive.await.unwrap();
+    bulk.await.unwrap();
+    assert_eq!(
+        *order.lock().unwrap(),
+        [CallPriority::Interactive, CallPriority::Bulk]
+    );
+    assert_eq!(rl.queue_depth(), 0);
+}
+
+#[tokio::test]
+async fn cancelled_waiter_leaves_the_queue() {
+    let rl = limiter(1.0, 1, Duration::from_secs(5));
+    rl.acquire(CallPriority::Interactive, None).await.unwrap();
+    let res = tokio::time::timeout(
+        Duration::from_millis(20),
+        rl.acquire(CallPriority::Bulk, None),
+    )
+    .await;
+    assert!(res.is_err(), "waiter should still be queued");
+    assert_eq!(rl.queue_depth(), 0);
+}
+
+#[test]
+fn builder_places_rate_limiter_inside_quota_and_roundtrips() {
+    let raw = mock();
+    let builder = ConnectorBuilder::new(Arc::clone(&raw))
+        .with_rate_limit(&RateLimitConfig {
+            requests_per_second: 2.5,
+            burst: 3,
+            max_wait: Duration::from_secs(4),
+        })
+        .with_quota(&QuotaConfig::default())
+        .with_retry(&RetryConfig::default());
+    let stack = builder.to_stack();
+    let names: Vec<_> = stack.layers.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "RetryConnector",
+            "QuotaAwareConnector",
+            "RateLimiterConnector",
+            "RawConnector"
+        ]
+    );
+    assert_eq!(stack.layers[2].config["max_wait_ms"], 4_000);
+
+    let rebuilt = ConnectorBuilder::try_from_stack(raw, &stack).expect("known layers");
+    assert_eq!(
+        serde_json::to_value(rebuilt.to_stack()).unwrap(),
+        serde_json::to_value(&stack).unwrap()
+    );
+    rebuilt.build().expect("valid stack");
+}
+
+#[test]
+fn zero_burst_is_rejected() {
+    let err = ConnectorBuilder::new(mock())
+        .layer(RateLimiterMiddleware::new(RateLimitConfig {
+            burst: 0,
+            ..RateLimitConfig::default()
+        }))
+        .build()
+        .err()
+        .expect("burst must be positive");
+    assert!(matches!(err, BorsaError::InvalidMiddlewareStack { .. }));
+}
+
+#[test]
+fn non_positive_rate_is_rejected_by_new() {
+    for requests_per_second in [0.0, -1.0, f64::NAN] {
+        let err = RateLimiterConnector::new(
+            mock(),
+            RateLimitConfig {
+                requests_per_second,
+                ..RateLimitConfig::default()
+            },
+        )
+        .err()
+        .expect("rate must be positive");
+        assert!(matches!(err, BorsaError::InvalidMiddlewareStack { .. }));
+    }
+}
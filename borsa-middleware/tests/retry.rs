@@ -0,0 +1,286 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use borsa_core::{
+    AssetKind, BackoffConfig, BorsaConnector, BorsaError, CallDeadline, CallOrigin, Capability,
+    Instrument, connector::ProfileProvider,
+};
+use borsa_middleware::{ConnectorBuilder, RetryConnector};
+use borsa_types::{CacheConfig, QuotaConfig, RetryConfig, RetryPolicy};
+
+/// Profile connector that replays scripted errors, then succeeds.
+struct ScriptedConnector {
+    errors: Mutex<VecDeque<BorsaError>>,
+    calls: Mutex<usize>,
+}
+
+impl ScriptedConnector {
+    fn new(errors: Vec<BorsaError>) -> Arc<Self> {
+        Arc::new(Self {
+            errors: Mutex::new(errors.into()),
+            calls: Mutex::new(0),
+        })
+    }
+
+    fn calls(&self) -> usize {
+        *self.calls.lock().unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+impl BorsaConnector for ScriptedConnector {
+    fn name(&self) -> &'static str {
+        "scripted"
+    }
+    fn vendor(&self) -> &'static str {
+        "test"
+    }
+    fn supports_kind(&self, _k: AssetKind) -> bool {
+        true
+    }
+    fn as_profile_provider(&self) -> Option<&dyn ProfileProvider> {
+        Some(self as &dyn ProfileProvider)
+    }
+}
+
+#[async_trait::async_trait]
+impl ProfileProvider for ScriptedConnector {
+    async fn profile(&self, _instrument: &Instrument) -> Result<borsa_core::Profile, BorsaError> {
+        *self.calls.lock().unwrap() += 1;
+        match self.errors.lock().unwrap().pop_front() {
+            Some(err) => Err(err),
+            None => Err(BorsaError::not_found("profile (scripted success marker)")),
+        }
+    }
+}
+
+const fn fast_policy(max_attempts: u32, retry_unknown: bool) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        backoff: BackoffConfig {
+            min_backoff_ms: 1,
+            max_backoff_ms: 5,
+            factor: 2,
+            jitter_percent: 0,
+        },
+        retry_unknown,
+    }
+}
+
+fn cfg(policy: RetryPolicy) -> RetryConfig {
+    RetryConfig {
+        default: policy,
+        ..RetryConfig::default()
+    }
+}
+
+fn inst() -> Instrument {
+    Instrument::from_symbol("AAPL", AssetKind::Equity).unwrap()
+}
+
+fn timeout() -> BorsaError {
+    BorsaError::connector(
+        "scripted",
+        BorsaError::provider_timeout("scripted", "profile"),
+    )
+}
+
+fn is_marker(res: &Result<borsa_core::Profile, BorsaError>) -> bool {
+    matches!(res, Err(BorsaError::NotFound { .. }))
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried_until_success() {
+    let raw = ScriptedConnector::new(vec![timeout(), timeout()]);
+    let wrapped = RetryConnector::new(raw.clone(), cfg(fast_policy(3, false)));
+    let res = wrapped
+        .as_profile_provider()
+        .unwrap()
+        .profile(&inst())
+        .await;
+    assert!(is_marker(&res), "got {res:?}");
+    assert_eq!(raw.calls(), 3);
+}
+
+#[tokio::test]
+async fn attempts_are_capped() {
+    let raw = ScriptedConnector::new(vec![timeout(), timeout(), timeout()]);
+    let wrapped = RetryConnector::new(raw.clone(), cfg(fast_policy(2, false)));
+    let err = wrapped
+        .as_profile_provider()
+        .unwrap()
+        .profile(&inst())
+        .await
+        .expect_err("still failing");
+    assert!(err.is_transient());
+    assert_eq!(raw.calls(), 2);
+}
+
+#[tokio::test]
+async fn unknown_errors_are_retried_only_when_enabled() {
+    let other = || BorsaError::Other("http status 500".into());
+
+    let raw = ScriptedConnector::new(vec![other()]);
+    let wrapped = RetryConnector::new(raw.clone(), cfg(fast_policy(3, false)));
+    let _ = wrapped
+        .as_profile_provider()
+        .unwrap()
+        .profile(&inst())
+        .await;
+    assert_eq!(raw.calls(), 1);
+
+    let raw = ScriptedConnector::new(vec![other()]);
+    let wrapped = RetryConnector::new(raw.clone(), cfg(fast_policy(3, true)));
+    let res = wrapped
+        .as_profile_provider()
+        .unwrap()
+        .profile(&inst())
+        .await;
+    assert!(is_marker(&res));
+    assert_eq!(raw.calls(), 2);
+}
+
+#[tokio::test]
+async fn per_capability_override_applies() {
+    let raw = ScriptedConnector::new(vec![timeout(), timeout()]);
+    let mut config = cfg(fast_policy(3, false));
+    config.per_capability.insert(
+        Capability::Profile.as_str().to_string(),
+        fast_policy(1, false),
+    );
+    let wrapped = RetryConnector::new(raw.clone(), config);
+    let _ = wrapped
+        .as_profile_provider()
+        .unwrap()
+        .profile(&inst())
+        .await;
+    assert_eq!(raw.calls(), 1);
+}
+
+#[tokio::test]
+async fn reset_hints_are_honoured_or_short_circuit() {
+    let hinted = |ms| BorsaError::QuotaExceeded {
+        remaining: 0,
+        reset_in_ms: ms,
+    };
+
+    // A short hint is waited out and retried.
+    let raw = ScriptedConnector::new(vec![hinted(20)]);
+    let wrapped = RetryConnector::new(raw.clone(), cfg(fast_policy(3, false)));
+    let started = tokio::time::Instant::now();
+    let res = wrapped
+        .as_profile_provider()
+        .unwrap()
+        .profile(&inst())
+        .await;
+    assert!(is_marker(&res));
+    assert!(started.elapsed() >= Duration::from_millis(20));
+    assert_eq!(raw.calls(), 2);
+
+    // A hint longer than max_hint_wait is returned immediately.
+    let raw = ScriptedConnector::new(vec![BorsaError::TemporarilyBlacklisted {
+        reset_in_ms: 60_000,
+    }]);
+    let wrapped = RetryConnector::new(raw.clone(), cfg(fast_policy(3, false)));
+    let err = wrapped
+        .as_profile_provider()
+        .unwrap()
+        .profile(&inst())
+        .await
+        .expect_err("not retried");
+    assert!(matches!(err, BorsaError::TemporarilyBlacklisted { .. }));
+    assert_eq!(raw.calls(), 1);
+}
+
+#[tokio::test]
+async fn retries_that_would_pass_the_call_deadline_are_skipped() {
+    let raw = ScriptedConnector::new(vec![BorsaError::QuotaExceeded {
+        remaining: 0,
+        reset_in_ms: 200,
+    }]);
+    let wrapped = RetryConnector::new(raw.clone(), cfg(fast_policy(3, false)));
+    let p = wrapped.as_profile_provider().unwrap();
+    let started = tokio::time::Instant::now();
+    let err = CallDeadline::scope(started + Duration::from_millis(50), p.profile(&inst()))
+        .await
+        .expect_err("not retried");
+    assert!(matches!(err, BorsaError::QuotaExceeded { .. }));
+    assert!(started.elapsed() < Duration::from_millis(50));
+    assert_eq!(raw.calls(), 1);
+}
+
+#[tokio::test]
+async fn budget_limits_retries_across_calls() {
+    let raw = ScriptedConnector::new(vec![timeout(), timeout(), timeout()]);
+    let config = RetryConfig {
+        budget: Some(1),
+        ..cfg(fast_policy(3, false))
+    };
+    let wrapped = RetryConnector::new(raw.clone(), config);
+    let p = wrapped.as_profile_provider().unwrap();
+    let _ = p.profile(&inst()).await;
+    // First call: initial attempt + the single budgeted retry.
+    assert_eq!(raw.calls(), 2);
+    let _ = p.profile(&inst()).await;
+    assert_eq!(raw.calls(), 3);
+}
+
+#[tokio::test]
+async fn internal_calls_are_not_retried() {
+    let raw = ScriptedConnector::new(vec![timeout()]);
+    let wrapped = RetryConnector::new(raw.clone(), cfg(fast_policy(3, false)));
+    let p = wrapped.as_profile_provider().unwrap();
+    let _ = CallOrigin::scope(
+        CallOrigin::internal(Capability::Profile, "test"),
+        p.profile(&inst()),
+    )
+    .await;
+    assert_eq!(raw.calls(), 1);
+}
+
+#[test]
+fn builder_orders_retry_between_cache_and_quota_and_roundtrips() {
+    let raw: Arc<dyn BorsaConnector> = ScriptedConnector::new(vec![]);
+    let mut config = cfg(fast_policy(4, true));
+    config
+        .per_capability
+        .insert("quote".into(), fast_policy(2, false));
+    config.budget = Some(10);
+    let builder = ConnectorBuilder::new(Arc::clone(&raw))
+        .with_quota(&QuotaConfig::default())
+        .with_retry(&config)
+        .with_cache(&CacheConfig::default());
+    let stack = builder.to_stack();
+    let names: Vec<_> = stack.layers.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "CachingMiddleware",
+            "RetryConnector",
+            "QuotaAwareConnector",
+            "RawConnector"
+        ]
+    );
+
+    let rebuilt = ConnectorBuilder::try_from_stack(raw, &stack).expect("known layers");
+    assert_eq!(
+        serde_json::to_value(rebuilt.to_stack()).unwrap(),
+        serde_json::to_value(&stack).unwrap()
+    );
+    rebuilt.build().expect("valid stack");
+}
+
+#[test]
+fn retry_outside_cache_is_rejected() {
+    let raw: Arc<dyn BorsaConnector> = ScriptedConnector::new(vec![]);
+    let err = ConnectorBuilder::new(raw)
+        .with_cache(&CacheConfig::default())
+        .layer(borsa_middleware::RetryMiddleware::new(
+            RetryConfig::default(),
+        ))
+        .build()
+        .err()
+        .expect("retry must sit inside the cache");
+    assert!(matches!(err, BorsaError::InvalidMiddlewareStack { .. }));
+}
@@ -1,18 +1,20 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use borsa_core::connector::{
-    AnalystPriceTargetProvider, BalanceSheetProvider, BorsaConnector, CalendarProvider,
-    CashflowProvider, EarningsProvider, EsgProvider, HistoryProvider, IncomeStatementProvider,
-    InsiderRosterHoldersProvider, InsiderTransactionsProvider, InstitutionalHoldersProvider,
-    MajorHoldersProvider, MutualFundHoldersProvider, NetSharePurchaseActivityProvider,
-    NewsProvider, OptionChainProvider, OptionsExpirationsProvider, ProfileProvider, QuoteProvider,
-    RecommendationsProvider, RecommendationsSummaryProvider, SearchProvider,
-    UpgradesDowngradesProvider,
+    AnalystPriceTargetProvider, BalanceSheetProvider, BatchQuoteProvider, BorsaConnector,
+    CalendarProvider, CashflowProvider, EarningsProvider, EsgProvider, HistoryProvider,
+    IncomeStatementProvider, InsiderRosterHoldersProvider, InsiderTransactionsProvider,
+    InstitutionalHoldersProvider, MajorHoldersProvider, MutualFundHoldersProvider,
+    NetSharePurchaseActivityProvider, NewsProvider, OptionChainProvider,
+    OptionsExpirationsProvider, ProfileProvider, QuoteProvider, RecommendationsProvider,
+    RecommendationsSummaryProvider, SearchProvider, UpgradesDowngradesProvider,
 };
 use borsa_core::{
-    AssetKind, BalanceSheetRow, BorsaError, Calendar, CashflowRow, Earnings, EsgScores,
-    HistoryRequest, HistoryResponse, IncomeStatementRow, Instrument, Interval, NewsRequest,
-    OptionChain, Profile, Quote, RecommendationRow, RecommendationSummary, SearchRequest,
-    SearchResponse, UpgradeDowngradeRow, types,
+    AssetKind, BalanceSheetRow, BorsaError, Calendar, CashflowRow, ConnectorRegistry, Earnings,
+    EsgScores, HistoryRequest, HistoryResponse, IncomeStatementRow, Instrument, Interval,
+    NewsRequest, OptionChain, Profile, Quote, RecommendationRow, RecommendationSummary,
+    SearchRequest, SearchResponse, UpgradeDowngradeRow, types,
 };
 
 pub mod dynamic;
@@ -34,6 +36,28 @@ impl MockConnector {
         Self
     }
 
+    /// Add a `borsa-mock` constructor to `registry`.
+    ///
+    /// The mock takes no options, so its `config` section must be empty or absent.
+    pub fn register(registry: &mut ConnectorRegistry) {
+        registry.register("borsa-mock", Self::from_config);
+    }
+
+    /// Add a `borsa-mock` constructor to the process-wide registry.
+    pub fn register_global() {
+        borsa_core::register_connector("borsa-mock", Self::from_config);
+    }
+
+    fn from_config(config: &serde_json::Value) -> Result<Arc<dyn BorsaConnector>, BorsaError> {
+        match config.as_object() {
+            Some(map) if !map.is_empty() => Err(BorsaError::InvalidArg(format!(
+                "borsa-mock takes no options (got: {})",
+                map.keys().cloned().collect::<Vec<_>>().join(", ")
+            ))),
+            _ => Ok(Arc::new(Self::new())),
+        }
+    }
+
     fn not_found(what: &str) -> BorsaError {
         BorsaError::not_found(what.to_string())
     }
@@ -90,6 +114,9 @@ impl BorsaConnector for MockConnector {
     fn as_quote_provider(&self) -> Option<&dyn QuoteProvider> {
         Some(self as &dyn QuoteProvider)
     }
+    fn as_batch_quote_provider(&self) -> Option<&dyn BatchQuoteProvider> {
+        Some(self as &dyn BatchQuoteProvider)
+    }
     fn as_history_provider(&self) -> Option<&dyn HistoryProvider> {
         Some(self as &dyn HistoryProvider)
     }
@@ -170,6 +197,20 @@ impl QuoteProvider for MockConnector {
     }
 }
 
+#[async_trait]
+impl BatchQuoteProvider for MockConnector {
+    async fn quotes(&self, instruments: &[Instrument]) -> Result<Vec<Quote>, BorsaError> {
+        // Symbols without a fixture (or forced failures) are omitted from the batch.
+        let mut out = Vec::with_capacity(instruments.len());
+        for inst in instruments {
+            if let Ok(q) = self.quote(inst).await {
+                out.push(q);
+            }
+        }
+        Ok(out)
+    }
+}
+
 #[async_trait]
 impl HistoryProvider for MockConnector {
     async fn history(
@@ -0,0 +1,638 @@
+//! Local history store with incremental sync through the `borsa` router.
+//!
+//! [`HistoryStore`] keeps one series of candles and actions per instrument and interval in an
+//! embedded [`redb`] database, together with the attribution spans of the providers that
+//! supplied it. Candles are stored one row each, so [`HistoryStore::sync`] requests only the
+//! tail after the last stored candle through [`Borsa::history_with_attribution`], stitches it
+//! onto the stored series with [`stitch_history`] and writes back just the rows it touched.
+//! Database work runs on the blocking thread pool.
+//!
+//! The store is also a connector: it implements [`HistoryProvider`] for the series it holds,
+//! so it can sit in front of live connectors in a routing policy. Syncs route around
+//! connectors named `borsa-store`, so a store never feeds itself through the router it serves.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use borsa::Borsa;
+use borsa_core::connector::{BorsaConnector, HistoryProvider, intern_intervals};
+use borsa_core::timeseries::window::range_start;
+use borsa_core::{
+    Action, AssetKind, BorsaError, CallPriority, Candle, ConnectorRegistry, HistoryMeta,
+    HistoryRequest, HistoryRequestBuilder, HistoryResponse, Instrument, Interval, Range, Span,
+    slice_to_request, stitch_history,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const NAME: &str = "borsa-store";
+
+// key: "<instrument unique key>\u{1f}<interval code>", value: JSON-encoded `SeriesRecord`
+const SERIES: TableDefinition<&str, &[u8]> = TableDefinition::new("series");
+// key: (series key, candle time in unix seconds), value: JSON-encoded `Candle`
+const CANDLES: TableDefinition<(&str, i64), &[u8]> = TableDefinition::new("candles");
+
+/// One stored series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredHistory {
+    /// Instrument the series belongs to.
+    pub instrument: Instrument,
+    /// Candle interval of the series.
+    pub interval: Interval,
+    /// Merged candles and actions.
+    pub history: HistoryResponse,
+    /// Provider spans, as in [`Attribution::spans`](borsa_core::Attribution), with owned names.
+    pub spans: Vec<(String, Span)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredSpan {
+    provider: String,
+    start: i64,
+    end: i64,
+}
+
+/// Everything stored for a series except its candles.
+#[derive(Serialize, Deserialize)]
+struct SeriesRecord {
+    instrument: Instrument,
+    interval: Interval,
+    actions: Vec<Action>,
+    adjusted: bool,
+    meta: Option<HistoryMeta>,
+    spans: Vec<StoredSpan>,
+}
+
+impl SeriesRecord {
+    fn new(
+        instrument: &Instrument,
+        interval: Interval,
+        history: &HistoryResponse,
+        spans: &[(String, Span)],
+    ) -> Self {
+        Self {
+            instrument: instrument.clone(),
+            interval,
+            actions: history.actions.clone(),
+            adjusted: history.adjusted,
+            meta: history.meta.clone(),
+            spans: spans
+                .iter()
+                .map(|(provider, span)| StoredSpan {
+                    provider: provider.clone(),
+                    start: span.start,
+                    end: span.end,
+                })
+                .collect(),
+        }
+    }
+
+    fn into_stored(self, candles: Vec<Candle>) -> StoredHistory {
+        StoredHistory {
+            instrument: self.instrument,
+            interval: self.interval,
+            history: HistoryResponse {
+                candles,
+                actions: self.actions,
+                adjusted: self.adjusted,
+                meta: self.meta,
+            },
+            spans: self
+                .spans
+                .into_iter()
+                .map(|s| {
+                    (
+                        s.provider,
+                        Span {
+                            start: s.start,
+                            end: s.end,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Result of syncing one instrument.
+#[derive(Debug)]
+pub struct SyncOutcome {
+    /// Instrument that was synced.
+    pub instrument: Instrument,
+    /// Number of candles added to the stored series, or the error that stopped the sync.
+    pub result: Result<usize, BorsaError>,
+}
+
+/// Embedded, persistent store of merged history series.
+pub struct HistoryStore {
+    db: Arc<Db>,
+    initial_range: Range,
+    concurrency: usize,
+}
+
+/// The database and what is derived from it, shared with blocking tasks.
+struct Db {
+    path: PathBuf,
+    db: Database,
+    // stored (kind, interval) pairs, for `supported_history_intervals`
+    index: RwLock<Vec<(AssetKind, Interval)>>,
+}
+
+fn key(instrument: &Instrument, interval: Interval) -> String {
+    let code = serde_json::to_value(interval)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_owned))
+        .unwrap_or_else(|| format!("{interval:?}"));
+    format!("{}\u{1f}{code}", instrument.id().unique_key())
+}
+
+impl Db {
+    fn err(&self, e: impl std::fmt::Display) -> BorsaError {
+        store_err(&self.path, e)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, raw: &[u8]) -> Result<T, BorsaError> {
+        serde_json::from_slice(raw).map_err(|e| self.err(e))
+    }
+
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, BorsaError> {
+        serde_json::to_vec(value).map_err(|e| self.err(e))
+    }
+
+    fn index_series(&self, instrument: &Instrument, interval: Interval) {
+        let entry = (*instrument.kind(), interval);
+        let mut index = self.index.write().expect("lock poisoned");
+        if !index.contains(&entry) {
+            index.push(entry);
+        }
+    }
+
+    /// Candles of the series `key` with `from <= ts < to`, oldest first.
+    fn candles(
+        &self,
+        table: &impl ReadableTable<(&'static str, i64), &'static [u8]>,
+        key: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<Candle>, BorsaError> {
+        let mut out = Vec::new();
+        for entry in table
+            .range((key, from)..(key, to))
+            .map_err(|e| self.err(e))?
+        {
+            let (_, raw) = entry.map_err(|e| self.err(e))?;
+            out.push(self.decode(raw.value())?);
+        }
+        Ok(out)
+    }
+
+    /// The stored series with its candles cut to `from <= ts < to`.
+    fn read(
+        &self,
+        instrument: &Instrument,
+        interval: Interval,
+        from: i64,
+        to: i64,
+    ) -> Result<Option<StoredHistory>, BorsaError> {
+        let key = key(instrument, interval);
+        let txn = self.db.begin_read().map_err(|e| self.err(e))?;
+        let series = txn.open_table(SERIES).map_err(|e| self.err(e))?;
+        let Some(raw) = series.get(key.as_str()).map_err(|e| self.err(e))? else {
+            return Ok(None);
+        };
+        let record: SeriesRecord = self.decode(raw.value())?;
+        let table = txn.open_table(CANDLES).map_err(|e| self.err(e))?;
+        let candles = self.candles(&table, &key, from, to)?;
+        Ok(Some(record.into_stored(candles)))
+    }
+
+    fn last_ts(
+        &self,
+        instrument: &Instrument,
+        interval: Interval,
+    ) -> Result<Option<DateTime<Utc>>, BorsaError> {
+        let key = key(instrument, interval);
+        let txn = self.db.begin_read().map_err(|e| self.err(e))?;
+        let table = txn.open_table(CANDLES).map_err(|e| self.err(e))?;
+        let last = table
+            .range((key.as_str(), i64::MIN)..(key.as_str(), i64::MAX))
+            .map_err(|e| self.err(e))?
+            .next_back()
+            .transpose()
+            .map_err(|e| self.err(e))?;
+        Ok(last.and_then(|(k, _)| DateTime::from_timestamp(k.value().1, 0)))
+    }
+
+    fn put(&self, stored: &StoredHistory) -> Result<(), BorsaError> {
+        let key = key(&stored.instrument, stored.interval);
+        let record = SeriesRecord::new(
+            &stored.instrument,
+            stored.interval,
+            &stored.history,
+            &stored.spans,
+        );
+        let txn = self.db.begin_write().map_err(|e| self.err(e))?;
+        {
+            let mut table = txn.open_table(CANDLES).map_err(|e| self.err(e))?;
+            let old: Vec<i64> = table
+                .range((key.as_str(), i64::MIN)..(key.as_str(), i64::MAX))
+                .map_err(|e| self.err(e))?
+                .map(|entry| entry.map(|(k, _)| k.value().1))
+                .collect::<Result<_, _>>()
+                .map_err(|e| self.err(e))?;
+            for ts in old {
+                table.remove((key.as_str(), ts)).map_err(|e| self.err(e))?;
+            }
+            for candle in &stored.history.candles {
+                table
+                    .insert(
+                        (key.as_str(), candle.ts.timestamp()),
+                        self.encode(candle)?.as_slice(),
+                    )
+                    .map_err(|e| self.err(e))?;
+            }
+            let mut series = txn.open_table(SERIES).map_err(|e| self.err(e))?;
+            series
+                .insert(key.as_str(), self.encode(&record)?.as_slice())
+                .map_err(|e| self.err(e))?;
+        }
+        txn.commit().map_err(|e| self.err(e))?;
+        self.index_series(&stored.instrument, stored.interval);
+        Ok(())
+    }
+
+    /// Stitch `fresh` onto the stored series and write back the candles it touches.
+    ///
+    /// Returns the number of candles added.
+    fn stitch(
+        &self,
+        instrument: &Instrument,
+        interval: Interval,
+        fresh: HistoryResponse,
+        fresh_spans: Vec<(String, Span)>,
+    ) -> Result<usize, BorsaError> {
+        let key = key(instrument, interval);
+        // A single write transaction, so concurrent syncs of one series cannot interleave.
+        let txn = self.db.begin_write().map_err(|e| self.err(e))?;
+        let added = {
+            let mut series = txn.open_table(SERIES).map_err(|e| self.err(e))?;
+            let mut table = txn.open_table(CANDLES).map_err(|e| self.err(e))?;
+            let record: Option<SeriesRecord> = series
+                .get(key.as_str())
+                .map_err(|e| self.err(e))?
+                .map(|raw| self.decode(raw.value()))
+                .transpose()?;
+
+            // Only the stored candles fresh ones may replace are read, plus the one before
+            // them, which anchors the currency check and span clipping to the older series.
+            let fresh_start = fresh.candles.first().map_or(i64::MAX, |c| c.ts.timestamp());
+            let (previous, mut spans) = match record {
+                Some(record) => {
+                    let mut candles: Vec<Candle> = table
+                        .range((key.as_str(), i64::MIN)..(key.as_str(), fresh_start))
+                        .map_err(|e| self.err(e))?
+                        .next_back()
+                        .transpose()
+                        .map_err(|e| self.err(e))?
+                        .map(|(_, raw)| self.decode(raw.value()))
+                        .transpose()?
+                        .into_iter()
+                        .collect();
+                    candles.extend(self.candles(&table, &key, fresh_start, i64::MAX)?);
+                    let stored = record.into_stored(candles);
+                    (Some(stored.history), stored.spans)
+                }
+                None => (None, Vec::new()),
+            };
+            if let (Some(first), Some(previous)) = (fresh.candles.first(), previous.as_ref()) {
+                clip_spans(&mut spans, previous, first.ts.timestamp());
+            }
+            spans.extend(fresh_spans);
+
+            let before = previous.as_ref().map_or(0, |h| h.candles.len());
+            let stitched = stitch_history(std::iter::once(fresh).chain(previous))?;
+            let added = stitched.candles.len().saturating_sub(before);
+            for candle in &stitched.candles {
+                table
+                    .insert(
+                        (key.as_str(), candle.ts.timestamp()),
+                        self.encode(candle)?.as_slice(),
+                    )
+                    .map_err(|e| self.err(e))?;
+            }
+            let record = SeriesRecord::new(instrument, interval, &stitched, &spans);
+            series
+                .insert(key.as_str(), self.encode(&record)?.as_slice())
+                .map_err(|e| self.err(e))?;
+            added
+        };
+        txn.commit().map_err(|e| self.err(e))?;
+        self.index_series(instrument, interval);
+        Ok(added)
+    }
+
+    fn series(&self) -> Result<Vec<(Instrument, Interval)>, BorsaError> {
+        let txn = self.db.begin_read().map_err(|e| self.err(e))?;
+        let table = txn.open_table(SERIES).map_err(|e| self.err(e))?;
+        let mut out = Vec::new();
+        for entry in table.iter().map_err(|e| self.err(e))? {
+            let (_, raw) = entry.map_err(|e| self.err(e))?;
+            let record: SeriesRecord = self.decode(raw.value())?;
+            out.push((record.instrument, record.interval));
+        }
+        Ok(out)
+    }
+}
+
+impl HistoryStore {
+    /// Open (or create) the store database at `path`.
+    ///
+    /// A database file can be open only once at a time; share the store through an `Arc`
+    /// rather than opening the same path twice.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be created, is locked by another handle, or is not
+    /// a store database.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BorsaError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                BorsaError::Other(format!("history store {}: {e}", parent.display()))
+            })?;
+        }
+        let db = Db {
+            path: path.to_path_buf(),
+            db: Database::create(path).map_err(|e| store_err(path, e))?,
+            index: RwLock::new(Vec::new()),
+        };
+        // Create the tables up front so readers never find them missing.
+        let txn = db.db.begin_write().map_err(|e| db.err(e))?;
+        txn.open_table(SERIES).map_err(|e| db.err(e))?;
+        txn.open_table(CANDLES).map_err(|e| db.err(e))?;
+        txn.commit().map_err(|e| db.err(e))?;
+        for (instrument, interval) in db.series()? {
+            db.index_series(&instrument, interval);
+        }
+        Ok(Self {
+            db: Arc::new(db),
+            initial_range: Range::Max,
+            concurrency: 4,
+        })
+    }
+
+    /// Range fetched for instruments with nothing stored yet (default: `Range::Max`).
+    #[must_use]
+    pub const fn initial_range(mut self, range: Range) -> Self {
+        self.initial_range = range;
+        self
+    }
+
+    /// Instruments fetched at once during [`sync`](Self::sync) (default: 4, minimum 1).
+    #[must_use]
+    pub fn concurrency(mut self, n: usize) -> Self {
+        self.concurrency = n.max(1);
+        self
+    }
+
+    /// Run database work on the blocking thread pool.
+    async fn blocking<T, F>(&self, f: F) -> Result<T, BorsaError>
+    where
+        F: FnOnce(&Db) -> Result<T, BorsaError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let db = Arc::clone(&self.db);
+        tokio::task::spawn_blocking(move || f(&db))
+            .await
+            .map_err(|e| store_err(&self.db.path, format!("task failed: {e}")))?
+    }
+
+    /// Stored series for `instrument` at `interval`, if any.
+    ///
+    /// Blocks on database IO; async callers should move it to a blocking thread.
+    ///
+    /// # Errors
+    /// Returns an error if the database cannot be read or the record cannot be decoded.
+    pub fn get(
+        &self,
+        instrument: &Instrument,
+        interval: Interval,
+    ) -> Result<Option<StoredHistory>, BorsaError> {
+        self.db.read(instrument, interval, i64::MIN, i64::MAX)
+    }
+
+    /// Replace the stored series for `stored.instrument` at `stored.interval`.
+    ///
+    /// Blocks on database IO; async callers should move it to a blocking thread.
+    ///
+    /// # Errors
+    /// Returns an error if the record cannot be encoded or the database cannot be written.
+    ///
+    /// # Panics
+    /// Panics if the internal lock is poisoned.
+    pub fn put(&self, stored: &StoredHistory) -> Result<(), BorsaError> {
+        self.db.put(stored)
+    }
+
+    /// Every stored `(instrument, interval)` pair.
+    ///
+    /// # Errors
+    /// Returns an error if the database cannot be read or a record cannot be decoded.
+    pub fn series(&self) -> Result<Vec<(Instrument, Interval)>, BorsaError> {
+        self.db.series()
+    }
+
+    /// Bring the stored `interval` series of each instrument up to date through `borsa`.
+    ///
+    /// Behavior:
+    /// - Connectors named `borsa-store` are left out of routing, so a store registered in
+    ///   `borsa` (this one or another) is never its own source.
+    /// - Instruments with nothing stored fetch [`initial_range`](Self::initial_range).
+    /// - Otherwise only the period from the last stored candle to now is requested. The last
+    ///   candle is fetched again because it may have been stored before its bar closed.
+    /// - Fresh candles win over stored ones at the seam and keep their unadjusted closes;
+    ///   actions are merged and de-duplicated. Only the candles from the seam on are
+    ///   rewritten.
+    /// - A `NotFound` tail for an instrument that already has data counts as nothing new.
+    /// - Calls run with [`CallPriority::Bulk`], like downloads.
+    ///
+    /// Outcomes are returned in input order; one instrument failing does not stop the others.
+    pub async fn sync(
+        &self,
+        borsa: &Borsa,
+        instruments: &[Instrument],
+        interval: Interval,
+    ) -> Vec<SyncOutcome> {
+        let live = borsa.without_connector(NAME);
+        let live = &live;
+        let mut outcomes: Vec<(usize, SyncOutcome)> = CallPriority::scope(
+            CallPriority::Bulk,
+            futures::stream::iter(instruments.iter().enumerate())
+                .map(|(i, instrument)| async move {
+                    let result = self.sync_one(live, instrument, interval).await;
+                    (
+                        i,
+                        SyncOutcome {
+                            instrument: instrument.clone(),
+                            result,
+                        },
+                    )
+                })
+                .buffer_unordered(self.concurrency)
+                .collect(),
+        )
+        .await;
+        outcomes.sort_by_key(|(i, _)| *i);
+        outcomes.into_iter().map(|(_, o)| o).collect()
+    }
+
+    async fn sync_one(
+        &self,
+        live: &Borsa,
+        instrument: &Instrument,
+        interval: Interval,
+    ) -> Result<usize, BorsaError> {
+        let owned = instrument.clone();
+        let last = self
+            .blocking(move |db| db.last_ts(&owned, interval))
+            .await?;
+        let now = Utc::now();
+        let builder = HistoryRequestBuilder::default()
+            .interval(interval)
+            .include_actions(true);
+        let req = match last {
+            Some(last) if last >= now => return Ok(0),
+            Some(last) => builder.period(last, now).build()?,
+            None => builder.range(self.initial_range).build()?,
+        };
+
+        let (fresh, attribution) = match live.history_with_attribution(instrument, req).await {
+            Ok(fetched) => fetched,
+            Err(BorsaError::NotFound { .. }) if last.is_some() => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let spans: Vec<(String, Span)> = attribution
+            .spans
+            .iter()
+            .map(|(provider, span)| ((*provider).to_string(), *span))
+            .collect();
+        let owned = instrument.clone();
+        self.blocking(move |db| db.stitch(&owned, interval, fresh, spans))
+            .await
+    }
+
+    /// Add a `borsa-store` constructor to `registry`.
+    ///
+    /// Options: `path` (required database file).
+    pub fn register(registry: &mut ConnectorRegistry) {
+        registry.register(NAME, Self::from_config);
+    }
+
+    /// Add a `borsa-store` constructor to the process-wide registry.
+    pub fn register_global() {
+        borsa_core::register_connector(NAME, Self::from_config);
+    }
+
+    fn from_config(config: &serde_json::Value) -> Result<Arc<dyn BorsaConnector>, BorsaError> {
+        let invalid = |what: &str| BorsaError::InvalidArg(format!("{NAME}: {what}"));
+        let map = config
+            .as_object()
+            .ok_or_else(|| invalid("options must include `path`"))?;
+        if let Some(key) = map.keys().find(|k| k.as_str() != "path") {
+            return Err(invalid(&format!("unknown option `{key}`")));
+        }
+        let path = map
+            .get("path")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| invalid("`path` must be a database file path"))?;
+        Ok(Arc::new(Self::open(path)?))
+    }
+}
+
+fn store_err(path: &Path, e: impl std::fmt::Display) -> BorsaError {
+    BorsaError::Other(format!("history store {}: {e}", path.display()))
+}
+
+/// Cut stored spans so they end before `fresh_start`, where fetched candles take over.
+fn clip_spans(spans: &mut Vec<(String, Span)>, previous: &HistoryResponse, fresh_start: i64) {
+    let kept_end = previous
+        .candles
+        .iter()
+        .map(|c| c.ts.timestamp())
+        .filter(|ts| *ts < fresh_start)
+        .max();
+    spans.retain_mut(|(_, span)| match kept_end {
+        Some(end) if span.start <= end => {
+            span.end = span.end.min(end);
+            true
+        }
+        _ => false,
+    });
+}
+
+#[async_trait]
+impl BorsaConnector for HistoryStore {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+    fn vendor(&self) -> &'static str {
+        "Local store"
+    }
+
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+
+    fn as_history_provider(&self) -> Option<&dyn HistoryProvider> {
+        Some(self as &dyn HistoryProvider)
+    }
+}
+
+#[async_trait]
+impl HistoryProvider for HistoryStore {
+    async fn history(
+        &self,
+        instrument: &Instrument,
+        req: HistoryRequest,
+    ) -> Result<HistoryResponse, BorsaError> {
+        // Read only the candles the request can keep; ranges count back from now.
+        let (from, to) = match req.period() {
+            Some((start, end)) => (start.timestamp(), end.timestamp()),
+            None => (
+                req.range()
+                    .and_then(|range| range_start(range, Utc::now()))
+                    .map_or(i64::MIN, |start| start.timestamp()),
+                i64::MAX,
+            ),
+        };
+        let owned = instrument.clone();
+        let interval = req.interval();
+        let stored = self
+            .blocking(move |db| db.read(&owned, interval, from, to))
+            .await?
+            .ok_or_else(|| {
+                BorsaError::not_found(format!(
+                    "{NAME} {:?} history for {}",
+                    req.interval(),
+                    instrument.id().unique_key()
+                ))
+            })?;
+        Ok(slice_to_request(stored.history, &req))
+    }
+
+    fn supported_history_intervals(&self, kind: AssetKind) -> &'static [Interval] {
+        let intervals: Vec<Interval> = self
+            .db
+            .index
+            .read()
+            .expect("lock poisoned")
+            .iter()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, interval)| *interval)
+            .collect();
+        intern_intervals(&intervals)
+    }
+}
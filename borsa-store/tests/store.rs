@@ -0,0 +1,220 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use borsa::Borsa;
+use borsa_core::connector::{BorsaConnector, HistoryProvider};
+use borsa_core::{
+    AssetKind, BorsaError, Candle, Currency, HistoryRequest, HistoryResponse, Instrument, Interval,
+    IsoCurrency, Money, Range,
+};
+use borsa_store::HistoryStore;
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Upstream serving a daily series that tests can extend, recording every request.
+#[derive(Default)]
+struct Upstream {
+    candles: Mutex<Vec<Candle>>,
+    requests: Mutex<Vec<HistoryRequest>>,
+}
+
+impl Upstream {
+    fn set_days(&self, days: u32, last_close: &str) {
+        let mut candles: Vec<Candle> = (1..days).map(|d| candle(d, "100")).collect();
+        candles.push(candle(days, last_close));
+        *self.candles.lock().unwrap() = candles;
+    }
+}
+
+#[async_trait]
+impl BorsaConnector for Upstream {
+    fn name(&self) -> &'static str {
+        "upstream"
+    }
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+    fn as_history_provider(&self) -> Option<&dyn HistoryProvider> {
+        Some(self as &dyn HistoryProvider)
+    }
+}
+
+#[async_trait]
+impl HistoryProvider for Upstream {
+    async fn history(
+        &self,
+        _instrument: &Instrument,
+        req: HistoryRequest,
+    ) -> Result<HistoryResponse, BorsaError> {
+        let mut candles = self.candles.lock().unwrap().clone();
+        if let Some((start, end)) = req.period() {
+            candles.retain(|c| c.ts >= start && c.ts < end);
+        }
+        self.requests.lock().unwrap().push(req);
+        Ok(HistoryResponse {
+            candles,
+            actions: vec![],
+            adjusted: false,
+            meta: None,
+        })
+    }
+
+    fn supported_history_intervals(&self, _kind: AssetKind) -> &'static [Interval] {
+        &[Interval::D1]
+    }
+}
+
+fn day(d: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, d, 0, 0, 0).unwrap()
+}
+
+fn usd(amount: &str) -> Money {
+    Money::from_canonical_str(amount, Currency::Iso(IsoCurrency::USD)).unwrap()
+}
+
+fn candle(d: u32, close: &str) -> Candle {
+    Candle {
+        ts: day(d),
+        open: usd("100"),
+        high: usd("110"),
+        low: usd("90"),
+        close: usd(close),
+        close_unadj: None,
+        volume: Some(1_000),
+    }
+}
+
+/// A database path in a fresh directory, removed when the returned guard drops.
+fn db() -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("history.redb");
+    (dir, path)
+}
+
+fn inst() -> Instrument {
+    Instrument::from_symbol("AAPL", AssetKind::Equity).expect("valid symbol")
+}
+
+#[tokio::test]
+async fn sync_fetches_only_the_missing_tail() {
+    let upstream = Arc::new(Upstream::default());
+    upstream.set_days(3, "103");
+    let borsa = Borsa::builder()
+        .with_connector(upstream.clone())
+        .build()
+        .unwrap();
+    let (_dir, path) = db();
+    let store = HistoryStore::open(path).unwrap();
+
+    let first = store.sync(&borsa, &[inst()], Interval::D1).await;
+    assert_eq!(*first[0].result.as_ref().unwrap(), 3);
+    assert!(upstream.requests.lock().unwrap()[0].period().is_none());
+
+    // Two new days arrive and the last stored bar is revised.
+    upstream.set_days(5, "105");
+    *upstream.candles.lock().unwrap().get_mut(2).unwrap() = candle(3, "104");
+    let second = store.sync(&borsa, &[inst()], Interval::D1).await;
+    assert_eq!(*second[0].result.as_ref().unwrap(), 2);
+    let (start, _) = upstream.requests.lock().unwrap()[1]
+        .period()
+        .expect("tail period");
+    assert_eq!(start, day(3));
+
+    let stored = store.get(&inst(), Interval::D1).unwrap().expect("stored");
+    let closes: Vec<_> = stored
+        .history
+        .candles
+        .iter()
+        .map(|c| c.close.clone())
+        .collect();
+    assert_eq!(
+        closes,
+        [usd("100"), usd("100"), usd("104"), usd("100"), usd("105")]
+    );
+    let spans: Vec<_> = stored
+        .spans
+        .iter()
+        .map(|(p, s)| (p.as_str(), s.start, s.end))
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            ("upstream", day(1).timestamp(), day(2).timestamp()),
+            ("upstream", day(3).timestamp(), day(5).timestamp()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn store_serves_history_as_a_connector_after_reopening() {
+    let (_dir, path) = db();
+    {
+        let upstream = Arc::new(Upstream::default());
+        upstream.set_days(4, "104");
+        let borsa = Borsa::builder().with_connector(upstream).build().unwrap();
+        let store = HistoryStore::open(&path).unwrap();
+        store.sync(&borsa, &[inst()], Interval::D1).await;
+    }
+
+    let store = Arc::new(HistoryStore::open(&path).unwrap());
+    assert_eq!(store.series().unwrap(), [(inst(), Interval::D1)]);
+    assert_eq!(
+        store
+            .as_history_provider()
+            .unwrap()
+            .supported_history_intervals(AssetKind::Equity),
+        [Interval::D1]
+    );
+
+    // Ranges count back from now, so a short one misses the 2024 series.
+    let recent = HistoryRequest::try_from_range(Range::D5, Interval::D1).unwrap();
+    let history = store
+        .as_history_provider()
+        .unwrap()
+        .history(&inst(), recent)
+        .await
+        .unwrap();
+    assert!(history.candles.is_empty());
+
+    let borsa = Borsa::builder().with_connector(store).build().unwrap();
+    let all = HistoryRequest::try_from_range(Range::Max, Interval::D1).unwrap();
+    assert_eq!(borsa.history(&inst(), all).await.unwrap().candles.len(), 4);
+    let req = HistoryRequest::try_from_period(day(2), day(4), Interval::D1).unwrap();
+    let history = borsa.history(&inst(), req).await.unwrap();
+    assert_eq!(history.candles.len(), 2);
+    assert_eq!(history.candles[0].ts, day(2));
+}
+
+#[tokio::test]
+async fn sync_routes_around_a_store_registered_in_front_of_upstream() {
+    let upstream = Arc::new(Upstream::default());
+    upstream.set_days(3, "103");
+    let (_dir, path) = db();
+    let store = Arc::new(HistoryStore::open(path).unwrap());
+    let borsa = Borsa::builder()
+        .with_connector(store.clone())
+        .with_connector(upstream.clone())
+        .build()
+        .unwrap();
+
+    store.sync(&borsa, &[inst()], Interval::D1).await;
+    upstream.set_days(5, "105");
+    let second = store.sync(&borsa, &[inst()], Interval::D1).await;
+
+    assert_eq!(*second[0].result.as_ref().unwrap(), 2);
+    assert_eq!(upstream.requests.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn sync_reports_failures_per_instrument() {
+    let upstream = Arc::new(Upstream::default());
+    let borsa = Borsa::builder().with_connector(upstream).build().unwrap();
+    let (_dir, path) = db();
+    let store = HistoryStore::open(path).unwrap();
+
+    // Nothing stored and nothing upstream.
+    let outcomes = store.sync(&borsa, &[inst()], Interval::D1).await;
+    assert_eq!(outcomes.len(), 1);
+    assert!(outcomes[0].result.is_err());
+    assert!(store.get(&inst(), Interval::D1).unwrap().is_none());
+}
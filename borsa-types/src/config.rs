@@ -2,9 +2,13 @@
 
 // no extra prelude imports
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use crate::routing_policy::RoutingPolicy;
+use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use paft::domain::{AssetKind, Exchange};
 use serde::{Deserialize, Serialize};
 
 /// Strategy for selecting among eligible data providers.
@@ -51,7 +55,8 @@ pub enum QuotaConsumptionStrategy {
     /// Each request deducts exactly one unit from the quota budget.
     #[default]
     Unit,
-    /// The caller specifies a weight (units) to deduct per request.
+    /// Each request deducts its capability's weight from [`QuotaConfig::weights`]
+    /// (optionally rescaled per call by the quota middleware's weigher hook).
     /// This allows modeling provider-specific costs.
     Weighted,
     /// Evenly spread requests across hours within the window. This temporarily
@@ -61,7 +66,11 @@ pub enum QuotaConsumptionStrategy {
 }
 
 /// Configuration for a token-like quota budget over a sliding window.
+///
+/// Build it with [`QuotaConfig::new`] and the `with_*` methods; the struct is
+/// `#[non_exhaustive]` so new settings can be added without breaking callers.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct QuotaConfig {
     /// Maximum number of units that may be consumed within a single window.
     pub limit: u64,
@@ -69,6 +78,61 @@ pub struct QuotaConfig {
     pub window: Duration,
     /// Strategy for how requests consume units from the budget.
     pub strategy: QuotaConsumptionStrategy,
+    /// Units charged per call by capability string (e.g. `"history"`) under
+    /// [`QuotaConsumptionStrategy::Weighted`]. Capabilities not listed cost one unit.
+    #[serde(default)]
+    pub weights: HashMap<String, u64>,
+    /// Name of a shared quota pool. Quota middlewares naming the same pool charge one budget
+    /// and must agree on `limit`, `window` and `strategy`; `None` gives the middleware a
+    /// private budget.
+    #[serde(default)]
+    pub pool: Option<String>,
+    /// File backing the named pool so processes on one host share it and its window survives
+    /// restarts. The pool is created on first use in this process; a later quota naming the
+    /// same pool with a different `pool_path` is rejected.
+    #[serde(default)]
+    pub pool_path: Option<PathBuf>,
+}
+
+impl QuotaConfig {
+    /// Budget of `limit` units per `window`, consumed according to `strategy`, with no
+    /// weights and a private pool.
+    #[must_use]
+    pub fn new(limit: u64, window: Duration, strategy: QuotaConsumptionStrategy) -> Self {
+        Self {
+            limit,
+            window,
+            strategy,
+            ..Self::default()
+        }
+    }
+
+    /// Charge `units` per call to `capability` under [`QuotaConsumptionStrategy::Weighted`].
+    #[must_use]
+    pub fn with_weight(mut self, capability: impl Into<String>, units: u64) -> Self {
+        self.weights.insert(capability.into(), units);
+        self
+    }
+
+    /// Share the budget with every quota naming the pool `name`.
+    #[must_use]
+    pub fn with_pool(mut self, name: impl Into<String>) -> Self {
+        self.pool = Some(name.into());
+        self
+    }
+
+    /// Back the named pool by the file at `path` (see [`QuotaConfig::pool_path`]).
+    #[must_use]
+    pub fn with_pool_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.pool_path = Some(path.into());
+        self
+    }
+
+    /// Units charged for one call to `capability` under the weighted strategy.
+    #[must_use]
+    pub fn weight_for(&self, capability: &str) -> u64 {
+        self.weights.get(capability).copied().unwrap_or(1)
+    }
 }
 
 impl Default for QuotaConfig {
@@ -77,6 +141,9 @@ impl Default for QuotaConfig {
             limit: 1000,
             window: Duration::from_secs(60),
             strategy: QuotaConsumptionStrategy::Unit,
+            weights: HashMap::new(),
+            pool: None,
+            pool_path: None,
         }
     }
 }
@@ -92,6 +159,42 @@ pub struct QuotaState {
     pub reset_in: Duration,
 }
 
+/// Configuration for a per-capability circuit breaker around one connector.
+///
+/// A capability's circuit opens after `failure_threshold` consecutive failures, or once
+/// `failure_rate` of the calls seen within `window` failed (with at least `min_calls`
+/// calls). Errors classified as permanent by [`BorsaError::retry_class`](crate::BorsaError::retry_class)
+/// are answers rather than outages and never count.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit. `0` disables the consecutive trigger.
+    pub failure_threshold: u32,
+    /// Failure share in `(0, 1]` within `window` that opens the circuit; `None` disables it.
+    pub failure_rate: Option<f64>,
+    /// Sliding window used for the failure-rate trigger.
+    pub window: Duration,
+    /// Calls required inside `window` before `failure_rate` applies.
+    pub min_calls: u32,
+    /// How long the circuit stays open before admitting probe calls.
+    pub open_duration: Duration,
+    /// Probe calls admitted concurrently while half-open.
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            failure_rate: None,
+            window: Duration::from_secs(60),
+            min_calls: 10,
+            open_duration: Duration::from_secs(30),
+            half_open_probes: 1,
+        }
+    }
+}
+
 /// Exponential backoff configuration for reconnecting streaming sessions.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct BackoffConfig {
@@ -116,8 +219,422 @@ impl Default for BackoffConfig {
     }
 }
 
+/// Retry behaviour for one capability.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Total attempts including the first call; `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay schedule between attempts.
+    pub backoff: BackoffConfig,
+    /// Also retry errors whose `retry_class()` is `Unknown` (e.g. opaque HTTP 5xx).
+    pub retry_unknown: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: BackoffConfig {
+                min_backoff_ms: 200,
+                max_backoff_ms: 5_000,
+                factor: 2,
+                jitter_percent: 20,
+            },
+            retry_unknown: false,
+        }
+    }
+}
+
+/// Configuration for retrying failed calls against the same connector.
+///
+/// Transient errors (see `BorsaError::retry_class`) are retried with jittered exponential
+/// backoff; errors carrying a `reset_in_ms` hint wait for that hint instead, or fail
+/// immediately when it exceeds `max_hint_wait`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Policy for capabilities without an override.
+    pub default: RetryPolicy,
+    /// Per-capability overrides keyed by capability string (e.g. "quote", "history").
+    pub per_capability: HashMap<String, RetryPolicy>,
+    /// Longest `reset_in_ms` hint worth waiting for before retrying.
+    pub max_hint_wait: Duration,
+    /// Retries allowed across all calls within `budget_window`; `None` means unlimited.
+    pub budget: Option<u32>,
+    /// Sliding window for `budget`.
+    pub budget_window: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            default: RetryPolicy::default(),
+            per_capability: HashMap::new(),
+            max_hint_wait: Duration::from_secs(2),
+            budget: None,
+            budget_window: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Policy in effect for the capability string `capability`.
+    #[must_use]
+    pub fn policy_for(&self, capability: &str) -> &RetryPolicy {
+        self.per_capability.get(capability).unwrap_or(&self.default)
+    }
+}
+
+/// Token-bucket rate limit applied to calls against one connector.
+///
+/// Tokens refill at `requests_per_second` up to `burst`. A call without a token waits in
+/// line for up to `max_wait` (and never past the router's provider deadline); interactive
+/// calls are served before bulk ones. A `max_wait` of zero rejects instead of waiting.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Sustained rate at which tokens are added.
+    pub requests_per_second: f64,
+    /// Bucket size: calls that may start back to back after an idle period.
+    pub burst: u32,
+    /// Longest time a call waits for a token before it is rejected.
+    pub max_wait: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 5.0,
+            burst: 5,
+            max_wait: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Adaptive provider ranking driven by observed latency and failure rates.
+///
+/// Each call's latency and outcome is recorded per `(connector, capability)`. Providers are
+/// scored by `latency_percentile` latency plus `failure_rate * provider_timeout` (the time a
+/// failed attempt costs before falling back). Only providers the routing policy leaves
+/// unranked are reordered; explicitly ranked providers keep their order ahead of them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveRankingConfig {
+    /// Samples older than this are discarded.
+    pub window: Duration,
+    /// Age at which a sample's weight halves within `window`.
+    pub half_life: Duration,
+    /// Latency percentile in `(0, 1]` used for scoring (e.g. `0.9` for p90).
+    pub latency_percentile: f64,
+    /// Samples required inside `window` before a provider is reordered.
+    pub min_samples: usize,
+    /// Probability in `[0, 1]` that a call keeps the policy order, so demoted providers
+    /// keep being probed and can recover.
+    pub exploration_rate: f64,
+}
+
+impl Default for AdaptiveRankingConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(3600),
+            half_life: Duration::from_secs(600),
+            latency_percentile: 0.9,
+            min_samples: 5,
+            exploration_rate: 0.05,
+        }
+    }
+}
+
+/// What a stream output does when its consumer falls behind and the channel is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum OverflowPolicy {
+    /// Wait for the consumer to make room. A slow consumer delays everything sharing its source.
+    #[default]
+    Block,
+    /// Discard the update that does not fit.
+    DropNewest,
+    /// Discard the oldest buffered update to make room.
+    DropOldest,
+    /// Keep only the latest buffered update per symbol; candle updates are kept per bar,
+    /// so a final bar is never replaced by the next bar. When the buffer is full of
+    /// distinct keys, the oldest update is discarded.
+    Conflate,
+}
+
+/// What candle synthesis emits for intervals in which no tick arrived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum EmptyIntervals {
+    /// Emit nothing; the series has a gap until the next tick.
+    #[default]
+    Skip,
+    /// Emit a final flat bar at the previous close with zero volume. Intervals in which the
+    /// instrument's exchange is closed throughout, per the stream staleness
+    /// `exchange_hours`, are skipped.
+    CarryForward,
+}
+
+/// What candle synthesis does with a tick whose interval was already finalized.
+///
+/// A tick is late when a later tick or `finalize_after` already closed its bar. While
+/// `stream_enforce_monotonic_timestamps` is on, only the second case can occur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum LateTicks {
+    /// Drop the tick.
+    #[default]
+    Drop,
+    /// Fold the tick into the most recently finalized bar and emit that bar again with
+    /// `is_final` set. Ticks older than that bar are dropped.
+    Revise,
+}
+
+/// How the router builds candles from quote ticks when no connector streams candles natively.
+///
+/// Bars are aligned to interval boundaries in the instrument's exchange timezone, looked up
+/// in `timezones` and falling back to `default_timezone`. The default map covers the major
+/// exchanges of the Americas, Europe, Africa and Asia-Pacific; instruments on other exchanges
+/// are logged once per symbol when they fall back. Tick volumes are treated as cumulative
+/// session volume, so a bar's volume is the increase over the bar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CandleSynthesisConfig {
+    /// Handling of intervals without ticks.
+    pub empty_intervals: EmptyIntervals,
+    /// Handling of ticks for intervals that were already finalized.
+    pub late_ticks: LateTicks,
+    /// Also finalize an open bar once the wall clock passes its end by this much, so quiet
+    /// instruments still get a final bar. With `None`, a bar is finalized only when a tick
+    /// for a later interval arrives or the quote stream ends.
+    pub finalize_after: Option<Duration>,
+    /// Timezone per exchange used to align bars.
+    pub timezones: HashMap<Exchange, Tz>,
+    /// Timezone for instruments without an exchange or with an exchange not in `timezones`.
+    pub default_timezone: Tz,
+}
+
+impl Default for CandleSynthesisConfig {
+    fn default() -> Self {
+        Self {
+            empty_intervals: EmptyIntervals::Skip,
+            late_ticks: LateTicks::Drop,
+            finalize_after: None,
+            timezones: default_exchange_timezones(),
+            default_timezone: Tz::UTC,
+        }
+    }
+}
+
+/// Exchange codes and the timezones their sessions are held in.
+const EXCHANGE_TIMEZONES: &[(&str, Tz)] = {
+    use chrono_tz::{America, Asia, Australia, Europe, Pacific};
+    &[
+        ("NASDAQ", America::New_York),
+        ("NYSE", America::New_York),
+        ("AMEX", America::New_York),
+        ("NYSEARCA", America::New_York),
+        ("BATS", America::New_York),
+        ("OTC", America::New_York),
+        ("CME", America::Chicago),
+        ("CBOT", America::Chicago),
+        ("TSX", America::Toronto),
+        ("TSXV", America::Toronto),
+        ("BMV", America::Mexico_City),
+        ("B3", America::Sao_Paulo),
+        ("LSE", Europe::London),
+        ("XETRA", Europe::Berlin),
+        ("FWB", Europe::Berlin),
+        ("EURONEXT", Europe::Paris),
+        ("SIX", Europe::Zurich),
+        ("BME", Europe::Madrid),
+        ("BIT", Europe::Rome),
+        ("OMX", Europe::Stockholm),
+        ("OSE", Europe::Oslo),
+        ("WSE", Europe::Warsaw),
+        ("TASE", Asia::Jerusalem),
+        ("JSE", chrono_tz::Africa::Johannesburg),
+        ("TSE", Asia::Tokyo),
+        ("HKEX", Asia::Hong_Kong),
+        ("SSE", Asia::Shanghai),
+        ("SZSE", Asia::Shanghai),
+        ("KRX", Asia::Seoul),
+        ("TWSE", Asia::Taipei),
+        ("SGX", Asia::Singapore),
+        ("NSE", Asia::Kolkata),
+        ("BSE", Asia::Kolkata),
+        ("ASX", Australia::Sydney),
+        ("NZX", Pacific::Auckland),
+    ]
+};
+
+/// Timezones of the exchanges in [`EXCHANGE_TIMEZONES`] that parse as an [`Exchange`].
+fn default_exchange_timezones() -> HashMap<Exchange, Tz> {
+    EXCHANGE_TIMEZONES
+        .iter()
+        .filter_map(|(code, tz)| Some((Exchange::try_from_str(code).ok()?, *tz)))
+        .collect()
+}
+
+impl CandleSynthesisConfig {
+    /// Timezone used to align bars on `exchange`.
+    #[must_use]
+    pub fn timezone_for(&self, exchange: Option<&Exchange>) -> Tz {
+        exchange
+            .and_then(|ex| self.timezones.get(ex))
+            .copied()
+            .unwrap_or(self.default_timezone)
+    }
+}
+
+/// Weekly trading hours of a market in its local timezone.
+///
+/// A session opens at `open` on each of `weekdays` and closes at `close`. When `close` is not
+/// after `open`, the session runs overnight and closes on the following day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradingHours {
+    /// Timezone of `open` and `close`.
+    pub timezone: Tz,
+    /// Local time the session opens.
+    pub open: NaiveTime,
+    /// Local time the session closes.
+    pub close: NaiveTime,
+    /// Days on which a session opens.
+    pub weekdays: Vec<Weekday>,
+}
+
+impl TradingHours {
+    /// Hours from `open` to `close` in `timezone`, Monday to Friday.
+    #[must_use]
+    pub fn monday_to_friday(timezone: Tz, open: NaiveTime, close: NaiveTime) -> Self {
+        Self {
+            timezone,
+            open,
+            close,
+            weekdays: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+        }
+    }
+
+    /// Whether a session is open at `at`.
+    #[must_use]
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(&self.timezone);
+        let (day, time) = (local.weekday(), local.time());
+        if self.open < self.close {
+            return self.weekdays.contains(&day) && time >= self.open && time < self.close;
+        }
+        (self.weekdays.contains(&day) && time >= self.open)
+            || (self.weekdays.contains(&day.pred()) && time < self.close)
+    }
+
+    /// Whether a session is open at any point in `[start, end)`.
+    #[must_use]
+    pub fn is_open_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        if start >= end {
+            return false;
+        }
+        if self.is_open(start) {
+            return true;
+        }
+        // Closed at `start`, so a session has to open inside the range.
+        let last = end.with_timezone(&self.timezone).date_naive();
+        start
+            .with_timezone(&self.timezone)
+            .date_naive()
+            .iter_days()
+            .take_while(|day| *day <= last)
+            .filter(|day| self.weekdays.contains(&day.weekday()))
+            .filter_map(|day| {
+                self.timezone
+                    .from_local_datetime(&day.and_time(self.open))
+                    .earliest()
+            })
+            .any(|open| open > start && open < end)
+    }
+}
+
+/// Staleness thresholds for the stream sessions of one asset kind.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StalenessThresholds {
+    /// A session is stale for a symbol once it delivered nothing for that symbol for this long.
+    pub symbol: Option<Duration>,
+    /// A session is stale for all its symbols once it delivered nothing at all for this long.
+    pub session: Option<Duration>,
+    /// Only count time while these hours are open. With `None`, time counts around the clock.
+    pub hours: Option<TradingHours>,
+}
+
+impl StalenessThresholds {
+    /// Whether any threshold is set.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.symbol.is_some() || self.session.is_some()
+    }
+}
+
+/// Detection of stream sessions that stay connected but stop delivering updates.
+///
+/// A stale session is stopped like one that ended and its symbols move to the next eligible
+/// provider. The provider is then passed over for the stale symbols for `hold`, unless no
+/// other provider can stream them, so a flaky feed does not take them back at every backoff
+/// tick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StalenessConfig {
+    /// Thresholds per asset kind. Kinds without an entry are not checked.
+    pub kinds: HashMap<AssetKind, StalenessThresholds>,
+    /// Trading hours per exchange, used instead of the kind's `hours` for instruments listed
+    /// there. Candle synthesis also uses them to skip empty intervals while the exchange is
+    /// closed.
+    pub exchange_hours: HashMap<Exchange, TradingHours>,
+    /// How long a provider is passed over for symbols it let go stale.
+    pub hold: Duration,
+}
+
+impl StalenessConfig {
+    /// Thresholds for instruments of `kind` listed on `exchange`, or `None` if the kind is
+    /// not checked.
+    #[must_use]
+    pub fn thresholds_for(
+        &self,
+        kind: AssetKind,
+        exchange: Option<&Exchange>,
+    ) -> Option<StalenessThresholds> {
+        let mut thresholds = self.kinds.get(&kind)?.clone();
+        if let Some(hours) = exchange.and_then(|ex| self.exchange_hours.get(ex)) {
+            thresholds.hours = Some(hours.clone());
+        }
+        Some(thresholds)
+    }
+}
+
+impl Default for StalenessConfig {
+    fn default() -> Self {
+        Self {
+            kinds: HashMap::new(),
+            exchange_hours: HashMap::new(),
+            hold: Duration::from_secs(60),
+        }
+    }
+}
+
 /// Global configuration for the `Borsa` orchestrator.
+///
+/// Fields missing during deserialization take their [`Default`] values; unknown fields are
+/// rejected so a misspelled setting is not silently ignored.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BorsaConfig {
     /// Unified routing policy controlling provider and exchange ordering.
     ///
@@ -147,6 +664,16 @@ pub struct BorsaConfig {
     /// Equal timestamps are allowed. Enabled by default.
     #[serde(default = "default_true")]
     pub stream_enforce_monotonic_timestamps: bool,
+    /// Optional adaptive reordering of eligible providers. Disabled when `None`.
+    pub adaptive_ranking: Option<AdaptiveRankingConfig>,
+    /// Candle synthesis from quote streams for `stream_candles` without a native provider.
+    pub candle_synthesis: CandleSynthesisConfig,
+    /// Number of stream updates buffered for a consumer that falls behind. Must be at least 1.
+    pub stream_channel_capacity: usize,
+    /// What stream outputs do when the consumer falls behind and the buffer is full.
+    pub stream_overflow: OverflowPolicy,
+    /// Detection of stream sessions that stop delivering updates. Disabled by default.
+    pub stream_staleness: StalenessConfig,
 }
 
 impl Default for BorsaConfig {
@@ -162,6 +689,11 @@ impl Default for BorsaConfig {
             request_timeout: None,
             backoff: None,
             stream_enforce_monotonic_timestamps: true,
+            adaptive_ranking: None,
+            candle_synthesis: CandleSynthesisConfig::default(),
+            stream_channel_capacity: 1024,
+            stream_overflow: OverflowPolicy::default(),
+            stream_staleness: StalenessConfig::default(),
         }
     }
 }
@@ -170,6 +702,40 @@ const fn default_true() -> bool {
     true
 }
 
+/// Storage backend for a per-capability cache.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum CacheBackend {
+    /// In-process memory cache; entries are lost when the process exits.
+    #[default]
+    Memory,
+    /// File-backed cache persisted under `dir` so entries survive restarts.
+    ///
+    /// Positive and negative entries are stored in separate files per connector and capability,
+    /// together with their absolute expiry time.
+    File {
+        /// Directory that holds the cache files; created on first use.
+        dir: PathBuf,
+    },
+}
+
+/// How the cache stores history responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum HistoryCacheMode {
+    /// Cache each request under its exact range or period.
+    #[default]
+    Exact,
+    /// Keep one time-indexed candle series per instrument, interval and request flags.
+    ///
+    /// Requests are served from the cached window where it overlaps and only the missing head
+    /// or tail is fetched upstream. Ranges are measured back from now; `Range::Max` keeps
+    /// using exact-key caching.
+    Series,
+}
+
 /// Configuration for per-capability response caching in middleware.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheConfig {
@@ -200,6 +766,17 @@ pub struct CacheConfig {
     /// Per-capability overrides for negative TTLs in milliseconds.
     #[serde(default)]
     pub per_capability_negative_ttl_ms: HashMap<String, u64>,
+
+    /// Default storage backend for capabilities without an override.
+    #[serde(default)]
+    pub default_backend: CacheBackend,
+    /// Per-capability backend overrides keyed by capability string.
+    #[serde(default)]
+    pub per_capability_backend: HashMap<String, CacheBackend>,
+
+    /// Storage layout for history responses.
+    #[serde(default)]
+    pub history_mode: HistoryCacheMode,
 }
 
 impl Default for CacheConfig {
@@ -214,6 +791,9 @@ impl Default for CacheConfig {
             // Short negative cache by default to avoid quota hammering on permanent failures
             default_negative_ttl_ms: default_negative_ttl_ms(),
             per_capability_negative_ttl_ms: HashMap::new(),
+            default_backend: CacheBackend::Memory,
+            per_capability_backend: HashMap::new(),
+            history_mode: HistoryCacheMode::Exact,
         }
     }
 }
@@ -349,6 +929,14 @@ impl CacheConfig {
             .unwrap_or(self.default_max_entries)
     }
 
+    /// Returns the storage backend for a capability, falling back to `default_backend`.
+    #[must_use]
+    pub fn backend_for(&self, cap: crate::Capability) -> &CacheBackend {
+        self.per_capability_backend
+            .get(cap.as_str())
+            .unwrap_or(&self.default_backend)
+    }
+
     /// Returns the negative TTL for a capability, or `None` if disabled (TTL == 0).
     #[must_use]
     pub fn negative_ttl_for(&self, cap: crate::Capability) -> Option<Duration> {
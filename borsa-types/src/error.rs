@@ -108,12 +108,30 @@ pub enum BorsaError {
         reset_in_ms: u64,
     },
 
+    /// A connector's circuit breaker is open for this capability; retry after `reset_in_ms`.
+    #[error("circuit open for {capability}: reset_in_ms={reset_in_ms}")]
+    CircuitOpen {
+        /// Capability label whose circuit is open.
+        capability: String,
+        /// Milliseconds until the circuit admits a probe call.
+        reset_in_ms: u64,
+    },
+
     /// Middleware stack configuration is invalid (missing dependencies, wrong order, etc.).
     #[error("invalid middleware stack: {message}")]
     InvalidMiddlewareStack {
         /// Human-readable description of the validation failure.
         message: String,
     },
+
+    /// A declarative configuration failed to load or validate.
+    #[error("invalid config at {path}: {message}")]
+    InvalidConfig {
+        /// Location of the offending value, e.g. `connectors[1].middleware.layers[0].name`.
+        path: String,
+        /// Human-readable description of the failure.
+        message: String,
+    },
 }
 
 impl BorsaError {
@@ -137,6 +155,14 @@ impl BorsaError {
         Self::NotFound { what: what.into() }
     }
 
+    /// Helper: build an `InvalidConfig` error for the value at `path`.
+    pub fn invalid_config(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::InvalidConfig {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
     /// Helper: build a `ProviderTimeout` error.
     pub fn provider_timeout(connector: impl Into<String>, capability: impl Into<String>) -> Self {
         Self::ProviderTimeout {
@@ -187,6 +213,7 @@ impl BorsaError {
             | Self::StrictSymbolsRejected { .. }
             | Self::InvalidArg(_)
             | Self::InvalidMiddlewareStack { .. }
+            | Self::InvalidConfig { .. }
             | Self::InconsistentCurrencyData => RetryClass::Permanent,
 
             // Transient (retriable)
@@ -195,7 +222,8 @@ impl BorsaError {
             | Self::AllProvidersTimedOut { .. }
             | Self::QuotaExceeded { .. }
             | Self::RateLimitExceeded { .. }
-            | Self::TemporarilyBlacklisted { .. } => RetryClass::Transient,
+            | Self::TemporarilyBlacklisted { .. }
+            | Self::CircuitOpen { .. } => RetryClass::Transient,
 
             // Aggregate: any permanent -> Permanent; all transient -> Transient; else Unknown
             Self::AllProvidersFailed(inner) => {
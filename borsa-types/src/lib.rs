@@ -9,12 +9,16 @@ mod error;
 mod middleware;
 mod reports;
 pub mod routing_policy;
+mod setup;
 
 pub use attribution::{Attribution, Span};
 pub use capability::Capability;
 pub use config::{
-    BackoffConfig, BorsaConfig, CacheConfig, FetchStrategy, MergeStrategy, QuotaConfig,
-    QuotaConsumptionStrategy, QuotaState, Resampling,
+    AdaptiveRankingConfig, BackoffConfig, BorsaConfig, CacheBackend, CacheConfig,
+    CandleSynthesisConfig, CircuitBreakerConfig, EmptyIntervals, FetchStrategy, HistoryCacheMode,
+    LateTicks, MergeStrategy, OverflowPolicy, QuotaConfig, QuotaConsumptionStrategy, QuotaState,
+    RateLimitConfig, Resampling, RetryConfig, RetryPolicy, StalenessConfig, StalenessThresholds,
+    TradingHours,
 };
 pub use connector::ConnectorKey;
 pub use error::BorsaError;
@@ -23,3 +27,4 @@ pub use reports::{DownloadReport, InfoReport, SearchReport};
 pub use routing_policy::{
     Preference, RoutingContext, RoutingPolicy, RoutingPolicyBuilder, ScopeKey,
 };
+pub use setup::{BorsaSetup, ConnectorSpec};
@@ -10,8 +10,8 @@
 //!   Rules are matched against a [`RoutingContext`]. When multiple rules match, the
 //!   one with the highest [specificity](Selector::specificity) wins (i.e., the
 //!   rule with more populated selector fields). Ties are broken by preferring
-//!   rules that target a symbol, then a kind, then an exchange; if a tie remains,
-//!   the rule defined last wins. A rule can be marked `strict` to exclude any
+//!   rules that target a symbol, then a capability, then a kind, then an exchange;
+//!   if a tie remains, the rule defined last wins. A rule can be marked `strict` to exclude any
 //!   provider that is not explicitly listed by that rule. A global rule applies
 //!   when no more-specific rule matches.
 //! - Exchange preferences: provide an ordering for exchanges and are currently
@@ -27,6 +27,7 @@
 //!   `strict`; they will be placed after listed ones, preserving registration
 //!   order.
 
+use crate::capability::Capability;
 use crate::connector::ConnectorKey;
 use paft::domain::{AssetKind, Exchange, Symbol};
 use serde::de::{SeqAccess, Visitor};
@@ -416,7 +417,7 @@ where
 }
 
 /// Generic selector identifying when a provider rule applies.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Selector {
     /// Optional symbol constraint for a rule. When set, the rule applies only to this symbol.
     pub symbol: Option<Symbol>,
@@ -424,14 +425,20 @@ pub struct Selector {
     pub kind: Option<AssetKind>,
     /// Optional exchange constraint. When set, the rule applies only to this exchange.
     pub exchange: Option<Exchange>,
+    /// Optional capability constraint. When set, the rule applies only to calls for this
+    /// capability (e.g. route `Earnings` to one provider and `Quote` to another).
+    #[serde(default)]
+    pub capability: Option<Capability>,
 }
 
 impl Selector {
-    /// Compute precedence bits for tie-breaking between selectors.
+    /// Compute precedence bits for tie-breaking between selectors, in tie-break order:
+    /// `(symbol, capability, kind, exchange)`.
     #[must_use]
-    pub const fn specificity_bits(&self) -> (u8, u8, u8) {
+    pub const fn specificity_bits(&self) -> (u8, u8, u8, u8) {
         (
             self.symbol.is_some() as u8,
+            self.capability.is_some() as u8,
             self.kind.is_some() as u8,
             self.exchange.is_some() as u8,
         )
@@ -465,7 +472,7 @@ impl ProviderPolicy {
         &'a self,
         ctx: &RoutingContext,
     ) -> Option<(&'a RankedList<ConnectorKey>, bool)> {
-        type SpecBits = (u8, u8, u8, u8);
+        type SpecBits = (u8, u8, u8, u8, u8);
         type BestState<'b> = (&'b RankedList<ConnectorKey>, bool, SpecBits, usize);
         let mut best: Option<BestState<'_>> = None;
         for (idx, r) in self.rules.iter().enumerate() {
@@ -479,9 +486,12 @@ impl ProviderPolicy {
             if s.exchange.is_some() && s.exchange != ctx.exchange {
                 continue;
             }
-            let (sb, kb, eb) = s.specificity_bits();
-            let count = sb + kb + eb;
-            let spec: SpecBits = (count, sb, kb, eb);
+            if s.capability.is_some() && s.capability != ctx.capability {
+                continue;
+            }
+            let (sb, cb, kb, eb) = s.specificity_bits();
+            let count = sb + cb + kb + eb;
+            let spec: SpecBits = (count, sb, cb, kb, eb);
             match best {
                 None => best = Some((&r.list, r.strict, spec, idx)),
                 Some((_, _, bspec, bidx)) => {
@@ -577,14 +587,7 @@ impl ProviderPolicy {
             }
             *global = RankedList::new(&filtered);
             if !missing.is_empty() {
-                unknown.push((
-                    Selector {
-                        symbol: None,
-                        kind: None,
-                        exchange: None,
-                    },
-                    missing,
-                ));
+                unknown.push((Selector::default(), missing));
             }
         }
 
@@ -664,9 +667,8 @@ impl RoutingPolicyBuilder {
     #[must_use]
     pub fn providers_for_kind(mut self, kind: AssetKind, list: &[ConnectorKey]) -> Self {
         let selector = Selector {
-            symbol: None,
             kind: Some(kind),
-            exchange: None,
+            ..Selector::default()
         };
         self.policy.providers.add_rule(selector, list, false);
         self
@@ -680,8 +682,7 @@ impl RoutingPolicyBuilder {
     pub fn providers_for_symbol(mut self, symbol: &Symbol, list: &[ConnectorKey]) -> Self {
         let selector = Selector {
             symbol: Some(symbol.clone()),
-            kind: None,
-            exchange: None,
+            ..Selector::default()
         };
         self.policy.providers.add_rule(selector, list, false);
         self
@@ -695,9 +696,27 @@ impl RoutingPolicyBuilder {
     #[must_use]
     pub fn providers_for_exchange(mut self, exchange: Exchange, list: &[ConnectorKey]) -> Self {
         let selector = Selector {
-            symbol: None,
-            kind: None,
             exchange: Some(exchange),
+            ..Selector::default()
+        };
+        self.policy.providers.add_rule(selector, list, false);
+        self
+    }
+
+    /// Set provider ordering for a specific capability (fallback allowed).
+    ///
+    /// Lets one instrument be served by different providers per endpoint, e.g.
+    /// fundamentals from one connector and quotes from another. Capability rules rank
+    /// above kind and exchange rules of equal specificity.
+    #[must_use]
+    pub fn providers_for_capability(
+        mut self,
+        capability: Capability,
+        list: &[ConnectorKey],
+    ) -> Self {
+        let selector = Selector {
+            capability: Some(capability),
+            ..Selector::default()
         };
         self.policy.providers.add_rule(selector, list, false);
         self
@@ -760,10 +779,12 @@ pub struct RoutingContext<'a> {
     pub kind: Option<AssetKind>,
     /// Optional exchange under consideration.
     pub exchange: Option<Exchange>,
+    /// Capability being routed. Capability-scoped rules only match when this is set.
+    pub capability: Option<Capability>,
 }
 
 impl<'a> RoutingContext<'a> {
-    /// Construct a new context from optional `symbol` and `kind`.
+    /// Construct a new context from optional `symbol`, `kind` and `exchange`.
     #[must_use]
     pub const fn new(
         symbol: Option<&'a Symbol>,
@@ -774,8 +795,16 @@ impl<'a> RoutingContext<'a> {
             symbol,
             kind,
             exchange,
+            capability: None,
         }
     }
+
+    /// Scope the context to `capability`.
+    #[must_use]
+    pub const fn with_capability(mut self, capability: Capability) -> Self {
+        self.capability = Some(capability);
+        self
+    }
 }
 
 impl RoutingPolicy {
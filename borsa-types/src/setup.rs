@@ -0,0 +1,38 @@
+//! Declarative description of a complete `Borsa` instance.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::BorsaConfig;
+use crate::middleware::MiddlewareStack;
+use crate::routing_policy::RoutingPolicy;
+
+/// One connector of a [`BorsaSetup`]: a registered constructor plus its middleware stack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectorSpec {
+    /// Name under which the connector constructor is registered (e.g. "borsa-yfinance").
+    pub name: String,
+    /// Free-form options passed to the connector constructor.
+    #[serde(default)]
+    pub config: serde_json::Value,
+    /// Middleware layers wrapped around the connector, outermost first.
+    #[serde(default)]
+    pub middleware: MiddlewareStack,
+}
+
+/// Connectors, middleware and router settings loadable from a TOML or JSON file.
+///
+/// `borsa` may list any subset of [`BorsaConfig`] fields; omitted fields keep their
+/// defaults. A top-level `routing_policy`, when present, replaces `borsa.routing_policy`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BorsaSetup {
+    /// Connectors in registration order.
+    pub connectors: Vec<ConnectorSpec>,
+    /// Orchestrator settings.
+    #[serde(default)]
+    pub borsa: BorsaConfig,
+    /// Routing policy overriding `borsa.routing_policy`.
+    #[serde(default)]
+    pub routing_policy: Option<RoutingPolicy>,
+}
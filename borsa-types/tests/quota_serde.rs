@@ -2,11 +2,11 @@ use borsa_types::{QuotaConfig, QuotaConsumptionStrategy, QuotaState};
 
 #[test]
 fn quota_config_roundtrip() {
-    let cfg = QuotaConfig {
-        limit: 500,
-        window: std::time::Duration::from_secs(120),
-        strategy: QuotaConsumptionStrategy::Weighted,
-    };
+    let cfg = QuotaConfig::new(
+        500,
+        std::time::Duration::from_secs(120),
+        QuotaConsumptionStrategy::Weighted,
+    );
 
     let json = serde_json::to_string(&cfg).expect("serialize quota config");
     let de: QuotaConfig = serde_json::from_str(&json).expect("deserialize quota config");
@@ -16,6 +16,32 @@ fn quota_config_roundtrip() {
     assert!(matches!(de.strategy, QuotaConsumptionStrategy::Weighted));
 }
 
+#[test]
+fn quota_config_weights_and_pool_roundtrip() {
+    let cfg = QuotaConfig::new(
+        500,
+        std::time::Duration::from_secs(120),
+        QuotaConsumptionStrategy::Weighted,
+    )
+    .with_weight("history", 5)
+    .with_pool("vendor-key");
+
+    let json = serde_json::to_string(&cfg).expect("serialize quota config");
+    let de: QuotaConfig = serde_json::from_str(&json).expect("deserialize quota config");
+
+    assert_eq!(de.weight_for("history"), 5);
+    assert_eq!(de.weight_for("quote"), 1);
+    assert_eq!(de.pool.as_deref(), Some("vendor-key"));
+}
+
+#[test]
+fn quota_config_without_weights_deserializes() {
+    let json = r#"{"limit":10,"window":{"secs":60,"nanos":0},"strategy":"Unit"}"#;
+    let de: QuotaConfig = serde_json::from_str(json).expect("deserialize legacy quota config");
+    assert!(de.weights.is_empty());
+    assert!(de.pool.is_none());
+}
+
 #[test]
 fn quota_state_roundtrip() {
     let st = QuotaState {
@@ -17,6 +17,7 @@ fn policy_fixture() -> RoutingPolicy {
                 symbol: None,
                 kind: Some(AssetKind::Crypto),
                 exchange: None,
+                capability: None,
             },
             &[slow],
             true,
@@ -110,6 +111,7 @@ fn precedence_prefers_more_fields_over_symbol_only() {
                 symbol: None,
                 kind: Some(AssetKind::Equity),
                 exchange: Some(nyse.clone()),
+                capability: None,
             },
             &[slow.clone(), fast.clone()],
             false,
@@ -138,6 +140,56 @@ fn precedence_prefers_more_fields_over_symbol_only() {
     );
 }
 
+#[test]
+fn capability_rules_roundtrip_and_outrank_kind_rules() {
+    use borsa_types::Capability;
+
+    let cheap = ConnectorKey::new("cheap");
+    let paid = ConnectorKey::new("paid");
+
+    let policy = RoutingPolicyBuilder::new()
+        .providers_for_kind(AssetKind::Equity, &[cheap.clone(), paid.clone()])
+        .providers_for_capability(Capability::Earnings, &[paid.clone(), cheap.clone()])
+        .build();
+    let json = serde_json::to_string(&policy).expect("serialize policy");
+    let de: RoutingPolicy = serde_json::from_str(&json).expect("deserialize policy");
+
+    let aapl = Symbol::new("AAPL").unwrap();
+    let rank = |ctx: &RoutingContext, key: &ConnectorKey| {
+        de.providers.provider_rank(ctx, key).expect("eligible").0
+    };
+
+    let earnings = RoutingContext::new(Some(&aapl), Some(AssetKind::Equity), None)
+        .with_capability(Capability::Earnings);
+    assert!(
+        rank(&earnings, &paid) < rank(&earnings, &cheap),
+        "capability rule should beat a kind rule of equal specificity"
+    );
+
+    let quote = RoutingContext::new(Some(&aapl), Some(AssetKind::Equity), None)
+        .with_capability(Capability::Quote);
+    assert!(
+        rank(&quote, &cheap) < rank(&quote, &paid),
+        "capability rule must not match other capabilities"
+    );
+}
+
+#[test]
+fn selector_without_capability_still_deserializes() {
+    use borsa_types::routing_policy::Selector;
+
+    let sel = Selector {
+        kind: Some(AssetKind::Equity),
+        ..Selector::default()
+    };
+    let mut json = serde_json::to_value(&sel).expect("serialize selector");
+    json.as_object_mut()
+        .expect("selector object")
+        .remove("capability");
+    let de: Selector = serde_json::from_value(json).expect("selector without capability");
+    assert_eq!(de, sel);
+}
+
 #[test]
 fn borsa_config_roundtrip_serde() {
     use borsa_types::{BackoffConfig, BorsaConfig, FetchStrategy, MergeStrategy, Resampling};
@@ -157,6 +209,10 @@ fn borsa_config_roundtrip_serde() {
             jitter_percent: 25,
         }),
         stream_enforce_monotonic_timestamps: true,
+        adaptive_ranking: None,
+        stream_channel_capacity: 64,
+        stream_overflow: borsa_types::OverflowPolicy::Conflate,
+        ..BorsaConfig::default()
     };
 
     let json = serde_json::to_string(&cfg).expect("serialize cfg");
@@ -169,6 +225,8 @@ fn borsa_config_roundtrip_serde() {
     assert_eq!(de.merge_history_strategy, MergeStrategy::Fallback);
     assert_eq!(de.provider_timeout.as_secs(), 7);
     assert_eq!(de.request_timeout.unwrap().as_millis(), 1500);
+    assert_eq!(de.stream_channel_capacity, 64);
+    assert_eq!(de.stream_overflow, borsa_types::OverflowPolicy::Conflate);
 
     // Sanity-check provider behavior survives roundtrip
     let fast = ConnectorKey::new("fast");
@@ -0,0 +1,115 @@
+use borsa_types::{StalenessConfig, StalenessThresholds, TradingHours};
+use chrono::{NaiveTime, TimeZone, Utc};
+use paft::domain::{AssetKind, Exchange};
+
+fn time(h: u32, m: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(h, m, 0).unwrap()
+}
+
+#[test]
+fn regular_hours_follow_the_local_clock() {
+    let nyse =
+        TradingHours::monday_to_friday(chrono_tz::America::New_York, time(9, 30), time(16, 0));
+
+    // 2024-06-18 is a Tuesday; New York is UTC-4 in June.
+    assert!(!nyse.is_open(Utc.with_ymd_and_hms(2024, 6, 18, 13, 29, 0).unwrap()));
+    assert!(nyse.is_open(Utc.with_ymd_and_hms(2024, 6, 18, 13, 30, 0).unwrap()));
+    assert!(nyse.is_open(Utc.with_ymd_and_hms(2024, 6, 18, 19, 59, 0).unwrap()));
+    assert!(!nyse.is_open(Utc.with_ymd_and_hms(2024, 6, 18, 20, 0, 0).unwrap()));
+    // Saturday
+    assert!(!nyse.is_open(Utc.with_ymd_and_hms(2024, 6, 22, 15, 0, 0).unwrap()));
+}
+
+#[test]
+fn overnight_hours_close_on_the_next_day() {
+    let overnight = TradingHours::monday_to_friday(chrono_tz::UTC, time(18, 0), time(17, 0));
+
+    // Opens Friday evening and runs into Saturday.
+    assert!(overnight.is_open(Utc.with_ymd_and_hms(2024, 6, 21, 18, 0, 0).unwrap()));
+    assert!(overnight.is_open(Utc.with_ymd_and_hms(2024, 6, 22, 16, 59, 0).unwrap()));
+    assert!(!overnight.is_open(Utc.with_ymd_and_hms(2024, 6, 22, 17, 0, 0).unwrap()));
+    // Nothing opened on Saturday, so Sunday morning is closed.
+    assert!(!overnight.is_open(Utc.with_ymd_and_hms(2024, 6, 23, 10, 0, 0).unwrap()));
+    // The daily break between close and open.
+    assert!(!overnight.is_open(Utc.with_ymd_and_hms(2024, 6, 18, 17, 30, 0).unwrap()));
+}
+
+#[test]
+fn open_between_covers_sessions_opening_inside_the_range() {
+    let nyse =
+        TradingHours::monday_to_friday(chrono_tz::America::New_York, time(9, 30), time(16, 0));
+    let at = |d, h, m| Utc.with_ymd_and_hms(2024, 6, d, h, m, 0).unwrap();
+
+    // 09:00-10:00 on Tuesday: closed at the start, opens at 09:30.
+    assert!(nyse.is_open_between(at(18, 13, 0), at(18, 14, 0)));
+    // 08:00-09:30 ends as the session opens.
+    assert!(!nyse.is_open_between(at(18, 12, 0), at(18, 13, 30)));
+    // Friday close to Monday morning.
+    assert!(!nyse.is_open_between(at(21, 20, 0), at(24, 13, 0)));
+    // A week-long range spans sessions.
+    assert!(nyse.is_open_between(at(22, 0, 0), at(29, 0, 0)));
+}
+
+#[test]
+fn staleness_config_roundtrip() {
+    let cfg = StalenessConfig {
+        kinds: [(
+            AssetKind::Equity,
+            StalenessThresholds {
+                symbol: Some(std::time::Duration::from_secs(30)),
+                session: Some(std::time::Duration::from_secs(10)),
+                hours: Some(TradingHours::monday_to_friday(
+                    chrono_tz::America::New_York,
+                    time(9, 30),
+                    time(16, 0),
+                )),
+            },
+        )]
+        .into_iter()
+        .collect(),
+        exchange_hours: [(
+            Exchange::try_from_str("LSE").unwrap(),
+            TradingHours::monday_to_friday(chrono_tz::Europe::London, time(8, 0), time(16, 30)),
+        )]
+        .into_iter()
+        .collect(),
+        ..StalenessConfig::default()
+    };
+    let json = serde_json::to_string(&cfg).unwrap();
+    let back: StalenessConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, cfg);
+}
+
+#[test]
+fn exchange_hours_replace_the_kind_hours() {
+    let lse = Exchange::try_from_str("LSE").unwrap();
+    let london =
+        TradingHours::monday_to_friday(chrono_tz::Europe::London, time(8, 0), time(16, 30));
+    let cfg = StalenessConfig {
+        kinds: [(
+            AssetKind::Equity,
+            StalenessThresholds {
+                symbol: Some(std::time::Duration::from_secs(30)),
+                hours: Some(TradingHours::monday_to_friday(
+                    chrono_tz::America::New_York,
+                    time(9, 30),
+                    time(16, 0),
+                )),
+                ..StalenessThresholds::default()
+            },
+        )]
+        .into_iter()
+        .collect(),
+        exchange_hours: [(lse.clone(), london.clone())].into_iter().collect(),
+        ..StalenessConfig::default()
+    };
+
+    let on_lse = cfg.thresholds_for(AssetKind::Equity, Some(&lse)).unwrap();
+    assert_eq!(on_lse.hours, Some(london));
+    assert_eq!(on_lse.symbol, Some(std::time::Duration::from_secs(30)));
+    assert_eq!(
+        cfg.thresholds_for(AssetKind::Equity, None).unwrap().hours,
+        cfg.kinds[&AssetKind::Equity].hours
+    );
+    assert!(cfg.thresholds_for(AssetKind::Crypto, Some(&lse)).is_none());
+}
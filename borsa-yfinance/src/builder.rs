@@ -26,12 +26,12 @@ impl YfConnector {
     #[must_use]
     pub fn rate_limited() -> YfConnectorBuilder {
         let raw: Arc<dyn BorsaConnector> = Arc::new(Self::new_default());
-        let cfg = QuotaConfig {
-            // 15 per minute -> ~1 per 4 seconds when evenly spread
-            limit: 15,
-            window: Duration::from_secs(60),
-            strategy: QuotaConsumptionStrategy::EvenSpreadHourly,
-        };
+        // 15 per minute -> ~1 per 4 seconds when evenly spread
+        let cfg = QuotaConfig::new(
+            15,
+            Duration::from_secs(60),
+            QuotaConsumptionStrategy::EvenSpreadHourly,
+        );
         GenericConnectorBuilder::new(raw)
             .with_quota(&cfg)
             .with_blacklist(Duration::from_secs(5 * 60))
@@ -20,16 +20,16 @@ use adapter::{
 };
 use async_trait::async_trait;
 use borsa_core::{
-    AssetKind, BorsaError, HistoryRequest, HistoryResponse, Instrument, Quote, SearchRequest,
-    SearchResponse,
+    AssetKind, BorsaError, ConnectorRegistry, HistoryRequest, HistoryResponse, Instrument, Quote,
+    SearchRequest, SearchResponse,
     connector::{
-        AnalystPriceTargetProvider, BalanceSheetProvider, BorsaConnector, CalendarProvider,
-        CashflowProvider, ConnectorKey, EarningsProvider, EsgProvider, HistoryProvider,
-        IncomeStatementProvider, InsiderRosterHoldersProvider, InsiderTransactionsProvider,
-        InstitutionalHoldersProvider, IsinProvider, MajorHoldersProvider,
-        MutualFundHoldersProvider, NetSharePurchaseActivityProvider, NewsProvider,
-        OptionChainProvider, OptionsExpirationsProvider, ProfileProvider, QuoteProvider,
-        RecommendationsProvider, RecommendationsSummaryProvider, SearchProvider,
+        AnalystPriceTargetProvider, BalanceSheetProvider, BatchQuoteProvider, BorsaConnector,
+        CalendarProvider, CashflowProvider, ConnectorKey, EarningsProvider, EsgProvider,
+        HistoryProvider, IncomeStatementProvider, InsiderRosterHoldersProvider,
+        InsiderTransactionsProvider, InstitutionalHoldersProvider, IsinProvider,
+        MajorHoldersProvider, MutualFundHoldersProvider, NetSharePurchaseActivityProvider,
+        NewsProvider, OptionChainProvider, OptionsExpirationsProvider, ProfileProvider,
+        QuoteProvider, RecommendationsProvider, RecommendationsSummaryProvider, SearchProvider,
         UpgradesDowngradesProvider,
     },
 };
@@ -139,6 +139,28 @@ impl YfConnector {
         Self::from_adapter(&a)
     }
 
+    /// Add a `borsa-yfinance` constructor (using [`YfConnector::new_default`]) to `registry`.
+    ///
+    /// The connector takes no options, so its `config` section must be empty or absent.
+    pub fn register(registry: &mut ConnectorRegistry) {
+        registry.register("borsa-yfinance", Self::from_config);
+    }
+
+    /// Add a `borsa-yfinance` constructor to the process-wide registry.
+    pub fn register_global() {
+        borsa_core::register_connector("borsa-yfinance", Self::from_config);
+    }
+
+    fn from_config(config: &serde_json::Value) -> Result<Arc<dyn BorsaConnector>, BorsaError> {
+        match config.as_object() {
+            Some(map) if !map.is_empty() => Err(BorsaError::InvalidArg(format!(
+                "borsa-yfinance takes no options (got: {})",
+                map.keys().cloned().collect::<Vec<_>>().join(", ")
+            ))),
+            _ => Ok(Arc::new(Self::new_default())),
+        }
+    }
+
     /// Build from an existing `yfinance_rs::YfClient`.
     #[must_use]
     pub fn new_with_client(client: yfinance_rs::YfClient) -> Self {
@@ -237,6 +259,38 @@ impl QuoteProvider for YfConnector {
     }
 }
 
+#[async_trait]
+impl BatchQuoteProvider for YfConnector {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "borsa_yfinance::quotes",
+            skip(self, instruments),
+            fields(count = instruments.len()),
+        )
+    )]
+    async fn quotes(&self, instruments: &[Instrument]) -> Result<Vec<Quote>, BorsaError> {
+        // Non-security instruments are omitted; the router resolves them individually.
+        let symbols: Vec<String> = instruments
+            .iter()
+            .filter_map(|inst| require_security_symbol(inst).ok())
+            .map(|s| s.as_str().to_string())
+            .collect();
+        if symbols.is_empty() {
+            return Ok(vec![]);
+        }
+        self.quotes
+            .fetch(&symbols)
+            .await
+            .map_err(|e| Self::normalize_error(e, "quotes"))
+    }
+
+    fn max_batch_size(&self) -> usize {
+        // Yahoo's quote endpoint accepts comma-separated symbol lists; keep URLs reasonable.
+        100
+    }
+}
+
 #[async_trait]
 impl HistoryProvider for YfConnector {
     #[cfg_attr(
@@ -806,6 +860,10 @@ impl BorsaConnector for YfConnector {
         Some(self as &dyn QuoteProvider)
     }
 
+    fn as_batch_quote_provider(&self) -> Option<&dyn borsa_core::connector::BatchQuoteProvider> {
+        Some(self as &dyn BatchQuoteProvider)
+    }
+
     fn as_stream_provider(&self) -> Option<&dyn borsa_core::connector::StreamProvider> {
         Some(self as &dyn borsa_core::connector::StreamProvider)
     }
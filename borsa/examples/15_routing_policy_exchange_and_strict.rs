@@ -96,6 +96,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 symbol: None,
                 kind: Some(AssetKind::Crypto),
                 exchange: None,
+                capability: None,
             },
             &[slow.key()],
             true, // strict: no fallback beyond listed providers
@@ -11,11 +11,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Wrap the selected connector with a quota-aware middleware.
     // In CI, BORSA_EXAMPLES_USE_MOCK=1 will provide the mock connector via common::get_connector.
     let inner: Arc<dyn BorsaConnector> = common::get_connector();
-    let cfg = QuotaConfig {
-        limit: 1000,
-        window: Duration::from_secs(24 * 60 * 60),
-        strategy: QuotaConsumptionStrategy::Unit,
-    };
+    let cfg = QuotaConfig::new(
+        1000,
+        Duration::from_secs(24 * 60 * 60),
+        QuotaConsumptionStrategy::Unit,
+    );
     let wrapped = Arc::new(QuotaAwareConnector::new(inner, cfg));
 
     let borsa = Borsa::builder().with_connector(wrapped).build()?;
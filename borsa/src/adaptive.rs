@@ -0,0 +1,206 @@
+//! Adaptive provider ranking from observed latency and failure rates.
+//!
+//! Every provider call routed through `Borsa::provider_call_with_timeout` is recorded per
+//! `(connector, capability)`. When ranking is enabled, eligible providers are reordered by
+//! a decay-weighted score without leaving the tier the routing policy placed them in.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use borsa_core::{AdaptiveRankingConfig, BorsaConnector, BorsaError, Capability};
+use rand::Rng;
+
+/// Upper bound on retained samples per `(connector, capability)`.
+const MAX_SAMPLES: usize = 512;
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    latency: Duration,
+    failed: bool,
+}
+
+/// Observed health of one connector for one capability.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProviderHealth {
+    /// Samples currently inside the decay window.
+    pub samples: usize,
+    /// Decay-weighted latency at the configured percentile.
+    pub latency: Duration,
+    /// Decay-weighted share of failed calls in `[0, 1]`.
+    pub failure_rate: f64,
+}
+
+/// Shared latency/failure statistics used to reorder providers.
+#[derive(Debug)]
+pub(crate) struct ProviderStats {
+    cfg: AdaptiveRankingConfig,
+    samples: Mutex<HashMap<(&'static str, Capability), VecDeque<Sample>>>,
+}
+
+impl ProviderStats {
+    pub(crate) fn new(cfg: AdaptiveRankingConfig) -> Self {
+        Self {
+            cfg,
+            samples: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Validate a configuration before building the orchestrator.
+    pub(crate) fn validate(cfg: &AdaptiveRankingConfig) -> Result<(), BorsaError> {
+        if !(cfg.latency_percentile > 0.0 && cfg.latency_percentile <= 1.0) {
+            return Err(BorsaError::InvalidArg(
+                "adaptive ranking latency_percentile must be in (0, 1]".into(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&cfg.exploration_rate) {
+            return Err(BorsaError::InvalidArg(
+                "adaptive ranking exploration_rate must be in [0, 1]".into(),
+            ));
+        }
+        if cfg.window.is_zero() || cfg.half_life.is_zero() {
+            return Err(BorsaError::InvalidArg(
+                "adaptive ranking window and half_life must be non-zero".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Record the outcome of one provider call.
+    ///
+    /// `NotFound`, `Unsupported` and argument errors are answers rather than degradation,
+    /// so they count as successful calls.
+    pub(crate) fn record<T>(
+        &self,
+        connector: &'static str,
+        capability: Capability,
+        latency: Duration,
+        outcome: &Result<T, BorsaError>,
+    ) {
+        let failed = outcome.as_ref().err().is_some_and(counts_as_failure);
+        let now = Instant::now();
+        let mut guard = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = guard.entry((connector, capability)).or_default();
+        if entry.len() == MAX_SAMPLES {
+            entry.pop_front();
+        }
+        entry.push_back(Sample {
+            at: now,
+            latency,
+            failed,
+        });
+        self.prune(entry, now);
+    }
+
+    /// Current health for `(connector, capability)`, or `None` below `min_samples`.
+    pub(crate) fn health(
+        &self,
+        connector: &'static str,
+        capability: Capability,
+    ) -> Option<ProviderHealth> {
+        let now = Instant::now();
+        let mut guard = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = guard.get_mut(&(connector, capability))?;
+        self.prune(entry, now);
+        if entry.is_empty() || entry.len() < self.cfg.min_samples {
+            return None;
+        }
+
+        let half_life = self.cfg.half_life.as_secs_f64();
+        let mut weighted: Vec<(Duration, f64)> = entry
+            .iter()
+            .map(|s| {
+                let age = now.saturating_duration_since(s.at).as_secs_f64();
+                (s.latency, 0.5_f64.powf(age / half_life))
+            })
+            .collect();
+        let total: f64 = weighted.iter().map(|(_, w)| w).sum();
+        let failed: f64 = entry
+            .iter()
+            .zip(&weighted)
+            .filter(|(s, _)| s.failed)
+            .map(|(_, (_, w))| w)
+            .sum();
+
+        weighted.sort_by_key(|(latency, _)| *latency);
+        let target = total * self.cfg.latency_percentile;
+        let mut acc = 0.0;
+        let mut latency = weighted.last().map_or(Duration::ZERO, |(l, _)| *l);
+        for (l, w) in &weighted {
+            acc += w;
+            if acc >= target {
+                latency = *l;
+                break;
+            }
+        }
+
+        Some(ProviderHealth {
+            samples: entry.len(),
+            latency,
+            failure_rate: if total > 0.0 { failed / total } else { 0.0 },
+        })
+    }
+
+    /// Reorder `providers` by score for `capability`.
+    ///
+    /// Providers explicitly ranked by the matching policy rule (`listed[i]`) keep their
+    /// slots, so only unlisted fallbacks swap places among themselves. Providers without
+    /// enough samples keep their slot too. With probability `exploration_rate` the policy
+    /// order is kept as-is.
+    pub(crate) fn reorder(
+        &self,
+        capability: Capability,
+        failure_cost: Duration,
+        providers: &mut [Arc<dyn BorsaConnector>],
+        listed: &[bool],
+    ) {
+        if self.cfg.exploration_rate > 0.0
+            && rand::rng().random_bool(self.cfg.exploration_rate.min(1.0))
+        {
+            return;
+        }
+        let scores: Vec<Option<f64>> = providers
+            .iter()
+            .map(|c| {
+                self.health(c.name(), capability)
+                    .map(|h| h.latency.as_secs_f64() + h.failure_rate * failure_cost.as_secs_f64())
+            })
+            .collect();
+
+        let slots: Vec<(usize, f64)> = scores
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !listed[*i])
+            .filter_map(|(i, s)| s.map(|s| (i, s)))
+            .collect();
+        let mut ranked = slots.clone();
+        ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let moved: Vec<Arc<dyn BorsaConnector>> = ranked
+            .iter()
+            .map(|(i, _)| Arc::clone(&providers[*i]))
+            .collect();
+        for ((slot, _), c) in slots.into_iter().zip(moved) {
+            providers[slot] = c;
+        }
+    }
+
+    fn prune(&self, entry: &mut VecDeque<Sample>, now: Instant) {
+        while entry
+            .front()
+            .is_some_and(|s| now.saturating_duration_since(s.at) > self.cfg.window)
+        {
+            entry.pop_front();
+        }
+    }
+}
+
+fn counts_as_failure(err: &BorsaError) -> bool {
+    match err {
+        BorsaError::Connector { error, .. } => counts_as_failure(error),
+        BorsaError::NotFound { .. }
+        | BorsaError::Unsupported { .. }
+        | BorsaError::InvalidArg(_) => false,
+        _ => true,
+    }
+}
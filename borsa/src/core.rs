@@ -7,13 +7,20 @@ use borsa_core::{
     AssetKind, BorsaConnector, BorsaError, Capability, Instrument, RoutingContext, Symbol,
 };
 use futures::stream::{FuturesUnordered, StreamExt};
-use std::collections::HashSet;
+
+use crate::adaptive::{ProviderHealth, ProviderStats};
+use crate::router::streaming::hub::StreamHub;
+use std::collections::{HashMap, HashSet};
 use std::mem;
+use std::sync::Mutex;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 /// Orchestrator that routes requests across registered providers.
 pub struct Borsa {
     pub(crate) connectors: Vec<Arc<dyn BorsaConnector>>,
     pub(crate) cfg: BorsaConfig,
+    pub(crate) stats: Option<Arc<ProviderStats>>,
+    pub(crate) hub: StreamHub,
 }
 
 /// Builder for constructing a `Borsa` orchestrator with custom configuration.
@@ -59,6 +66,16 @@ impl BorsaBuilder {
         self
     }
 
+    /// Replace the whole orchestrator configuration.
+    ///
+    /// Useful when the configuration is loaded from a file. Modifiers called afterwards
+    /// (e.g. `routing_policy`) override individual fields of `cfg`.
+    #[must_use]
+    pub fn with_config(mut self, cfg: BorsaConfig) -> Self {
+        self.cfg = cfg;
+        self
+    }
+
     /// Set the unified routing policy controlling provider and exchange ordering.
     ///
     /// Semantics:
@@ -182,11 +199,86 @@ impl BorsaBuilder {
         self
     }
 
+    /// Reorder eligible providers from observed latency and failure rates.
+    ///
+    /// Behavior and trade-offs:
+    /// - Providers ranked explicitly by the matching routing rule keep that order and stay
+    ///   ahead of unlisted fallbacks; only the unlisted fallbacks are reordered, and strict
+    ///   rules still exclude them.
+    /// - A degraded provider is demoted after `min_samples` calls instead of costing the
+    ///   provider timeout on every request; `exploration_rate` keeps probing it so it can
+    ///   recover once healthy.
+    /// - Ordering becomes non-deterministic, which can make behavior harder to reproduce.
+    #[must_use]
+    pub const fn adaptive_ranking(mut self, cfg: borsa_core::AdaptiveRankingConfig) -> Self {
+        self.cfg.adaptive_ranking = Some(cfg);
+        self
+    }
+
+    /// Set how many stream updates are buffered for a consumer that falls behind.
+    ///
+    /// Behavior and trade-offs:
+    /// - Applies to every stream output, including each consumer of a shared stream.
+    /// - A larger buffer absorbs longer stalls but delivers older data after one.
+    #[must_use]
+    pub const fn stream_channel_capacity(mut self, capacity: usize) -> Self {
+        self.cfg.stream_channel_capacity = capacity;
+        self
+    }
+
+    /// Choose what stream outputs do once a consumer's buffer is full.
+    ///
+    /// Behavior and trade-offs:
+    /// - `Block` (default) loses nothing, but a slow consumer stalls the provider sessions and
+    ///   with them every symbol of the stream.
+    /// - `DropNewest` and `DropOldest` keep sessions running at the cost of gaps.
+    /// - `Conflate` keeps the latest update per symbol, suited to displays that only need
+    ///   current prices. Discarded updates are reported as `StreamEvent::UpdatesDropped`.
+    #[must_use]
+    pub const fn stream_overflow(mut self, policy: borsa_core::OverflowPolicy) -> Self {
+        self.cfg.stream_overflow = policy;
+        self
+    }
+
+    /// Configure how `stream_candles` builds candles from quote ticks.
+    ///
+    /// Behavior and trade-offs:
+    /// - Only used for instruments whose asset kind has no connector streaming candles
+    ///   natively; native candle streams are always preferred.
+    /// - Synthesized bars reflect only the ticks the quote feed delivers, so highs and lows can
+    ///   differ from exchange-reported bars for thinly sampled feeds.
+    /// - Carried-forward bars skip intervals in which the exchange is closed according to the
+    ///   `exchange_hours` of [`Self::stream_staleness`]; without hours for an exchange, a flat
+    ///   bar is emitted for every empty interval, nights and weekends included.
+    #[must_use]
+    pub fn candle_synthesis(mut self, cfg: borsa_core::CandleSynthesisConfig) -> Self {
+        self.cfg.candle_synthesis = cfg;
+        self
+    }
+
+    /// Fail over stream sessions that stay connected but stop delivering updates.
+    ///
+    /// Behavior and trade-offs:
+    /// - Thresholds are set per asset kind; kinds without thresholds are never considered stale.
+    /// - Only the stale symbols move to the next eligible provider; the provider restarts on
+    ///   the rest. It is passed over for the stale symbols for `hold` to avoid flapping.
+    /// - Thresholds shorter than an instrument's quiet periods cause needless failovers; set
+    ///   `hours`, or `exchange_hours` for markets in other timezones, so time outside trading
+    ///   hours does not count.
+    #[must_use]
+    pub fn stream_staleness(mut self, cfg: borsa_core::StalenessConfig) -> Self {
+        self.cfg.stream_staleness = cfg;
+        self
+    }
+
     /// Build the `Borsa` orchestrator.
     ///
     /// # Errors
     /// - `InvalidArg` if no connectors have been registered via `with_connector`.
     /// - `InvalidArg` if the routing policy references unknown connector keys.
+    /// - `InvalidArg` if the adaptive ranking configuration is out of range.
+    /// - `InvalidArg` if the stream channel capacity is zero.
+    /// - `InvalidArg` if a stream staleness threshold is zero.
     pub fn build(mut self) -> Result<Borsa, BorsaError> {
         // Collect registered connector names for validation.
         let known: HashSet<&'static str> = self.connectors.iter().map(|c| c.name()).collect();
@@ -213,9 +305,37 @@ impl BorsaBuilder {
             ));
         }
 
+        if let Some(adaptive) = &self.cfg.adaptive_ranking {
+            ProviderStats::validate(adaptive)?;
+        }
+
+        if self.cfg.stream_channel_capacity == 0 {
+            return Err(BorsaError::InvalidArg(
+                "stream_channel_capacity must be at least 1".to_string(),
+            ));
+        }
+
+        let zero = std::time::Duration::is_zero;
+        if self
+            .cfg
+            .stream_staleness
+            .kinds
+            .values()
+            .any(|t| t.symbol.is_some_and(zero) || t.session.is_some_and(zero))
+        {
+            return Err(BorsaError::InvalidArg(
+                "stream staleness thresholds must be greater than zero".to_string(),
+            ));
+        }
+
         Ok(Borsa {
             connectors: self.connectors,
+            stats: self
+                .cfg
+                .adaptive_ranking
+                .map(|cfg| Arc::new(ProviderStats::new(cfg))),
             cfg: self.cfg,
+            hub: StreamHub::default(),
         })
     }
 }
@@ -238,6 +358,9 @@ pub fn tag_err(connector: &str, e: BorsaError) -> BorsaError {
 /// When the timeout elapses, returns `BorsaError::RequestTimeout` with a generic
 /// capability label; call sites can remap the label if needed.
 /// If `deadline` is `None`, simply awaits the future.
+///
+/// The deadline is also scoped as the [`CallDeadline`](borsa_core::CallDeadline) of the
+/// provider calls inside, so calls cut off by it are recorded as timeouts.
 pub async fn with_request_deadline<T, Fut>(
     deadline: Option<std::time::Duration>,
     fut: Fut,
@@ -248,12 +371,84 @@ where
     let Some(d) = deadline else {
         return Ok(fut.await);
     };
+    let fut = borsa_core::CallDeadline::scope(tokio::time::Instant::now() + d, fut);
     tokio::time::timeout(d, fut)
         .await
         .map_err(|_| BorsaError::request_timeout("request"))
 }
 
-const fn symbol_opt(inst: &Instrument) -> Option<&Symbol> {
+tokio::task_local! {
+    static PROVIDER_CAPS: Arc<ProviderCaps>;
+}
+
+/// Per-connector caps on in-flight provider calls, shared by every call inside a scope.
+///
+/// Bulk operations such as downloads use it so their many requests do not pile onto one
+/// connector. Calls made outside a scope are not capped.
+pub(crate) struct ProviderCaps {
+    per_provider: usize,
+    slots: Mutex<HashMap<&'static str, Arc<Semaphore>>>,
+}
+
+impl ProviderCaps {
+    pub(crate) fn new(per_provider: usize) -> Self {
+        Self {
+            per_provider,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `fut` with the provider calls it makes capped by `caps`.
+    pub(crate) async fn scope<F: Future>(caps: Arc<Self>, fut: F) -> F::Output {
+        PROVIDER_CAPS.scope(caps, fut).await
+    }
+
+    /// Wait for a free slot on `connector` when the current task runs inside a scope.
+    /// The slot is held until the returned permit is dropped.
+    async fn acquire(connector: &'static str) -> Option<OwnedSemaphorePermit> {
+        let semaphore = PROVIDER_CAPS
+            .try_with(|caps| {
+                let mut map = caps.slots.lock().expect("mutex poisoned");
+                Arc::clone(
+                    map.entry(connector)
+                        .or_insert_with(|| Arc::new(Semaphore::new(caps.per_provider))),
+                )
+            })
+            .ok()?;
+        semaphore.acquire_owned().await.ok()
+    }
+}
+
+/// Records a timeout sample for a provider call dropped after its deadline.
+struct TimeoutGuard<'a> {
+    stats: Option<&'a ProviderStats>,
+    connector_name: &'static str,
+    capability: Capability,
+    started: tokio::time::Instant,
+    deadline: tokio::time::Instant,
+}
+
+impl Drop for TimeoutGuard<'_> {
+    fn drop(&mut self) {
+        let Some(stats) = self.stats else {
+            return;
+        };
+        if tokio::time::Instant::now() >= self.deadline {
+            let timeout: Result<(), BorsaError> = Err(BorsaError::provider_timeout(
+                self.connector_name,
+                self.capability.to_string(),
+            ));
+            stats.record(
+                self.connector_name,
+                self.capability,
+                self.started.elapsed(),
+                &timeout,
+            );
+        }
+    }
+}
+
+pub(crate) const fn symbol_opt(inst: &Instrument) -> Option<&Symbol> {
     match inst.id() {
         borsa_core::IdentifierScheme::Security(sec) => Some(&sec.symbol),
         borsa_core::IdentifierScheme::Prediction(_) => None,
@@ -344,6 +539,7 @@ impl Borsa {
         )
     )]
     pub(crate) async fn provider_call_with_timeout<T, Fut>(
+        stats: Option<&ProviderStats>,
         connector_name: &'static str,
         capability: Capability,
         timeout: std::time::Duration,
@@ -352,12 +548,51 @@ impl Borsa {
     where
         Fut: core::future::Future<Output = Result<T, BorsaError>>,
     {
-        (tokio::time::timeout(timeout, fut).await).unwrap_or_else(|_| {
+        // Waiting for a capped connector's slot does not count toward the timeout.
+        let _slot = ProviderCaps::acquire(connector_name).await;
+        let started = tokio::time::Instant::now();
+        // A request deadline that fires first drops this call; the guard still records it.
+        let deadline = borsa_core::CallDeadline::current()
+            .map_or(started + timeout, |outer| outer.min(started + timeout));
+        let guard = TimeoutGuard {
+            stats,
+            connector_name,
+            capability,
+            started,
+            deadline,
+        };
+        // Let waiting middleware (e.g. rate limiters) see when this call will be abandoned.
+        let fut = borsa_core::CallDeadline::scope(started + timeout, fut);
+        let res = (tokio::time::timeout(timeout, fut).await).unwrap_or_else(|_| {
             Err(BorsaError::provider_timeout(
                 connector_name,
                 capability.to_string(),
             ))
-        })
+        });
+        std::mem::forget(guard);
+        if let Some(stats) = stats {
+            stats.record(connector_name, capability, started.elapsed(), &res);
+        }
+        res
+    }
+
+    /// Observed latency and failure rate of `connector` for `capability`.
+    ///
+    /// Returns `None` when adaptive ranking is disabled, the connector is not registered,
+    /// or fewer than `min_samples` calls fall inside the decay window.
+    #[must_use]
+    pub fn provider_health(
+        &self,
+        connector: &str,
+        capability: Capability,
+    ) -> Option<ProviderHealth> {
+        let stats = self.stats.as_ref()?;
+        let name = self
+            .connectors
+            .iter()
+            .map(|c| c.name())
+            .find(|n| *n == connector)?;
+        stats.health(name, capability)
     }
     /// Start building a new `Borsa` instance.
     ///
@@ -421,20 +656,74 @@ impl Borsa {
                 .routing_policy
                 .provider_sort_key(ctx, &key, *orig_i)
         });
-        out.into_iter().map(|(_, c)| c).collect()
+        let mut ordered: Vec<Arc<dyn BorsaConnector>> = out.into_iter().map(|(_, c)| c).collect();
+        if let (Some(stats), Some(capability)) = (&self.stats, ctx.capability) {
+            let listed: Vec<bool> = ordered
+                .iter()
+                .map(|c| {
+                    self.cfg
+                        .routing_policy
+                        .providers
+                        .provider_rank(ctx, &c.key())
+                        .is_some_and(|(rank, _)| rank != usize::MAX)
+                })
+                .collect();
+            stats.reorder(capability, self.cfg.provider_timeout, &mut ordered, &listed);
+        }
+        ordered
+    }
+
+    /// Detached copy of the router for long-lived background tasks.
+    pub(crate) fn snapshot(&self) -> Arc<Self> {
+        Arc::new(Self {
+            connectors: self.connectors.clone(),
+            cfg: self.cfg.clone(),
+            stats: self.stats.clone(),
+            // Snapshots run inside stream tasks; sharing the hub would keep it alive from them.
+            hub: StreamHub::default(),
+        })
     }
 
-    pub(crate) fn ordered(&self, inst: &Instrument) -> Vec<Arc<dyn BorsaConnector>> {
+    /// Copy of the router without the connectors named `name`.
+    ///
+    /// The copy shares configuration and provider statistics but not live streams. A
+    /// component registered as a connector uses it to route through everything but itself,
+    /// e.g. a history store syncing through the router that also serves it.
+    #[must_use]
+    pub fn without_connector(&self, name: &str) -> Self {
+        Self {
+            connectors: self
+                .connectors
+                .iter()
+                .filter(|c| c.name() != name)
+                .cloned()
+                .collect(),
+            cfg: self.cfg.clone(),
+            stats: self.stats.clone(),
+            hub: StreamHub::default(),
+        }
+    }
+
+    pub(crate) fn ordered(
+        &self,
+        inst: &Instrument,
+        capability: Capability,
+    ) -> Vec<Arc<dyn BorsaConnector>> {
         let exch_opt: Option<borsa_core::Exchange> = match inst.id() {
             borsa_core::IdentifierScheme::Security(sec) => sec.exchange.clone(),
             borsa_core::IdentifierScheme::Prediction(_) => None,
         };
-        let ctx = RoutingContext::new(symbol_opt(inst), Some(*inst.kind()), exch_opt);
+        let ctx = RoutingContext::new(symbol_opt(inst), Some(*inst.kind()), exch_opt)
+            .with_capability(capability);
         self.ordered_for_context(&ctx)
     }
 
-    pub(crate) fn ordered_for_kind(&self, kind: Option<AssetKind>) -> Vec<Arc<dyn BorsaConnector>> {
-        let ctx = RoutingContext::new(None, kind, None);
+    pub(crate) fn ordered_for_kind(
+        &self,
+        kind: Option<AssetKind>,
+        capability: Capability,
+    ) -> Vec<Arc<dyn BorsaConnector>> {
+        let ctx = RoutingContext::new(None, kind, None).with_capability(capability);
         self.ordered_for_context(&ctx)
     }
 
@@ -512,10 +801,11 @@ impl Borsa {
         let mut errors: Vec<BorsaError> = Vec::new();
         let mut _all_not_found = true;
 
-        for c in self.ordered(inst) {
+        for c in self.ordered(inst, capability_label) {
             if let Some(fut) = call(c.clone(), inst.clone()) {
                 attempted_any = true;
                 match Self::provider_call_with_timeout(
+                    self.stats.as_deref(),
                     c.name(),
                     capability_label,
                     self.cfg.provider_timeout,
@@ -571,15 +861,22 @@ impl Borsa {
     {
         let mut futs = FuturesUnordered::new();
         let mut attempted_any = false;
-        for c in self.ordered(inst) {
+        for c in self.ordered(inst, capability_label) {
             if let Some(fut) = call(c.clone(), inst.clone()) {
                 let name = c.name();
                 let timeout = self.cfg.provider_timeout;
+                let stats = self.stats.as_deref();
                 futs.push(async move {
                     (
                         name,
-                        Self::provider_call_with_timeout(name, capability_label, timeout, fut)
-                            .await,
+                        Self::provider_call_with_timeout(
+                            stats,
+                            name,
+                            capability_label,
+                            timeout,
+                            fut,
+                        )
+                        .await,
                     )
                 });
                 attempted_any = true;
@@ -0,0 +1,651 @@
+//! Writers and readers for history and download results in CSV, Parquet and Arrow IPC.
+//!
+//! Every format uses the same long table, one row per record, so files from different
+//! formats (and from single histories or whole downloads) can be concatenated:
+//!
+//! | column | type | content |
+//! |---|---|---|
+//! | `instrument` | string | JSON-encoded [`Instrument`], the lossless identifier |
+//! | `symbol` | string? | ticker, when the instrument has one |
+//! | `record` | string | `meta`, `candle`, `dividend`, `split`, `capital_gain` or `span` |
+//! | `ts` | i64 | seconds since the Unix epoch (span start for `span` rows) |
+//! | `open`, `high`, `low`, `close`, `close_unadj` | f64? | candle prices |
+//! | `volume` | u64? | candle volume |
+//! | `amount` | f64? | dividend amount or capital gain |
+//! | `numerator`, `denominator` | u32? | split ratio |
+//! | `currency` | string? | currency code of the row's prices or amount |
+//! | `adjusted` | bool | [`HistoryResponse::adjusted`] of the instrument |
+//! | `timezone`, `utc_offset_seconds` | string?, i32? | [`HistoryMeta`] of the instrument |
+//! | `provider` | string? | connector that supplied a candle, or the connector of a span |
+//! | `span_end` | i64? | inclusive span end for `span` rows |
+//!
+//! Each instrument starts with one `meta` row, so histories without candles round-trip.
+//! When an [`Attribution`] is written, its spans are kept as `span` rows and each candle's
+//! `provider` names the connector whose span covers it.
+//!
+//! Prices and amounts are `Float64` so the tables can be analysed without casting. Readers
+//! turn each float back into the shortest decimal that prints it, which restores prices
+//! and amounts of up to 15 significant digits exactly.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+
+use borsa_core::{
+    Action, Attribution, BorsaError, Candle, Currency, Decimal, DownloadEntry, DownloadResponse,
+    HistoryMeta, HistoryResponse, Instrument, Money, Span,
+};
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+
+/// File format of an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// Apache Parquet.
+    Parquet,
+    /// Arrow IPC file (Feather v2).
+    Ipc,
+}
+
+impl ExportFormat {
+    /// Guess the format from a file extension (`csv`, `parquet`/`pq`, `arrow`/`ipc`/`feather`).
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "csv" => Some(Self::Csv),
+            "parquet" | "pq" => Some(Self::Parquet),
+            "arrow" | "ipc" | "feather" => Some(Self::Ipc),
+            _ => None,
+        }
+    }
+}
+
+/// One instrument's history as stored in an export file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedHistory {
+    /// Instrument the history belongs to.
+    pub instrument: Instrument,
+    /// Candles, actions and metadata.
+    pub history: HistoryResponse,
+    /// Attribution spans as `(connector, span)`; empty when none were written.
+    pub spans: Vec<(String, Span)>,
+}
+
+fn export_err(e: impl std::fmt::Display) -> BorsaError {
+    BorsaError::Other(format!("export: {e}"))
+}
+
+fn data_err(what: &str) -> BorsaError {
+    BorsaError::Data(format!("export: {what}"))
+}
+
+/// Rows of the export table, column by column.
+#[derive(Default)]
+struct Rows {
+    instrument: Vec<String>,
+    symbol: Vec<Option<String>>,
+    record: Vec<&'static str>,
+    ts: Vec<i64>,
+    open: Vec<Option<f64>>,
+    high: Vec<Option<f64>>,
+    low: Vec<Option<f64>>,
+    close: Vec<Option<f64>>,
+    close_unadj: Vec<Option<f64>>,
+    volume: Vec<Option<u64>>,
+    amount: Vec<Option<f64>>,
+    numerator: Vec<Option<u32>>,
+    denominator: Vec<Option<u32>>,
+    currency: Vec<Option<String>>,
+    adjusted: Vec<bool>,
+    timezone: Vec<Option<String>>,
+    utc_offset_seconds: Vec<Option<i32>>,
+    provider: Vec<Option<String>>,
+    span_end: Vec<Option<i64>>,
+}
+
+/// Values of one row that are not shared by every row of an instrument.
+#[derive(Default)]
+struct Row {
+    ts: i64,
+    prices: Option<[f64; 4]>,
+    close_unadj: Option<f64>,
+    volume: Option<u64>,
+    amount: Option<f64>,
+    split: Option<(u32, u32)>,
+    currency: Option<String>,
+    provider: Option<String>,
+    span_end: Option<i64>,
+}
+
+fn number(m: &Money) -> Result<f64, BorsaError> {
+    f64::try_from(m.amount())
+        .map_err(|_| data_err(&format!("amount {} is not a float", m.amount())))
+}
+
+fn provider_at(spans: &[(&'static str, Span)], ts: i64) -> Option<String> {
+    spans
+        .iter()
+        .find(|(_, s)| s.start <= ts && ts <= s.end)
+        .map(|(c, _)| (*c).to_string())
+}
+
+impl Rows {
+    fn push_history(
+        &mut self,
+        instrument: &Instrument,
+        history: &HistoryResponse,
+        attribution: Option<&Attribution>,
+    ) -> Result<(), BorsaError> {
+        let id = serde_json::to_string(instrument).map_err(export_err)?;
+        let symbol = crate::core::symbol_opt(instrument).map(|s| s.as_str().to_string());
+        let meta = history.meta.as_ref();
+        let timezone = meta
+            .and_then(|m| m.timezone)
+            .map(|tz| tz.name().to_string());
+        let utc_offset = meta.and_then(|m| m.utc_offset_seconds);
+        let spans = attribution.map_or(&[][..], |a| a.spans.as_slice());
+
+        let mut push = |record: &'static str, row: Row| {
+            self.instrument.push(id.clone());
+            self.symbol.push(symbol.clone());
+            self.record.push(record);
+            self.ts.push(row.ts);
+            let [open, high, low, close] =
+                row.prices.map_or([None, None, None, None], |p| p.map(Some));
+            self.open.push(open);
+            self.high.push(high);
+            self.low.push(low);
+            self.close.push(close);
+            self.close_unadj.push(row.close_unadj);
+            self.volume.push(row.volume);
+            self.amount.push(row.amount);
+            self.numerator.push(row.split.map(|(n, _)| n));
+            self.denominator.push(row.split.map(|(_, d)| d));
+            self.currency.push(row.currency);
+            self.adjusted.push(history.adjusted);
+            self.timezone.push(timezone.clone());
+            self.utc_offset_seconds.push(utc_offset);
+            self.provider.push(row.provider);
+            self.span_end.push(row.span_end);
+        };
+
+        push("meta", Row::default());
+        for c in &history.candles {
+            let ts = c.ts.timestamp();
+            push(
+                "candle",
+                Row {
+                    ts,
+                    prices: Some([
+                        number(&c.open)?,
+                        number(&c.high)?,
+                        number(&c.low)?,
+                        number(&c.close)?,
+                    ]),
+                    close_unadj: c.close_unadj.as_ref().map(number).transpose()?,
+                    volume: c.volume,
+                    currency: Some(c.close.currency().to_string()),
+                    provider: provider_at(spans, ts),
+                    ..Row::default()
+                },
+            );
+        }
+        for a in &history.actions {
+            let (record, row) = match a {
+                Action::Dividend { ts, amount } => (
+                    "dividend",
+                    Row {
+                        ts: ts.timestamp(),
+                        amount: Some(number(amount)?),
+                        currency: Some(amount.currency().to_string()),
+                        ..Row::default()
+                    },
+                ),
+                Action::Split {
+                    ts,
+                    numerator,
+                    denominator,
+                } => (
+                    "split",
+                    Row {
+                        ts: ts.timestamp(),
+                        split: Some((*numerator, *denominator)),
+                        ..Row::default()
+                    },
+                ),
+                Action::CapitalGain { ts, gain } => (
+                    "capital_gain",
+                    Row {
+                        ts: ts.timestamp(),
+                        amount: Some(number(gain)?),
+                        currency: Some(gain.currency().to_string()),
+                        ..Row::default()
+                    },
+                ),
+            };
+            push(record, row);
+        }
+        for (connector, span) in spans {
+            push(
+                "span",
+                Row {
+                    ts: span.start,
+                    provider: Some((*connector).to_string()),
+                    span_end: Some(span.end),
+                    ..Row::default()
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn into_frame(self) -> Result<DataFrame, BorsaError> {
+        DataFrame::new(vec![
+            Column::new("instrument".into(), self.instrument),
+            Column::new("symbol".into(), self.symbol),
+            Column::new("record".into(), self.record),
+            Column::new("ts".into(), self.ts),
+            Column::new("open".into(), self.open),
+            Column::new("high".into(), self.high),
+            Column::new("low".into(), self.low),
+            Column::new("close".into(), self.close),
+            Column::new("close_unadj".into(), self.close_unadj),
+            Column::new("volume".into(), self.volume),
+            Column::new("amount".into(), self.amount),
+            Column::new("numerator".into(), self.numerator),
+            Column::new("denominator".into(), self.denominator),
+            Column::new("currency".into(), self.currency),
+            Column::new("adjusted".into(), self.adjusted),
+            Column::new("timezone".into(), self.timezone),
+            Column::new("utc_offset_seconds".into(), self.utc_offset_seconds),
+            Column::new("provider".into(), self.provider),
+            Column::new("span_end".into(), self.span_end),
+        ])
+        .map_err(export_err)
+    }
+}
+
+/// Schema of the export table, used to read CSV without type inference.
+fn schema() -> Schema {
+    Schema::from_iter([
+        Field::new("instrument".into(), DataType::String),
+        Field::new("symbol".into(), DataType::String),
+        Field::new("record".into(), DataType::String),
+        Field::new("ts".into(), DataType::Int64),
+        Field::new("open".into(), DataType::Float64),
+        Field::new("high".into(), DataType::Float64),
+        Field::new("low".into(), DataType::Float64),
+        Field::new("close".into(), DataType::Float64),
+        Field::new("close_unadj".into(), DataType::Float64),
+        Field::new("volume".into(), DataType::UInt64),
+        Field::new("amount".into(), DataType::Float64),
+        Field::new("numerator".into(), DataType::UInt32),
+        Field::new("denominator".into(), DataType::UInt32),
+        Field::new("currency".into(), DataType::String),
+        Field::new("adjusted".into(), DataType::Boolean),
+        Field::new("timezone".into(), DataType::String),
+        Field::new("utc_offset_seconds".into(), DataType::Int32),
+        Field::new("provider".into(), DataType::String),
+        Field::new("span_end".into(), DataType::Int64),
+    ])
+}
+
+/// Build the export table for one instrument's history.
+///
+/// # Errors
+/// Returns an error if the table cannot be assembled.
+pub fn history_frame(
+    instrument: &Instrument,
+    history: &HistoryResponse,
+    attribution: Option<&Attribution>,
+) -> Result<DataFrame, BorsaError> {
+    let mut rows = Rows::default();
+    rows.push_history(instrument, history, attribution)?;
+    rows.into_frame()
+}
+
+/// Build the export table for every entry of a download.
+///
+/// # Errors
+/// Returns an error if the table cannot be assembled.
+pub fn download_frame(download: &DownloadResponse) -> Result<DataFrame, BorsaError> {
+    let mut rows = Rows::default();
+    for entry in &download.entries {
+        rows.push_history(&entry.instrument, &entry.history, None)?;
+    }
+    rows.into_frame()
+}
+
+/// Write an export table to `path` in `format`, replacing any existing file.
+///
+/// # Errors
+/// Returns an error if the file cannot be created or written.
+pub fn write_frame(
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+    frame: &mut DataFrame,
+) -> Result<(), BorsaError> {
+    let file = File::create(path.as_ref()).map_err(export_err)?;
+    match format {
+        ExportFormat::Csv => CsvWriter::new(file).finish(frame).map_err(export_err),
+        ExportFormat::Parquet => ParquetWriter::new(file)
+            .finish(frame)
+            .map(|_| ())
+            .map_err(export_err),
+        ExportFormat::Ipc => IpcWriter::new(file).finish(frame).map_err(export_err),
+    }
+}
+
+/// Write one instrument's history, and optionally its attribution, to `path`.
+///
+/// # Errors
+/// Returns an error if the file cannot be created or written.
+pub fn write_history(
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+    instrument: &Instrument,
+    history: &HistoryResponse,
+    attribution: Option<&Attribution>,
+) -> Result<(), BorsaError> {
+    write_frame(
+        path,
+        format,
+        &mut history_frame(instrument, history, attribution)?,
+    )
+}
+
+/// Write every entry of a download to `path`.
+///
+/// # Errors
+/// Returns an error if the file cannot be created or written.
+pub fn write_download(
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+    download: &DownloadResponse,
+) -> Result<(), BorsaError> {
+    write_frame(path, format, &mut download_frame(download)?)
+}
+
+/// Read an export table from `path`.
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed.
+pub fn read_frame(path: impl AsRef<Path>, format: ExportFormat) -> Result<DataFrame, BorsaError> {
+    let path = path.as_ref();
+    match format {
+        ExportFormat::Csv => CsvReadOptions::default()
+            .with_has_header(true)
+            .with_schema(Some(Arc::new(schema())))
+            .try_into_reader_with_file_path(Some(path.to_path_buf()))
+            .and_then(SerReader::finish)
+            .map_err(export_err),
+        ExportFormat::Parquet => {
+            let file = File::open(path).map_err(export_err)?;
+            ParquetReader::new(file).finish().map_err(export_err)
+        }
+        ExportFormat::Ipc => {
+            let file = File::open(path).map_err(export_err)?;
+            IpcReader::new(file).finish().map_err(export_err)
+        }
+    }
+}
+
+/// Typed access to the columns of an export table.
+struct Cols<'a> {
+    instrument: &'a StringChunked,
+    record: &'a StringChunked,
+    ts: &'a Int64Chunked,
+    open: &'a Float64Chunked,
+    high: &'a Float64Chunked,
+    low: &'a Float64Chunked,
+    close: &'a Float64Chunked,
+    close_unadj: &'a Float64Chunked,
+    volume: &'a UInt64Chunked,
+    amount: &'a Float64Chunked,
+    numerator: &'a UInt32Chunked,
+    denominator: &'a UInt32Chunked,
+    currency: &'a StringChunked,
+    adjusted: &'a BooleanChunked,
+    timezone: &'a StringChunked,
+    utc_offset_seconds: &'a Int32Chunked,
+    provider: &'a StringChunked,
+    span_end: &'a Int64Chunked,
+}
+
+impl<'a> Cols<'a> {
+    fn new(df: &'a DataFrame) -> PolarsResult<Self> {
+        Ok(Self {
+            instrument: df.column("instrument")?.str()?,
+            record: df.column("record")?.str()?,
+            ts: df.column("ts")?.i64()?,
+            open: df.column("open")?.f64()?,
+            high: df.column("high")?.f64()?,
+            low: df.column("low")?.f64()?,
+            close: df.column("close")?.f64()?,
+            close_unadj: df.column("close_unadj")?.f64()?,
+            volume: df.column("volume")?.u64()?,
+            amount: df.column("amount")?.f64()?,
+            numerator: df.column("numerator")?.u32()?,
+            denominator: df.column("denominator")?.u32()?,
+            currency: df.column("currency")?.str()?,
+            adjusted: df.column("adjusted")?.bool()?,
+            timezone: df.column("timezone")?.str()?,
+            utc_offset_seconds: df.column("utc_offset_seconds")?.i32()?,
+            provider: df.column("provider")?.str()?,
+            span_end: df.column("span_end")?.i64()?,
+        })
+    }
+
+    fn ts(&self, i: usize) -> Result<DateTime<Utc>, BorsaError> {
+        let secs = self.ts.get(i).ok_or_else(|| data_err("missing ts"))?;
+        DateTime::from_timestamp(secs, 0).ok_or_else(|| data_err("ts out of range"))
+    }
+
+    fn currency(&self, i: usize) -> Result<Currency, BorsaError> {
+        let code = self
+            .currency
+            .get(i)
+            .ok_or_else(|| data_err("missing currency"))?;
+        Currency::from_str(code).map_err(|e| data_err(&format!("currency '{code}': {e}")))
+    }
+
+    fn money(
+        &self,
+        col: &Float64Chunked,
+        i: usize,
+        currency: &Currency,
+    ) -> Result<Option<Money>, BorsaError> {
+        col.get(i)
+            .map(|value| {
+                // `Display` prints the shortest decimal that parses back to the same float.
+                let raw = value.to_string();
+                let amount = Decimal::from_str(&raw)
+                    .map_err(|e| data_err(&format!("decimal '{raw}': {e}")))?;
+                Money::new(amount, currency.clone()).map_err(|e| data_err(&e.to_string()))
+            })
+            .transpose()
+    }
+
+    fn price(
+        &self,
+        col: &Float64Chunked,
+        i: usize,
+        currency: &Currency,
+    ) -> Result<Money, BorsaError> {
+        self.money(col, i, currency)?
+            .ok_or_else(|| data_err("missing candle price"))
+    }
+
+    fn candle(&self, i: usize) -> Result<Candle, BorsaError> {
+        let currency = self.currency(i)?;
+        Ok(Candle {
+            ts: self.ts(i)?,
+            open: self.price(self.open, i, &currency)?,
+            high: self.price(self.high, i, &currency)?,
+            low: self.price(self.low, i, &currency)?,
+            close: self.price(self.close, i, &currency)?,
+            close_unadj: self.money(self.close_unadj, i, &currency)?,
+            volume: self.volume.get(i),
+        })
+    }
+
+    fn action(&self, record: &str, i: usize) -> Result<Action, BorsaError> {
+        let ts = self.ts(i)?;
+        match record {
+            "dividend" | "capital_gain" => {
+                let currency = self.currency(i)?;
+                let amount = self
+                    .money(self.amount, i, &currency)?
+                    .ok_or_else(|| data_err("missing amount"))?;
+                Ok(if record == "dividend" {
+                    Action::Dividend { ts, amount }
+                } else {
+                    Action::CapitalGain { ts, gain: amount }
+                })
+            }
+            _ => Ok(Action::Split {
+                ts,
+                numerator: self
+                    .numerator
+                    .get(i)
+                    .ok_or_else(|| data_err("missing split numerator"))?,
+                denominator: self
+                    .denominator
+                    .get(i)
+                    .ok_or_else(|| data_err("missing split denominator"))?,
+            }),
+        }
+    }
+}
+
+/// Load every instrument's history from an export table, in file order.
+///
+/// # Errors
+/// Returns an error if a column is missing or has the wrong type, or a row cannot be parsed.
+pub fn histories_from_frame(frame: &DataFrame) -> Result<Vec<ExportedHistory>, BorsaError> {
+    let cols = Cols::new(frame).map_err(export_err)?;
+    let mut out: Vec<ExportedHistory> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+
+    for i in 0..frame.height() {
+        let id = cols
+            .instrument
+            .get(i)
+            .ok_or_else(|| data_err("missing instrument"))?;
+        let slot = if let Some(slot) = index.get(id) {
+            *slot
+        } else {
+            let instrument: Instrument = serde_json::from_str(id).map_err(export_err)?;
+            out.push(ExportedHistory {
+                instrument,
+                history: HistoryResponse {
+                    candles: vec![],
+                    actions: vec![],
+                    adjusted: false,
+                    meta: None,
+                },
+                spans: vec![],
+            });
+            index.insert(id, out.len() - 1);
+            out.len() - 1
+        };
+        let entry = &mut out[slot];
+
+        let record = cols
+            .record
+            .get(i)
+            .ok_or_else(|| data_err("missing record"))?;
+        match record {
+            "meta" => {
+                entry.history.adjusted = cols.adjusted.get(i).unwrap_or(false);
+                let timezone = cols
+                    .timezone
+                    .get(i)
+                    .map(|tz| {
+                        tz.parse::<chrono_tz::Tz>()
+                            .map_err(|e| data_err(&format!("timezone '{tz}': {e}")))
+                    })
+                    .transpose()?;
+                let utc_offset_seconds = cols.utc_offset_seconds.get(i);
+                if timezone.is_some() || utc_offset_seconds.is_some() {
+                    entry.history.meta = Some(HistoryMeta {
+                        timezone,
+                        utc_offset_seconds,
+                    });
+                }
+            }
+            "candle" => entry.history.candles.push(cols.candle(i)?),
+            "dividend" | "split" | "capital_gain" => {
+                entry.history.actions.push(cols.action(record, i)?);
+            }
+            "span" => {
+                let connector = cols
+                    .provider
+                    .get(i)
+                    .ok_or_else(|| data_err("missing span provider"))?;
+                let start = cols.ts.get(i).ok_or_else(|| data_err("missing ts"))?;
+                let end = cols
+                    .span_end
+                    .get(i)
+                    .ok_or_else(|| data_err("missing span_end"))?;
+                entry
+                    .spans
+                    .push((connector.to_string(), Span { start, end }));
+            }
+            other => return Err(data_err(&format!("unknown record '{other}'"))),
+        }
+    }
+    Ok(out)
+}
+
+/// Read every instrument's history, with attribution spans, from `path`.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed.
+pub fn read_histories(
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+) -> Result<Vec<ExportedHistory>, BorsaError> {
+    histories_from_frame(&read_frame(path, format)?)
+}
+
+/// Read a file written by [`write_history`] back into its history.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed, or does not hold exactly one
+/// instrument.
+pub fn read_history(
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+) -> Result<HistoryResponse, BorsaError> {
+    let mut histories = read_histories(path, format)?;
+    if histories.len() != 1 {
+        return Err(data_err(&format!(
+            "expected one instrument, found {}",
+            histories.len()
+        )));
+    }
+    Ok(histories.remove(0).history)
+}
+
+/// Read a file written by [`write_download`] back into a download response.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed.
+pub fn read_download(
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+) -> Result<DownloadResponse, BorsaError> {
+    let entries = read_histories(path, format)?
+        .into_iter()
+        .map(|h| DownloadEntry {
+            instrument: h.instrument,
+            history: h.history,
+        })
+        .collect();
+    Ok(DownloadResponse { entries })
+}
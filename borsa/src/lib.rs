@@ -30,17 +30,29 @@
 //! - More examples in `./examples/`.
 #![warn(missing_docs)]
 
+mod adaptive;
 pub(crate) mod core;
+#[cfg(feature = "export")]
+pub mod export;
 mod router;
+mod setup;
 
+pub use adaptive::ProviderHealth;
 pub use borsa_core::{
-    Attribution, BackoffConfig, BorsaConfig, FetchStrategy, MergeStrategy, Resampling, Span,
+    AdaptiveRankingConfig, Attribution, BackoffConfig, BorsaConfig, FetchStrategy, MergeStrategy,
+    Resampling, Span,
 };
 pub use core::{Borsa, BorsaBuilder};
-pub use router::download::DownloadBuilder;
+pub use router::download::{DownloadBuilder, DownloadProgress};
+pub use router::streaming::hub::SharedSubscription;
+pub use router::streaming::subscription::StreamSubscription;
 pub use router::util::{collapse_errors, join_with_deadline};
+pub use setup::{ConfigFormat, parse_setup};
 
-pub use borsa_middleware::{BlacklistMiddleware, CacheMiddleware, QuotaMiddleware};
+pub use borsa_middleware::{
+    BlacklistMiddleware, CacheMiddleware, CircuitBreakerMiddleware, CoalescingMiddleware,
+    QuotaMiddleware, RateLimiterMiddleware, RetryMiddleware,
+};
 
 // Re-export core types for convenience
 pub use borsa_core::{
@@ -51,25 +63,34 @@ pub use borsa_core::{
     BalanceSheetRow,
     BorsaConnector,
     BorsaError,
+    BorsaSetup,
+    CacheBackend,
     CacheConfig,
     Calendar,
+    CallPriority,
     Candle,
+    CandleSynthesisConfig,
     CandleUpdate,
     Capability,
     CashflowRow,
+    CircuitBreakerConfig,
     CompanyProfile,
+    ConnectorRegistry,
+    ConnectorSpec,
     Currency,
     Decimal,
     DownloadEntry,
     DownloadReport,
     DownloadResponse,
     Earnings,
+    EmptyIntervals,
     EsgScores,
     Exchange,
     FastInfo,
     FundKind,
     FundProfile,
     // Request types
+    HistoryCacheMode,
     HistoryRequest,
     HistoryRequestBuilder,
     HistoryResponse,
@@ -83,6 +104,7 @@ pub use borsa_core::{
     Interval,
     Isin,
     IsoCurrency,
+    LateTicks,
     MajorHolder,
     MarketState,
     Money,
@@ -93,6 +115,7 @@ pub use borsa_core::{
     OptionChain,
     OptionContract,
     OptionUpdate,
+    OverflowPolicy,
     PriceTarget,
     Profile,
     QuotaConfig,
@@ -102,12 +125,18 @@ pub use borsa_core::{
     Quote,
     QuoteUpdate,
     Range,
+    RateLimitConfig,
     RecommendationRow,
     RecommendationSummary,
+    RetryConfig,
+    RetryPolicy,
     RoundingStrategy,
     SearchReport,
     SearchRequest,
 
     SearchResult,
+    StalenessConfig,
+    StalenessThresholds,
+    TradingHours,
     UpgradeDowngradeRow,
 };
@@ -0,0 +1,140 @@
+//! On-disk checkpoint for resumable bulk downloads.
+//!
+//! A checkpoint directory holds a `manifest.json` describing the download request and one
+//! JSON record per finished instrument under `records/`, holding either its history or its
+//! error. Records are written to a temporary file and renamed into place, so a crash never
+//! leaves a half-written record behind. File IO runs on the blocking pool so a download
+//! does not stall the runtime while records are read or written.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use borsa_core::{BorsaError, HistoryResponse, Instrument};
+use serde::{Deserialize, Serialize};
+
+const MANIFEST: &str = "manifest.json";
+const RECORDS: &str = "records";
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    instrument: Instrument,
+    result: Result<HistoryResponse, BorsaError>,
+}
+
+pub(crate) struct DownloadCheckpoint {
+    dir: PathBuf,
+}
+
+fn io_error(path: &Path, err: &std::io::Error) -> BorsaError {
+    BorsaError::Other(format!("download checkpoint {}: {err}", path.display()))
+}
+
+/// FNV-1a, stable across builds and platforms unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Run blocking checkpoint IO off the async runtime.
+async fn blocking<T, F>(f: F) -> Result<T, BorsaError>
+where
+    F: FnOnce() -> Result<T, BorsaError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| BorsaError::Other(format!("download checkpoint task failed: {e}")))?
+}
+
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), BorsaError> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents).map_err(|e| io_error(&tmp, &e))?;
+    fs::rename(&tmp, path).map_err(|e| io_error(path, &e))
+}
+
+impl DownloadCheckpoint {
+    /// Open (or create) the checkpoint in `dir` for a download described by `request`.
+    ///
+    /// # Errors
+    /// Returns `InvalidArg` when `dir` already holds a checkpoint for a different request,
+    /// and `Other` when the directory cannot be read or written.
+    pub(crate) async fn open(dir: &Path, request: serde_json::Value) -> Result<Self, BorsaError> {
+        let dir = dir.to_path_buf();
+        blocking(move || Self::open_blocking(dir, &request)).await
+    }
+
+    fn open_blocking(dir: PathBuf, request: &serde_json::Value) -> Result<Self, BorsaError> {
+        let records = dir.join(RECORDS);
+        fs::create_dir_all(&records).map_err(|e| io_error(&records, &e))?;
+
+        let manifest = dir.join(MANIFEST);
+        match fs::read_to_string(&manifest) {
+            Ok(raw) => {
+                let stored: serde_json::Value = serde_json::from_str(&raw).map_err(|e| {
+                    BorsaError::Other(format!("download checkpoint {}: {e}", manifest.display()))
+                })?;
+                if stored != *request {
+                    return Err(BorsaError::InvalidArg(format!(
+                        "download checkpoint {} was created for a different request",
+                        dir.display()
+                    )));
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                write_atomic(&manifest, request.to_string().as_bytes())?;
+            }
+            Err(e) => return Err(io_error(&manifest, &e)),
+        }
+        Ok(Self { dir })
+    }
+
+    fn record_path(&self, instrument: &Instrument) -> PathBuf {
+        let key = instrument.id().unique_key();
+        // Keep names readable while the hash keeps sanitized keys distinct.
+        let readable: String = key
+            .chars()
+            .take(48)
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let name = format!("{readable}-{:016x}.json", fnv1a(key.as_bytes()));
+        self.dir.join(RECORDS).join(name)
+    }
+
+    /// Result recorded for `instrument` by an earlier run, if any.
+    ///
+    /// Unreadable records are ignored so the instrument is fetched again.
+    pub(crate) async fn load(
+        &self,
+        instrument: &Instrument,
+    ) -> Option<Result<HistoryResponse, BorsaError>> {
+        let path = self.record_path(instrument);
+        let record = blocking(move || {
+            let raw = fs::read_to_string(path).ok();
+            Ok(raw.and_then(|raw| serde_json::from_str::<Record>(&raw).ok()))
+        })
+        .await
+        .ok()??;
+        (record.instrument == *instrument).then_some(record.result)
+    }
+
+    /// Record the outcome of fetching `instrument`.
+    ///
+    /// # Errors
+    /// Returns `Other` when the record cannot be serialized or written.
+    pub(crate) async fn save(
+        &self,
+        instrument: &Instrument,
+        result: &Result<HistoryResponse, BorsaError>,
+    ) -> Result<(), BorsaError> {
+        #[derive(Serialize)]
+        struct RecordRef<'a> {
+            instrument: &'a Instrument,
+            result: &'a Result<HistoryResponse, BorsaError>,
+        }
+        let json = serde_json::to_vec(&RecordRef { instrument, result })
+            .map_err(|e| BorsaError::Other(format!("download checkpoint serialize: {e}")))?;
+        let path = self.record_path(instrument);
+        blocking(move || write_atomic(&path, &json)).await
+    }
+}
@@ -1,10 +1,72 @@
 use crate::Borsa;
+use crate::core::ProviderCaps;
+use crate::router::checkpoint::DownloadCheckpoint;
 use borsa_core::{
-    BorsaError, Capability, DownloadEntry, DownloadReport, DownloadResponse, HistoryRequest,
-    HistoryResponse, Instrument, Range,
+    Action, BorsaError, CallPriority, Capability, DownloadEntry, DownloadReport, DownloadResponse,
+    HistoryRequest, HistoryRequestBuilder, HistoryResponse, Instrument, Range, timeseries,
 };
-use chrono::DateTime;
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::stream::{self, Stream, StreamExt};
 use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Progress of a download, reported each time an instrument finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Instruments finished so far, successfully or not.
+    pub completed: usize,
+    /// Instruments among `completed` that failed.
+    pub failed: usize,
+    /// Instruments in the download.
+    pub total: usize,
+}
+
+type ProgressFn<'a> = Arc<dyn Fn(DownloadProgress) + Send + Sync + 'a>;
+
+type Fetched = (usize, Instrument, Result<HistoryResponse, BorsaError>);
+
+/// An instrument to fetch, with the history a checkpoint recorded for it when only its
+/// tail needs refreshing.
+type Pending = (usize, Instrument, Option<HistoryResponse>);
+
+/// Request for the bars from the last recorded candle up to now.
+///
+/// `None` when nothing was recorded, in which case the whole request is fetched again.
+fn tail_request(req: &HistoryRequest, recorded: &HistoryResponse) -> Option<HistoryRequest> {
+    let last = recorded.candles.last()?.ts;
+    // Periods are end-exclusive; include a bar that starts in the current second.
+    let end = Utc::now() + TimeDelta::seconds(1);
+    HistoryRequestBuilder::default()
+        .period(last, end)
+        .interval(req.interval())
+        .include_prepost(req.include_prepost())
+        .include_actions(req.include_actions())
+        .auto_adjust(req.auto_adjust())
+        .keepna(req.keepna())
+        .build()
+        .ok()
+}
+
+/// Extend a recorded range download with its refreshed tail and drop what the range,
+/// measured from now, no longer covers.
+fn refresh_recorded(
+    range: Option<Range>,
+    recorded: HistoryResponse,
+    tail: HistoryResponse,
+) -> Result<HistoryResponse, BorsaError> {
+    // The fresh tail comes first so it replaces the recorded (possibly partial) last bar.
+    let mut history = borsa_core::stitch_history([tail, recorded])?;
+    if let Some(start) = range.and_then(|r| timeseries::window::range_start(r, Utc::now())) {
+        history.candles.retain(|c| c.ts >= start);
+        history.actions.retain(|a| match a {
+            Action::Dividend { ts, .. }
+            | Action::Split { ts, .. }
+            | Action::CapitalGain { ts, .. } => *ts >= start,
+        });
+    }
+    Ok(history)
+}
 
 // Validate that all instruments have unique identity keys (scheme-agnostic).
 fn validate_unique_instruments(insts: &[Instrument]) -> Result<(), BorsaError> {
@@ -33,6 +95,10 @@ pub struct DownloadBuilder<'a> {
     pub(crate) range: Option<Range>,
     pub(crate) period: Option<(i64, i64)>,
     pub(crate) interval: borsa_core::Interval,
+    pub(crate) concurrency: Option<usize>,
+    pub(crate) per_provider: Option<usize>,
+    pub(crate) progress: Option<ProgressFn<'a>>,
+    pub(crate) checkpoint: Option<PathBuf>,
 }
 
 impl<'a> DownloadBuilder<'a> {
@@ -49,6 +115,10 @@ impl<'a> DownloadBuilder<'a> {
             range: Some(Range::M6),
             period: None,
             interval: borsa_core::Interval::D1,
+            concurrency: None,
+            per_provider: None,
+            progress: None,
+            checkpoint: None,
         }
     }
 
@@ -121,20 +191,52 @@ impl<'a> DownloadBuilder<'a> {
         self
     }
 
-    /// Execute the download across eligible providers and aggregate results.
+    /// Limit how many instruments are fetched at once.
     ///
-    /// Behavior and trade-offs:
-    /// - Validates the request and then concurrently fetches per-symbol history using
-    ///   the same merge/resample rules as `Borsa::history_with_attribution`.
-    /// - Populates the returned [`DownloadReport`] with a [`borsa_core::DownloadResponse`]
-    ///   containing per-symbol candles, actions, and metadata keyed by symbol when at
-    ///   least one instrument succeeds.
-    /// - Partial failures populate the `warnings` vector with `{symbol}: {error}` entries
-    ///   without aborting the entire batch.
-    /// # Errors
-    /// Returns an error only if no instruments are specified or if an overall
-    /// request-level timeout elapses.
-    pub async fn run(self) -> Result<DownloadReport, BorsaError> {
+    /// By default every instrument is fetched concurrently, which can flood providers for
+    /// large universes. Values below 1 are treated as 1.
+    #[must_use]
+    pub const fn concurrency(mut self, n: usize) -> Self {
+        self.concurrency = Some(if n == 0 { 1 } else { n });
+        self
+    }
+
+    /// Limit how many calls may be in flight to any single provider during this download.
+    ///
+    /// Applies to every provider call the download makes, including the fan-out of a
+    /// `Deep` history merge. Time spent waiting for a slot does not count toward
+    /// `provider_timeout`. Values below 1 are treated as 1.
+    #[must_use]
+    pub const fn max_in_flight_per_provider(mut self, n: usize) -> Self {
+        self.per_provider = Some(if n == 0 { 1 } else { n });
+        self
+    }
+
+    /// Call `f` with the running totals each time an instrument finishes.
+    #[must_use]
+    pub fn on_progress(mut self, f: impl Fn(DownloadProgress) + Send + Sync + 'a) -> Self {
+        self.progress = Some(Arc::new(f));
+        self
+    }
+
+    /// Record per-instrument results in `dir` so an interrupted [`run`](Self::run) can resume.
+    ///
+    /// Each instrument's history or error is written to `dir` as soon as it finishes. A later
+    /// run with the same directory and request reuses recorded histories and permanent
+    /// failures, fetches again only instruments that are missing or failed with a transient
+    /// error (see [`BorsaError::is_transient`]), and returns the same [`DownloadReport`] an
+    /// uninterrupted run would. Instruments can be added between runs.
+    ///
+    /// Range-based requests end at the time of the run, so histories recorded by an earlier
+    /// range run are refreshed from their last candle up to now and trimmed to the range
+    /// before they are reused. Only `run` supports checkpoints.
+    #[must_use]
+    pub fn checkpoint(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(dir.into());
+        self
+    }
+
+    fn build_request(&self) -> Result<HistoryRequest, BorsaError> {
         if self.instruments.is_empty() {
             return Err(BorsaError::InvalidArg(
                 "no instruments specified for download".into(),
@@ -145,53 +247,236 @@ impl<'a> DownloadBuilder<'a> {
         validate_unique_instruments(&self.instruments)?;
 
         // Build a validated HistoryRequest now; convert timestamp seconds safely.
-        let req: HistoryRequest = if let Some((start, end)) = self.period {
+        if let Some((start, end)) = self.period {
             let start_dt = DateTime::from_timestamp(start, 0).ok_or_else(|| {
                 BorsaError::InvalidArg(format!("invalid start timestamp: {start}"))
             })?;
             let end_dt = DateTime::from_timestamp(end, 0)
                 .ok_or_else(|| BorsaError::InvalidArg(format!("invalid end timestamp: {end}")))?;
-            HistoryRequest::try_from_period(start_dt, end_dt, self.interval)?
+            HistoryRequest::try_from_period(start_dt, end_dt, self.interval)
         } else {
             let range = self.range.unwrap_or(Range::M6);
-            HistoryRequest::try_from_range(range, self.interval)?
-        };
+            HistoryRequest::try_from_range(range, self.interval)
+        }
+    }
 
-        let tasks = self.instruments.iter().map(|inst| {
-            let borsa = self.borsa;
-            let req = req.clone();
-            let inst = inst.clone();
+    /// Fetches of `pending` instruments tagged with their input index, at most `concurrency`
+    /// in flight, yielded in completion order. Progress counts start from `done`.
+    ///
+    /// Instruments with a recorded history fetch only its tail and are stitched onto it.
+    fn fetches(
+        self,
+        req: HistoryRequest,
+        pending: Vec<Pending>,
+        done: DownloadProgress,
+    ) -> impl Stream<Item = Fetched> + 'a {
+        let borsa = self.borsa;
+        let range = req.range();
+        let limit = self.concurrency.unwrap_or(pending.len()).max(1);
+        let caps = self.per_provider.map(|n| Arc::new(ProviderCaps::new(n)));
+        let progress = self.progress.map(|f| (Arc::new(Mutex::new(done)), f));
+
+        let tasks = pending.into_iter().map(move |(idx, instrument, recorded)| {
+            let (req, recorded) = match recorded.and_then(|r| tail_request(&req, &r).zip(Some(r))) {
+                Some((tail, recorded)) => (tail, Some(recorded)),
+                None => (req.clone(), None),
+            };
+            let caps = caps.clone();
+            let progress = progress.clone();
             async move {
-                match borsa.history_with_attribution(&inst, req).await {
-                    Ok((hr, _attr)) => (inst, Ok(hr)),
-                    Err(e) => (inst, Err(e)),
+                let fetch = CallPriority::scope(
+                    CallPriority::Bulk,
+                    borsa.history_with_attribution(&instrument, req),
+                );
+                let result = match caps {
+                    Some(caps) => ProviderCaps::scope(caps, fetch).await,
+                    None => fetch.await,
+                };
+                let result = result.and_then(|(history, attr)| match recorded {
+                    Some(recorded) => Ok((refresh_recorded(range, recorded, history)?, attr)),
+                    None => Ok((history, attr)),
+                });
+                if let Some((done, f)) = progress {
+                    let snapshot = {
+                        let mut done = done.lock().expect("mutex poisoned");
+                        done.completed += 1;
+                        done.failed += usize::from(result.is_err());
+                        *done
+                    };
+                    f(snapshot);
                 }
+                (idx, instrument, result.map(|(history, _attr)| history))
             }
         });
 
-        // Apply optional request-level deadline across the fan-out
-        let joined: Vec<(Instrument, Result<HistoryResponse, BorsaError>)> =
-            match crate::router::util::join_with_deadline(tasks, self.borsa.cfg.request_timeout)
-                .await
-            {
-                Ok(v) => v,
-                Err(_) => {
-                    return Err(BorsaError::request_timeout(
-                        Capability::DownloadHistory.to_string(),
-                    ));
+        stream::iter(tasks).buffer_unordered(limit)
+    }
+
+    /// Request description stored in a checkpoint manifest.
+    fn checkpoint_request(&self) -> serde_json::Value {
+        serde_json::json!({
+            "range": self.range,
+            "period": self.period,
+            "interval": self.interval,
+        })
+    }
+
+    /// Execute the download and yield each instrument's result as soon as it finishes.
+    ///
+    /// Behavior and trade-offs:
+    /// - Fetches with the same rules as [`run`](Self::run), at most `concurrency`
+    ///   instruments at once, so memory stays bounded by the number of fetches in flight
+    ///   and results can be written out before the whole batch completes.
+    /// - Results arrive in completion order, not input order. Failed instruments yield
+    ///   their connector-tagged error without ending the stream.
+    /// - When the request-level timeout (counted from the first poll) elapses, the stream
+    ///   yields one `RequestTimeout` error and ends.
+    ///
+    /// # Errors
+    /// Returns an error if no instruments are specified or the request is invalid.
+    pub fn run_stream(
+        self,
+    ) -> Result<impl Stream<Item = Result<DownloadEntry, BorsaError>> + 'a, BorsaError> {
+        if self.checkpoint.is_some() {
+            return Err(BorsaError::InvalidArg(
+                "checkpointed downloads must be collected with run()".into(),
+            ));
+        }
+        let req = self.build_request()?;
+        let timeout = self.borsa.cfg.request_timeout;
+        let total = self.instruments.len();
+        let pending: Vec<Pending> = self
+            .instruments
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(idx, instrument)| (idx, instrument, None))
+            .collect();
+        let done = DownloadProgress {
+            completed: 0,
+            failed: 0,
+            total,
+        };
+        let results = Box::pin(
+            self.fetches(req, pending, done)
+                .map(|(_, instrument, result)| {
+                    result.map(|history| DownloadEntry {
+                        instrument,
+                        history,
+                    })
+                }),
+        );
+        Ok(stream::unfold(
+            (results, None, false),
+            move |(mut results, mut deadline, finished)| async move {
+                if finished {
+                    return None;
                 }
+                let Some(limit) = timeout else {
+                    return results
+                        .next()
+                        .await
+                        .map(|item| (item, (results, deadline, false)));
+                };
+                let at = *deadline.get_or_insert_with(|| tokio::time::Instant::now() + limit);
+                match tokio::time::timeout_at(at, results.next()).await {
+                    Ok(item) => item.map(|item| (item, (results, deadline, false))),
+                    Err(_) => Some((
+                        Err(BorsaError::request_timeout(
+                            Capability::DownloadHistory.to_string(),
+                        )),
+                        (results, deadline, true),
+                    )),
+                }
+            },
+        ))
+    }
+
+    /// Execute the download across eligible providers and aggregate results.
+    ///
+    /// Behavior and trade-offs:
+    /// - Validates the request and then concurrently fetches per-symbol history using
+    ///   the same merge/resample rules as `Borsa::history_with_attribution`, at most
+    ///   `concurrency` instruments at once. Use [`run_stream`](Self::run_stream) to handle
+    ///   results as they finish instead of holding the whole batch in memory.
+    /// - Populates the returned [`DownloadReport`] with a [`borsa_core::DownloadResponse`]
+    ///   containing per-symbol candles, actions, and metadata keyed by symbol when at
+    ///   least one instrument succeeds.
+    /// - Partial failures populate the `warnings` vector with `{symbol}: {error}` entries
+    ///   without aborting the entire batch.
+    /// - With a [`checkpoint`](Self::checkpoint), results recorded by an earlier run are
+    ///   reused (range histories after refreshing their tail) and every newly finished
+    ///   instrument is recorded before the batch completes.
+    /// # Errors
+    /// Returns an error if no instruments are specified, if an overall request-level
+    /// timeout elapses, or if the checkpoint directory cannot be used.
+    pub async fn run(mut self) -> Result<DownloadReport, BorsaError> {
+        let req = self.build_request()?;
+        let timeout = self.borsa.cfg.request_timeout;
+        let checkpoint = match &self.checkpoint {
+            Some(dir) => Some(DownloadCheckpoint::open(dir, self.checkpoint_request()).await?),
+            None => None,
+        };
+
+        // Reuse recorded results; refetch missing instruments and transient failures, and
+        // refresh the tail of histories recorded for a range that has since moved on.
+        let ranged = req.range().is_some();
+        let total = self.instruments.len();
+        let mut joined: Vec<Fetched> = Vec::with_capacity(total);
+        let mut pending = Vec::new();
+        for (idx, instrument) in std::mem::take(&mut self.instruments)
+            .into_iter()
+            .enumerate()
+        {
+            let recorded = match &checkpoint {
+                Some(c) => c.load(&instrument).await,
+                None => None,
             };
+            match recorded {
+                Some(Ok(history)) if ranged => pending.push((idx, instrument, Some(history))),
+                Some(Err(e)) if e.is_transient() => pending.push((idx, instrument, None)),
+                Some(result) => joined.push((idx, instrument, result)),
+                None => pending.push((idx, instrument, None)),
+            }
+        }
+        let done = DownloadProgress {
+            completed: joined.len(),
+            failed: joined.iter().filter(|(_, _, r)| r.is_err()).count(),
+            total,
+        };
+
+        let mut fetches = std::pin::pin!(self.fetches(req, pending, done));
+        let collect = async {
+            while let Some((idx, instrument, result)) = fetches.next().await {
+                if let Some(checkpoint) = &checkpoint {
+                    checkpoint.save(&instrument, &result).await?;
+                }
+                joined.push((idx, instrument, result));
+            }
+            Ok::<(), BorsaError>(())
+        };
+
+        // Apply optional request-level deadline across the fan-out
+        match crate::core::with_request_deadline(timeout, collect).await {
+            Ok(res) => res?,
+            Err(_) => {
+                return Err(BorsaError::request_timeout(
+                    Capability::DownloadHistory.to_string(),
+                ));
+            }
+        }
+        joined.sort_unstable_by_key(|(idx, _, _)| *idx);
 
         let mut entries: Vec<DownloadEntry> = Vec::new();
         let mut had_success = false;
         let mut warnings: Vec<BorsaError> = Vec::new();
-        for (instrument, result) in joined {
+        for (_, instrument, result) in joined {
             match result {
-                Ok(resp) => {
+                Ok(history) => {
                     had_success = true;
                     entries.push(DownloadEntry {
                         instrument,
-                        history: resp,
+                        history,
                     });
                 }
                 Err(e) => {
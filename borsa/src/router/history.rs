@@ -1,4 +1,5 @@
 use crate::Resampling;
+use crate::adaptive::ProviderStats;
 use crate::{Attribution, Borsa, MergeStrategy, Span};
 use borsa_core::{
     AssetKind, BorsaConnector, BorsaError, Capability, Currency, HistoryRequest,
@@ -131,12 +132,17 @@ impl Borsa {
         provider_timeout: std::time::Duration,
         request_timeout: Option<std::time::Duration>,
     ) -> Result<Vec<HistoryTaskResult>, BorsaError> {
+        let stats = self.stats.as_deref();
         let make_future = || async {
             match self.cfg.merge_history_strategy {
                 MergeStrategy::Deep => {
-                    Ok(Self::parallel_history(eligible, inst, &req_copy, provider_timeout).await)
+                    Ok(
+                        Self::parallel_history(stats, eligible, inst, &req_copy, provider_timeout)
+                            .await,
+                    )
                 }
                 MergeStrategy::Fallback => Ok(Self::sequential_history(
+                    stats,
                     eligible.to_vec(),
                     inst,
                     req_copy,
@@ -389,7 +395,7 @@ impl Borsa {
         &self,
         inst: &Instrument,
     ) -> Result<Vec<IndexedConnector>, BorsaError> {
-        let ordered = self.ordered(inst);
+        let ordered = self.ordered(inst, Capability::History);
         let mut eligible: Vec<(usize, std::sync::Arc<dyn BorsaConnector>)> = Vec::new();
         for (idx, c) in ordered.into_iter().enumerate() {
             if c.supports_kind(*inst.kind()) && c.as_history_provider().is_some() {
@@ -403,13 +409,21 @@ impl Borsa {
     }
 
     async fn parallel_history(
+        stats: Option<&ProviderStats>,
         eligible: &[(usize, Arc<dyn BorsaConnector>)],
         inst: &Instrument,
         req_copy: &HistoryRequest,
         provider_timeout: std::time::Duration,
     ) -> Vec<HistoryTaskResult> {
         let tasks = eligible.iter().map(|(idx, c)| {
-            Self::spawn_history_task(*idx, c.clone(), inst.clone(), req_copy, provider_timeout)
+            Self::spawn_history_task(
+                stats,
+                *idx,
+                c.clone(),
+                inst.clone(),
+                req_copy,
+                provider_timeout,
+            )
         });
         join_all(tasks).await
     }
@@ -443,6 +457,7 @@ impl Borsa {
     }
 
     fn spawn_history_task(
+        stats: Option<&ProviderStats>,
         idx: usize,
         c: Arc<dyn BorsaConnector>,
         inst: Instrument,
@@ -468,6 +483,7 @@ impl Borsa {
                 .expect("checked is_some above")
                 .history(&inst, eff_req);
             let resp = Self::provider_call_with_timeout(
+                stats,
                 c.name(),
                 Capability::History,
                 provider_timeout,
@@ -479,6 +495,7 @@ impl Borsa {
     }
 
     async fn sequential_history(
+        stats: Option<&ProviderStats>,
         eligible: Vec<IndexedConnector>,
         inst: &Instrument,
         req_copy: HistoryRequest,
@@ -500,6 +517,7 @@ impl Borsa {
                 .expect("checked is_some above")
                 .history(inst, eff_req);
             let resp = Self::provider_call_with_timeout(
+                stats,
                 c.name(),
                 Capability::History,
                 provider_timeout,
@@ -117,10 +117,11 @@ macro_rules! borsa_router_search {
         ) -> Result<borsa_core::SearchReport, borsa_core::BorsaError> {
             // Request type validates on construction
 
-            let ordered = self.ordered_for_kind($req_ident.kind());
+            let ordered = self.ordered_for_kind($req_ident.kind(), $capability);
 
             let req_copy = $req_ident.clone();
             let call_timeout = self.cfg.provider_timeout;
+            let stats = self.stats.as_deref();
             let tasks = ordered.into_iter().map(|c| {
                 let r = req_copy.clone();
                 async move {
@@ -130,6 +131,7 @@ macro_rules! borsa_router_search {
                     }
                     if let Some(p) = c.$accessor() {
                         let res = $crate::Borsa::provider_call_with_timeout(
+                            stats,
                             name,
                             $capability,
                             call_timeout,
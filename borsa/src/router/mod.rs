@@ -1,4 +1,5 @@
 pub mod analysis;
+pub mod checkpoint;
 pub mod download;
 pub mod esg;
 pub mod fundamentals;
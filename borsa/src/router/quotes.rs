@@ -1,8 +1,12 @@
-use borsa_core::{BorsaError, Capability, Instrument, Quote};
+use std::sync::Arc;
+
+use borsa_core::connector::BatchQuoteProvider;
+use borsa_core::{BorsaConnector, BorsaError, Capability, FetchStrategy, Instrument, Quote};
 // QuoteProvider trait is used via returned trait objects; no direct import needed
 
 use crate::Borsa;
 use crate::borsa_router_method;
+use crate::core::symbol_opt;
 
 impl Borsa {
     borsa_router_method! {
@@ -27,15 +31,26 @@ impl Borsa {
     /// Fetch quotes for multiple instruments.
     ///
     /// Behavior and trade-offs:
-    /// - Executes single-quote requests concurrently and aggregates outcomes.
+    /// - With `FetchStrategy::PriorityWithFallback`, instruments are grouped by their
+    ///   routed (highest-priority eligible) provider. Providers that advertise
+    ///   `BatchQuoteProvider` receive one call per chunk of `max_batch_size()` instruments
+    ///   instead of one call per instrument.
+    /// - Instruments missing from a batch response (or whose quote fails the exchange
+    ///   check), and every instrument of a batch that failed transiently, fall back to the
+    ///   regular per-instrument routing of [`Borsa::quote`], including provider fallback.
+    /// - A batch that fails with a permanent error is not retried per instrument: each of its
+    ///   instruments is reported in `failures` with that error.
+    /// - With `FetchStrategy::Latency`, batching is skipped and single-quote requests race
+    ///   per instrument as before.
     /// - Returns `(successful_quotes, failures)` where `failures` contains per-symbol
     ///   errors (including `NotFound`). This allows partial success without failing the
-    ///   entire batch.
-    /// - Overall `Err` is returned only if joining tasks fails or a systemic error
-    ///   occurs before per-symbol routing.
+    ///   entire batch. Successful quotes follow the input order.
+    /// - Overall `Err` is returned only if the request deadline elapses or a systemic
+    ///   error occurs before per-symbol routing.
     ///
     /// # Errors
-    /// Returns an error only if joining tasks fails before per-symbol routing.
+    /// Returns an error only if the request deadline elapses before all instruments
+    /// are resolved.
     pub async fn quotes(
         &self,
         insts: &[Instrument],
@@ -44,28 +59,153 @@ impl Borsa {
             return Ok((vec![], vec![]));
         }
 
-        let tasks = insts.iter().map(|inst| {
-            let borsa = self;
-            let inst_clone = inst.clone();
-            async move {
-                let res = borsa.quote(&inst_clone).await;
-                (inst_clone, res)
+        let work = async {
+            let mut resolved: Vec<Option<Quote>> = vec![None; insts.len()];
+
+            // Phase 1: one upstream call per batch chunk of each routed provider.
+            let batch_tasks =
+                self.plan_quote_batches(insts)
+                    .into_iter()
+                    .map(|(connector, idxs)| async move {
+                        let res = self.batch_quote_chunk(&connector, insts, &idxs).await;
+                        (connector, idxs, res)
+                    });
+            let mut failed: Vec<Option<BorsaError>> = vec![None; insts.len()];
+            for (connector, idxs, res) in futures::future::join_all(batch_tasks).await {
+                match res {
+                    Ok(quotes) => {
+                        for (idx, q) in quotes {
+                            resolved[idx] = Some(q);
+                        }
+                    }
+                    // Retrying a permanent failure once per instrument only multiplies
+                    // the load on the provider; the whole chunk fails with it instead.
+                    Err(e) if e.is_permanent() => {
+                        let e = crate::core::tag_err(connector.name(), e);
+                        for &idx in &idxs {
+                            failed[idx] = Some(e.clone());
+                        }
+                    }
+                    Err(_) => {}
+                }
+            }
+
+            // Phase 2: everything not served by a batch goes through single-quote routing.
+            let single_tasks = resolved
+                .iter()
+                .zip(&failed)
+                .enumerate()
+                .filter(|(_, (q, e))| q.is_none() && e.is_none())
+                .map(|(idx, _)| async move { (idx, self.quote(&insts[idx]).await) })
+                .collect::<Vec<_>>();
+            let mut failures: Vec<(Instrument, BorsaError)> = failed
+                .into_iter()
+                .enumerate()
+                .filter_map(|(idx, e)| Some((insts[idx].clone(), e?)))
+                .collect();
+            for (idx, res) in futures::future::join_all(single_tasks).await {
+                match res {
+                    Ok(q) => resolved[idx] = Some(q),
+                    Err(e) => failures.push((insts[idx].clone(), e)),
+                }
             }
-        });
 
-        let results = crate::router::util::join_with_deadline(tasks, self.cfg.request_timeout)
+            let ok_quotes: Vec<Quote> = resolved.into_iter().flatten().collect();
+            (ok_quotes, failures)
+        };
+
+        crate::core::with_request_deadline(self.cfg.request_timeout, work)
             .await
-            .map_err(|_| BorsaError::request_timeout(Capability::Quote.to_string()))?;
+            .map_err(|_| BorsaError::request_timeout(Capability::Quote.to_string()))
+    }
 
-        let mut ok_quotes: Vec<Quote> = Vec::new();
-        let mut failures: Vec<(Instrument, BorsaError)> = Vec::new();
-        for (inst, res) in results {
-            match res {
-                Ok(q) => ok_quotes.push(q),
-                Err(e) => failures.push((inst, e)),
+    /// Group instruments by their routed quote provider and split each group into chunks
+    /// sized for that provider's batch endpoint.
+    ///
+    /// Only instruments whose highest-priority eligible provider advertises
+    /// `BatchQuoteProvider` are included; the rest are left to single-quote routing.
+    fn plan_quote_batches(
+        &self,
+        insts: &[Instrument],
+    ) -> Vec<(Arc<dyn BorsaConnector>, Vec<usize>)> {
+        if !matches!(self.cfg.fetch_strategy, FetchStrategy::PriorityWithFallback) {
+            return vec![];
+        }
+
+        let mut groups: Vec<(Arc<dyn BorsaConnector>, Vec<usize>)> = Vec::new();
+        for (idx, inst) in insts.iter().enumerate() {
+            let routed = self
+                .ordered(inst, Capability::Quote)
+                .into_iter()
+                .find(|c| c.supports_kind(*inst.kind()) && c.as_quote_provider().is_some());
+            let Some(c) = routed else { continue };
+            if c.as_batch_quote_provider().is_none() {
+                continue;
+            }
+            match groups.iter_mut().find(|(g, _)| g.name() == c.name()) {
+                Some((_, idxs)) => idxs.push(idx),
+                None => groups.push((c, vec![idx])),
             }
         }
 
-        Ok((ok_quotes, failures))
+        let mut chunks: Vec<(Arc<dyn BorsaConnector>, Vec<usize>)> = Vec::new();
+        for (c, idxs) in groups {
+            let size = c
+                .as_batch_quote_provider()
+                .map_or(1, BatchQuoteProvider::max_batch_size)
+                .max(1);
+            for chunk in idxs.chunks(size) {
+                chunks.push((Arc::clone(&c), chunk.to_vec()));
+            }
+        }
+        chunks
+    }
+
+    /// Execute one batch chunk and return the quotes matched back to input positions.
+    ///
+    /// Positions missing from a successful response fall back to single-quote routing. A
+    /// failed call is returned as is; the caller decides whether the chunk falls back.
+    async fn batch_quote_chunk(
+        &self,
+        connector: &Arc<dyn BorsaConnector>,
+        insts: &[Instrument],
+        idxs: &[usize],
+    ) -> Result<Vec<(usize, Quote)>, BorsaError> {
+        let Some(provider) = connector.as_batch_quote_provider() else {
+            return Ok(vec![]);
+        };
+        let chunk: Vec<Instrument> = idxs.iter().map(|&i| insts[i].clone()).collect();
+        let quotes = Self::provider_call_with_timeout(
+            self.stats.as_deref(),
+            connector.name(),
+            Capability::Quote,
+            self.cfg.provider_timeout,
+            provider.quotes(&chunk),
+        )
+        .await?;
+
+        let mut out: Vec<(usize, Quote)> = Vec::with_capacity(quotes.len());
+        for q in quotes {
+            // Prefer an exact instrument match; providers that normalize identifiers
+            // (e.g., attach an exchange) are matched by symbol instead.
+            let pos = idxs
+                .iter()
+                .copied()
+                .find(|&i| insts[i] == q.instrument)
+                .or_else(|| {
+                    let sym = symbol_opt(&q.instrument)?;
+                    idxs.iter()
+                        .copied()
+                        .find(|&i| symbol_opt(&insts[i]) == Some(sym))
+                });
+            let Some(idx) = pos else { continue };
+            if out.iter().any(|(i, _)| *i == idx) {
+                continue;
+            }
+            if Self::enforce_quote_exchange(&insts[idx], &q).is_ok() {
+                out.push((idx, q));
+            }
+        }
+        Ok(out)
     }
 }
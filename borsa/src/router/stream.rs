@@ -1,43 +1,34 @@
-use crate::router::streaming::{
-    EligibleStreamProviders, KindSupervisorParams, StreamUpdateKind, collapse_stream_errors,
-    spawn_kind_supervisor,
-};
+use crate::router::streaming::events::EventSink;
+use crate::router::streaming::hub::SharedSubscription;
+use crate::router::streaming::output::spawn_output;
+use crate::router::streaming::planner::{EligibleFn, SupervisorKey, SupervisorPlan};
+use crate::router::streaming::subscription::{StreamSubscription, SubscriptionManager};
+use crate::router::streaming::synth::{CandleSynthesizer, spawn_candle_synthesis};
+use crate::router::streaming::{EligibleStreamProviders, StreamUpdateKind};
 use crate::{BackoffConfig, Borsa};
 use borsa_core::{
     AssetKind, BorsaConnector, BorsaError, CandleUpdate, Capability, Exchange, Instrument,
-    Interval, OptionUpdate, QuoteUpdate, RoutingContext, Symbol, stream::StreamHandle,
+    Interval, OptionUpdate, OverflowPolicy, QuoteUpdate, RoutingContext, Symbol,
+    stream::{StreamEvent, StreamHandle},
 };
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot, watch};
+use tokio::sync::mpsc;
+
+/// Capacity of a stream's event channel.
+const EVENT_CAPACITY: usize = 256;
 
 impl Borsa {
+    /// Plan kind supervisors for `instruments`: group by `(kind, exchange)`, resolve eligible
+    /// providers, apply strict policy checks and split per primary provider when explicit
+    /// per-symbol preferences exist.
     #[allow(clippy::too_many_lines)]
-    async fn stream_updates_with_backoff<T, F>(
+    pub(crate) fn plan_stream_supervisors(
         &self,
         instruments: &[Instrument],
-        context: T::Context,
-        backoff_override: Option<BackoffConfig>,
         capability: Capability,
-        eligible_fn: F,
-    ) -> Result<(StreamHandle, mpsc::Receiver<T>), BorsaError>
-    where
-        T: StreamUpdateKind,
-        T::Context: Clone,
-        F: Fn(
-            &Self,
-            AssetKind,
-            Option<&Exchange>,
-            &[Instrument],
-        ) -> Result<EligibleStreamProviders, BorsaError>,
-    {
-        tokio::task::yield_now().await;
-        if instruments.is_empty() {
-            return Err(borsa_core::BorsaError::InvalidArg(
-                "instruments list cannot be empty".into(),
-            ));
-        }
-
+        eligible_fn: EligibleFn,
+    ) -> Result<Vec<SupervisorPlan>, BorsaError> {
         let mut by_group: HashMap<(AssetKind, Option<Exchange>), Vec<Instrument>> = HashMap::new();
         for inst in instruments.iter().cloned() {
             let exch_opt = match inst.id() {
@@ -50,15 +41,7 @@ impl Borsa {
                 .push(inst);
         }
 
-        let resolved_backoff: BackoffConfig =
-            backoff_override.or(self.cfg.backoff).unwrap_or_default();
-
-        let (tx, rx) = mpsc::channel::<T>(1024);
-        let (stop_tx, stop_rx) = oneshot::channel::<()>();
-        let (stop_broadcast_tx, stop_broadcast_rx) = watch::channel(false);
-
-        let mut joins = Vec::new();
-        let mut init_receivers: Vec<oneshot::Receiver<Result<(), BorsaError>>> = Vec::new();
+        let mut plans: Vec<SupervisorPlan> = Vec::new();
         for ((kind, ex), list) in by_group {
             let EligibleStreamProviders {
                 providers,
@@ -100,7 +83,8 @@ impl Borsa {
                     if !candidates.is_empty() {
                         let mut any_allowed = false;
                         for c in &candidates {
-                            let ctx = RoutingContext::new(Some(sym), Some(kind), ex.clone());
+                            let ctx = RoutingContext::new(Some(sym), Some(kind), ex.clone())
+                                .with_capability(capability);
                             if self
                                 .cfg
                                 .routing_policy
@@ -127,7 +111,8 @@ impl Borsa {
             let mut group_has_explicit: bool = false;
             'outer: for (_, sym) in &list_pairs {
                 for p in &providers {
-                    let ctx = RoutingContext::new(Some(sym), Some(kind), ex.clone());
+                    let ctx = RoutingContext::new(Some(sym), Some(kind), ex.clone())
+                        .with_capability(capability);
                     if let Some((rank, _)) = self
                         .cfg
                         .routing_policy
@@ -155,7 +140,8 @@ impl Borsa {
                         {
                             continue;
                         }
-                        let ctx = RoutingContext::new(Some(sym), Some(kind), ex.clone());
+                        let ctx = RoutingContext::new(Some(sym), Some(kind), ex.clone())
+                            .with_capability(capability);
                         if let Some((rank, _strict)) = self
                             .cfg
                             .routing_policy
@@ -211,32 +197,17 @@ impl Borsa {
                         provider_allow.push(filtered_allow);
                     }
 
-                    let min_backoff_ms = resolved_backoff.min_backoff_ms;
-                    let max_backoff_ms = resolved_backoff.max_backoff_ms;
-                    let factor = resolved_backoff.factor.max(1);
-                    let jitter_percent = resolved_backoff.jitter_percent.min(100);
-
-                    let required_symbols: HashSet<Symbol> = group_syms_set.clone();
-                    let (init_tx, init_rx) = oneshot::channel();
-                    let context_arc = Arc::new(context.clone());
-                    let params = KindSupervisorParams {
+                    plans.push(SupervisorPlan {
+                        key: SupervisorKey {
+                            kind,
+                            exchange: ex.clone(),
+                            chain: chain_providers.iter().map(|p| p.name()).collect(),
+                        },
                         providers: chain_providers,
                         provider_instruments,
                         provider_allow,
-                        required_symbols,
-                        min_backoff_ms,
-                        max_backoff_ms,
-                        factor,
-                        jitter_percent: u32::from(jitter_percent),
-                        initial_notify: Some(init_tx),
-                        enforce_monotonic: self.cfg.stream_enforce_monotonic_timestamps,
-                        capability,
-                        context: context_arc,
-                    };
-                    let join =
-                        spawn_kind_supervisor::<T>(params, stop_broadcast_rx.clone(), tx.clone());
-                    joins.push(join);
-                    init_receivers.push(init_rx);
+                        required_symbols: group_syms_set,
+                    });
                 }
             } else {
                 let mut provider_instruments: Vec<Vec<Instrument>> =
@@ -252,77 +223,104 @@ impl Borsa {
                     provider_allow.push(allow.clone());
                 }
 
-                let min_backoff_ms = resolved_backoff.min_backoff_ms;
-                let max_backoff_ms = resolved_backoff.max_backoff_ms;
-                let factor = resolved_backoff.factor.max(1);
-                let jitter_percent = resolved_backoff.jitter_percent.min(100);
-
-                let (init_tx, init_rx) = oneshot::channel();
-
                 let required_symbols: HashSet<Symbol> = list_pairs
                     .iter()
                     .filter(|(_, sym)| union_symbols.contains(sym))
                     .map(|(_, sym)| sym.clone())
                     .collect();
 
-                let context_arc = Arc::new(context.clone());
-                let params = KindSupervisorParams {
+                plans.push(SupervisorPlan {
+                    key: SupervisorKey {
+                        kind,
+                        exchange: ex,
+                        chain: providers.iter().map(|p| p.name()).collect(),
+                    },
                     providers,
                     provider_instruments,
                     provider_allow,
                     required_symbols,
-                    min_backoff_ms,
-                    max_backoff_ms,
-                    factor,
-                    jitter_percent: u32::from(jitter_percent),
-                    initial_notify: Some(init_tx),
-                    enforce_monotonic: self.cfg.stream_enforce_monotonic_timestamps,
-                    capability,
-                    context: context_arc,
-                };
-                let join =
-                    spawn_kind_supervisor::<T>(params, stop_broadcast_rx.clone(), tx.clone());
-                joins.push(join);
-                init_receivers.push(init_rx);
+                });
             }
         }
 
-        let mut init_errors: Vec<BorsaError> = Vec::new();
-        let mut success_kinds: usize = 0;
-        for rx in init_receivers {
-            match rx.await {
-                Ok(Ok(())) => {
-                    success_kinds += 1;
-                }
-                Ok(Err(e)) => init_errors.push(e),
-                Err(_) => init_errors.push(BorsaError::Other(
-                    "stream supervisor dropped before initialization".into(),
-                )),
-            }
+        Ok(plans)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn subscribe_updates_with_backoff<T>(
+        &self,
+        instruments: &[Instrument],
+        context: T::Context,
+        backoff_override: Option<BackoffConfig>,
+        capability: Capability,
+        eligible_fn: EligibleFn,
+        overflow: OverflowPolicy,
+    ) -> Result<(StreamSubscription, mpsc::Receiver<T>), BorsaError>
+    where
+        T: StreamUpdateKind,
+    {
+        tokio::task::yield_now().await;
+        if instruments.is_empty() {
+            return Err(borsa_core::BorsaError::InvalidArg(
+                "instruments list cannot be empty".into(),
+            ));
         }
 
-        if success_kinds == 0 || !init_errors.is_empty() {
-            let err = collapse_stream_errors(capability, init_errors);
-            for join in joins {
-                join.abort();
+        let resolved_backoff: BackoffConfig =
+            backoff_override.or(self.cfg.backoff).unwrap_or_default();
+
+        let (events_tx, events_rx) = mpsc::channel::<StreamEvent>(EVENT_CAPACITY);
+        let events = EventSink::new(events_tx);
+        let (tx, rx) = spawn_output::<T>(
+            self.cfg.stream_channel_capacity,
+            overflow,
+            events.clone(),
+            Arc::default(),
+        );
+        let mut manager = SubscriptionManager::<T>::new(
+            self.snapshot(),
+            context,
+            capability,
+            eligible_fn,
+            resolved_backoff,
+            tx,
+            events,
+        );
+        let mut initial: Vec<Instrument> = Vec::with_capacity(instruments.len());
+        for inst in instruments {
+            if !initial.contains(inst) {
+                initial.push(inst.clone());
             }
-            let _ = stop_broadcast_tx.send(true);
-            return Err(err);
         }
+        manager.apply(initial).await?;
 
-        let supervisor = tokio::spawn(async move {
-            let mut stop_rx_inner = stop_rx;
-            tokio::select! {
-                _ = &mut stop_rx_inner => {}
-                () = tx.closed() => {}
-            }
-            let _ = stop_broadcast_tx.send(true);
-            for j in joins {
-                let _ = j.await;
-            }
-        });
+        Ok((manager.spawn().with_events(events_rx), rx))
+    }
 
-        Ok((StreamHandle::new(supervisor, stop_tx), rx))
+    #[allow(clippy::too_many_arguments)]
+    async fn stream_updates_with_backoff<T>(
+        &self,
+        instruments: &[Instrument],
+        context: T::Context,
+        backoff_override: Option<BackoffConfig>,
+        capability: Capability,
+        eligible_fn: EligibleFn,
+        overflow: OverflowPolicy,
+    ) -> Result<(StreamHandle, mpsc::Receiver<T>), BorsaError>
+    where
+        T: StreamUpdateKind,
+    {
+        let (subscription, rx) = self
+            .subscribe_updates_with_backoff::<T>(
+                instruments,
+                context,
+                backoff_override,
+                capability,
+                eligible_fn,
+                overflow,
+            )
+            .await?;
+        Ok((subscription.into_handle(), rx))
     }
 
     /// Start streaming quotes with automatic backoff, provider failover, and policy-aware routing.
@@ -363,6 +361,18 @@ impl Borsa {
     /// - Optional monotonic timestamp enforcement is applied if enabled in config.
     /// - Dropping or stopping the returned `StreamHandle` terminates all supervised tasks.
     ///
+    /// Output:
+    /// - Up to `stream_channel_capacity` updates are buffered for a consumer that falls behind.
+    ///   Once the buffer is full, `stream_overflow` decides whether sessions wait or updates
+    ///   are discarded.
+    /// - Discarded updates are reported as `StreamEvent::UpdatesDropped` on the receiver from
+    ///   [`StreamHandle::take_events`].
+    ///
+    /// Events:
+    /// - The same receiver reports session starts, start failures and session ends per provider,
+    ///   failovers between providers, symbols left without a session, scheduled backoff and
+    ///   updates discarded by the unassigned-symbol and monotonic filters.
+    ///
     /// # Errors
     /// - Returns an error if initialization fails across all providers for all groups, or when no
     ///   streaming-capable providers are available.
@@ -379,14 +389,13 @@ impl Borsa {
         instruments: &[Instrument],
         backoff_override: Option<BackoffConfig>,
     ) -> Result<(StreamHandle, mpsc::Receiver<QuoteUpdate>), BorsaError> {
-        self.stream_updates_with_backoff::<QuoteUpdate, _>(
+        self.stream_updates_with_backoff::<QuoteUpdate>(
             instruments,
             (),
             backoff_override,
             Capability::StreamQuotes,
-            |this, kind, exchange, list| {
-                this.eligible_stream_providers_for_context(kind, exchange, list)
-            },
+            Self::eligible_stream_providers_for_context,
+            self.cfg.stream_overflow,
         )
         .await
     }
@@ -405,6 +414,76 @@ impl Borsa {
         self.stream_quotes_with_backoff(instruments, None).await
     }
 
+    /// Start a quote stream whose instrument set can be changed while it runs.
+    ///
+    /// Routing, strict policy handling and backoff match [`Self::stream_quotes_with_backoff`].
+    /// The returned [`StreamSubscription`] accepts `add`/`remove` calls that re-plan the combined
+    /// instrument set: only provider sessions whose assignment changed are restarted, and updates
+    /// keep flowing on the same receiver.
+    ///
+    /// # Errors
+    /// Returns the same errors as [`Self::stream_quotes_with_backoff`] for the initial set.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "borsa::router::subscribe_quotes_with_backoff",
+            skip(self, instruments, backoff_override)
+        )
+    )]
+    pub async fn subscribe_quotes_with_backoff(
+        &self,
+        instruments: &[Instrument],
+        backoff_override: Option<BackoffConfig>,
+    ) -> Result<(StreamSubscription, mpsc::Receiver<QuoteUpdate>), BorsaError> {
+        self.subscribe_updates_with_backoff::<QuoteUpdate>(
+            instruments,
+            (),
+            backoff_override,
+            Capability::StreamQuotes,
+            Self::eligible_stream_providers_for_context,
+            self.cfg.stream_overflow,
+        )
+        .await
+    }
+
+    /// Start a dynamic quote subscription using the configured backoff settings.
+    ///
+    /// # Errors
+    /// Returns an error if streaming initialization fails for all providers.
+    pub async fn subscribe_quotes(
+        &self,
+        instruments: &[Instrument],
+    ) -> Result<(StreamSubscription, mpsc::Receiver<QuoteUpdate>), BorsaError> {
+        self.subscribe_quotes_with_backoff(instruments, None).await
+    }
+
+    /// Receive quotes for `instruments` from the stream shared by every caller of this method.
+    ///
+    /// The first call starts one upstream quote subscription, routed like
+    /// [`Self::subscribe_quotes`] with the configured backoff. Later calls add only the
+    /// instruments no current consumer reads, and an instrument leaves the upstream once its
+    /// last consumer is released, so provider sessions are restarted only when the combined
+    /// instrument set changes. Each consumer has its own receiver; `overflow` decides what
+    /// happens when it falls behind.
+    ///
+    /// Behavior and trade-offs:
+    /// - `OverflowPolicy::Block` never loses updates: once a slow consumer's buffer is full,
+    ///   its further updates queue in memory without holding back the other consumers.
+    /// - The other policies keep other consumers flowing; discarded updates are counted by
+    ///   [`SharedSubscription::dropped`].
+    /// - Each consumer buffers up to `stream_channel_capacity` updates.
+    ///
+    /// # Errors
+    /// Returns `InvalidArg` for an empty instrument list, and the errors of
+    /// [`Self::subscribe_quotes`] when the new instruments cannot be streamed.
+    pub async fn share_quotes(
+        &self,
+        instruments: &[Instrument],
+        overflow: OverflowPolicy,
+    ) -> Result<(SharedSubscription<QuoteUpdate>, mpsc::Receiver<QuoteUpdate>), BorsaError> {
+        self.hub.quotes.subscribe(self, instruments, overflow).await
+    }
+
     /// Start streaming candle updates with automatic backoff, provider failover, and policy-aware routing.
     ///
     /// Parameters mirror [`Self::stream_quotes_with_backoff`] with an additional `interval`
@@ -415,9 +494,21 @@ impl Borsa {
     /// [`CandleStreamProvider`](borsa_core::connector::CandleStreamProvider) and will produce
     /// [`CandleUpdate`] frames with `is_final` flagged when upstream closes the interval.
     ///
+    /// Synthesized candles:
+    /// - Instruments with no candle-streaming connector eligible under the routing policy get
+    ///   candles built by the router from a quote stream (routed like
+    ///   [`Self::stream_quotes_with_backoff`]).
+    /// - Each tick emits the in-progress bar of its interval; bars are aligned in the
+    ///   instrument's exchange timezone and emitted again with `is_final` once closed. Empty
+    ///   intervals, late ticks and wall-clock finalization follow
+    ///   [`CandleSynthesisConfig`](borsa_core::CandleSynthesisConfig).
+    /// - Native and synthesized candles share the returned receiver.
+    ///
     /// # Errors
     /// Returns an error if candle-capable providers cannot be started for any requested group or
-    /// when strict routing rules reject every symbol.
+    /// when strict routing rules reject every symbol. Synthesis returns `Unsupported` for
+    /// intervals other than intraday, daily and weekly, and the quote-streaming errors when
+    /// no quote stream can be started.
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(
@@ -432,16 +523,75 @@ impl Borsa {
         interval: Interval,
         backoff_override: Option<BackoffConfig>,
     ) -> Result<(StreamHandle, mpsc::Receiver<CandleUpdate>), BorsaError> {
-        self.stream_updates_with_backoff::<CandleUpdate, _>(
-            instruments,
-            interval,
-            backoff_override,
-            Capability::StreamCandles,
-            |this, kind, exchange, list| {
-                this.eligible_candle_stream_providers_for_context(kind, exchange, list)
-            },
-        )
-        .await
+        // An instrument streams natively only when the routing policy leaves it a
+        // candle-streaming provider; everything else is synthesized from quotes.
+        let (native, synthesized): (Vec<Instrument>, Vec<Instrument>) =
+            instruments.iter().cloned().partition(|inst| {
+                self.eligible_candle_stream_providers_for_context(
+                    *inst.kind(),
+                    None,
+                    std::slice::from_ref(inst),
+                )
+                .is_ok_and(|eligible| !eligible.union_symbols.is_empty())
+            });
+        if synthesized.is_empty() {
+            return self
+                .stream_updates_with_backoff::<CandleUpdate>(
+                    instruments,
+                    interval,
+                    backoff_override,
+                    Capability::StreamCandles,
+                    Self::eligible_candle_stream_providers_for_context,
+                    self.cfg.stream_overflow,
+                )
+                .await;
+        }
+
+        // The inputs block rather than drop: every tick matters to a bar, so the overflow
+        // policy applies to the candles only.
+        let synth = CandleSynthesizer::new(interval, self.cfg.candle_synthesis.clone())?
+            .with_trading_hours(self.cfg.stream_staleness.exchange_hours.clone());
+        let mut native = if native.is_empty() {
+            None
+        } else {
+            Some(
+                self.stream_updates_with_backoff::<CandleUpdate>(
+                    &native,
+                    interval,
+                    backoff_override,
+                    Capability::StreamCandles,
+                    Self::eligible_candle_stream_providers_for_context,
+                    OverflowPolicy::Block,
+                )
+                .await?,
+            )
+        };
+        let mut quotes = self
+            .stream_updates_with_backoff::<QuoteUpdate>(
+                &synthesized,
+                (),
+                backoff_override,
+                Capability::StreamQuotes,
+                Self::eligible_stream_providers_for_context,
+                OverflowPolicy::Block,
+            )
+            .await?;
+        let (events_tx, events_rx) = mpsc::channel::<StreamEvent>(EVENT_CAPACITY);
+        let events = EventSink::new(events_tx);
+        // Lifecycle events of the input streams are reported on the candle stream.
+        for handle in std::iter::once(&mut quotes.0).chain(native.as_mut().map(|(h, _)| h)) {
+            if let Some(rx) = handle.take_events() {
+                events.forward(rx);
+            }
+        }
+        let (tx, rx) = spawn_output::<CandleUpdate>(
+            self.cfg.stream_channel_capacity,
+            self.cfg.stream_overflow,
+            events,
+            Arc::default(),
+        );
+        let handle = spawn_candle_synthesis(quotes, native, synth, tx).with_events(events_rx);
+        Ok((handle, rx))
     }
 
     /// Start streaming candles using configured backoff settings.
@@ -487,14 +637,13 @@ impl Borsa {
         instruments: &[Instrument],
         backoff_override: Option<BackoffConfig>,
     ) -> Result<(StreamHandle, mpsc::Receiver<OptionUpdate>), BorsaError> {
-        self.stream_updates_with_backoff::<OptionUpdate, _>(
+        self.stream_updates_with_backoff::<OptionUpdate>(
             instruments,
             (),
             backoff_override,
             Capability::StreamOptions,
-            |this, kind, exchange, list| {
-                this.eligible_option_stream_providers_for_context(kind, exchange, list)
-            },
+            Self::eligible_option_stream_providers_for_context,
+            self.cfg.stream_overflow,
         )
         .await
     }
@@ -509,4 +658,28 @@ impl Borsa {
     ) -> Result<(StreamHandle, mpsc::Receiver<OptionUpdate>), BorsaError> {
         self.stream_options_with_backoff(instruments, None).await
     }
+
+    /// Receive option updates from the shared option stream.
+    ///
+    /// Shares one upstream subscription across consumers like [`Self::share_quotes`].
+    ///
+    /// # Errors
+    /// Returns `InvalidArg` for an empty instrument list, and the errors of
+    /// [`Self::stream_options`] when the new instruments cannot be streamed.
+    pub async fn share_options(
+        &self,
+        instruments: &[Instrument],
+        overflow: OverflowPolicy,
+    ) -> Result<
+        (
+            SharedSubscription<OptionUpdate>,
+            mpsc::Receiver<OptionUpdate>,
+        ),
+        BorsaError,
+    > {
+        self.hub
+            .options
+            .subscribe(self, instruments, overflow)
+            .await
+    }
 }
@@ -1,15 +1,19 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 
-use borsa_core::{BorsaConnector, BorsaError, Capability, Instrument, Symbol};
+use borsa_core::stream::StreamEvent;
+use borsa_core::{BorsaConnector, BorsaError, Capability, Instrument, StalenessThresholds, Symbol};
 use tokio::sync::{mpsc, oneshot, watch};
 use tokio::task::JoinHandle;
 
 use super::StreamUpdateKind;
 use super::backoff::jitter_wait;
 use super::error::collapse_stream_errors;
+use super::events::{CoverageTracker, EventSink};
 use super::filters::MonotonicGate;
-use super::session::SessionManager;
+use super::session::{SessionManager, SessionReport};
+use super::staleness::StalenessWatch;
 
 pub struct KindSupervisorParams<C> {
     pub providers: Vec<Arc<dyn BorsaConnector>>,
@@ -27,6 +31,47 @@ pub struct KindSupervisorParams<C> {
     pub enforce_monotonic: bool,
     pub capability: Capability,
     pub context: Arc<C>,
+    /// Receives updated assignments for a running supervisor (dynamic subscriptions).
+    pub replan_rx: Option<mpsc::UnboundedReceiver<SupervisorReplan>>,
+    /// Externally owned monotonic gates, aligned with `providers`, so ordering state survives
+    /// supervisor respawns. When `None`, fresh gates are created if enforcement is enabled.
+    pub monotonic_gates: Option<Vec<Arc<MonotonicGate>>>,
+    /// Receives lifecycle events of the supervised sessions.
+    pub events: EventSink,
+    /// Staleness thresholds for this asset kind, if it is checked.
+    pub staleness: Option<StalenessThresholds>,
+    /// How long a provider is passed over for symbols it let go stale.
+    pub stale_hold: Duration,
+}
+
+/// Updated assignment tables for a running kind supervisor, aligned with its providers.
+pub struct SupervisorReplan {
+    pub provider_instruments: Vec<Vec<Instrument>>,
+    pub provider_allow: Vec<HashSet<Symbol>>,
+    pub required_symbols: HashSet<Symbol>,
+}
+
+type StartResult<T> = (
+    usize,
+    Arc<[Symbol]>,
+    Result<(borsa_core::stream::StreamHandle, mpsc::Receiver<T>), BorsaError>,
+);
+
+fn spawn_start<T: StreamUpdateKind>(
+    provider: Arc<dyn BorsaConnector>,
+    id: usize,
+    instruments: Vec<Instrument>,
+    symbols: Arc<[Symbol]>,
+    context: Arc<T::Context>,
+    start_tx: mpsc::UnboundedSender<StartResult<T>>,
+) {
+    tokio::spawn(async move {
+        let provider_name = provider.name();
+        let res = T::start_stream(provider.as_ref(), &instruments, context.as_ref())
+            .await
+            .map_err(|err| crate::core::tag_err(provider_name, err));
+        let _ = start_tx.send((id, symbols, res));
+    });
 }
 
 #[allow(clippy::too_many_lines)]
@@ -43,7 +88,6 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
     tokio::spawn(async move {
         use super::supervisor_sm as sm;
         use std::pin::Pin;
-        use tokio::time::Duration;
 
         let KindSupervisorParams {
             providers,
@@ -58,6 +102,11 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
             enforce_monotonic,
             capability,
             context,
+            mut replan_rx,
+            monotonic_gates,
+            events,
+            staleness,
+            stale_hold,
         } = params;
 
         if providers.is_empty() {
@@ -68,14 +117,17 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
             return;
         }
 
-        let monotonic_gates: Vec<Option<Arc<MonotonicGate>>> = if enforce_monotonic {
-            (0..providers.len())
+        let monotonic_gates: Vec<Option<Arc<MonotonicGate>>> = match monotonic_gates {
+            Some(gates) if enforce_monotonic => gates.into_iter().map(Some).collect(),
+            None if enforce_monotonic => (0..providers.len())
                 .map(|_| Some(Arc::new(MonotonicGate::new())))
-                .collect()
-        } else {
-            vec![None; providers.len()]
+                .collect(),
+            _ => vec![None; providers.len()],
         };
 
+        let names: Vec<&'static str> = providers.iter().map(|p| p.name()).collect();
+        let mut coverage = CoverageTracker::new(names.clone());
+
         let providers_can_stream: Vec<bool> = providers
             .iter()
             .map(|p| T::can_stream(p.as_ref(), context.as_ref()))
@@ -100,21 +152,13 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
                 accumulated_errors: Vec::new(),
             },
             capability,
+            stale_holds: vec![HashMap::new(); providers.len()],
+            stale_hold,
         };
 
         let (event_tx, mut event_rx) =
-            tokio::sync::mpsc::unbounded_channel::<(usize, Arc<[Symbol]>)>();
-        let (start_tx, mut start_rx) = tokio::sync::mpsc::unbounded_channel::<(
-            usize,
-            Result<
-                (
-                    borsa_core::stream::StreamHandle,
-                    tokio::sync::mpsc::Receiver<T>,
-                    Arc<[Symbol]>,
-                ),
-                BorsaError,
-            >,
-        )>();
+            tokio::sync::mpsc::unbounded_channel::<(usize, SessionReport)>();
+        let (start_tx, mut start_rx) = tokio::sync::mpsc::unbounded_channel::<StartResult<T>>();
 
         let mut session_tasks: HashMap<usize, ActiveSession> = HashMap::new();
         let mut backoff_timer: Option<Pin<Box<tokio::time::Sleep>>> =
@@ -126,33 +170,20 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
         if supervisor.should_attempt_starts() {
             let initial_actions = supervisor.compute_needed_starts();
             for action in initial_actions {
-                if let sm::Action::RequestStart { id, instruments } = action {
-                    let provider = Arc::clone(&providers[id]);
-                    let syms: Arc<[Symbol]> = Arc::from(
-                        instruments
-                            .iter()
-                            .filter_map(|inst| match inst.id() {
-                                borsa_core::IdentifierScheme::Security(sec) => {
-                                    Some(sec.symbol.clone())
-                                }
-                                borsa_core::IdentifierScheme::Prediction(_) => None,
-                            })
-                            .collect::<Vec<_>>()
-                            .into_boxed_slice(),
+                if let sm::Action::RequestStart {
+                    id,
+                    instruments,
+                    symbols,
+                } = action
+                {
+                    spawn_start::<T>(
+                        Arc::clone(&providers[id]),
+                        id,
+                        instruments,
+                        symbols,
+                        Arc::clone(&context),
+                        start_tx.clone(),
                     );
-                    let start_tx_clone = start_tx.clone();
-                    let ctx = Arc::clone(&context);
-                    tokio::spawn(async move {
-                        let provider_name = provider.name();
-                        let res =
-                            match T::start_stream(provider.as_ref(), &instruments, ctx.as_ref())
-                                .await
-                            {
-                                Ok((handle, prx)) => Ok((handle, prx, syms)),
-                                Err(err) => Err(crate::core::tag_err(provider_name, err)),
-                            };
-                        let _ = start_tx_clone.send((id, res));
-                    });
                 }
             }
         }
@@ -162,13 +193,53 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
                 _ = stop_watch.changed() => sm::Event::Shutdown,
                 () = async {}, if *stop_watch.borrow() => sm::Event::Shutdown,
                 () = tx_clone.closed() => sm::Event::DownstreamClosed,
-                Some((id, syms)) = event_rx.recv() => sm::Event::SessionEnded { id, symbols: syms },
-                Some((id, res)) = start_rx.recv() => {
+                Some((id, report)) = event_rx.recv() => match report {
+                    SessionReport::Ended(syms) => {
+                        if supervisor.is_current_session(id, &syms) {
+                            events.emit(StreamEvent::ProviderEnded {
+                                provider: names[id],
+                                symbols: syms.to_vec(),
+                            });
+                        }
+                        sm::Event::SessionEnded { id, symbols: syms }
+                    }
+                    SessionReport::Stale { symbols, stale } => {
+                        if supervisor.is_current_session(id, &symbols) {
+                            #[cfg(feature = "tracing")]
+                            tracing::warn!(provider = names[id], stale = stale.len(), "stream session went stale");
+                            events.emit(StreamEvent::Stale {
+                                provider: names[id],
+                                symbols: stale.clone(),
+                            });
+                        }
+                        sm::Event::SessionStale { id, symbols, stale }
+                    }
+                },
+                Some(replan) = async {
+                    match replan_rx.as_mut() {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => sm::Event::Replan {
+                    provider_instruments: replan.provider_instruments,
+                    provider_allow: replan.provider_allow,
+                    required_symbols: replan.required_symbols,
+                },
+                Some((id, symbols, res)) = start_rx.recv() => {
+                    if !supervisor.is_pending_start(id, &symbols) {
+                        // Superseded by a replan; dropping the handle stops the provider stream.
+                        continue;
+                    }
                     match res {
-                        Ok((handle, prx, symbols)) => {
+                        Ok((handle, prx)) => {
+                            events.emit(StreamEvent::ProviderStarted {
+                                provider: names[id],
+                                symbols: symbols.to_vec(),
+                            });
                             let allowed = supervisor.provider_allow.get(id).cloned();
                             let spawned = SessionManager::spawn(
                                 id,
+                                names[id],
                                 handle,
                                 prx,
                                 allowed,
@@ -178,11 +249,22 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
                                 tx_clone.clone(),
                                 event_tx.clone(),
                                 Arc::clone(&symbols),
+                                events.clone(),
+                                staleness
+                                    .as_ref()
+                                    .and_then(|t| StalenessWatch::new(t, &symbols)),
                             );
                             session_tasks.insert(id, ActiveSession { join: spawned.join, stop_tx: spawned.stop_tx });
                             sm::Event::ProviderStartSucceeded { id, symbols }
                         }
-                        Err(e) => sm::Event::ProviderStartFailed { id, error: e },
+                        Err(e) => {
+                            events.emit(StreamEvent::ProviderFailed {
+                                provider: names[id],
+                                symbols: symbols.to_vec(),
+                                error: e.clone(),
+                            });
+                            sm::Event::ProviderStartFailed { id, error: e }
+                        }
                     }
                 }
                 () = async { backoff_timer.as_mut().unwrap().await }, if backoff_timer.is_some() => sm::Event::BackoffTick,
@@ -191,38 +273,22 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
             let (new_sm, actions) = supervisor.handle(event);
             supervisor = new_sm;
 
+            let mut rearmed = false;
             for action in actions {
                 match action {
-                    sm::Action::RequestStart { id, instruments } => {
-                        let provider = Arc::clone(&providers[id]);
-                        let syms: Arc<[Symbol]> = Arc::from(
-                            instruments
-                                .iter()
-                                .filter_map(|inst| match inst.id() {
-                                    borsa_core::IdentifierScheme::Security(sec) => {
-                                        Some(sec.symbol.clone())
-                                    }
-                                    borsa_core::IdentifierScheme::Prediction(_) => None,
-                                })
-                                .collect::<Vec<_>>()
-                                .into_boxed_slice(),
+                    sm::Action::RequestStart {
+                        id,
+                        instruments,
+                        symbols,
+                    } => {
+                        spawn_start::<T>(
+                            Arc::clone(&providers[id]),
+                            id,
+                            instruments,
+                            symbols,
+                            Arc::clone(&context),
+                            start_tx.clone(),
                         );
-                        let start_tx_clone = start_tx.clone();
-                        let ctx = Arc::clone(&context);
-                        tokio::spawn(async move {
-                            let provider_name = provider.name();
-                            let res = match T::start_stream(
-                                provider.as_ref(),
-                                &instruments,
-                                ctx.as_ref(),
-                            )
-                            .await
-                            {
-                                Ok((handle, prx)) => Ok((handle, prx, syms)),
-                                Err(err) => Err(crate::core::tag_err(provider_name, err)),
-                            };
-                            let _ = start_tx_clone.send((id, res));
-                        });
                     }
                     sm::Action::StopAll => {
                         for sess in session_tasks.values_mut() {
@@ -244,6 +310,7 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
                         }
                     }
                     sm::Action::ScheduleBackoffTick { delay_ms } => {
+                        rearmed = true;
                         backoff_timer = Some(Box::pin(tokio::time::sleep(Duration::from_millis(
                             jitter_wait(delay_ms, jitter_percent),
                         ))));
@@ -262,8 +329,24 @@ pub fn spawn_kind_supervisor<T: StreamUpdateKind>(
                             }
                         }
                     }
+                    sm::Action::StopSessions { provider_ids } => {
+                        for id in provider_ids {
+                            if let Some(mut sess) = session_tasks.remove(&id)
+                                && let Some(tx) = sess.stop_tx.take()
+                            {
+                                let _ = tx.send(());
+                            }
+                        }
+                    }
                 }
             }
+
+            let next_attempt = backoff_timer.as_ref().map_or(Duration::ZERO, |timer| {
+                timer
+                    .deadline()
+                    .saturating_duration_since(tokio::time::Instant::now())
+            });
+            coverage.update(&supervisor, rearmed, next_attempt, &events);
         }
     })
 }
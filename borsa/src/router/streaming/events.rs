@@ -0,0 +1,158 @@
+//! Lifecycle events reported on a stream's event channel.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use borsa_core::{Symbol, stream::StreamEvent};
+use tokio::sync::mpsc;
+
+use super::supervisor_sm::{ProviderState, Supervisor};
+
+/// Best-effort sender of [`StreamEvent`]s.
+///
+/// Events are discarded when the receiver lags behind or is gone, so reporting never slows a
+/// stream down. Reports of discarded updates stop short of the last quarter of the buffer,
+/// which stays free for lifecycle events, so a flood of drops cannot crowd out a failover.
+/// The default sink discards everything.
+#[derive(Clone, Default)]
+pub struct EventSink(Option<mpsc::Sender<StreamEvent>>);
+
+impl EventSink {
+    pub const fn new(tx: mpsc::Sender<StreamEvent>) -> Self {
+        Self(Some(tx))
+    }
+
+    pub fn emit(&self, event: StreamEvent) {
+        let Some(tx) = &self.0 else {
+            return;
+        };
+        let reserved = tx.max_capacity() / 4;
+        if is_drop_report(&event) && tx.capacity() <= reserved {
+            // `UpdatesDropped` carries a running total, so a later report makes up for this one.
+            return;
+        }
+        let _ = tx.try_send(event);
+    }
+
+    /// Forward every event from `rx` until it closes.
+    pub fn forward(&self, mut rx: mpsc::Receiver<StreamEvent>) {
+        let sink = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                sink.emit(event);
+            }
+        });
+    }
+}
+
+const fn is_drop_report(event: &StreamEvent) -> bool {
+    matches!(
+        event,
+        StreamEvent::UpdatesDropped { .. }
+            | StreamEvent::OutOfOrderDropped { .. }
+            | StreamEvent::UnassignedDropped { .. }
+    )
+}
+
+/// Derives failover, coverage and backoff events from successive supervisor states.
+///
+/// A symbol is served by the first active provider streaming it and covered while a provider
+/// streams it or is connecting for it.
+pub struct CoverageTracker {
+    names: Vec<&'static str>,
+    /// Provider index that most recently served each symbol.
+    last_served: HashMap<Symbol, usize>,
+    /// Symbols covered at the last update.
+    covered: HashSet<Symbol>,
+    /// Required symbols that were not covered at the last update.
+    waiting: HashSet<Symbol>,
+}
+
+impl CoverageTracker {
+    pub fn new(names: Vec<&'static str>) -> Self {
+        Self {
+            names,
+            last_served: HashMap::new(),
+            covered: HashSet::new(),
+            waiting: HashSet::new(),
+        }
+    }
+
+    /// Compare `supervisor` with the previous state and report what changed.
+    ///
+    /// `rearmed` says whether the backoff timer was just scheduled, and `next_attempt` is the
+    /// time until it fires.
+    pub fn update(
+        &mut self,
+        supervisor: &Supervisor,
+        rearmed: bool,
+        next_attempt: Duration,
+        events: &EventSink,
+    ) {
+        let mut served: Vec<(&Symbol, usize)> = Vec::new();
+        let mut covered: HashSet<Symbol> = HashSet::new();
+        for (id, state) in supervisor.providers.iter().enumerate() {
+            match state {
+                ProviderState::Active { symbols, .. } => {
+                    for sym in symbols.iter() {
+                        if !served.iter().any(|(s, _)| *s == sym) {
+                            served.push((sym, id));
+                        }
+                    }
+                    covered.extend(symbols.iter().cloned());
+                }
+                ProviderState::Connecting { symbols } => covered.extend(symbols.iter().cloned()),
+                _ => {}
+            }
+        }
+
+        let mut moves: Vec<(usize, usize, Vec<Symbol>)> = Vec::new();
+        for (sym, to) in served {
+            if let Some(from) = self.last_served.insert(sym.clone(), to)
+                && from != to
+            {
+                match moves.iter_mut().find(|(f, t, _)| *f == from && *t == to) {
+                    Some((_, _, symbols)) => symbols.push(sym.clone()),
+                    None => moves.push((from, to, vec![sym.clone()])),
+                }
+            }
+        }
+        for (from, to, symbols) in moves {
+            events.emit(StreamEvent::Failover {
+                from: self.names[from],
+                to: self.names[to],
+                symbols,
+            });
+        }
+
+        let mut waiting: Vec<Symbol> = supervisor
+            .required_symbols
+            .iter()
+            .filter(|sym| !covered.contains(*sym))
+            .cloned()
+            .collect();
+        waiting.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        let lost: Vec<Symbol> = waiting
+            .iter()
+            .filter(|sym| self.covered.contains(*sym))
+            .cloned()
+            .collect();
+        if !lost.is_empty() {
+            events.emit(StreamEvent::CoverageLost { symbols: lost });
+        }
+
+        let waiting_set: HashSet<Symbol> = waiting.iter().cloned().collect();
+        if !waiting.is_empty() && (rearmed || waiting_set != self.waiting) {
+            events.emit(StreamEvent::BackoffScheduled {
+                delay: next_attempt,
+                symbols: waiting,
+            });
+        }
+
+        self.last_served
+            .retain(|sym, _| supervisor.required_symbols.contains(sym));
+        self.covered = covered;
+        self.waiting = waiting_set;
+    }
+}
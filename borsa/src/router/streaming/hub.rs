@@ -0,0 +1,340 @@
+//! Shared upstream streams fanned out to many consumers.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+use borsa_core::{
+    BorsaError, Capability, Instrument, OptionUpdate, OverflowPolicy, QuoteUpdate, Symbol,
+};
+use tokio::sync::mpsc;
+
+use super::StreamUpdateKind;
+use super::events::EventSink;
+use super::output::spawn_output;
+use super::planner::EligibleFn;
+use super::subscription::StreamSubscription;
+use crate::Borsa;
+use crate::core::symbol_opt;
+
+/// Shared streams owned by one [`Borsa`], one per capability.
+pub struct StreamHub {
+    pub(crate) quotes: Arc<HubInner<QuoteUpdate>>,
+    pub(crate) options: Arc<HubInner<OptionUpdate>>,
+}
+
+impl Default for StreamHub {
+    fn default() -> Self {
+        Self {
+            quotes: Arc::new(HubInner::new(
+                Capability::StreamQuotes,
+                Borsa::eligible_stream_providers_for_context,
+            )),
+            options: Arc::new(HubInner::new(
+                Capability::StreamOptions,
+                Borsa::eligible_option_stream_providers_for_context,
+            )),
+        }
+    }
+}
+
+struct Consumer<T> {
+    /// Upstream generation the consumer was registered with
+    generation: u64,
+    instruments: Vec<Instrument>,
+    symbols: HashSet<Symbol>,
+    /// Queue of the consumer's forwarding task (see [`spawn_forward`])
+    tx: mpsc::UnboundedSender<T>,
+}
+
+type Consumers<T> = Arc<Mutex<HashMap<u64, Consumer<T>>>>;
+
+struct HubState {
+    /// Started with the first consumer and kept until it ends; the next consumer after
+    /// that starts a new one.
+    upstream: Option<StreamSubscription>,
+    /// Incremented each time an upstream is started
+    generation: u64,
+    /// Number of consumers subscribed to each instrument of the current upstream
+    refs: HashMap<Instrument, usize>,
+}
+
+/// One upstream subscription for a capability and the consumers reading from it.
+pub(crate) struct HubInner<T> {
+    capability: Capability,
+    eligible_fn: EligibleFn,
+    state: tokio::sync::Mutex<HubState>,
+    consumers: Consumers<T>,
+    next_id: AtomicU64,
+}
+
+impl<T> HubInner<T>
+where
+    T: StreamUpdateKind<Context = ()> + Clone,
+{
+    fn new(capability: Capability, eligible_fn: EligibleFn) -> Self {
+        Self {
+            capability,
+            eligible_fn,
+            state: tokio::sync::Mutex::new(HubState {
+                upstream: None,
+                generation: 0,
+                refs: HashMap::new(),
+            }),
+            consumers: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Register a consumer for `instruments`, adding instruments no one else reads upstream.
+    pub(crate) async fn subscribe(
+        self: &Arc<Self>,
+        borsa: &Borsa,
+        instruments: &[Instrument],
+        overflow: OverflowPolicy,
+    ) -> Result<(SharedSubscription<T>, mpsc::Receiver<T>), BorsaError> {
+        if instruments.is_empty() {
+            return Err(BorsaError::InvalidArg(
+                "instruments list cannot be empty".into(),
+            ));
+        }
+        let mut unique: Vec<Instrument> = Vec::with_capacity(instruments.len());
+        for inst in instruments {
+            if !unique.contains(inst) {
+                unique.push(inst.clone());
+            }
+        }
+
+        let mut state = self.state.lock().await;
+        // An upstream that ended serves no one; its consumers are closed by its dispatcher.
+        if state
+            .upstream
+            .as_ref()
+            .is_some_and(StreamSubscription::is_finished)
+        {
+            state.upstream = None;
+            state.refs.clear();
+        }
+        let new: Vec<Instrument> = unique
+            .iter()
+            .filter(|inst| !state.refs.contains_key(*inst))
+            .cloned()
+            .collect();
+        if let Some(upstream) = &state.upstream {
+            if !new.is_empty() {
+                upstream.add(&new).await?;
+            }
+        } else {
+            let (upstream, rx) = borsa
+                .subscribe_updates_with_backoff::<T>(
+                    &new,
+                    (),
+                    None,
+                    self.capability,
+                    self.eligible_fn,
+                    // Consumers apply their own policies downstream of the dispatcher.
+                    OverflowPolicy::Block,
+                )
+                .await?;
+            state.generation += 1;
+            spawn_dispatch(
+                rx,
+                Arc::clone(&self.consumers),
+                Arc::downgrade(self),
+                state.generation,
+            );
+            state.upstream = Some(upstream);
+        }
+        for inst in &unique {
+            *state.refs.entry(inst.clone()).or_default() += 1;
+        }
+
+        // Registered while the state lock is held so no update for a new instrument is missed.
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (tx, rx) = spawn_output::<T>(
+            borsa.cfg.stream_channel_capacity,
+            overflow,
+            EventSink::default(),
+            Arc::clone(&dropped),
+        );
+        self.consumers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                id,
+                Consumer {
+                    generation: state.generation,
+                    symbols: unique.iter().filter_map(symbol_opt).cloned().collect(),
+                    instruments: unique.clone(),
+                    tx: spawn_forward(tx),
+                },
+            );
+        drop(state);
+
+        Ok((
+            SharedSubscription {
+                id,
+                instruments: unique,
+                dropped,
+                hub: Some(Arc::downgrade(self)),
+            },
+            rx,
+        ))
+    }
+}
+
+impl<T> HubInner<T> {
+    /// Remove a consumer and drop instruments no remaining consumer reads from upstream.
+    async fn release(&self, id: u64) {
+        let Some(consumer) = self
+            .consumers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&id)
+        else {
+            return;
+        };
+        let mut state = self.state.lock().await;
+        if consumer.generation != state.generation {
+            // Its upstream already ended.
+            return;
+        }
+        let mut unused = Vec::new();
+        for inst in consumer.instruments {
+            if let Some(count) = state.refs.get_mut(&inst) {
+                *count -= 1;
+                if *count == 0 {
+                    state.refs.remove(&inst);
+                    unused.push(inst);
+                }
+            }
+        }
+        // The lock is held so a concurrent subscribe cannot re-add an instrument before it
+        // is removed. The upstream sessions this waits on never wait on consumers, because
+        // the dispatcher only queues updates.
+        if !unused.is_empty()
+            && let Some(upstream) = &state.upstream
+        {
+            let _ = upstream.remove(&unused).await;
+        }
+    }
+
+    /// Forget the upstream of `generation` once it ended, so the next consumer starts anew.
+    async fn upstream_ended(&self, generation: u64) {
+        let mut state = self.state.lock().await;
+        if state.generation == generation {
+            state.upstream = None;
+            state.refs.clear();
+        }
+        self.consumers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|_, c| c.generation != generation);
+    }
+}
+
+/// Copy each upstream update to the consumers subscribed to its symbol.
+///
+/// When the upstream ends, its consumers are dropped, which closes their receivers, and
+/// the hub is cleared so the next subscription starts a new upstream.
+fn spawn_dispatch<T>(
+    mut rx: mpsc::Receiver<T>,
+    consumers: Consumers<T>,
+    hub: Weak<HubInner<T>>,
+    generation: u64,
+) where
+    T: StreamUpdateKind<Context = ()> + Clone,
+{
+    tokio::spawn(async move {
+        while let Some(update) = rx.recv().await {
+            let targets: Vec<_> = consumers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .filter(|(_, c)| {
+                    c.generation == generation && c.symbols.contains(update.stream_symbol())
+                })
+                .map(|(id, c)| (*id, c.tx.clone()))
+                .collect();
+            for (id, tx) in targets {
+                let open = tx.send(update.clone()).is_ok();
+                // Released on a separate task: the hub lock may be held by a subscribe that
+                // waits on the upstream sessions, which in turn wait on this loop.
+                if !open && let Some(inner) = hub.upgrade() {
+                    tokio::spawn(async move { inner.release(id).await });
+                }
+            }
+        }
+        if let Some(inner) = hub.upgrade() {
+            inner.upstream_ended(generation).await;
+        } else {
+            consumers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clear();
+        }
+    });
+}
+
+/// Forward one consumer's updates to its output.
+///
+/// The dispatcher only queues updates here, so a `Block` consumer that falls behind holds
+/// back its own queue instead of every consumer. The task ends, closing the queue, once the
+/// consumer drops its receiver.
+fn spawn_forward<T: Send + 'static>(out: mpsc::Sender<T>) -> mpsc::UnboundedSender<T> {
+    let (tx, mut queue) = mpsc::unbounded_channel::<T>();
+    tokio::spawn(async move {
+        while let Some(update) = queue.recv().await {
+            if out.send(update).await.is_err() {
+                break;
+            }
+        }
+    });
+    tx
+}
+
+/// One consumer's share of a hub stream.
+///
+/// Returned by [`Borsa::share_quotes`] and [`Borsa::share_options`]. Closing or dropping it,
+/// or dropping its receiver, releases its instruments; an instrument is removed from the
+/// upstream stream once no consumer reads it. If the upstream stream ends, every consumer's
+/// receiver closes and the next share call starts a new upstream.
+pub struct SharedSubscription<T: Send + 'static> {
+    id: u64,
+    instruments: Vec<Instrument>,
+    dropped: Arc<AtomicU64>,
+    hub: Option<Weak<HubInner<T>>>,
+}
+
+impl<T: Send + 'static> SharedSubscription<T> {
+    /// Instruments this consumer receives updates for.
+    #[must_use]
+    pub fn instruments(&self) -> &[Instrument] {
+        &self.instruments
+    }
+
+    /// Number of updates discarded because this consumer's channel was full.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Release this consumer and wait until the upstream stream has been updated.
+    pub async fn close(mut self) {
+        if let Some(hub) = self.hub.take().and_then(|hub| hub.upgrade()) {
+            hub.release(self.id).await;
+        }
+    }
+}
+
+impl<T: Send + 'static> Drop for SharedSubscription<T> {
+    fn drop(&mut self) {
+        if let Some(hub) = self.hub.take().and_then(|hub| hub.upgrade())
+            && let Ok(runtime) = tokio::runtime::Handle::try_current()
+        {
+            let id = self.id;
+            runtime.spawn(async move { hub.release(id).await });
+        }
+    }
+}
@@ -1,10 +1,16 @@
 pub mod backoff;
 pub mod controller;
 pub mod error;
+pub mod events;
 pub mod filters;
+pub mod hub;
+pub mod output;
 pub mod planner;
 pub mod session;
+pub mod staleness;
+pub mod subscription;
 pub mod supervisor_sm;
+pub mod synth;
 
 pub use controller::{KindSupervisorParams, spawn_kind_supervisor};
 pub use error::collapse_stream_errors;
@@ -23,6 +29,13 @@ pub trait StreamableUpdate: Send + 'static {
     fn stream_symbol(&self) -> &Symbol;
     /// Update timestamp for monotonic enforcement.
     fn stream_ts(&self) -> DateTime<Utc>;
+    /// Whether this update supersedes `pending` under [`OverflowPolicy::Conflate`]
+    /// (default: same symbol).
+    ///
+    /// [`OverflowPolicy::Conflate`]: borsa_core::OverflowPolicy::Conflate
+    fn supersedes(&self, pending: &Self) -> bool {
+        self.stream_symbol() == pending.stream_symbol()
+    }
 }
 
 impl StreamableUpdate for QuoteUpdate {
@@ -67,6 +80,14 @@ impl StreamableUpdate for CandleUpdate {
     fn stream_ts(&self) -> DateTime<Utc> {
         self.candle.ts
     }
+    /// Only a later state of the same bar supersedes it, so a final bar is never replaced
+    /// by the next bar's first update.
+    fn supersedes(&self, pending: &Self) -> bool {
+        self.stream_symbol() == pending.stream_symbol()
+            && self.interval == pending.interval
+            && self.candle.ts == pending.candle.ts
+            && (self.is_final || !pending.is_final)
+    }
 }
 
 /// Adapter trait to start a stream for a given update type.
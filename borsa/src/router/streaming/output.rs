@@ -0,0 +1,98 @@
+//! Bounded stream outputs that apply an [`OverflowPolicy`] when the consumer falls behind.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use borsa_core::{OverflowPolicy, Symbol, stream::StreamEvent};
+use tokio::sync::mpsc;
+
+use super::StreamableUpdate;
+use super::events::EventSink;
+
+/// Updates waiting for the consumer.
+struct Backlog<T> {
+    queue: VecDeque<T>,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+impl<T: StreamableUpdate> Backlog<T> {
+    /// Whether another update may be taken from the producers.
+    fn accepts(&self) -> bool {
+        self.policy != OverflowPolicy::Block || self.queue.len() < self.capacity
+    }
+
+    /// Queue `update`, returning the update discarded to make room, if any.
+    fn push(&mut self, update: T) -> Option<T> {
+        if self.policy == OverflowPolicy::Conflate
+            && let Some(pending) = self.queue.iter_mut().find(|u| update.supersedes(u))
+        {
+            return Some(std::mem::replace(pending, update));
+        }
+        if self.queue.len() < self.capacity {
+            self.queue.push_back(update);
+            return None;
+        }
+        if self.policy == OverflowPolicy::DropNewest {
+            return Some(update);
+        }
+        let oldest = self.queue.pop_front();
+        self.queue.push_back(update);
+        oldest
+    }
+}
+
+/// Create a stream output buffering up to `capacity` updates for the consumer.
+///
+/// Producers send on the returned sender and the consumer reads the returned receiver. A task
+/// between them holds the backlog and applies `policy` once it is full. Discarded updates are
+/// added to `dropped` and reported per symbol on `events`. The task ends when the consumer
+/// drops its receiver, or once every sender is gone and the backlog has been delivered.
+pub fn spawn_output<T: StreamableUpdate>(
+    capacity: usize,
+    policy: OverflowPolicy,
+    events: EventSink,
+    dropped: Arc<AtomicU64>,
+) -> (mpsc::Sender<T>, mpsc::Receiver<T>) {
+    let (tx, mut inbox) = mpsc::channel::<T>(1);
+    let (out, rx) = mpsc::channel::<T>(1);
+    tokio::spawn(async move {
+        let mut backlog = Backlog {
+            queue: VecDeque::new(),
+            capacity,
+            policy,
+        };
+        let mut totals: HashMap<Symbol, u64> = HashMap::new();
+        let mut open = true;
+        while open || !backlog.queue.is_empty() {
+            tokio::select! {
+                biased;
+                permit = out.reserve(), if !backlog.queue.is_empty() => {
+                    let Ok(permit) = permit else { break };
+                    if let Some(update) = backlog.queue.pop_front() {
+                        permit.send(update);
+                    }
+                }
+                update = inbox.recv(), if open && backlog.accepts() => {
+                    let Some(update) = update else {
+                        open = false;
+                        continue;
+                    };
+                    let Some(lost) = backlog.push(update) else {
+                        continue;
+                    };
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                    let total = totals.entry(lost.stream_symbol().clone()).or_default();
+                    *total += 1;
+                    events.emit(StreamEvent::UpdatesDropped {
+                        symbol: lost.stream_symbol().clone(),
+                        total: *total,
+                    });
+                }
+                () = out.closed(), if backlog.queue.is_empty() => break,
+            }
+        }
+    });
+    (tx, rx)
+}
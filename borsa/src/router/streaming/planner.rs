@@ -15,6 +15,35 @@ pub struct EligibleStreamProviders {
     pub union_symbols: HashSet<Symbol>,
 }
 
+/// Resolves eligible providers for one `(kind, exchange)` group of instruments.
+pub type EligibleFn = fn(
+    &Borsa,
+    AssetKind,
+    Option<&Exchange>,
+    &[Instrument],
+) -> Result<EligibleStreamProviders, BorsaError>;
+
+/// Identity of a kind supervisor: its group plus the provider chain it walks.
+///
+/// Two plans with the same key can be applied to the same running supervisor by replanning
+/// its assignment tables instead of respawning it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SupervisorKey {
+    pub kind: AssetKind,
+    pub exchange: Option<Exchange>,
+    /// Provider names in supervisor order (primary first in per-symbol routing mode)
+    pub chain: Vec<&'static str>,
+}
+
+/// Assignment tables for one kind supervisor, aligned by index with `providers`
+pub struct SupervisorPlan {
+    pub key: SupervisorKey,
+    pub providers: Vec<Arc<dyn BorsaConnector>>,
+    pub provider_instruments: Vec<Vec<Instrument>>,
+    pub provider_allow: Vec<HashSet<Symbol>>,
+    pub required_symbols: HashSet<Symbol>,
+}
+
 type StreamProviderScore = (usize, usize, Arc<dyn BorsaConnector>, HashSet<Symbol>);
 
 impl Borsa {
@@ -45,7 +74,8 @@ impl Borsa {
                     borsa_core::IdentifierScheme::Prediction(_) => None,
                 };
                 let ctx =
-                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()));
+                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()))
+                        .with_capability(Capability::StreamQuotes);
                 let any_allowed = candidates.iter().any(|c| {
                     self.cfg
                         .routing_policy
@@ -94,7 +124,8 @@ impl Borsa {
                     borsa_core::IdentifierScheme::Prediction(_) => None,
                 };
                 let ctx =
-                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()));
+                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()))
+                        .with_capability(Capability::StreamCandles);
                 let any_allowed = candidates.iter().any(|c| {
                     self.cfg
                         .routing_policy
@@ -145,7 +176,8 @@ impl Borsa {
                     borsa_core::IdentifierScheme::Prediction(_) => None,
                 };
                 let ctx =
-                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()));
+                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()))
+                        .with_capability(Capability::StreamOptions);
                 let any_allowed = candidates.iter().any(|c| {
                     self.cfg
                         .routing_policy
@@ -198,7 +230,8 @@ impl Borsa {
                     borsa_core::IdentifierScheme::Prediction(_) => None,
                 };
                 let ctx =
-                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()));
+                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()))
+                        .with_capability(Capability::StreamCandles);
                 if let Some((rank, _strict)) = self
                     .cfg
                     .routing_policy
@@ -271,7 +304,8 @@ impl Borsa {
                     borsa_core::IdentifierScheme::Prediction(_) => None,
                 };
                 let ctx =
-                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()));
+                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()))
+                        .with_capability(Capability::StreamQuotes);
                 if let Some((rank, _strict)) = self
                     .cfg
                     .routing_policy
@@ -344,7 +378,8 @@ impl Borsa {
                     borsa_core::IdentifierScheme::Prediction(_) => None,
                 };
                 let ctx =
-                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()));
+                    RoutingContext::new(sym_opt, Some(kind), ex_opt.or_else(|| exchange.cloned()))
+                        .with_capability(Capability::StreamOptions);
                 if let Some((rank, _strict)) = self
                     .cfg
                     .routing_policy
@@ -2,24 +2,38 @@ use std::collections::HashSet;
 use std::sync::Arc;
 
 use borsa_core::Symbol;
-use borsa_core::stream::StreamHandle;
+use borsa_core::stream::{StreamEvent, StreamHandle};
 use tokio::sync::{mpsc, oneshot, watch};
 use tokio::task::JoinHandle;
 
 use super::StreamableUpdate;
+use super::events::EventSink;
 use super::filters::MonotonicGate;
+use super::staleness::StalenessWatch;
 
 pub struct SpawnedSession {
     pub join: JoinHandle<()>,
     pub stop_tx: Option<oneshot::Sender<()>>,
 }
 
+/// Report from a session task to its supervisor.
+pub enum SessionReport {
+    /// The session ended.
+    Ended(Arc<[Symbol]>),
+    /// The session is still running but stopped delivering updates for `stale`.
+    Stale {
+        symbols: Arc<[Symbol]>,
+        stale: Vec<Symbol>,
+    },
+}
+
 pub struct SessionManager;
 
 impl SessionManager {
     #[allow(clippy::too_many_arguments)]
     pub fn spawn<T: StreamableUpdate>(
         session_index: usize,
+        provider: &'static str,
         handle: StreamHandle,
         mut prx: mpsc::Receiver<T>,
         allowed: Option<HashSet<Symbol>>,
@@ -27,8 +41,10 @@ impl SessionManager {
         enforce_monotonic: bool,
         monotonic_gate: Option<Arc<MonotonicGate>>,
         tx_out: mpsc::Sender<T>,
-        event_tx: tokio::sync::mpsc::UnboundedSender<(usize, Arc<[Symbol]>)>,
+        event_tx: tokio::sync::mpsc::UnboundedSender<(usize, SessionReport)>,
         session_symbols: Arc<[Symbol]>,
+        events: EventSink,
+        mut staleness: Option<StalenessWatch>,
     ) -> SpawnedSession {
         let (session_stop_tx, mut session_stop_rx) = oneshot::channel::<()>();
 
@@ -42,6 +58,9 @@ impl SessionManager {
             let mut provider_handle = Some(handle);
             let mut notify_session_end = true;
             let mut reset_monotonic = false;
+            let mut stale_check = staleness
+                .as_ref()
+                .map(|watch| tokio::time::interval(watch.period()));
             loop {
                 tokio::select! {
                     biased;
@@ -65,6 +84,10 @@ impl SessionManager {
                                 && !allowset.contains(u.stream_symbol()) {
                                     #[cfg(feature = "tracing")]
                                     tracing::warn!(symbol = %u.stream_symbol(), provider_index = session_index, "dropping update for unassigned symbol");
+                                    events.emit(StreamEvent::UnassignedDropped {
+                                        provider,
+                                        symbol: u.stream_symbol().clone(),
+                                    });
                                     continue;
                                 }
 
@@ -73,10 +96,19 @@ impl SessionManager {
                                 if !gate.allow(u.stream_symbol().as_str().to_string(), u.stream_ts()).await {
                                     #[cfg(feature = "tracing")]
                                     tracing::warn!(symbol = %u.stream_symbol(), ts = %u.stream_ts(), provider_index = session_index, "dropping out-of-order stream update (monotonic)");
+                                    events.emit(StreamEvent::OutOfOrderDropped {
+                                        provider,
+                                        symbol: u.stream_symbol().clone(),
+                                        ts: u.stream_ts(),
+                                    });
                                     continue;
                                 }
                             }
 
+                            if let Some(watch) = staleness.as_mut() {
+                                watch.observe(u.stream_symbol(), std::time::Instant::now());
+                            }
+
                             if tx_out.send(u).await.is_err() {
                                 // Downstream dropped
                                 notify_session_end = false;
@@ -89,6 +121,19 @@ impl SessionManager {
                             break;
                         }
                     }
+                    _ = async { stale_check.as_mut().unwrap().tick().await }, if stale_check.is_some() => {
+                        let stale = staleness
+                            .as_mut()
+                            .and_then(|watch| watch.check(chrono::Utc::now(), std::time::Instant::now()));
+                        if let Some(stale) = stale {
+                            // Reported once; the supervisor stops the session.
+                            stale_check = None;
+                            let _ = event_tx.send((
+                                session_index,
+                                SessionReport::Stale { symbols: Arc::clone(&session_symbols), stale },
+                            ));
+                        }
+                    }
                 }
             }
 
@@ -100,7 +145,10 @@ impl SessionManager {
             }
 
             if notify_session_end {
-                let _ = event_tx.send((session_index, Arc::clone(&session_symbols)));
+                let _ = event_tx.send((
+                    session_index,
+                    SessionReport::Ended(Arc::clone(&session_symbols)),
+                ));
             }
         });
 
@@ -0,0 +1,90 @@
+//! Detection of sessions that stay connected but stop delivering updates.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use borsa_core::{StalenessThresholds, Symbol, TradingHours};
+use chrono::{DateTime, Utc};
+
+/// Shortest interval between checks.
+const MIN_CHECK_PERIOD: Duration = Duration::from_millis(10);
+
+/// Tracks when a session last delivered updates, per symbol and overall.
+pub struct StalenessWatch {
+    symbol: Option<Duration>,
+    session: Option<Duration>,
+    hours: Option<TradingHours>,
+    last_seen: HashMap<Symbol, Instant>,
+    last_any: Instant,
+}
+
+impl StalenessWatch {
+    /// Start watching `symbols` of a session that starts now, or `None` without thresholds.
+    pub fn new(thresholds: &StalenessThresholds, symbols: &[Symbol]) -> Option<Self> {
+        if !thresholds.is_enabled() {
+            return None;
+        }
+        let now = Instant::now();
+        Some(Self {
+            symbol: thresholds.symbol,
+            session: thresholds.session,
+            hours: thresholds.hours.clone(),
+            last_seen: symbols.iter().map(|s| (s.clone(), now)).collect(),
+            last_any: now,
+        })
+    }
+
+    /// How often [`check`](Self::check) should run: a quarter of the shortest threshold.
+    pub fn period(&self) -> Duration {
+        self.symbol
+            .into_iter()
+            .chain(self.session)
+            .min()
+            .map_or(MIN_CHECK_PERIOD, |d| d / 4)
+            .max(MIN_CHECK_PERIOD)
+    }
+
+    /// Record an update delivered for `symbol`.
+    pub fn observe(&mut self, symbol: &Symbol, now: Instant) {
+        if let Some(seen) = self.last_seen.get_mut(symbol) {
+            *seen = now;
+        }
+        self.last_any = now;
+    }
+
+    /// Return the symbols the session let go stale, if any.
+    ///
+    /// A session-wide breach returns every watched symbol. While `hours` are closed the clocks
+    /// are held at `now`, so a market opening does not count the night as silence.
+    pub fn check(&mut self, wall: DateTime<Utc>, now: Instant) -> Option<Vec<Symbol>> {
+        if self.hours.as_ref().is_some_and(|h| !h.is_open(wall)) {
+            self.last_any = now;
+            for seen in self.last_seen.values_mut() {
+                *seen = now;
+            }
+            return None;
+        }
+
+        if self
+            .session
+            .is_some_and(|limit| now.duration_since(self.last_any) >= limit)
+        {
+            let mut all: Vec<Symbol> = self.last_seen.keys().cloned().collect();
+            all.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+            return Some(all);
+        }
+
+        let limit = self.symbol?;
+        let mut stale: Vec<Symbol> = self
+            .last_seen
+            .iter()
+            .filter(|(_, seen)| now.duration_since(**seen) >= limit)
+            .map(|(sym, _)| sym.clone())
+            .collect();
+        if stale.is_empty() {
+            return None;
+        }
+        stale.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        Some(stale)
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use borsa_core::{
+    AssetKind, BorsaError, Capability, Exchange, Instrument, Symbol,
+    stream::{StreamEvent, StreamHandle},
+};
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+
+use super::StreamUpdateKind;
+use super::controller::{KindSupervisorParams, SupervisorReplan, spawn_kind_supervisor};
+use super::error::collapse_stream_errors;
+use super::events::EventSink;
+use super::filters::MonotonicGate;
+use super::planner::{EligibleFn, SupervisorKey, SupervisorPlan};
+use crate::{BackoffConfig, Borsa};
+
+type Reply = oneshot::Sender<Result<(), BorsaError>>;
+
+enum Command {
+    Add(Vec<Instrument>, Reply),
+    Remove(Vec<Instrument>, Reply),
+}
+
+/// Handle to a running stream whose instrument set can change while it runs.
+///
+/// Returned by [`Borsa::subscribe_quotes`]. Updates for every subscribed instrument arrive on
+/// the receiver returned alongside the subscription. Adding or removing instruments re-plans
+/// routing for the combined set and restarts only the provider sessions whose assignment
+/// changed. Dropping the subscription stops all sessions.
+pub struct StreamSubscription {
+    handle: StreamHandle,
+    commands: mpsc::Sender<Command>,
+}
+
+impl StreamSubscription {
+    /// Add instruments to the subscription. Instruments already subscribed are ignored.
+    ///
+    /// # Errors
+    /// Returns an error if strict routing rules reject a new symbol, if no provider can be
+    /// started for a newly required group, or if the subscription has stopped. On error the
+    /// previous instrument set stays in effect.
+    pub async fn add(&self, instruments: &[Instrument]) -> Result<(), BorsaError> {
+        let (reply, rx) = oneshot::channel();
+        self.request(Command::Add(instruments.to_vec(), reply), rx)
+            .await
+    }
+
+    /// Remove instruments from the subscription. Instruments not subscribed are ignored.
+    ///
+    /// Removing every instrument keeps the subscription and its receiver open until more
+    /// instruments are added.
+    ///
+    /// # Errors
+    /// Returns an error if the subscription has stopped.
+    pub async fn remove(&self, instruments: &[Instrument]) -> Result<(), BorsaError> {
+        let (reply, rx) = oneshot::channel();
+        self.request(Command::Remove(instruments.to_vec(), reply), rx)
+            .await
+    }
+
+    /// Gracefully stop all sessions and wait for them to finish.
+    pub async fn stop(self) {
+        self.handle.stop().await;
+    }
+
+    /// Force-abort the subscription without waiting for sessions to finish.
+    pub fn abort(self) {
+        self.handle.abort();
+    }
+
+    /// Return `true` if the subscription has terminated.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Take the receiver of [`StreamEvent`]s reported by the subscription.
+    ///
+    /// Returns `None` once taken. See [`StreamHandle::take_events`].
+    pub const fn take_events(&mut self) -> Option<mpsc::Receiver<StreamEvent>> {
+        self.handle.take_events()
+    }
+
+    pub(crate) fn into_handle(self) -> StreamHandle {
+        self.handle
+    }
+
+    pub(crate) fn with_events(self, events: mpsc::Receiver<StreamEvent>) -> Self {
+        Self {
+            handle: self.handle.with_events(events),
+            commands: self.commands,
+        }
+    }
+
+    async fn request(
+        &self,
+        command: Command,
+        rx: oneshot::Receiver<Result<(), BorsaError>>,
+    ) -> Result<(), BorsaError> {
+        let closed = || BorsaError::Other("stream subscription has stopped".into());
+        self.commands.send(command).await.map_err(|_| closed())?;
+        rx.await.map_err(|_| closed())?
+    }
+}
+
+struct RunningSupervisor {
+    join: JoinHandle<()>,
+    stop_tx: watch::Sender<bool>,
+    replan_tx: mpsc::UnboundedSender<SupervisorReplan>,
+    provider_instruments: Vec<Vec<Instrument>>,
+    provider_allow: Vec<HashSet<Symbol>>,
+    required_symbols: HashSet<Symbol>,
+}
+
+impl RunningSupervisor {
+    async fn stop(self) {
+        let _ = self.stop_tx.send(true);
+        let _ = self.join.await;
+    }
+
+    fn abort(self) {
+        let _ = self.stop_tx.send(true);
+        self.join.abort();
+    }
+}
+
+/// Owns the kind supervisors behind one output channel and reconciles them with the
+/// subscribed instrument set.
+pub struct SubscriptionManager<T: StreamUpdateKind> {
+    borsa: Arc<Borsa>,
+    context: T::Context,
+    capability: Capability,
+    eligible_fn: EligibleFn,
+    backoff: BackoffConfig,
+    tx: mpsc::Sender<T>,
+    events: EventSink,
+    instruments: Vec<Instrument>,
+    running: HashMap<SupervisorKey, RunningSupervisor>,
+    /// Monotonic gates per `(kind, exchange, provider)`, shared across supervisor respawns
+    gates: HashMap<(AssetKind, Option<Exchange>, &'static str), Arc<MonotonicGate>>,
+}
+
+impl<T: StreamUpdateKind> SubscriptionManager<T> {
+    pub fn new(
+        borsa: Arc<Borsa>,
+        context: T::Context,
+        capability: Capability,
+        eligible_fn: EligibleFn,
+        backoff: BackoffConfig,
+        tx: mpsc::Sender<T>,
+        events: EventSink,
+    ) -> Self {
+        Self {
+            borsa,
+            context,
+            capability,
+            eligible_fn,
+            backoff,
+            tx,
+            events,
+            instruments: Vec::new(),
+            running: HashMap::new(),
+            gates: HashMap::new(),
+        }
+    }
+
+    /// Reconcile running supervisors with `instruments`.
+    ///
+    /// Supervisors whose key is unchanged receive a replan, new keys are spawned and awaited,
+    /// and keys no longer planned are stopped. If planning or any new supervisor fails, nothing
+    /// already running is touched.
+    ///
+    /// # Errors
+    /// Returns planning errors (e.g. strict symbol rejection) and startup failures of newly
+    /// spawned supervisors.
+    pub async fn apply(&mut self, instruments: Vec<Instrument>) -> Result<(), BorsaError> {
+        let plans = if instruments.is_empty() {
+            Vec::new()
+        } else {
+            self.borsa
+                .plan_stream_supervisors(&instruments, self.capability, self.eligible_fn)?
+        };
+        if plans.is_empty() && !instruments.is_empty() {
+            return Err(collapse_stream_errors(self.capability, Vec::new()));
+        }
+
+        let mut keep: HashSet<SupervisorKey> = HashSet::new();
+        let mut replans: Vec<SupervisorPlan> = Vec::new();
+        let mut started: Vec<(SupervisorKey, RunningSupervisor)> = Vec::new();
+        let mut init_receivers: Vec<oneshot::Receiver<Result<(), BorsaError>>> = Vec::new();
+        for plan in plans {
+            keep.insert(plan.key.clone());
+            if self.running.contains_key(&plan.key) {
+                replans.push(plan);
+            } else {
+                let key = plan.key.clone();
+                let (running, init_rx) = self.spawn_supervisor(plan);
+                started.push((key, running));
+                init_receivers.push(init_rx);
+            }
+        }
+
+        let mut init_errors: Vec<BorsaError> = Vec::new();
+        for rx in init_receivers {
+            match rx.await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => init_errors.push(e),
+                Err(_) => init_errors.push(BorsaError::Other(
+                    "stream supervisor dropped before initialization".into(),
+                )),
+            }
+        }
+        if !init_errors.is_empty() {
+            for (_, running) in started {
+                running.abort();
+            }
+            return Err(collapse_stream_errors(self.capability, init_errors));
+        }
+
+        for plan in replans {
+            let Some(running) = self.running.get_mut(&plan.key) else {
+                continue;
+            };
+            if running.provider_instruments == plan.provider_instruments
+                && running.provider_allow == plan.provider_allow
+                && running.required_symbols == plan.required_symbols
+            {
+                continue;
+            }
+            let _ = running.replan_tx.send(SupervisorReplan {
+                provider_instruments: plan.provider_instruments.clone(),
+                provider_allow: plan.provider_allow.clone(),
+                required_symbols: plan.required_symbols.clone(),
+            });
+            running.provider_instruments = plan.provider_instruments;
+            running.provider_allow = plan.provider_allow;
+            running.required_symbols = plan.required_symbols;
+        }
+
+        let obsolete: Vec<SupervisorKey> = self
+            .running
+            .keys()
+            .filter(|key| !keep.contains(*key))
+            .cloned()
+            .collect();
+        for key in obsolete {
+            if let Some(running) = self.running.remove(&key) {
+                running.stop().await;
+            }
+        }
+
+        self.running.extend(started);
+        self.instruments = instruments;
+        Ok(())
+    }
+
+    /// Run the manager in the background and return the caller-facing subscription.
+    pub fn spawn(mut self) -> StreamSubscription {
+        let (commands_tx, mut commands_rx) = mpsc::channel::<Command>(32);
+        let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+        let tx = self.tx.clone();
+
+        let join = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut stop_rx => break,
+                    () = tx.closed() => break,
+                    Some(command) = commands_rx.recv() => self.handle_command(command).await,
+                }
+            }
+            self.shutdown().await;
+        });
+
+        StreamSubscription {
+            handle: StreamHandle::new(join, stop_tx),
+            commands: commands_tx,
+        }
+    }
+
+    async fn handle_command(&mut self, command: Command) {
+        match command {
+            Command::Add(list, reply) => {
+                let mut next = self.instruments.clone();
+                for inst in list {
+                    if !next.contains(&inst) {
+                        next.push(inst);
+                    }
+                }
+                let _ = reply.send(self.apply(next).await);
+            }
+            Command::Remove(list, reply) => {
+                let next: Vec<Instrument> = self
+                    .instruments
+                    .iter()
+                    .filter(|inst| !list.contains(inst))
+                    .cloned()
+                    .collect();
+                let _ = reply.send(self.apply(next).await);
+            }
+        }
+    }
+
+    async fn shutdown(self) {
+        for running in self.running.values() {
+            let _ = running.stop_tx.send(true);
+        }
+        for (_, running) in self.running {
+            let _ = running.join.await;
+        }
+    }
+
+    fn spawn_supervisor(
+        &mut self,
+        plan: SupervisorPlan,
+    ) -> (RunningSupervisor, oneshot::Receiver<Result<(), BorsaError>>) {
+        let SupervisorPlan {
+            key,
+            providers,
+            provider_instruments,
+            provider_allow,
+            required_symbols,
+        } = plan;
+
+        let enforce_monotonic = self.borsa.cfg.stream_enforce_monotonic_timestamps;
+        let monotonic_gates = enforce_monotonic.then(|| {
+            providers
+                .iter()
+                .map(|p| {
+                    Arc::clone(
+                        self.gates
+                            .entry((key.kind, key.exchange.clone(), p.name()))
+                            .or_insert_with(|| Arc::new(MonotonicGate::new())),
+                    )
+                })
+                .collect()
+        });
+
+        let staleness = &self.borsa.cfg.stream_staleness;
+        let (init_tx, init_rx) = oneshot::channel();
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let (replan_tx, replan_rx) = mpsc::unbounded_channel();
+        let params = KindSupervisorParams {
+            providers,
+            provider_instruments: provider_instruments.clone(),
+            provider_allow: provider_allow.clone(),
+            required_symbols: required_symbols.clone(),
+            min_backoff_ms: self.backoff.min_backoff_ms,
+            max_backoff_ms: self.backoff.max_backoff_ms,
+            factor: self.backoff.factor.max(1),
+            jitter_percent: u32::from(self.backoff.jitter_percent.min(100)),
+            initial_notify: Some(init_tx),
+            enforce_monotonic,
+            capability: self.capability,
+            context: Arc::new(self.context.clone()),
+            replan_rx: Some(replan_rx),
+            monotonic_gates,
+            events: self.events.clone(),
+            staleness: staleness.thresholds_for(key.kind, key.exchange.as_ref()),
+            stale_hold: staleness.hold,
+        };
+        let join = spawn_kind_supervisor::<T>(params, stop_rx, self.tx.clone());
+
+        (
+            RunningSupervisor {
+                join,
+                stop_tx,
+                replan_tx,
+                provider_instruments,
+                provider_allow,
+                required_symbols,
+            },
+            init_rx,
+        )
+    }
+}
@@ -1,6 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use borsa_core::{BorsaError, Capability, Instrument, Symbol};
 use tokio::sync::oneshot;
@@ -41,9 +41,32 @@ pub enum Phase {
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Event {
-    ProviderStartSucceeded { id: usize, symbols: Arc<[Symbol]> },
-    ProviderStartFailed { id: usize, error: BorsaError },
-    SessionEnded { id: usize, symbols: Arc<[Symbol]> },
+    ProviderStartSucceeded {
+        id: usize,
+        symbols: Arc<[Symbol]>,
+    },
+    ProviderStartFailed {
+        id: usize,
+        error: BorsaError,
+    },
+    SessionEnded {
+        id: usize,
+        symbols: Arc<[Symbol]>,
+    },
+    /// A running session stopped delivering updates for `stale`. The provider restarts on its
+    /// remaining symbols, or cools down like after `SessionEnded` when all of them went stale,
+    /// and is passed over for `stale` while the hold lasts.
+    SessionStale {
+        id: usize,
+        symbols: Arc<[Symbol]>,
+        stale: Vec<Symbol>,
+    },
+    /// The subscribed instrument set changed; tables are aligned with the existing providers.
+    Replan {
+        provider_instruments: Vec<Vec<Instrument>>,
+        provider_allow: Vec<HashSet<Symbol>>,
+        required_symbols: HashSet<Symbol>,
+    },
     BackoffTick,
     DownstreamClosed,
     Shutdown,
@@ -54,6 +77,8 @@ pub enum Action {
     RequestStart {
         id: usize,
         instruments: Vec<Instrument>,
+        /// Symbol set recorded in `ProviderState::Connecting` for this request.
+        symbols: Arc<[Symbol]>,
     },
     StopAll,
     AwaitAll,
@@ -67,6 +92,10 @@ pub enum Action {
     PreemptSessions {
         provider_ids: Vec<usize>,
     },
+    /// Stop sessions whose assignment changed; their end events are treated as stale.
+    StopSessions {
+        provider_ids: Vec<usize>,
+    },
 }
 
 #[derive(Debug)]
@@ -91,6 +120,10 @@ pub struct Supervisor {
 
     pub attempted_since_last_tick: bool,
     pub phase: Phase,
+    /// Symbols each provider let go stale, with the instant its hold on them ends.
+    pub stale_holds: Vec<HashMap<Symbol, Instant>>,
+    /// How long a provider is passed over for symbols it let go stale.
+    pub stale_hold: Duration,
 }
 
 impl Supervisor {
@@ -138,12 +171,34 @@ impl Supervisor {
                 self.advance_scan_cursor_for_failure(id);
                 (Self { phase, ..self }, Vec::new())
             }
-            (phase, Event::SessionEnded { id, .. }) => {
-                self.providers[id] = ProviderState::InCooldown {
-                    failed_at: Instant::now(),
-                };
+            (phase, Event::SessionEnded { id, symbols }) => {
+                // Sessions stopped by a replan end after the provider moved on; ignore them.
+                if self.is_current_session(id, &symbols) {
+                    self.providers[id] = ProviderState::InCooldown {
+                        failed_at: Instant::now(),
+                    };
+                }
                 (Self { phase, ..self }, Vec::new())
             }
+            (
+                phase @ (Phase::Startup { .. } | Phase::Running),
+                Event::SessionStale { id, symbols, stale },
+            ) => {
+                let actions = self.handle_session_stale(id, &symbols, stale);
+                (Self { phase, ..self }, actions)
+            }
+            (
+                phase @ (Phase::Startup { .. } | Phase::Running),
+                Event::Replan {
+                    provider_instruments,
+                    provider_allow,
+                    required_symbols,
+                },
+            ) => {
+                let actions =
+                    self.handle_replan(provider_instruments, provider_allow, required_symbols);
+                (Self { phase, ..self }, actions)
+            }
             (phase, Event::BackoffTick) => self.handle_backoff_tick(phase),
             (_, Event::Shutdown | Event::DownstreamClosed) => (
                 Self {
@@ -214,22 +269,15 @@ impl Supervisor {
                 let instruments = self.compute_needed_instruments_for(i);
                 if !instruments.is_empty() {
                     // mark provider as connecting with the planned symbol set
-                    let syms: Arc<[Symbol]> = Arc::from(
-                        instruments
-                            .iter()
-                            .filter_map(|inst| match inst.id() {
-                                borsa_core::IdentifierScheme::Security(sec) => {
-                                    Some(sec.symbol.clone())
-                                }
-                                borsa_core::IdentifierScheme::Prediction(_) => None,
-                            })
-                            .collect::<Vec<_>>()
-                            .into_boxed_slice(),
-                    );
+                    let syms = symbols_of(&instruments);
                     self.providers[i] = ProviderState::Connecting {
                         symbols: Arc::clone(&syms),
                     };
-                    actions.push(Action::RequestStart { id: i, instruments });
+                    actions.push(Action::RequestStart {
+                        id: i,
+                        instruments,
+                        symbols: syms,
+                    });
                 }
             }
             if !first && i == start {
@@ -319,6 +367,10 @@ impl Supervisor {
             return false;
         }
 
+        if self.is_held(provider_id, sym) && self.has_unheld_alternative(provider_id, sym) {
+            return false;
+        }
+
         let already_covered = self.compute_coverage_count(sym) > 0;
         if !already_covered {
             return true;
@@ -327,6 +379,134 @@ impl Supervisor {
         !self.provider_has_symbol_before(provider_id, sym)
     }
 
+    fn is_held(&self, provider_id: usize, sym: &Symbol) -> bool {
+        self.stale_holds
+            .get(provider_id)
+            .and_then(|holds| holds.get(sym))
+            .is_some_and(|until| *until > Instant::now())
+    }
+
+    /// Whether a provider other than `provider_id` may stream `sym` without being held.
+    fn has_unheld_alternative(&self, provider_id: usize, sym: &Symbol) -> bool {
+        self.provider_allow.iter().enumerate().any(|(j, allow)| {
+            j != provider_id
+                && allow.contains(sym)
+                && self.can_provider_stream(j)
+                && !self.is_held(j, sym)
+        })
+    }
+
+    fn handle_session_stale(
+        &mut self,
+        id: usize,
+        symbols: &Arc<[Symbol]>,
+        stale: Vec<Symbol>,
+    ) -> Vec<Action> {
+        // A report from a session that was already replaced is out of date.
+        if !self.is_current_session(id, symbols) {
+            return Vec::new();
+        }
+        let until = Instant::now() + self.stale_hold;
+        let stale: HashSet<Symbol> = stale.into_iter().collect();
+        if let Some(holds) = self.stale_holds.get_mut(id) {
+            holds.extend(stale.iter().map(|sym| (sym.clone(), until)));
+        }
+
+        let mut actions = vec![Action::StopSessions {
+            provider_ids: vec![id],
+        }];
+        // Restart the provider on the symbols it still delivers; only the stale ones move on,
+        // and only where another provider can take them.
+        let keep: HashSet<&Symbol> = symbols
+            .iter()
+            .filter(|sym| !stale.contains(*sym) || !self.has_unheld_alternative(id, sym))
+            .collect();
+        let instruments: Vec<Instrument> = if symbols.iter().all(|sym| stale.contains(sym)) {
+            Vec::new()
+        } else {
+            self.provider_instruments
+                .get(id)
+                .map(|insts| {
+                    insts
+                        .iter()
+                        .filter(|inst| match inst.id() {
+                            borsa_core::IdentifierScheme::Security(sec) => {
+                                keep.contains(&sec.symbol)
+                            }
+                            borsa_core::IdentifierScheme::Prediction(_) => false,
+                        })
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        if instruments.is_empty() {
+            self.providers[id] = ProviderState::InCooldown {
+                failed_at: Instant::now(),
+            };
+        } else {
+            let syms = symbols_of(&instruments);
+            self.providers[id] = ProviderState::Connecting {
+                symbols: Arc::clone(&syms),
+            };
+            actions.push(Action::RequestStart {
+                id,
+                instruments,
+                symbols: syms,
+            });
+        }
+        // Rescan so the stale symbols are picked up by the next eligible provider.
+        self.scan_cursor = self.start_index;
+        self.round_exhausted = false;
+        actions
+    }
+
+    /// Drop expired holds and restart active sessions that may take their symbols back.
+    fn release_expired_holds(&mut self) -> Vec<Action> {
+        let now = Instant::now();
+        let released: Vec<Vec<Symbol>> = self
+            .stale_holds
+            .iter_mut()
+            .map(|holds| {
+                let mut released = Vec::new();
+                holds.retain(|sym, until| {
+                    let keep = *until > now;
+                    if !keep {
+                        released.push(sym.clone());
+                    }
+                    keep
+                });
+                released
+            })
+            .collect();
+        let mut restart: Vec<usize> = Vec::new();
+        for (id, released) in released.iter().enumerate() {
+            let Some(ProviderState::Active { symbols, .. }) = self.providers.get(id) else {
+                continue;
+            };
+            let Some(allow) = self.provider_allow.get(id) else {
+                continue;
+            };
+            if released.iter().any(|sym| {
+                allow.contains(sym) && self.required_symbols.contains(sym) && !symbols.contains(sym)
+            }) {
+                restart.push(id);
+            }
+        }
+        // Idle providers are restarted with the released symbols by the next start scan, and
+        // the new sessions preempt the lower-priority ones that took the symbols over.
+        for id in &restart {
+            self.providers[*id] = ProviderState::Idle;
+        }
+        if restart.is_empty() {
+            Vec::new()
+        } else {
+            vec![Action::StopSessions {
+                provider_ids: restart,
+            }]
+        }
+    }
+
     fn handle_provider_activated(&mut self, id: usize, symbols: &Arc<[Symbol]>) -> Vec<Action> {
         let from_cooldown = Self::is_provider_idle_from_cooldown(&self.providers[id]);
         self.providers[id] = ProviderState::Active {
@@ -355,6 +535,112 @@ impl Supervisor {
         actions
     }
 
+    /// Whether `symbols` belongs to the session currently recorded for provider `id`.
+    pub fn is_current_session(&self, id: usize, symbols: &Arc<[Symbol]>) -> bool {
+        matches!(
+            self.providers.get(id),
+            Some(ProviderState::Active { symbols: active, .. }) if Arc::ptr_eq(active, symbols)
+        )
+    }
+
+    /// Whether a start result carrying `symbols` matches the in-flight request for provider `id`.
+    pub fn is_pending_start(&self, id: usize, symbols: &Arc<[Symbol]>) -> bool {
+        matches!(
+            self.providers.get(id),
+            Some(ProviderState::Connecting { symbols: pending }) if Arc::ptr_eq(pending, symbols)
+        )
+    }
+
+    fn handle_replan(
+        &mut self,
+        provider_instruments: Vec<Vec<Instrument>>,
+        provider_allow: Vec<HashSet<Symbol>>,
+        required_symbols: HashSet<Symbol>,
+    ) -> Vec<Action> {
+        let mut unclaimed: HashSet<Symbol> = required_symbols
+            .difference(&self.required_symbols)
+            .cloned()
+            .collect();
+        self.provider_instruments = provider_instruments;
+        self.provider_allow = provider_allow;
+        self.required_symbols = required_symbols;
+
+        let mut actions = Vec::new();
+        for id in 0..self.providers.len() {
+            let current = match &self.providers[id] {
+                ProviderState::Active { symbols, .. } | ProviderState::Connecting { symbols } => {
+                    Arc::clone(symbols)
+                }
+                _ => continue,
+            };
+            let Some(allow) = self.provider_allow.get(id) else {
+                continue;
+            };
+
+            // Keep what is still assigned, and claim newly added symbols in provider order so
+            // that running sessions absorb additions instead of spawning lower-priority ones.
+            let mut desired: HashSet<Symbol> = current
+                .iter()
+                .filter(|s| allow.contains(*s) && self.required_symbols.contains(*s))
+                .cloned()
+                .collect();
+            if self.can_provider_stream(id) {
+                let gained: Vec<Symbol> = unclaimed
+                    .iter()
+                    .filter(|s| allow.contains(*s))
+                    .cloned()
+                    .collect();
+                for sym in gained {
+                    unclaimed.remove(&sym);
+                    desired.insert(sym);
+                }
+            }
+
+            let unchanged =
+                desired.len() == current.len() && current.iter().all(|s| desired.contains(s));
+            if unchanged {
+                continue;
+            }
+
+            actions.push(Action::StopSessions {
+                provider_ids: vec![id],
+            });
+            let instruments: Vec<Instrument> = self
+                .provider_instruments
+                .get(id)
+                .map(|insts| {
+                    insts
+                        .iter()
+                        .filter(|inst| match inst.id() {
+                            borsa_core::IdentifierScheme::Security(sec) => {
+                                desired.contains(&sec.symbol)
+                            }
+                            borsa_core::IdentifierScheme::Prediction(_) => false,
+                        })
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default();
+            if instruments.is_empty() {
+                self.providers[id] = ProviderState::Idle;
+                continue;
+            }
+            let syms = symbols_of(&instruments);
+            self.providers[id] = ProviderState::Connecting {
+                symbols: Arc::clone(&syms),
+            };
+            actions.push(Action::RequestStart {
+                id,
+                instruments,
+                symbols: syms,
+            });
+        }
+
+        self.scan_cursor = self.start_index;
+        self.round_exhausted = false;
+        actions
+    }
+
     fn advance_scan_cursor_for_failure(&mut self, id: usize) {
         self.providers[id] = ProviderState::InCooldown {
             failed_at: Instant::now(),
@@ -413,6 +699,7 @@ impl Supervisor {
                 *p = ProviderState::IdleFromCooldown;
             }
         }
+        let mut actions = self.release_expired_holds();
 
         if self.attempted_since_last_tick {
             if self.has_any_active() {
@@ -447,11 +734,9 @@ impl Supervisor {
         self.scan_cursor = self.start_index;
         self.round_exhausted = false;
         let delay = self.current_delay_ms();
+        actions.push(Action::ScheduleBackoffTick { delay_ms: delay });
 
-        (
-            Self { phase, ..self },
-            vec![Action::ScheduleBackoffTick { delay_ms: delay }],
-        )
+        (Self { phase, ..self }, actions)
     }
 
     fn increase_backoff(&mut self) {
@@ -461,3 +746,16 @@ impl Supervisor {
             .min(self.max_backoff_ms);
     }
 }
+
+fn symbols_of(instruments: &[Instrument]) -> Arc<[Symbol]> {
+    Arc::from(
+        instruments
+            .iter()
+            .filter_map(|inst| match inst.id() {
+                borsa_core::IdentifierScheme::Security(sec) => Some(sec.symbol.clone()),
+                borsa_core::IdentifierScheme::Prediction(_) => None,
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice(),
+    )
+}
@@ -0,0 +1,364 @@
+//! Candle synthesis from quote ticks for instruments without a native candle stream.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use borsa_core::{
+    BorsaError, Candle, CandleSynthesisConfig, CandleUpdate, EmptyIntervals, Exchange,
+    IdentifierScheme, Instrument, Interval, LateTicks, Money, QuoteUpdate, Symbol, TradingHours,
+    interval_bounds, stream::StreamHandle,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use tokio::sync::{mpsc, oneshot};
+
+use super::StreamableUpdate;
+
+/// How often open bars are checked against the wall clock when `finalize_after` is set.
+const CLOCK_TICK: Duration = Duration::from_secs(1);
+
+/// One bar being built or most recently finalized.
+struct Bar {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    open: Money,
+    high: Money,
+    low: Money,
+    close: Money,
+    // timestamp of the tick that set `close`
+    close_ts: DateTime<Utc>,
+    volume: Option<u64>,
+}
+
+impl Bar {
+    fn new(start: DateTime<Utc>, end: DateTime<Utc>, price: Money, ts: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            end,
+            open: price.clone(),
+            high: price.clone(),
+            low: price.clone(),
+            close: price,
+            close_ts: ts,
+            volume: None,
+        }
+    }
+
+    /// Bar at `close` with zero volume, for an interval without ticks.
+    fn flat(start: DateTime<Utc>, end: DateTime<Utc>, close: &Money) -> Self {
+        let mut bar = Self::new(start, end, close.clone(), start);
+        bar.volume = Some(0);
+        bar
+    }
+
+    fn fold(&mut self, price: Money, ts: DateTime<Utc>, volume: Option<u64>) {
+        if price.amount() > self.high.amount() {
+            self.high = price.clone();
+        }
+        if price.amount() < self.low.amount() {
+            self.low = price.clone();
+        }
+        if ts >= self.close_ts {
+            self.close = price;
+            self.close_ts = ts;
+        }
+        if let Some(v) = volume {
+            self.volume = Some(self.volume.unwrap_or(0).saturating_add(v));
+        }
+    }
+
+    fn update(&self, instrument: &Instrument, interval: Interval, is_final: bool) -> CandleUpdate {
+        CandleUpdate {
+            instrument: instrument.clone(),
+            interval,
+            candle: Candle {
+                ts: self.start,
+                open: self.open.clone(),
+                high: self.high.clone(),
+                low: self.low.clone(),
+                close: self.close.clone(),
+                close_unadj: None,
+                volume: self.volume,
+            },
+            is_final,
+        }
+    }
+}
+
+/// Bar state of one instrument.
+struct Series {
+    instrument: Instrument,
+    tz: Tz,
+    hours: Option<TradingHours>,
+    open: Option<Bar>,
+    last_final: Option<Bar>,
+    // end of the last interval that was finalized or passed over
+    settled_until: Option<DateTime<Utc>>,
+    // last cumulative session volume seen
+    last_volume: Option<u64>,
+}
+
+impl Series {
+    /// Volume traded since the previous tick, from cumulative session volume.
+    ///
+    /// A drop in cumulative volume on a tick that opens a new bar is taken as a new session.
+    fn volume_delta(&mut self, volume: Option<u64>, new_bar: bool) -> Option<u64> {
+        let v = volume?;
+        let delta = match self.last_volume {
+            None => 0,
+            Some(prev) if v >= prev => v - prev,
+            Some(_) if new_bar => v,
+            Some(_) => return Some(0),
+        };
+        self.last_volume = Some(v);
+        Some(delta)
+    }
+
+    fn finalize_open(&mut self, interval: Interval, out: &mut Vec<CandleUpdate>) {
+        if let Some(bar) = self.open.take() {
+            out.push(bar.update(&self.instrument, interval, true));
+            self.settled_until = Some(bar.end);
+            self.last_final = Some(bar);
+        }
+    }
+
+    /// Emit flat final bars after the last settled interval for intervals ending by `until`.
+    ///
+    /// Intervals in which the exchange is closed throughout are passed over without a bar.
+    fn carry_forward(
+        &mut self,
+        interval: Interval,
+        until: DateTime<Utc>,
+        out: &mut Vec<CandleUpdate>,
+    ) {
+        let (Some(close), Some(mut from)) = (
+            self.last_final.as_ref().map(|bar| bar.close.clone()),
+            self.settled_until,
+        ) else {
+            return;
+        };
+        while let Some((start, end)) = interval_bounds(from, interval, self.tz)
+            && end <= until
+            && start >= from
+        {
+            if self
+                .hours
+                .as_ref()
+                .is_none_or(|hours| hours.is_open_between(start, end))
+            {
+                let bar = Bar::flat(start, end, &close);
+                out.push(bar.update(&self.instrument, interval, true));
+                self.last_final = Some(bar);
+            }
+            from = end;
+        }
+        self.settled_until = Some(from);
+    }
+}
+
+/// Builds [`CandleUpdate`]s for one interval from a quote stream.
+///
+/// Every tick with a price emits the in-progress bar of its interval. A bar is emitted once
+/// more with `is_final` set when a tick for a later interval arrives, when the wall clock
+/// passes its end by `finalize_after`, or when the quote stream ends.
+pub struct CandleSynthesizer {
+    interval: Interval,
+    cfg: CandleSynthesisConfig,
+    trading_hours: HashMap<Exchange, TradingHours>,
+    series: HashMap<Symbol, Series>,
+}
+
+impl CandleSynthesizer {
+    /// Create a synthesizer for `interval`.
+    ///
+    /// # Errors
+    /// Returns `Unsupported` for intervals that are neither intraday, daily nor weekly.
+    pub fn new(interval: Interval, cfg: CandleSynthesisConfig) -> Result<Self, BorsaError> {
+        if interval_bounds(Utc::now(), interval, cfg.default_timezone).is_none() {
+            return Err(BorsaError::unsupported(
+                "stream_candles (interval cannot be synthesized from quotes)",
+            ));
+        }
+        Ok(Self {
+            interval,
+            cfg,
+            trading_hours: HashMap::new(),
+            series: HashMap::new(),
+        })
+    }
+
+    /// Use `hours` to skip carried-forward intervals while an instrument's exchange is closed.
+    #[must_use]
+    pub fn with_trading_hours(mut self, hours: HashMap<Exchange, TradingHours>) -> Self {
+        self.trading_hours = hours;
+        self
+    }
+
+    fn uses_clock(&self) -> bool {
+        self.cfg.finalize_after.is_some()
+    }
+
+    /// Fold one quote tick into its bar.
+    pub fn on_tick(&mut self, tick: &QuoteUpdate) -> Vec<CandleUpdate> {
+        let mut out = Vec::new();
+        let Some(price) = tick.price.clone() else {
+            return out;
+        };
+        let interval = self.interval;
+        let cfg = &self.cfg;
+        let trading_hours = &self.trading_hours;
+        let series = self
+            .series
+            .entry(tick.stream_symbol().clone())
+            .or_insert_with(|| {
+                let exchange = match tick.instrument.id() {
+                    IdentifierScheme::Security(sec) => sec.exchange.as_ref(),
+                    IdentifierScheme::Prediction(_) => None,
+                };
+                #[cfg(feature = "tracing")]
+                if let Some(ex) = exchange
+                    && !cfg.timezones.contains_key(ex)
+                {
+                    tracing::warn!(
+                        symbol = %tick.stream_symbol(),
+                        exchange = ?ex,
+                        timezone = %cfg.default_timezone,
+                        "no timezone configured for exchange; aligning bars in the default timezone"
+                    );
+                }
+                Series {
+                    instrument: tick.instrument.clone(),
+                    tz: cfg.timezone_for(exchange),
+                    hours: exchange.and_then(|ex| trading_hours.get(ex)).cloned(),
+                    open: None,
+                    last_final: None,
+                    settled_until: None,
+                    last_volume: None,
+                }
+            });
+        let Some((start, end)) = interval_bounds(tick.ts, interval, series.tz) else {
+            return out;
+        };
+
+        if series.open.as_ref().is_some_and(|bar| bar.start == start) {
+            let volume = series.volume_delta(tick.volume, false);
+            if let Some(bar) = series.open.as_mut() {
+                bar.fold(price, tick.ts, volume);
+                out.push(bar.update(&series.instrument, interval, false));
+            }
+            return out;
+        }
+
+        let late = match (&series.open, series.settled_until) {
+            (Some(open), _) => start < open.start,
+            (None, Some(settled)) => start < settled,
+            (None, None) => false,
+        };
+        if late {
+            // Only the most recently finalized bar can be revised; its cumulative volume is
+            // already settled, so late ticks never change volume.
+            if cfg.late_ticks == LateTicks::Revise
+                && let Some(bar) = series.last_final.as_mut().filter(|b| b.start == start)
+            {
+                bar.fold(price, tick.ts, None);
+                out.push(bar.update(&series.instrument, interval, true));
+            }
+            return out;
+        }
+
+        let volume = series.volume_delta(tick.volume, true);
+        series.finalize_open(interval, &mut out);
+        if cfg.empty_intervals == EmptyIntervals::CarryForward {
+            series.carry_forward(interval, start, &mut out);
+        }
+        let mut bar = Bar::new(start, end, price, tick.ts);
+        bar.volume = volume;
+        out.push(bar.update(&series.instrument, interval, false));
+        series.open = Some(bar);
+        out
+    }
+
+    /// Finalize bars whose end plus `finalize_after` is at or before `now`.
+    pub fn on_clock(&mut self, now: DateTime<Utc>) -> Vec<CandleUpdate> {
+        let mut out = Vec::new();
+        let Some(delay) = self
+            .cfg
+            .finalize_after
+            .and_then(|d| chrono::Duration::from_std(d).ok())
+        else {
+            return out;
+        };
+        let Some(cutoff) = now.checked_sub_signed(delay) else {
+            return out;
+        };
+        for series in self.series.values_mut() {
+            if series.open.as_ref().is_some_and(|bar| bar.end <= cutoff) {
+                series.finalize_open(self.interval, &mut out);
+            }
+            if series.open.is_none() && self.cfg.empty_intervals == EmptyIntervals::CarryForward {
+                series.carry_forward(self.interval, cutoff, &mut out);
+            }
+        }
+        out
+    }
+
+    /// Finalize every open bar, e.g. when the quote stream ends.
+    pub fn finish(&mut self) -> Vec<CandleUpdate> {
+        let mut out = Vec::new();
+        for series in self.series.values_mut() {
+            series.finalize_open(self.interval, &mut out);
+        }
+        out
+    }
+}
+
+/// Drive `synth` from a running quote stream, sending candles to `tx`.
+///
+/// Updates from `native`, a candle stream for the instruments that have one, are forwarded to
+/// the same channel. The task ends once every input has ended, and the returned handle stops
+/// the inputs together with it.
+pub fn spawn_candle_synthesis(
+    quotes: (StreamHandle, mpsc::Receiver<QuoteUpdate>),
+    native: Option<(StreamHandle, mpsc::Receiver<CandleUpdate>)>,
+    mut synth: CandleSynthesizer,
+    tx: mpsc::Sender<CandleUpdate>,
+) -> StreamHandle {
+    let (quote_handle, mut quote_rx) = quotes;
+    let (native_handle, native_rx) = native.unzip();
+    let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+    let join = tokio::spawn(async move {
+        let mut clock = tokio::time::interval(CLOCK_TICK);
+        clock.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let uses_clock = synth.uses_clock();
+        let mut quotes_open = true;
+        let mut native_open = native_rx.is_some();
+        let mut native_rx = native_rx;
+        'run: while quotes_open || native_open {
+            let updates = tokio::select! {
+                biased;
+                _ = &mut stop_rx => break 'run,
+                tick = quote_rx.recv(), if quotes_open => if let Some(tick) = tick {
+                    synth.on_tick(&tick)
+                } else {
+                    quotes_open = false;
+                    synth.finish()
+                },
+                update = async { native_rx.as_mut()?.recv().await }, if native_open => {
+                    native_open = update.is_some();
+                    update.into_iter().collect()
+                }
+                _ = clock.tick(), if uses_clock && quotes_open => synth.on_clock(Utc::now()),
+            };
+            for update in updates {
+                if tx.send(update).await.is_err() {
+                    break 'run;
+                }
+            }
+        }
+        quote_handle.stop().await;
+        if let Some(handle) = native_handle {
+            handle.stop().await;
+        }
+    });
+    StreamHandle::new(join, stop_tx)
+}
@@ -0,0 +1,139 @@
+//! Building a `Borsa` from a declarative TOML or JSON configuration file.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use borsa_core::{BorsaError, BorsaSetup, ConnectorRegistry};
+use borsa_middleware::ConnectorBuilder;
+
+use crate::Borsa;
+
+/// Syntax of a configuration document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// JSON document.
+    Json,
+    /// TOML document.
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Infer the format from a file extension: `.toml` is TOML, anything else JSON.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// Parse a [`BorsaSetup`] document.
+///
+/// # Errors
+/// Returns `InvalidConfig` whose `path` names the offending value (e.g.
+/// `connectors[0].middleware.layers[1].config`), or `<root>` for syntax errors.
+pub fn parse_setup(text: &str, format: ConfigFormat) -> Result<BorsaSetup, BorsaError> {
+    let value: serde_json::Value = match format {
+        ConfigFormat::Json => serde_json::from_str(text)
+            .map_err(|e| BorsaError::invalid_config("<root>", e.to_string()))?,
+        ConfigFormat::Toml => {
+            toml::from_str(text).map_err(|e| BorsaError::invalid_config("<root>", e.to_string()))?
+        }
+    };
+    serde_path_to_error::deserialize(value).map_err(|e| {
+        let path = e.path().to_string();
+        BorsaError::invalid_config(path, e.into_inner().to_string())
+    })
+}
+
+impl Borsa {
+    /// Build a `Borsa` from a configuration file using the process-wide connector registry.
+    ///
+    /// Connector crates add their constructors to that registry (e.g.
+    /// `borsa_yfinance::register_global()`); the file format follows the extension
+    /// (see [`ConfigFormat::from_path`]).
+    ///
+    /// # Errors
+    /// Returns `InvalidConfig` pointing at the failing config path when the file cannot be
+    /// read or parsed, names an unregistered connector or unknown middleware layer, or the
+    /// resulting orchestrator fails validation.
+    pub fn from_config(path: impl AsRef<Path>) -> Result<Self, BorsaError> {
+        Self::from_config_with_registry(path, &borsa_core::global_registry())
+    }
+
+    /// Build a `Borsa` from a configuration file using an explicit connector registry.
+    ///
+    /// # Errors
+    /// See [`Borsa::from_config`].
+    pub fn from_config_with_registry(
+        path: impl AsRef<Path>,
+        registry: &ConnectorRegistry,
+    ) -> Result<Self, BorsaError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            BorsaError::invalid_config("<root>", format!("cannot read {}: {e}", path.display()))
+        })?;
+        let setup = parse_setup(&text, ConfigFormat::from_path(path))?;
+        Self::from_setup(&setup, registry)
+    }
+
+    /// Build a `Borsa` from an already parsed [`BorsaSetup`].
+    ///
+    /// Each connector is constructed through `registry`, wrapped in its middleware stack and
+    /// registered in file order; the routing policy is applied last.
+    ///
+    /// # Errors
+    /// See [`Borsa::from_config`].
+    pub fn from_setup(
+        setup: &BorsaSetup,
+        registry: &ConnectorRegistry,
+    ) -> Result<Self, BorsaError> {
+        let mut builder = Self::builder().with_config(setup.borsa.clone());
+        if let Some(policy) = &setup.routing_policy {
+            builder = builder.routing_policy(policy.clone());
+        }
+
+        let mut seen: HashSet<&str> = HashSet::new();
+        for (idx, spec) in setup.connectors.iter().enumerate() {
+            let at = |field: &str| format!("connectors[{idx}].{field}");
+            if !registry.contains(&spec.name) {
+                let known = registry.names().collect::<Vec<_>>().join(", ");
+                return Err(BorsaError::invalid_config(
+                    at("name"),
+                    format!("unknown connector '{}' (registered: {known})", spec.name),
+                ));
+            }
+            if !seen.insert(spec.name.as_str()) {
+                return Err(BorsaError::invalid_config(
+                    at("name"),
+                    format!("connector '{}' is listed more than once", spec.name),
+                ));
+            }
+
+            let raw = registry
+                .build(&spec.name, &spec.config)
+                .map_err(|e| BorsaError::invalid_config(at("config"), e.to_string()))?;
+            let connector = ConnectorBuilder::try_from_stack(raw, &spec.middleware)
+                .and_then(ConnectorBuilder::build)
+                .map_err(|e| match e {
+                    BorsaError::InvalidConfig { path, message } => {
+                        BorsaError::invalid_config(at(&format!("middleware.{path}")), message)
+                    }
+                    other => BorsaError::invalid_config(at("middleware"), other.to_string()),
+                })?;
+            builder = builder.with_connector(connector);
+        }
+
+        builder.build().map_err(|e| {
+            let path = if setup.connectors.is_empty() {
+                "connectors"
+            } else if setup.routing_policy.is_some() {
+                "routing_policy"
+            } else {
+                "borsa.routing_policy"
+            };
+            BorsaError::invalid_config(path, e.to_string())
+        })
+    }
+}
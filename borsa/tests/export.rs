@@ -0,0 +1,171 @@
+#![cfg(feature = "export")]
+
+use std::str::FromStr;
+
+use borsa::export::{self, ExportFormat};
+use borsa_core::{
+    Action, AssetKind, Attribution, Candle, Currency, Decimal, DownloadEntry, DownloadResponse,
+    HistoryMeta, HistoryResponse, Instrument, IsoCurrency, Money, Span,
+};
+use chrono::{TimeZone, Utc};
+
+fn usd(amount: &str) -> Money {
+    Money::new(
+        Decimal::from_str(amount).unwrap(),
+        Currency::Iso(IsoCurrency::USD),
+    )
+    .unwrap()
+}
+
+fn candle(ts: i64, close: &str) -> Candle {
+    Candle {
+        ts: Utc.timestamp_opt(ts, 0).unwrap(),
+        open: usd("10.25"),
+        high: usd("11.125"),
+        low: usd("9.5"),
+        close: usd(close),
+        close_unadj: Some(usd("20.5")),
+        volume: Some(1_000),
+    }
+}
+
+fn history() -> HistoryResponse {
+    HistoryResponse {
+        candles: vec![candle(86_400, "10.75"), candle(172_800, "10.8")],
+        actions: vec![
+            Action::Dividend {
+                ts: Utc.timestamp_opt(86_400, 0).unwrap(),
+                amount: usd("0.24"),
+            },
+            Action::Split {
+                ts: Utc.timestamp_opt(172_800, 0).unwrap(),
+                numerator: 2,
+                denominator: 1,
+            },
+        ],
+        adjusted: true,
+        meta: Some(HistoryMeta {
+            timezone: Some(chrono_tz::America::New_York),
+            utc_offset_seconds: Some(-18_000),
+        }),
+    }
+}
+
+fn inst(symbol: &str) -> Instrument {
+    Instrument::from_symbol(symbol, AssetKind::Equity).unwrap()
+}
+
+const FORMATS: [ExportFormat; 3] = [ExportFormat::Csv, ExportFormat::Parquet, ExportFormat::Ipc];
+
+#[test]
+fn history_roundtrips_with_attribution() {
+    let mut attribution = Attribution::new("AAPL".into());
+    attribution.spans.push((
+        "mock",
+        Span {
+            start: 86_400,
+            end: 172_800,
+        },
+    ));
+    let dir = tempfile::tempdir().unwrap();
+    for format in FORMATS {
+        let path = dir.path().join(format!("history-{format:?}"));
+        export::write_history(&path, format, &inst("AAPL"), &history(), Some(&attribution))
+            .unwrap();
+
+        let read = export::read_histories(&path, format).unwrap();
+        assert_eq!(read.len(), 1, "{format:?}");
+        assert_eq!(read[0].instrument, inst("AAPL"));
+        assert_eq!(read[0].history, history(), "{format:?}");
+        assert_eq!(
+            read[0].spans,
+            vec![(
+                "mock".to_string(),
+                Span {
+                    start: 86_400,
+                    end: 172_800,
+                },
+            )]
+        );
+        assert_eq!(export::read_history(&path, format).unwrap(), history());
+    }
+}
+
+#[test]
+fn download_roundtrips_including_empty_histories() {
+    let empty = HistoryResponse {
+        candles: vec![],
+        actions: vec![],
+        adjusted: false,
+        meta: None,
+    };
+    let download = DownloadResponse {
+        entries: vec![
+            DownloadEntry {
+                instrument: inst("AAPL"),
+                history: history(),
+            },
+            DownloadEntry {
+                instrument: inst("MSFT"),
+                history: empty,
+            },
+        ],
+    };
+    let dir = tempfile::tempdir().unwrap();
+    for format in FORMATS {
+        let path = dir.path().join(format!("download-{format:?}"));
+        export::write_download(&path, format, &download).unwrap();
+        assert_eq!(export::read_download(&path, format).unwrap(), download);
+    }
+}
+
+#[test]
+fn frame_has_stable_columns() {
+    let frame = export::history_frame(&inst("AAPL"), &history(), None).unwrap();
+    let names: Vec<_> = frame
+        .get_column_names()
+        .iter()
+        .map(|n| n.as_str().to_string())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "instrument",
+            "symbol",
+            "record",
+            "ts",
+            "open",
+            "high",
+            "low",
+            "close",
+            "close_unadj",
+            "volume",
+            "amount",
+            "numerator",
+            "denominator",
+            "currency",
+            "adjusted",
+            "timezone",
+            "utc_offset_seconds",
+            "provider",
+            "span_end",
+        ]
+    );
+    // One meta row, two candles and two actions.
+    assert_eq!(frame.height(), 5);
+    for price in ["open", "high", "low", "close", "close_unadj", "amount"] {
+        assert!(
+            frame.column(price).unwrap().f64().is_ok(),
+            "{price} is numeric"
+        );
+    }
+}
+
+#[test]
+fn format_is_guessed_from_extension() {
+    let guess = |p: &str| ExportFormat::from_path(std::path::Path::new(p));
+    assert_eq!(guess("out.csv"), Some(ExportFormat::Csv));
+    assert_eq!(guess("out.parquet"), Some(ExportFormat::Parquet));
+    assert_eq!(guess("out.arrow"), Some(ExportFormat::Ipc));
+    assert_eq!(guess("out.txt"), None);
+}
@@ -12,11 +12,11 @@ use borsa_core::{
     MajorHolder, NewsArticle, OptionChain, PriceTarget, Quote, RecommendationRow,
     RecommendationSummary, UpgradeDowngradeRow,
     connector::{
-        AnalystPriceTargetProvider, BalanceSheetProvider, CalendarProvider, CandleStreamProvider,
-        CashflowProvider, EsgProvider, HistoryProvider, IncomeStatementProvider,
-        MajorHoldersProvider, NewsProvider, OptionChainProvider, OptionsExpirationsProvider,
-        QuoteProvider, RecommendationsProvider, RecommendationsSummaryProvider, SearchProvider,
-        StreamProvider, UpgradesDowngradesProvider,
+        AnalystPriceTargetProvider, BalanceSheetProvider, BatchQuoteProvider, CalendarProvider,
+        CandleStreamProvider, CashflowProvider, EsgProvider, HistoryProvider,
+        IncomeStatementProvider, MajorHoldersProvider, NewsProvider, OptionChainProvider,
+        OptionsExpirationsProvider, QuoteProvider, RecommendationsProvider,
+        RecommendationsSummaryProvider, SearchProvider, StreamProvider, UpgradesDowngradesProvider,
     },
 };
 use borsa_core::{NewsRequest, SearchRequest, SearchResponse, SearchResult};
@@ -53,6 +53,9 @@ pub struct MockConnector {
 
     // Optional closures to customize behavior per test
     pub quote_fn: Option<Arc<dyn Fn(&Instrument) -> Result<Quote, BorsaError> + Send + Sync>>,
+    pub batch_quotes_fn:
+        Option<Arc<dyn Fn(&[Instrument]) -> Result<Vec<Quote>, BorsaError> + Send + Sync>>,
+    pub max_batch_size: usize,
     pub history_fn: Option<
         Arc<
             dyn Fn(&Instrument, HistoryRequest) -> Result<HistoryResponse, BorsaError>
@@ -123,6 +126,8 @@ impl Default for MockConnector {
             candle_stream_start_error: None,
 
             quote_fn: None,
+            batch_quotes_fn: None,
+            max_batch_size: 50,
             history_fn: None,
             search_fn: None,
             calendar_fn: None,
@@ -169,6 +174,23 @@ impl QuoteProvider for MockConnector {
     }
 }
 
+#[async_trait]
+impl BatchQuoteProvider for MockConnector {
+    async fn quotes(&self, instruments: &[Instrument]) -> Result<Vec<Quote>, BorsaError> {
+        if self.delay_ms > 0 {
+            sleep(Duration::from_millis(self.delay_ms)).await;
+        }
+        self.batch_quotes_fn.as_ref().map_or_else(
+            || Err(BorsaError::unsupported("quotes")),
+            |f| (f)(instruments),
+        )
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+}
+
 #[async_trait]
 impl HistoryProvider for MockConnector {
     async fn history(
@@ -561,6 +583,14 @@ impl BorsaConnector for MockConnector {
         }
     }
 
+    fn as_batch_quote_provider(&self) -> Option<&dyn borsa_core::connector::BatchQuoteProvider> {
+        if self.batch_quotes_fn.is_some() {
+            Some(self as &dyn BatchQuoteProvider)
+        } else {
+            None
+        }
+    }
+
     fn as_search_provider(&self) -> Option<&dyn borsa_core::connector::SearchProvider> {
         if self.search_fn.is_some() || self.search.is_some() {
             Some(self as &dyn SearchProvider)
@@ -726,6 +756,9 @@ pub struct MockConnectorBuilder {
     delay_ms: u64,
     history_intervals: &'static [borsa_core::Interval],
     quote_fn: Option<Arc<dyn Fn(&Instrument) -> Result<Quote, BorsaError> + Send + Sync>>,
+    batch_quotes_fn:
+        Option<Arc<dyn Fn(&[Instrument]) -> Result<Vec<Quote>, BorsaError> + Send + Sync>>,
+    max_batch_size: usize,
     history_fn: Option<
         Arc<
             dyn Fn(&Instrument, HistoryRequest) -> Result<HistoryResponse, BorsaError>
@@ -789,6 +822,8 @@ impl MockConnectorBuilder {
             delay_ms: 0,
             history_intervals: DEFAULT_HISTORY_INTERVALS,
             quote_fn: None,
+            batch_quotes_fn: None,
+            max_batch_size: 50,
             history_fn: None,
             search_fn: None,
             calendar_fn: None,
@@ -865,6 +900,17 @@ impl MockConnectorBuilder {
         self.quote_fn = Some(Arc::new(move |_i| Ok(q.clone())));
         self
     }
+    pub fn with_batch_quotes_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&[Instrument]) -> Result<Vec<Quote>, BorsaError> + Send + Sync + 'static,
+    {
+        self.batch_quotes_fn = Some(Arc::new(f));
+        self
+    }
+    pub fn max_batch_size(mut self, n: usize) -> Self {
+        self.max_batch_size = n;
+        self
+    }
 
     // History
     pub fn with_history_fn<F>(mut self, f: F) -> Self
@@ -1094,6 +1140,8 @@ impl MockConnectorBuilder {
             candle_stream_updates: self.candle_stream_updates,
             candle_stream_start_error: self.candle_stream_start_error,
             quote_fn: self.quote_fn,
+            batch_quotes_fn: self.batch_quotes_fn,
+            max_batch_size: self.max_batch_size,
             history_fn: self.history_fn,
             search_fn: self.search_fn,
             calendar_fn: self.calendar_fn,
@@ -1182,3 +1230,61 @@ pub fn candle(ts: i64, close: f64) -> Candle {
         volume: None,
     }
 }
+
+/// Create an equity quote update for `symbol` at unix time `ts`.
+#[allow(dead_code)]
+pub fn update(symbol: &borsa_core::Symbol, ts: i64) -> borsa_core::QuoteUpdate {
+    use chrono::TimeZone;
+    borsa_core::QuoteUpdate {
+        instrument: crate::helpers::instrument(symbol, AssetKind::Equity),
+        price: Some(crate::helpers::usd("100.0")),
+        previous_close: None,
+        ts: chrono::Utc.timestamp_opt(ts, 0).unwrap(),
+        volume: None,
+    }
+}
+
+/// Quote streamer that sends a fixed list of updates once, then stays open until stopped.
+pub struct BurstStreamer {
+    pub updates: Vec<borsa_core::QuoteUpdate>,
+}
+
+#[async_trait]
+impl StreamProvider for BurstStreamer {
+    async fn stream_quotes(
+        &self,
+        _instruments: &[Instrument],
+    ) -> Result<
+        (
+            borsa_core::stream::StreamHandle,
+            tokio::sync::mpsc::Receiver<borsa_core::QuoteUpdate>,
+        ),
+        BorsaError,
+    > {
+        let (tx, rx) = tokio::sync::mpsc::channel::<borsa_core::QuoteUpdate>(16);
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let updates = self.updates.clone();
+        let join = tokio::spawn(async move {
+            for u in updates {
+                if tx.send(u).await.is_err() {
+                    return;
+                }
+            }
+            let _ = stop_rx.await;
+        });
+        Ok((borsa_core::stream::StreamHandle::new(join, stop_tx), rx))
+    }
+}
+
+#[async_trait]
+impl BorsaConnector for BurstStreamer {
+    fn name(&self) -> &'static str {
+        "burst"
+    }
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+    fn as_stream_provider(&self) -> Option<&dyn StreamProvider> {
+        Some(self)
+    }
+}
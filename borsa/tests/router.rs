@@ -14,8 +14,14 @@ mod router_analysis_upgrades_downgrades;
 #[path = "router/calendar/router_calendar.rs"]
 mod router_calendar;
 
+#[path = "router/core/router_adaptive_ranking.rs"]
+mod router_adaptive_ranking;
+#[path = "router/core/router_capability_routing.rs"]
+mod router_capability_routing;
 #[path = "router/core/router_fetch_strategies.rs"]
 mod router_fetch_strategies;
+#[path = "router/core/router_from_config.rs"]
+mod router_from_config;
 #[path = "router/core/router_priority.rs"]
 mod router_priority;
 
@@ -91,6 +97,8 @@ mod router_quote_per_kind_priority;
 mod router_quote_provider_hot_swap;
 #[path = "router/quotes/router_quote_unsupported.rs"]
 mod router_quote_unsupported;
+#[path = "router/quotes/router_quotes_batch.rs"]
+mod router_quotes_batch;
 #[path = "router/quotes/router_quotes_fallback.rs"]
 mod router_quotes_fallback;
 #[path = "router/quotes/router_quotes_multi.rs"]
@@ -111,6 +119,8 @@ mod downstream_drop_prop;
 mod router_stream_backoff;
 #[path = "router/stream/router_stream_candles.rs"]
 mod router_stream_candles;
+#[path = "router/stream/router_stream_candles_synth.rs"]
+mod router_stream_candles_synth;
 #[path = "router/stream/router_stream_clock_skew.rs"]
 mod router_stream_clock_skew;
 #[path = "router/stream/router_stream_cooldown.rs"]
@@ -121,10 +131,14 @@ mod router_stream_downstream_drop;
 mod router_stream_drop_unassigned_warn;
 #[path = "router/stream/router_stream_empty_assignment.rs"]
 mod router_stream_empty_assignment;
+#[path = "router/stream/router_stream_events.rs"]
+mod router_stream_events;
 #[path = "router/stream/router_stream_failback_priority.rs"]
 mod router_stream_failback_priority;
 #[path = "router/stream/router_stream_failover_end.rs"]
 mod router_stream_failover_end;
+#[path = "router/stream/router_stream_hub.rs"]
+mod router_stream_hub;
 #[path = "router/stream/router_stream_kind_hint.rs"]
 mod router_stream_kind_hint;
 #[path = "router/stream/router_stream_model_multiplex_prop.rs"]
@@ -133,6 +147,8 @@ mod router_stream_model_multiplex_prop;
 mod router_stream_monotonic;
 #[path = "router/stream/router_stream_no_provider.rs"]
 mod router_stream_no_provider;
+#[path = "router/stream/router_stream_overflow.rs"]
+mod router_stream_overflow;
 #[path = "router/stream/router_stream_per_provider_subset.rs"]
 mod router_stream_per_provider_subset;
 #[path = "router/stream/router_stream_quotes.rs"]
@@ -143,12 +159,16 @@ mod router_stream_quotes_multi;
 mod router_stream_rapid_flapping;
 #[path = "router/stream/router_stream_slow_consumer.rs"]
 mod router_stream_slow_consumer;
+#[path = "router/stream/router_stream_staleness.rs"]
+mod router_stream_staleness;
 #[path = "router/stream/router_stream_startup_all_fail.rs"]
 mod router_stream_startup_all_fail;
 #[path = "router/stream/router_stream_startup_fallback.rs"]
 mod router_stream_startup_fallback;
 #[path = "router/stream/router_stream_strict_symbols_rejected.rs"]
 mod router_stream_strict_symbols_rejected;
+#[path = "router/stream/router_stream_subscription.rs"]
+mod router_stream_subscription;
 #[path = "router/stream/router_stream_symbol_filtering.rs"]
 mod router_stream_symbol_filtering;
 #[path = "router/stream/router_stream_unsupported.rs"]
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use borsa::{AdaptiveRankingConfig, Borsa};
+use borsa_core::{AssetKind, BorsaError, Capability, Quote, RoutingPolicyBuilder};
+
+use crate::helpers::{AAPL, MockConnector, usd};
+
+fn flaky(calls: Arc<AtomicUsize>) -> Arc<MockConnector> {
+    MockConnector::builder()
+        .name("flaky")
+        .with_quote_fn(move |_i| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(BorsaError::Other("upstream 503".into()))
+        })
+        .build()
+}
+
+fn healthy() -> Arc<MockConnector> {
+    counted_healthy("healthy", Arc::new(AtomicUsize::new(0)))
+}
+
+fn counted_healthy(name: &'static str, calls: Arc<AtomicUsize>) -> Arc<MockConnector> {
+    MockConnector::builder()
+        .name(name)
+        .with_quote_fn(move |i| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Quote {
+                instrument: i.clone(),
+                shortname: None,
+                price: Some(usd("10.0")),
+                previous_close: None,
+                exchange: None,
+                market_state: None,
+                day_volume: None,
+            })
+        })
+        .build()
+}
+
+fn adaptive() -> AdaptiveRankingConfig {
+    AdaptiveRankingConfig {
+        min_samples: 3,
+        exploration_rate: 0.0,
+        ..AdaptiveRankingConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn failing_provider_is_demoted_after_min_samples() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let borsa = Borsa::builder()
+        .with_connector(flaky(calls.clone()))
+        .with_connector(healthy())
+        .adaptive_ranking(adaptive())
+        .build()
+        .unwrap();
+
+    let inst = crate::helpers::instrument(&AAPL, AssetKind::Equity);
+    for _ in 0..10 {
+        borsa.quote(&inst).await.expect("healthy provider answers");
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3, "demoted after min_samples");
+    let health = borsa
+        .provider_health("flaky", Capability::Quote)
+        .expect("enough samples");
+    assert!((health.failure_rate - 1.0).abs() < f64::EPSILON);
+    assert_eq!(
+        borsa
+            .provider_health("healthy", Capability::Quote)
+            .map(|h| h.failure_rate),
+        Some(0.0)
+    );
+}
+
+#[tokio::test]
+async fn listed_providers_stay_ahead_of_unlisted_ones() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let steady_calls = Arc::new(AtomicUsize::new(0));
+    let fallback_calls = Arc::new(AtomicUsize::new(0));
+    let flaky = flaky(calls.clone());
+    let steady = counted_healthy("steady", steady_calls.clone());
+    let policy = RoutingPolicyBuilder::new()
+        .providers_for_kind(AssetKind::Equity, &[flaky.key(), steady.key()])
+        .build();
+    let borsa = Borsa::builder()
+        .with_connector(counted_healthy("fallback", fallback_calls.clone()))
+        .with_connector(steady)
+        .with_connector(flaky)
+        .routing_policy(policy)
+        .adaptive_ranking(adaptive())
+        .build()
+        .unwrap();
+
+    let inst = crate::helpers::instrument(&AAPL, AssetKind::Equity);
+    for _ in 0..10 {
+        borsa.quote(&inst).await.expect("steady answers");
+    }
+    // The explicit rank keeps flaky ahead of steady despite its failures.
+    assert_eq!(calls.load(Ordering::SeqCst), 10);
+    assert_eq!(steady_calls.load(Ordering::SeqCst), 10);
+    assert_eq!(fallback_calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn disabled_by_default() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let borsa = Borsa::builder()
+        .with_connector(flaky(calls.clone()))
+        .with_connector(healthy())
+        .build()
+        .unwrap();
+
+    let inst = crate::helpers::instrument(&AAPL, AssetKind::Equity);
+    for _ in 0..5 {
+        borsa.quote(&inst).await.unwrap();
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 5);
+    assert!(borsa.provider_health("flaky", Capability::Quote).is_none());
+}
+
+#[tokio::test]
+async fn calls_cut_off_by_the_request_deadline_count_as_timeouts() {
+    let slow = MockConnector::builder()
+        .name("slow")
+        .delay(std::time::Duration::from_secs(5))
+        .returns_quote_ok(Quote {
+            instrument: crate::helpers::instrument(&AAPL, AssetKind::Equity),
+            shortname: None,
+            price: Some(usd("10.0")),
+            previous_close: None,
+            exchange: None,
+            market_state: None,
+            day_volume: None,
+        })
+        .build();
+    let borsa = Borsa::builder()
+        .with_connector(slow)
+        .provider_timeout(std::time::Duration::from_secs(10))
+        .request_timeout(std::time::Duration::from_millis(20))
+        .adaptive_ranking(AdaptiveRankingConfig {
+            min_samples: 1,
+            ..adaptive()
+        })
+        .build()
+        .unwrap();
+
+    let inst = crate::helpers::instrument(&AAPL, AssetKind::Equity);
+    let err = borsa
+        .quotes(&[inst])
+        .await
+        .expect_err("request deadline fires");
+    assert!(matches!(err, BorsaError::RequestTimeout { .. }));
+
+    let health = borsa
+        .provider_health("slow", Capability::Quote)
+        .expect("the cut-off call is sampled");
+    assert!((health.failure_rate - 1.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn out_of_range_config_is_rejected() {
+    let err = Borsa::builder()
+        .with_connector(healthy())
+        .adaptive_ranking(AdaptiveRankingConfig {
+            exploration_rate: 1.5,
+            ..AdaptiveRankingConfig::default()
+        })
+        .build()
+        .err()
+        .expect("invalid exploration rate");
+    assert!(matches!(err, BorsaError::InvalidArg(_)));
+}
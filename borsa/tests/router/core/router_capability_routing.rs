@@ -0,0 +1,92 @@
+use borsa::Borsa;
+use borsa_core::{AssetKind, Capability, IncomeStatementRow, Period, Quote, RoutingPolicyBuilder};
+
+use crate::helpers::{AAPL, MockConnector, dt, usd};
+
+fn provider(
+    name: &'static str,
+    price: &'static str,
+    revenue: &'static str,
+) -> std::sync::Arc<MockConnector> {
+    MockConnector::builder()
+        .name(name)
+        .with_quote_fn(move |i| {
+            Ok(Quote {
+                instrument: i.clone(),
+                shortname: None,
+                price: Some(usd(price)),
+                previous_close: None,
+                exchange: None,
+                market_state: None,
+                day_volume: None,
+            })
+        })
+        .with_income_statement_fn(move |_i, _q| {
+            Ok(vec![IncomeStatementRow {
+                period: Period::Date(dt(2024, 12, 31, 0, 0, 0).date_naive()),
+                total_revenue: Some(usd(revenue)),
+                gross_profit: None,
+                operating_income: None,
+                net_income: None,
+            }])
+        })
+        .build()
+}
+
+#[tokio::test]
+async fn capability_rule_splits_providers_for_one_instrument() {
+    let cheap = provider("cheap", "1.0", "10");
+    let paid = provider("paid", "2.0", "20");
+
+    let policy = RoutingPolicyBuilder::new()
+        .providers_for_kind(AssetKind::Equity, &[cheap.key(), paid.key()])
+        .providers_for_capability(Capability::IncomeStatement, &[paid.key()])
+        .build();
+    let borsa = Borsa::builder()
+        .with_connector(cheap.clone())
+        .with_connector(paid.clone())
+        .routing_policy(policy)
+        .build()
+        .unwrap();
+
+    let inst = crate::helpers::instrument(&AAPL, AssetKind::Equity);
+    let quote = borsa.quote(&inst).await.unwrap();
+    assert_eq!(quote.price, Some(usd("1.0")), "quotes follow the kind rule");
+
+    let rows = borsa.income_statement(&inst, false).await.unwrap();
+    assert_eq!(
+        rows[0].total_revenue,
+        Some(usd("20")),
+        "income statements follow the capability rule"
+    );
+}
+
+#[tokio::test]
+async fn strict_capability_rule_only_applies_to_its_capability() {
+    let cheap = provider("cheap", "1.0", "10");
+    let paid = provider("paid", "2.0", "20");
+
+    let policy = RoutingPolicyBuilder::new()
+        .providers_rule(
+            borsa_core::Selector {
+                capability: Some(Capability::Quote),
+                ..borsa_core::Selector::default()
+            },
+            &[paid.key()],
+            true,
+        )
+        .build();
+    let borsa = Borsa::builder()
+        .with_connector(cheap.clone())
+        .with_connector(paid.clone())
+        .routing_policy(policy)
+        .build()
+        .unwrap();
+
+    let inst = crate::helpers::instrument(&AAPL, AssetKind::Equity);
+    let quote = borsa.quote(&inst).await.unwrap();
+    assert_eq!(quote.price, Some(usd("2.0")));
+
+    let rows = borsa.income_statement(&inst, false).await.unwrap();
+    assert_eq!(rows[0].total_revenue, Some(usd("10")));
+}
@@ -0,0 +1,152 @@
+use borsa::{Borsa, ConfigFormat, ConnectorRegistry, parse_setup};
+use borsa_core::{AssetKind, BorsaError, Instrument};
+
+fn registry() -> ConnectorRegistry {
+    let mut registry = ConnectorRegistry::new();
+    borsa_mock::MockConnector::register(&mut registry);
+    registry
+}
+
+fn build(text: &str, format: ConfigFormat) -> Result<Borsa, BorsaError> {
+    Borsa::from_setup(&parse_setup(text, format)?, &registry())
+}
+
+fn error_path(result: Result<Borsa, BorsaError>) -> String {
+    match result {
+        Err(BorsaError::InvalidConfig { path, .. }) => path,
+        Err(other) => panic!("expected InvalidConfig, got {other}"),
+        Ok(_) => panic!("expected InvalidConfig, got a Borsa"),
+    }
+}
+
+#[tokio::test]
+async fn toml_setup_builds_wrapped_connectors() {
+    let borsa = build(
+        r#"
+        [[connectors]]
+        name = "borsa-mock"
+
+        [[connectors.middleware.layers]]
+        name = "QuotaAwareConnector"
+        config = { limit = 100, window_ms = 60000, strategy = "Unit" }
+
+        [borsa]
+        prefer_adjusted_history = true
+        "#,
+        ConfigFormat::Toml,
+    )
+    .expect("valid setup");
+
+    let inst = Instrument::from_symbol("AAPL", AssetKind::Equity).unwrap();
+    let quote = borsa.quote(&inst).await.expect("quote from mock");
+    assert_eq!(quote.instrument, inst);
+}
+
+#[tokio::test]
+async fn json_file_is_loaded_by_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("borsa.json");
+    std::fs::write(&path, r#"{ "connectors": [{ "name": "borsa-mock" }] }"#).unwrap();
+
+    let borsa = Borsa::from_config_with_registry(&path, &registry()).expect("valid setup");
+    let inst = Instrument::from_symbol("MSFT", AssetKind::Equity).unwrap();
+    assert!(borsa.quote(&inst).await.is_ok());
+}
+
+#[test]
+fn unknown_connector_points_at_its_name() {
+    let result = build(
+        r#"{ "connectors": [{ "name": "borsa-mock" }, { "name": "nope" }] }"#,
+        ConfigFormat::Json,
+    );
+    assert_eq!(error_path(result), "connectors[1].name");
+}
+
+#[test]
+fn unknown_middleware_layer_points_at_the_layer() {
+    let result = build(
+        r#"{ "connectors": [{
+            "name": "borsa-mock",
+            "middleware": { "layers": [{ "name": "Bogus", "config": {} }] }
+        }] }"#,
+        ConfigFormat::Json,
+    );
+    assert_eq!(
+        error_path(result),
+        "connectors[0].middleware.layers[0].name"
+    );
+}
+
+#[test]
+fn connector_options_are_validated() {
+    let result = build(
+        r#"{ "connectors": [{ "name": "borsa-mock", "config": { "api_key": "x" } }] }"#,
+        ConfigFormat::Json,
+    );
+    assert_eq!(error_path(result), "connectors[0].config");
+}
+
+#[test]
+fn bad_field_reports_its_path() {
+    let result = build(
+        r#"
+        [[connectors]]
+        name = "borsa-mock"
+
+        [borsa]
+        fetch_strategy = "Sideways"
+        "#,
+        ConfigFormat::Toml,
+    );
+    assert_eq!(error_path(result), "borsa.fetch_strategy");
+
+    let result = build(
+        r#"
+        [[connectors]]
+        name = "borsa-mock"
+
+        [borsa]
+        provider_timout = { secs = 1, nanos = 0 }
+        "#,
+        ConfigFormat::Toml,
+    );
+    assert_eq!(error_path(result), "borsa.provider_timout");
+
+    let result = build(
+        r#"{ "connectors": [{ "name": "borsa-mock", "extra": 1 }] }"#,
+        ConfigFormat::Json,
+    );
+    assert!(error_path(result).starts_with("connectors[0]"));
+}
+
+#[test]
+fn invalid_layer_config_points_at_the_field() {
+    let result = build(
+        r#"{ "connectors": [{
+            "name": "borsa-mock",
+            "middleware": { "layers": [
+                { "name": "CoalescingConnector", "config": {} },
+                { "name": "QuotaAwareConnector", "config": { "limit": "lots" } }
+            ] }
+        }] }"#,
+        ConfigFormat::Json,
+    );
+    assert_eq!(
+        error_path(result),
+        "connectors[0].middleware.layers[1].config.limit"
+    );
+
+    let result = build(
+        r#"{ "connectors": [{
+            "name": "borsa-mock",
+            "middleware": { "layers": [
+                { "name": "RetryConnector", "config": { "max_attempts": 5 } }
+            ] }
+        }] }"#,
+        ConfigFormat::Json,
+    );
+    assert!(
+        error_path(result).starts_with("connectors[0].middleware.layers[0].config"),
+        "unknown layer fields are rejected"
+    );
+}
@@ -210,3 +210,390 @@ async fn download_builder_handles_empty_instruments_list() {
         }
     }
 }
+
+/// History connector that records the peak number of concurrent calls.
+struct InFlightHist {
+    name: &'static str,
+    in_flight: std::sync::atomic::AtomicUsize,
+    peak: std::sync::atomic::AtomicUsize,
+}
+
+impl InFlightHist {
+    fn new(name: &'static str) -> std::sync::Arc<Self> {
+        std::sync::Arc::new(Self {
+            name,
+            in_flight: std::sync::atomic::AtomicUsize::new(0),
+            peak: std::sync::atomic::AtomicUsize::new(0),
+        })
+    }
+
+    fn peak(&self) -> usize {
+        self.peak.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[async_trait::async_trait]
+impl borsa_core::connector::HistoryProvider for InFlightHist {
+    async fn history(
+        &self,
+        i: &Instrument,
+        r: borsa_core::HistoryRequest,
+    ) -> Result<borsa_core::HistoryResponse, borsa_core::BorsaError> {
+        use std::sync::atomic::Ordering;
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        m_hist("conn", &[1, 2]).history(i, r).await
+    }
+
+    fn supported_history_intervals(&self, _k: AssetKind) -> &'static [borsa_core::Interval] {
+        crate::helpers::INTERVALS
+    }
+}
+
+#[async_trait::async_trait]
+impl borsa_core::BorsaConnector for InFlightHist {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+
+    fn as_history_provider(&self) -> Option<&dyn borsa_core::connector::HistoryProvider> {
+        Some(self as &dyn borsa_core::connector::HistoryProvider)
+    }
+}
+
+fn universe(n: usize) -> Vec<Instrument> {
+    (0..n)
+        .map(|i| {
+            let sym = Symbol::new(&format!("S{i}")).expect("valid symbol");
+            crate::helpers::instrument(&sym, AssetKind::Equity)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn download_respects_concurrency_limit() {
+    let conn = InFlightHist::new("inflight");
+    let borsa = Borsa::builder()
+        .with_connector(conn.clone())
+        .build()
+        .unwrap();
+
+    let report = borsa
+        .download()
+        .instruments(&universe(8))
+        .unwrap()
+        .range(Range::D5)
+        .concurrency(2)
+        .run()
+        .await
+        .unwrap();
+
+    assert_eq!(report.response.expect("download response").entries.len(), 8);
+    assert_eq!(conn.peak(), 2);
+}
+
+#[tokio::test]
+async fn download_caps_in_flight_calls_per_provider() {
+    let slow = InFlightHist::new("slow");
+    let other = InFlightHist::new("other");
+    let borsa = Borsa::builder()
+        .with_connector(slow.clone())
+        .with_connector(other.clone())
+        .merge_history_strategy(borsa::MergeStrategy::Deep)
+        .build()
+        .unwrap();
+
+    let report = borsa
+        .download()
+        .instruments(&universe(4))
+        .unwrap()
+        .range(Range::D5)
+        .max_in_flight_per_provider(1)
+        .run()
+        .await
+        .unwrap();
+
+    assert_eq!(report.response.expect("download response").entries.len(), 4);
+    assert_eq!(slow.peak(), 1);
+    assert_eq!(other.peak(), 1);
+}
+
+#[tokio::test]
+async fn download_run_stream_yields_results_and_reports_progress() {
+    use futures::StreamExt;
+
+    let borsa = Borsa::builder()
+        .with_connector(std::sync::Arc::new(MultiSymbolHist))
+        .build()
+        .unwrap();
+    let instruments: Vec<_> = ["A", "B", "Z"]
+        .iter()
+        .map(|s| crate::helpers::instrument(&Symbol::new(s).unwrap(), AssetKind::Equity))
+        .collect();
+
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    let results: Vec<_> = borsa
+        .download()
+        .instruments(&instruments)
+        .unwrap()
+        .range(Range::D5)
+        .concurrency(1)
+        .on_progress(move |p| sink.lock().unwrap().push(p))
+        .run_stream()
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 2);
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 3);
+    assert_eq!(
+        seen.last().copied(),
+        Some(borsa::DownloadProgress {
+            completed: 3,
+            failed: 1,
+            total: 3,
+        })
+    );
+}
+
+/// History connector that counts calls per symbol and can fail "B" transiently.
+struct FlakyHist {
+    calls: std::sync::Mutex<std::collections::HashMap<String, usize>>,
+    fail_b: std::sync::atomic::AtomicBool,
+}
+
+impl FlakyHist {
+    fn calls(&self, sym: &str) -> usize {
+        self.calls.lock().unwrap().get(sym).copied().unwrap_or(0)
+    }
+}
+
+#[async_trait::async_trait]
+impl borsa_core::connector::HistoryProvider for FlakyHist {
+    async fn history(
+        &self,
+        i: &Instrument,
+        r: borsa_core::HistoryRequest,
+    ) -> Result<borsa_core::HistoryResponse, borsa_core::BorsaError> {
+        let sym = match i.id() {
+            borsa_core::IdentifierScheme::Security(sec) => sec.symbol.as_str().to_string(),
+            borsa_core::IdentifierScheme::Prediction(_) => String::new(),
+        };
+        *self.calls.lock().unwrap().entry(sym.clone()).or_default() += 1;
+        if sym == "B" && self.fail_b.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(borsa_core::BorsaError::RateLimitExceeded {
+                limit: 1,
+                window_ms: 1_000,
+            });
+        }
+        if sym == "Z" {
+            return Err(borsa_core::BorsaError::not_found("unknown symbol"));
+        }
+        m_hist("conn", &[1, 2]).history(i, r).await
+    }
+
+    fn supported_history_intervals(&self, _k: AssetKind) -> &'static [borsa_core::Interval] {
+        crate::helpers::INTERVALS
+    }
+}
+
+#[async_trait::async_trait]
+impl borsa_core::BorsaConnector for FlakyHist {
+    fn name(&self) -> &'static str {
+        "flaky"
+    }
+
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+
+    fn as_history_provider(&self) -> Option<&dyn borsa_core::connector::HistoryProvider> {
+        Some(self as &dyn borsa_core::connector::HistoryProvider)
+    }
+}
+
+#[tokio::test]
+async fn download_checkpoint_resumes_and_retries_transient_failures() {
+    let conn = std::sync::Arc::new(FlakyHist {
+        calls: std::sync::Mutex::new(std::collections::HashMap::new()),
+        fail_b: std::sync::atomic::AtomicBool::new(true),
+    });
+    let borsa = Borsa::builder()
+        .with_connector(conn.clone())
+        .build()
+        .unwrap();
+    let instruments: Vec<_> = ["A", "B", "Z"]
+        .iter()
+        .map(|s| crate::helpers::instrument(&Symbol::new(s).unwrap(), AssetKind::Equity))
+        .collect();
+    let dir = tempfile::tempdir().unwrap();
+    let run = || {
+        borsa
+            .download()
+            .instruments(&instruments)
+            .unwrap()
+            .period(0, 10 * 86_400)
+            .checkpoint(dir.path())
+            .run()
+    };
+
+    let first = run().await.unwrap();
+    assert_eq!(first.response.expect("A succeeded").entries.len(), 1);
+    assert_eq!(first.warnings.len(), 2);
+
+    conn.fail_b
+        .store(false, std::sync::atomic::Ordering::SeqCst);
+    let second = run().await.unwrap();
+    let entries = second.response.expect("download response").entries;
+    assert_eq!(entries.len(), 2);
+    assert_eq!(second.warnings.len(), 1);
+    assert!(second.warnings[0].is_permanent());
+
+    // Only the transient failure was fetched again.
+    assert_eq!(conn.calls("A"), 1);
+    assert_eq!(conn.calls("B"), 2);
+    assert_eq!(conn.calls("Z"), 1);
+
+    // A completed checkpoint reproduces the same report without fetching.
+    let third = run().await.unwrap();
+    assert_eq!(third.response.expect("download response").entries, entries);
+    assert_eq!(conn.calls("B"), 2);
+}
+
+/// Daily history ending today whose closes are the number of calls made so far.
+struct TailHist {
+    requests: std::sync::Mutex<Vec<borsa_core::HistoryRequest>>,
+}
+
+#[async_trait::async_trait]
+impl borsa_core::connector::HistoryProvider for TailHist {
+    async fn history(
+        &self,
+        _i: &Instrument,
+        r: borsa_core::HistoryRequest,
+    ) -> Result<borsa_core::HistoryResponse, borsa_core::BorsaError> {
+        let call = {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(r.clone());
+            requests.len()
+        };
+        let today = chrono::Utc::now().timestamp() / 86_400 * 86_400;
+        let candles = [today - 86_400, today]
+            .into_iter()
+            .filter(|ts| {
+                r.period()
+                    .is_none_or(|(start, end)| *ts >= start.timestamp() && *ts < end.timestamp())
+            })
+            .map(|ts| crate::helpers::candle(ts, f64::from(u32::try_from(call).unwrap())))
+            .collect();
+        Ok(borsa_core::HistoryResponse {
+            candles,
+            actions: vec![],
+            adjusted: false,
+            meta: None,
+        })
+    }
+
+    fn supported_history_intervals(&self, _k: AssetKind) -> &'static [borsa_core::Interval] {
+        crate::helpers::INTERVALS
+    }
+}
+
+#[async_trait::async_trait]
+impl borsa_core::BorsaConnector for TailHist {
+    fn name(&self) -> &'static str {
+        "tail"
+    }
+
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+
+    fn as_history_provider(&self) -> Option<&dyn borsa_core::connector::HistoryProvider> {
+        Some(self as &dyn borsa_core::connector::HistoryProvider)
+    }
+}
+
+#[tokio::test]
+async fn download_checkpoint_refreshes_the_tail_of_range_histories() {
+    let conn = std::sync::Arc::new(TailHist {
+        requests: std::sync::Mutex::new(Vec::new()),
+    });
+    let borsa = Borsa::builder()
+        .with_connector(conn.clone())
+        .build()
+        .unwrap();
+    let a = crate::helpers::instrument(&Symbol::new("A").unwrap(), AssetKind::Equity);
+    let dir = tempfile::tempdir().unwrap();
+    let run = || {
+        borsa
+            .download()
+            .instruments(std::slice::from_ref(&a))
+            .unwrap()
+            .range(Range::D5)
+            .checkpoint(dir.path())
+            .run()
+    };
+
+    let closes = |report: borsa_core::DownloadReport| -> Vec<String> {
+        report.response.expect("download response").entries[0]
+            .history
+            .candles
+            .iter()
+            .map(|c| c.close.amount().to_string())
+            .collect()
+    };
+    assert_eq!(closes(run().await.unwrap()), ["1", "1"]);
+
+    // The resumed run fetches only from the last recorded bar and replaces it.
+    assert_eq!(closes(run().await.unwrap()), ["1", "2"]);
+    let requests = conn.requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 2);
+    let (tail_start, _) = requests[1].period().expect("tail is a period request");
+    assert_eq!(
+        tail_start.timestamp(),
+        chrono::Utc::now().timestamp() / 86_400 * 86_400
+    );
+}
+
+#[tokio::test]
+async fn download_checkpoint_rejects_a_different_request() {
+    let borsa = Borsa::builder()
+        .with_connector(std::sync::Arc::new(MultiSymbolHist))
+        .build()
+        .unwrap();
+    let a = crate::helpers::instrument(&Symbol::new("A").unwrap(), AssetKind::Equity);
+    let dir = tempfile::tempdir().unwrap();
+
+    borsa
+        .download()
+        .instruments(std::slice::from_ref(&a))
+        .unwrap()
+        .range(Range::D5)
+        .checkpoint(dir.path())
+        .run()
+        .await
+        .unwrap();
+    let err = borsa
+        .download()
+        .instruments(&[a])
+        .unwrap()
+        .range(Range::M1)
+        .checkpoint(dir.path())
+        .run()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, borsa_core::BorsaError::InvalidArg(_)),
+        "got {err:?}"
+    );
+}
@@ -18,11 +18,11 @@ fn instrument() -> Instrument {
 }
 
 fn quota_config(limit: u64) -> QuotaConfig {
-    QuotaConfig {
+    QuotaConfig::new(
         limit,
-        window: Duration::from_secs(3_600),
-        strategy: QuotaConsumptionStrategy::Unit,
-    }
+        Duration::from_secs(3_600),
+        QuotaConsumptionStrategy::Unit,
+    )
 }
 
 fn base_quote() -> Quote {
@@ -153,6 +153,7 @@ async fn quote_strict_rule_blocks_fallback() {
                 symbol: Some(x),
                 kind: Some(AssetKind::Equity),
                 exchange: None,
+                capability: None,
             },
             &[only.key()],
             true,
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::helpers::{AAPL, GOOG, MSFT, MockConnector, TSLA, quote_fixture};
+use borsa::Borsa;
+use borsa_core::{AssetKind, BorsaConnector, Instrument, Quote, RoutingPolicyBuilder};
+
+fn symbol_of(q: &Quote) -> String {
+    match q.instrument.id() {
+        borsa_core::IdentifierScheme::Security(sec) => sec.symbol.as_str().to_string(),
+        borsa_core::IdentifierScheme::Prediction(_) => panic!("unexpected non-security"),
+    }
+}
+
+fn batch_response(insts: &[Instrument]) -> Vec<Quote> {
+    insts
+        .iter()
+        .map(|i| {
+            let mut q = quote_fixture(&AAPL, "10.0");
+            q.instrument = i.clone();
+            q
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn quotes_use_batch_provider_in_chunks() {
+    let batch_calls = Arc::new(AtomicUsize::new(0));
+    let single_calls = Arc::new(AtomicUsize::new(0));
+    let bc = Arc::clone(&batch_calls);
+    let sc = Arc::clone(&single_calls);
+
+    let conn = MockConnector::builder()
+        .name("batchy")
+        .max_batch_size(2)
+        .with_batch_quotes_fn(move |insts| {
+            bc.fetch_add(1, Ordering::SeqCst);
+            assert!(insts.len() <= 2, "chunk exceeds max_batch_size");
+            Ok(batch_response(insts))
+        })
+        .with_quote_fn(move |_i| {
+            sc.fetch_add(1, Ordering::SeqCst);
+            Ok(quote_fixture(&AAPL, "1.0"))
+        })
+        .build();
+
+    let borsa = Borsa::builder().with_connector(conn).build().unwrap();
+
+    let insts = &[
+        crate::helpers::instrument(&AAPL, AssetKind::Equity),
+        crate::helpers::instrument(&MSFT, AssetKind::Equity),
+        crate::helpers::instrument(&GOOG, AssetKind::Equity),
+    ];
+
+    let (quotes, errs) = borsa.quotes(insts).await.unwrap();
+    assert!(errs.is_empty());
+    assert_eq!(
+        quotes.iter().map(symbol_of).collect::<Vec<_>>(),
+        vec!["AAPL", "MSFT", "GOOG"],
+        "successes follow input order"
+    );
+    assert_eq!(
+        batch_calls.load(Ordering::SeqCst),
+        2,
+        "3 symbols in chunks of 2"
+    );
+    assert_eq!(single_calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn quotes_missing_from_batch_fall_back_to_single_routing() {
+    // Batch omits GOOG; single-quote routing must pick it up from the backup provider.
+    let primary = MockConnector::builder()
+        .name("primary")
+        .with_batch_quotes_fn(|insts| {
+            let keep: Vec<Instrument> = insts
+                .iter()
+                .filter(|i| **i != crate::helpers::instrument(&GOOG, AssetKind::Equity))
+                .cloned()
+                .collect();
+            Ok(batch_response(&keep))
+        })
+        .with_quote_fn(|_i| Err(borsa_core::BorsaError::not_found("quote")))
+        .build();
+    let backup = MockConnector::builder()
+        .name("backup")
+        .returns_quote_ok(quote_fixture(&GOOG, "30.0"))
+        .build();
+
+    let borsa = Borsa::builder()
+        .with_connector(primary)
+        .with_connector(backup)
+        .build()
+        .unwrap();
+
+    let insts = &[
+        crate::helpers::instrument(&AAPL, AssetKind::Equity),
+        crate::helpers::instrument(&GOOG, AssetKind::Equity),
+    ];
+
+    let (quotes, errs) = borsa.quotes(insts).await.unwrap();
+    assert!(errs.is_empty());
+    assert_eq!(quotes.len(), 2);
+    assert_eq!(symbol_of(&quotes[1]), "GOOG");
+    assert_eq!(
+        quotes[1].price.as_ref().unwrap().amount(),
+        rust_decimal::Decimal::from(30u8)
+    );
+}
+
+#[tokio::test]
+async fn quotes_transiently_failed_batch_falls_back_per_instrument() {
+    let primary = MockConnector::builder()
+        .name("primary")
+        .with_batch_quotes_fn(|_insts| {
+            Err(borsa_core::BorsaError::provider_timeout("primary", "quote"))
+        })
+        .returns_quote_ok(quote_fixture(&AAPL, "5.0"))
+        .build();
+
+    let borsa = Borsa::builder().with_connector(primary).build().unwrap();
+
+    let insts = &[
+        crate::helpers::instrument(&AAPL, AssetKind::Equity),
+        crate::helpers::instrument(&TSLA, AssetKind::Equity),
+    ];
+
+    let (quotes, errs) = borsa.quotes(insts).await.unwrap();
+    assert!(errs.is_empty());
+    assert_eq!(quotes.len(), 2);
+}
+
+#[tokio::test]
+async fn quotes_unclassified_batch_failure_falls_back_to_secondary_provider() {
+    let primary = MockConnector::builder()
+        .name("primary")
+        .with_batch_quotes_fn(|_insts| Err(borsa_core::BorsaError::Other("http 502".into())))
+        .with_quote_fn(|_i| Err(borsa_core::BorsaError::Other("http 502".into())))
+        .build();
+    let backup = MockConnector::builder()
+        .name("backup")
+        .returns_quote_ok(quote_fixture(&AAPL, "7.0"))
+        .build();
+
+    let borsa = Borsa::builder()
+        .with_connector(primary)
+        .with_connector(backup)
+        .build()
+        .unwrap();
+
+    let insts = &[
+        crate::helpers::instrument(&AAPL, AssetKind::Equity),
+        crate::helpers::instrument(&TSLA, AssetKind::Equity),
+    ];
+
+    let (quotes, errs) = borsa.quotes(insts).await.unwrap();
+    assert!(errs.is_empty());
+    assert_eq!(quotes.len(), 2);
+    assert!(
+        quotes
+            .iter()
+            .all(|q| q.price.as_ref().unwrap().amount() == rust_decimal::Decimal::from(7u8))
+    );
+}
+
+#[tokio::test]
+async fn quotes_permanently_failed_batch_fails_whole_chunk() {
+    let single_calls = Arc::new(AtomicUsize::new(0));
+    let sc = Arc::clone(&single_calls);
+    let primary = MockConnector::builder()
+        .name("primary")
+        .with_batch_quotes_fn(|_insts| Err(borsa_core::BorsaError::InvalidArg("boom".into())))
+        .with_quote_fn(move |_i| {
+            sc.fetch_add(1, Ordering::SeqCst);
+            Ok(quote_fixture(&AAPL, "5.0"))
+        })
+        .build();
+
+    let borsa = Borsa::builder().with_connector(primary).build().unwrap();
+
+    let insts = &[
+        crate::helpers::instrument(&AAPL, AssetKind::Equity),
+        crate::helpers::instrument(&TSLA, AssetKind::Equity),
+    ];
+
+    let (quotes, errs) = borsa.quotes(insts).await.unwrap();
+    assert!(quotes.is_empty());
+    assert_eq!(
+        errs.iter().map(|(i, _)| i.clone()).collect::<Vec<_>>(),
+        insts.to_vec()
+    );
+    for (_, e) in &errs {
+        assert_eq!(
+            *e,
+            borsa_core::BorsaError::connector(
+                "primary",
+                borsa_core::BorsaError::InvalidArg("boom".into())
+            )
+        );
+    }
+    assert_eq!(
+        single_calls.load(Ordering::SeqCst),
+        0,
+        "no per-instrument retries"
+    );
+}
+
+#[tokio::test]
+async fn quotes_group_batches_by_routed_provider() {
+    let a_batches = Arc::new(AtomicUsize::new(0));
+    let b_batches = Arc::new(AtomicUsize::new(0));
+    let ac = Arc::clone(&a_batches);
+    let bc = Arc::clone(&b_batches);
+
+    let a = MockConnector::builder()
+        .name("a")
+        .with_batch_quotes_fn(move |insts| {
+            ac.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(insts.len(), 1);
+            Ok(batch_response(insts))
+        })
+        .returns_quote_ok(quote_fixture(&AAPL, "1.0"))
+        .build();
+    let b = MockConnector::builder()
+        .name("b")
+        .with_batch_quotes_fn(move |insts| {
+            bc.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(insts.len(), 1);
+            Ok(batch_response(insts))
+        })
+        .returns_quote_ok(quote_fixture(&MSFT, "2.0"))
+        .build();
+
+    let policy = RoutingPolicyBuilder::new()
+        .providers_for_symbol(&AAPL, &[a.key(), b.key()])
+        .providers_for_symbol(&MSFT, &[b.key(), a.key()])
+        .build();
+
+    let borsa = Borsa::builder()
+        .with_connector(a.clone())
+        .with_connector(b.clone())
+        .routing_policy(policy)
+        .build()
+        .unwrap();
+
+    let insts = &[
+        crate::helpers::instrument(&AAPL, AssetKind::Equity),
+        crate::helpers::instrument(&MSFT, AssetKind::Equity),
+    ];
+
+    let (quotes, errs) = borsa.quotes(insts).await.unwrap();
+    assert!(errs.is_empty());
+    assert_eq!(quotes.len(), 2);
+    assert_eq!(a_batches.load(Ordering::SeqCst), 1);
+    assert_eq!(b_batches.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::helpers::{AAPL, BTC_USD, MockConnector, candle, dt, instrument, usd};
+use borsa_core::{
+    AssetKind, BorsaConnector, CandleSynthesisConfig, CandleUpdate, Capability, EmptyIntervals,
+    Exchange, Instrument, Interval, LateTicks, Money, QuoteUpdate, RoutingPolicyBuilder, Selector,
+    StalenessConfig, TradingHours,
+};
+use chrono::{DateTime, NaiveTime, Utc};
+
+fn tick(ts: DateTime<Utc>, price: &str, volume: Option<u64>) -> QuoteUpdate {
+    QuoteUpdate {
+        instrument: instrument(&AAPL, AssetKind::Equity),
+        price: Some(usd(price)),
+        previous_close: None,
+        ts,
+        volume,
+    }
+}
+
+type Bar = (DateTime<Utc>, Money, Money, Money, Money, Option<u64>, bool);
+
+fn bar(u: &CandleUpdate) -> Bar {
+    let c = &u.candle;
+    (
+        c.ts,
+        c.open.clone(),
+        c.high.clone(),
+        c.low.clone(),
+        c.close.clone(),
+        c.volume,
+        u.is_final,
+    )
+}
+
+fn flat(ts: DateTime<Utc>, price: &str, volume: Option<u64>, is_final: bool) -> Bar {
+    ohlc(ts, [price, price, price, price], volume, is_final)
+}
+
+fn ohlc(ts: DateTime<Utc>, [o, h, l, c]: [&str; 4], volume: Option<u64>, is_final: bool) -> Bar {
+    (ts, usd(o), usd(h), usd(l), usd(c), volume, is_final)
+}
+
+/// Receive `n` updates; the quote stream is restarted by the router once the mock runs out.
+async fn take(rx: &mut tokio::sync::mpsc::Receiver<CandleUpdate>, n: usize) -> Vec<CandleUpdate> {
+    let mut out = Vec::with_capacity(n);
+    for _ in 0..n {
+        let u = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .expect("update before timeout")
+            .expect("stream open");
+        out.push(u);
+    }
+    out
+}
+
+#[tokio::test]
+async fn stream_candles_synthesizes_bars_from_quotes() {
+    let quotes = MockConnector::builder()
+        .name("Q")
+        .supports_kind(AssetKind::Equity)
+        .with_stream_updates(vec![
+            tick(dt(2024, 3, 1, 14, 30, 5), "100", Some(1_000)),
+            tick(dt(2024, 3, 1, 14, 30, 40), "101", Some(1_500)),
+            tick(dt(2024, 3, 1, 14, 31, 10), "99", Some(1_800)),
+            tick(dt(2024, 3, 1, 14, 33, 0), "102", Some(2_000)),
+        ])
+        .build();
+    let borsa = borsa::Borsa::builder()
+        .with_connector(quotes)
+        .build()
+        .unwrap();
+
+    let (_handle, mut rx) = borsa
+        .stream_candles(&[instrument(&AAPL, AssetKind::Equity)], Interval::I1m)
+        .await
+        .expect("synthesized stream started");
+    let updates = take(&mut rx, 6).await;
+
+    assert!(updates.iter().all(|u| u.interval == Interval::I1m));
+    let m30 = dt(2024, 3, 1, 14, 30, 0);
+    let m31 = dt(2024, 3, 1, 14, 31, 0);
+    let m33 = dt(2024, 3, 1, 14, 33, 0);
+    assert_eq!(
+        updates.iter().map(bar).collect::<Vec<_>>(),
+        [
+            flat(m30, "100", Some(0), false),
+            ohlc(m30, ["100", "101", "100", "101"], Some(500), false),
+            ohlc(m30, ["100", "101", "100", "101"], Some(500), true),
+            flat(m31, "99", Some(300), false),
+            flat(m31, "99", Some(300), true),
+            // 14:32 had no ticks and is skipped by default.
+            flat(m33, "102", Some(200), false),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn synthesis_aligns_to_timezone_carries_forward_and_revises() {
+    let quotes = MockConnector::builder()
+        .name("Q")
+        .supports_kind(AssetKind::Equity)
+        .with_stream_updates(vec![
+            tick(dt(2024, 3, 1, 4, 0, 0), "100", None),
+            tick(dt(2024, 3, 1, 5, 0, 0), "101", None),
+            // Late tick for the bar finalized by the previous tick.
+            tick(dt(2024, 3, 1, 4, 10, 0), "105", None),
+            tick(dt(2024, 3, 1, 7, 0, 0), "103", None),
+        ])
+        .build();
+    let borsa = borsa::Borsa::builder()
+        .with_connector(quotes)
+        .stream_enforce_monotonic_timestamps(false)
+        .candle_synthesis(CandleSynthesisConfig {
+            empty_intervals: EmptyIntervals::CarryForward,
+            late_ticks: LateTicks::Revise,
+            // UTC+05:30, so hourly bars start at half past the UTC hour.
+            default_timezone: chrono_tz::Asia::Kolkata,
+            ..CandleSynthesisConfig::default()
+        })
+        .build()
+        .unwrap();
+
+    let (_handle, mut rx) = borsa
+        .stream_candles(&[instrument(&AAPL, AssetKind::Equity)], Interval::I1h)
+        .await
+        .expect("synthesized stream started");
+    let updates = take(&mut rx, 7).await;
+
+    let finals: Vec<Bar> = updates.iter().filter(|u| u.is_final).map(bar).collect();
+    assert_eq!(
+        finals,
+        [
+            flat(dt(2024, 3, 1, 3, 30, 0), "100", None, true),
+            // Revised by the late tick.
+            ohlc(
+                dt(2024, 3, 1, 3, 30, 0),
+                ["100", "105", "100", "105"],
+                None,
+                true
+            ),
+            flat(dt(2024, 3, 1, 4, 30, 0), "101", None, true),
+            // No ticks between 05:30 and 06:30: a flat bar at the previous close.
+            flat(dt(2024, 3, 1, 5, 30, 0), "101", Some(0), true),
+        ]
+    );
+    assert_eq!(
+        bar(&updates[6]),
+        flat(dt(2024, 3, 1, 6, 30, 0), "103", None, false)
+    );
+}
+
+#[tokio::test]
+async fn carry_forward_skips_intervals_while_the_exchange_is_closed() {
+    let nasdaq = Exchange::try_from_str("NASDAQ").unwrap();
+    let aapl =
+        Instrument::from_symbol_and_exchange("AAPL", nasdaq.clone(), AssetKind::Equity).unwrap();
+    let quotes = MockConnector::builder()
+        .name("Q")
+        .supports_kind(AssetKind::Equity)
+        .with_stream_updates(vec![
+            // Friday 15:30 and Monday 10:10 in New York (UTC-5).
+            QuoteUpdate {
+                instrument: aapl.clone(),
+                ..tick(dt(2024, 3, 1, 20, 30, 0), "100", None)
+            },
+            QuoteUpdate {
+                instrument: aapl.clone(),
+                ..tick(dt(2024, 3, 4, 15, 10, 0), "101", None)
+            },
+        ])
+        .build();
+    let hours = TradingHours::monday_to_friday(
+        chrono_tz::America::New_York,
+        NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+        NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+    );
+    let borsa = borsa::Borsa::builder()
+        .with_connector(quotes)
+        .candle_synthesis(CandleSynthesisConfig {
+            empty_intervals: EmptyIntervals::CarryForward,
+            ..CandleSynthesisConfig::default()
+        })
+        .stream_staleness(StalenessConfig {
+            exchange_hours: HashMap::from([(nasdaq, hours)]),
+            ..StalenessConfig::default()
+        })
+        .build()
+        .unwrap();
+
+    let (_handle, mut rx) = borsa
+        .stream_candles(&[aapl], Interval::I1h)
+        .await
+        .expect("synthesized stream started");
+    let updates = take(&mut rx, 4).await;
+
+    assert_eq!(
+        updates.iter().map(bar).collect::<Vec<_>>(),
+        [
+            flat(dt(2024, 3, 1, 20, 0, 0), "100", None, false),
+            flat(dt(2024, 3, 1, 20, 0, 0), "100", None, true),
+            // Nothing from Friday's close until the 09:00 bar, in which the market opens.
+            flat(dt(2024, 3, 4, 14, 0, 0), "100", Some(0), true),
+            flat(dt(2024, 3, 4, 15, 0, 0), "101", None, false),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn synthesis_finalizes_quiet_bars_on_the_wall_clock() {
+    let ts = Utc::now() - chrono::Duration::minutes(2);
+    let quotes = MockConnector::builder()
+        .name("Q")
+        .supports_kind(AssetKind::Equity)
+        .with_stream_updates(vec![tick(ts, "100", None)])
+        .build();
+    let borsa = borsa::Borsa::builder()
+        .with_connector(quotes)
+        .candle_synthesis(CandleSynthesisConfig {
+            finalize_after: Some(Duration::ZERO),
+            ..CandleSynthesisConfig::default()
+        })
+        .build()
+        .unwrap();
+
+    let (_handle, mut rx) = borsa
+        .stream_candles(&[instrument(&AAPL, AssetKind::Equity)], Interval::I1m)
+        .await
+        .expect("synthesized stream started");
+    let updates = take(&mut rx, 2).await;
+    assert!(!updates[0].is_final);
+    assert!(updates[1].is_final);
+    assert_eq!(updates[1].candle, updates[0].candle);
+}
+
+#[tokio::test]
+async fn stream_candles_mixes_native_and_synthesized_instruments() {
+    let native = CandleUpdate {
+        instrument: instrument(&AAPL, AssetKind::Equity),
+        interval: Interval::I1m,
+        candle: candle(60, 200.0),
+        is_final: true,
+    };
+    let candles = MockConnector::builder()
+        .name("C")
+        .supports_kind(AssetKind::Equity)
+        .with_candle_stream_updates(vec![native.clone()])
+        .build();
+    let btc = instrument(&BTC_USD, AssetKind::Crypto);
+    let quotes = MockConnector::builder()
+        .name("Q")
+        .supports_kind(AssetKind::Crypto)
+        .with_stream_updates(vec![QuoteUpdate {
+            instrument: btc.clone(),
+            ..tick(dt(2024, 3, 1, 0, 0, 30), "60000", None)
+        }])
+        .build();
+    let borsa = borsa::Borsa::builder()
+        .with_connector(candles)
+        .with_connector(quotes)
+        .build()
+        .unwrap();
+
+    let (_handle, mut rx) = borsa
+        .stream_candles(
+            &[instrument(&AAPL, AssetKind::Equity), btc.clone()],
+            Interval::I1m,
+        )
+        .await
+        .expect("mixed stream started");
+    let updates = take(&mut rx, 2).await;
+
+    assert!(updates.contains(&native));
+    let synthesized = updates
+        .iter()
+        .find(|u| u.instrument == btc)
+        .expect("synthesized BTC bar");
+    assert_eq!(synthesized.candle.ts, dt(2024, 3, 1, 0, 0, 0));
+    assert!(!synthesized.is_final);
+}
+
+#[tokio::test]
+async fn stream_candles_synthesizes_instruments_the_policy_keeps_from_candle_connectors() {
+    let candles = MockConnector::builder()
+        .name("C")
+        .supports_kind(AssetKind::Equity)
+        .with_candle_stream_updates(vec![])
+        .build();
+    let quotes = MockConnector::builder()
+        .name("Q")
+        .supports_kind(AssetKind::Equity)
+        .with_stream_updates(vec![tick(dt(2024, 3, 1, 14, 30, 5), "100", None)])
+        .build();
+    // Only Q may stream AAPL candles, and Q streams quotes only.
+    let policy = RoutingPolicyBuilder::new()
+        .providers_rule(
+            Selector {
+                symbol: Some(AAPL.clone()),
+                capability: Some(Capability::StreamCandles),
+                ..Selector::default()
+            },
+            &[quotes.key()],
+            true,
+        )
+        .build();
+    let borsa = borsa::Borsa::builder()
+        .with_connector(candles)
+        .with_connector(quotes)
+        .routing_policy(policy)
+        .build()
+        .unwrap();
+
+    let (_handle, mut rx) = borsa
+        .stream_candles(&[instrument(&AAPL, AssetKind::Equity)], Interval::I1m)
+        .await
+        .expect("synthesized stream started");
+    let updates = take(&mut rx, 1).await;
+    assert_eq!(
+        bar(&updates[0]),
+        flat(dt(2024, 3, 1, 14, 30, 0), "100", None, false)
+    );
+}
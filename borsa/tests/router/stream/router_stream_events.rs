@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+use crate::helpers::{AAPL, BurstStreamer, MSFT, MockConnector, StreamStep, instrument, update};
+use borsa::{BackoffConfig, Borsa, OverflowPolicy};
+use borsa_core::stream::StreamEvent;
+use borsa_core::{AssetKind, BorsaConnector, RoutingPolicyBuilder};
+use chrono::TimeZone;
+
+const BACKOFF: BackoffConfig = BackoffConfig {
+    min_backoff_ms: 40,
+    max_backoff_ms: 80,
+    factor: 1,
+    jitter_percent: 0,
+};
+
+/// Collect the next `n` events accepted by `keep`.
+async fn next_events(
+    events: &mut tokio::sync::mpsc::Receiver<StreamEvent>,
+    n: usize,
+    keep: impl Fn(&StreamEvent) -> bool,
+) -> Vec<StreamEvent> {
+    let mut out = Vec::with_capacity(n);
+    while out.len() < n {
+        let event = tokio::time::timeout(Duration::from_secs(2), events.recv())
+            .await
+            .expect("event before timeout")
+            .expect("events open");
+        if keep(&event) {
+            out.push(event);
+        }
+    }
+    out
+}
+
+#[tokio::test]
+async fn session_end_reports_failover_to_next_provider() {
+    let p1 = MockConnector::builder()
+        .name("P1")
+        .supports_kind(AssetKind::Equity)
+        .with_stream_steps(vec![
+            StreamStep::Updates(vec![update(&AAPL, 1)]),
+            StreamStep::StartError("p1 down"),
+        ])
+        .build();
+    let p2 = MockConnector::builder()
+        .name("P2")
+        .supports_kind(AssetKind::Equity)
+        .with_stream_updates(vec![update(&AAPL, 2)])
+        .build();
+    let policy = RoutingPolicyBuilder::new()
+        .providers_for_kind(AssetKind::Equity, &[p1.key(), p2.key()])
+        .build();
+    let borsa = Borsa::builder()
+        .with_connector(p1)
+        .with_connector(p2)
+        .routing_policy(policy)
+        .backoff(BACKOFF)
+        .build()
+        .unwrap();
+
+    let (mut handle, _rx) = borsa
+        .stream_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    let lifecycle = next_events(&mut events, 4, |e| {
+        matches!(
+            e,
+            StreamEvent::ProviderStarted { .. }
+                | StreamEvent::ProviderEnded { .. }
+                | StreamEvent::Failover { .. }
+        )
+    })
+    .await;
+    assert_eq!(
+        lifecycle,
+        vec![
+            StreamEvent::ProviderStarted {
+                provider: "P1",
+                symbols: vec![AAPL.clone()],
+            },
+            StreamEvent::ProviderEnded {
+                provider: "P1",
+                symbols: vec![AAPL.clone()],
+            },
+            StreamEvent::ProviderStarted {
+                provider: "P2",
+                symbols: vec![AAPL.clone()],
+            },
+            StreamEvent::Failover {
+                from: "P1",
+                to: "P2",
+                symbols: vec![AAPL.clone()],
+            },
+        ]
+    );
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn lost_coverage_reports_backoff_until_restart() {
+    let p1 = MockConnector::builder()
+        .name("P1")
+        .supports_kind(AssetKind::Equity)
+        .with_stream_steps(vec![
+            StreamStep::Updates(vec![update(&AAPL, 1)]),
+            StreamStep::Updates(vec![update(&AAPL, 2)]),
+        ])
+        .build();
+    let borsa = Borsa::builder()
+        .with_connector(p1)
+        .backoff(BACKOFF)
+        .build()
+        .unwrap();
+
+    let (mut handle, _rx) = borsa
+        .stream_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    let mut observed = next_events(&mut events, 5, |_| true).await;
+    let StreamEvent::BackoffScheduled { delay, symbols } = observed.remove(3) else {
+        panic!("expected a backoff event, got {observed:?}");
+    };
+    assert!(delay <= Duration::from_millis(BACKOFF.max_backoff_ms));
+    assert_eq!(symbols, vec![AAPL.clone()]);
+    assert_eq!(
+        observed,
+        vec![
+            StreamEvent::ProviderStarted {
+                provider: "P1",
+                symbols: vec![AAPL.clone()],
+            },
+            StreamEvent::ProviderEnded {
+                provider: "P1",
+                symbols: vec![AAPL.clone()],
+            },
+            StreamEvent::CoverageLost {
+                symbols: vec![AAPL.clone()],
+            },
+            StreamEvent::ProviderStarted {
+                provider: "P1",
+                symbols: vec![AAPL.clone()],
+            },
+        ],
+        "restarting on the same provider is not a failover"
+    );
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn filtered_updates_are_reported() {
+    let borsa = Borsa::builder()
+        .with_connector(std::sync::Arc::new(BurstStreamer {
+            updates: vec![update(&AAPL, 2), update(&AAPL, 1), update(&MSFT, 3)],
+        }))
+        .build()
+        .unwrap();
+
+    let (mut handle, mut rx) = borsa
+        .stream_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    assert_eq!(
+        next_events(&mut events, 3, |_| true).await,
+        vec![
+            StreamEvent::ProviderStarted {
+                provider: "burst",
+                symbols: vec![AAPL.clone()],
+            },
+            StreamEvent::OutOfOrderDropped {
+                provider: "burst",
+                symbol: AAPL.clone(),
+                ts: chrono::Utc.timestamp_opt(1, 0).unwrap(),
+            },
+            StreamEvent::UnassignedDropped {
+                provider: "burst",
+                symbol: MSFT.clone(),
+            },
+        ]
+    );
+    let first = rx.recv().await.expect("update");
+    assert_eq!(first.ts.timestamp(), 2);
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn dropped_update_reports_leave_room_for_lifecycle_events() {
+    let p1 = MockConnector::builder()
+        .name("P1")
+        .supports_kind(AssetKind::Equity)
+        .with_stream_steps(vec![
+            StreamStep::Updates((1..=600).map(|ts| update(&AAPL, ts)).collect()),
+            StreamStep::Updates(vec![]),
+        ])
+        .build();
+    let borsa = Borsa::builder()
+        .with_connector(p1)
+        .backoff(BACKOFF)
+        .stream_channel_capacity(1)
+        .stream_overflow(OverflowPolicy::DropNewest)
+        .build()
+        .unwrap();
+
+    let (mut handle, _rx) = borsa
+        .stream_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    // Nobody reads while the burst overflows the output and the session ends.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let mut observed = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        observed.push(event);
+    }
+    let drops = observed
+        .iter()
+        .filter(|e| matches!(e, StreamEvent::UpdatesDropped { .. }))
+        .count();
+    assert!(drops > 0 && drops < 256, "{drops} drop reports");
+    assert!(
+        observed.contains(&StreamEvent::CoverageLost {
+            symbols: vec![AAPL.clone()],
+        }),
+        "coverage loss is reported after the flood"
+    );
+    handle.stop().await;
+}
@@ -0,0 +1,257 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::helpers::{AAPL, MSFT, instrument, usd};
+use async_trait::async_trait;
+use borsa::{Borsa, OverflowPolicy};
+use borsa_core::{AssetKind, BorsaConnector, BorsaError, Instrument, QuoteUpdate};
+use chrono::TimeZone;
+
+/// Emits `burst` updates per subscribed instrument once `go` is set, then stays open until
+/// stopped.
+struct RecordingStreamer {
+    starts: Arc<Mutex<Vec<Vec<String>>>>,
+    burst: usize,
+    go: tokio::sync::watch::Receiver<bool>,
+    // increasing across sessions so restarted sessions pass the monotonic gate
+    ts: AtomicI64,
+}
+
+#[async_trait]
+impl borsa_core::connector::StreamProvider for RecordingStreamer {
+    async fn stream_quotes(
+        &self,
+        instruments: &[Instrument],
+    ) -> Result<
+        (
+            borsa_core::stream::StreamHandle,
+            tokio::sync::mpsc::Receiver<QuoteUpdate>,
+        ),
+        BorsaError,
+    > {
+        self.starts
+            .lock()
+            .unwrap()
+            .push(instruments.iter().map(symbol_of).collect());
+
+        let (tx, rx) = tokio::sync::mpsc::channel::<QuoteUpdate>(16);
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let mut updates = Vec::new();
+        for _ in 0..self.burst {
+            for inst in instruments {
+                updates.push(QuoteUpdate {
+                    instrument: inst.clone(),
+                    price: Some(usd("1.0")),
+                    previous_close: None,
+                    ts: chrono::Utc
+                        .timestamp_opt(self.ts.fetch_add(1, Ordering::Relaxed) + 1, 0)
+                        .unwrap(),
+                    volume: None,
+                });
+            }
+        }
+        let mut go = self.go.clone();
+        let join = tokio::spawn(async move {
+            let _ = go.wait_for(|go| *go).await;
+            for u in updates {
+                if tx.send(u).await.is_err() {
+                    return;
+                }
+            }
+            let _ = stop_rx.await;
+        });
+        Ok((borsa_core::stream::StreamHandle::new(join, stop_tx), rx))
+    }
+}
+
+#[async_trait]
+impl BorsaConnector for RecordingStreamer {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+    fn as_stream_provider(&self) -> Option<&dyn borsa_core::connector::StreamProvider> {
+        Some(self)
+    }
+}
+
+fn symbol_of(inst: &Instrument) -> String {
+    match inst.id() {
+        borsa_core::IdentifierScheme::Security(sec) => sec.symbol.as_str().to_string(),
+        borsa_core::IdentifierScheme::Prediction(_) => "<non-security>".to_string(),
+    }
+}
+
+fn setup(
+    burst: usize,
+    go: bool,
+) -> (
+    Borsa,
+    Arc<Mutex<Vec<Vec<String>>>>,
+    tokio::sync::watch::Sender<bool>,
+) {
+    let starts = Arc::new(Mutex::new(Vec::new()));
+    let (go_tx, go_rx) = tokio::sync::watch::channel(go);
+    let conn = Arc::new(RecordingStreamer {
+        starts: Arc::clone(&starts),
+        burst,
+        go: go_rx,
+        ts: AtomicI64::new(0),
+    });
+    let borsa = Borsa::builder()
+        .with_connector(conn)
+        .stream_channel_capacity(16)
+        .build()
+        .unwrap();
+    (borsa, starts, go_tx)
+}
+
+async fn next_symbol(rx: &mut tokio::sync::mpsc::Receiver<QuoteUpdate>) -> String {
+    let u = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("update before timeout")
+        .expect("channel open");
+    symbol_of(&u.instrument)
+}
+
+async fn wait_starts(starts: &Mutex<Vec<Vec<String>>>, n: usize) {
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while starts.lock().unwrap().len() < n {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("session restarted");
+}
+
+fn strings(symbols: &[&str]) -> Vec<String> {
+    symbols.iter().map(ToString::to_string).collect()
+}
+
+#[tokio::test]
+async fn consumers_share_one_upstream_session() {
+    let (borsa, starts, _go) = setup(1, true);
+    let aapl = instrument(&AAPL, AssetKind::Equity);
+
+    let (_first, mut first_rx) = borsa
+        .share_quotes(std::slice::from_ref(&aapl), OverflowPolicy::Block)
+        .await
+        .expect("first consumer");
+    assert_eq!(next_symbol(&mut first_rx).await, "AAPL");
+
+    let (_second, mut second_rx) = borsa
+        .share_quotes(std::slice::from_ref(&aapl), OverflowPolicy::Block)
+        .await
+        .expect("second consumer");
+    assert_eq!(
+        starts.lock().unwrap().len(),
+        1,
+        "an instrument already streamed does not restart the session"
+    );
+
+    let (_third, mut third_rx) = borsa
+        .share_quotes(
+            &[instrument(&MSFT, AssetKind::Equity)],
+            OverflowPolicy::Block,
+        )
+        .await
+        .expect("third consumer");
+    assert_eq!(next_symbol(&mut third_rx).await, "MSFT");
+    assert_eq!(next_symbol(&mut first_rx).await, "AAPL");
+    assert_eq!(next_symbol(&mut second_rx).await, "AAPL");
+
+    assert_eq!(
+        *starts.lock().unwrap(),
+        vec![strings(&["AAPL"]), strings(&["AAPL", "MSFT"])]
+    );
+}
+
+#[tokio::test]
+async fn instrument_leaves_upstream_with_its_last_consumer() {
+    let (borsa, starts, _go) = setup(1, true);
+    let aapl = instrument(&AAPL, AssetKind::Equity);
+    let msft = instrument(&MSFT, AssetKind::Equity);
+
+    let (aapl_only, _aapl_rx) = borsa
+        .share_quotes(std::slice::from_ref(&aapl), OverflowPolicy::Block)
+        .await
+        .expect("AAPL consumer");
+    let (both, _both_rx) = borsa
+        .share_quotes(&[aapl.clone(), msft.clone()], OverflowPolicy::Block)
+        .await
+        .expect("AAPL and MSFT consumer");
+    assert_eq!(both.instruments(), [aapl.clone(), msft]);
+
+    both.close().await;
+    wait_starts(&starts, 3).await;
+    assert_eq!(
+        starts.lock().unwrap().last(),
+        Some(&strings(&["AAPL"])),
+        "MSFT is removed, AAPL is still read by another consumer"
+    );
+
+    aapl_only.close().await;
+    let (_again, mut again_rx) = borsa
+        .share_quotes(std::slice::from_ref(&aapl), OverflowPolicy::Block)
+        .await
+        .expect("consumer after all were released");
+    assert_eq!(next_symbol(&mut again_rx).await, "AAPL");
+    assert_eq!(starts.lock().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn drop_newest_consumer_does_not_hold_back_others() {
+    let burst = 100;
+    let (borsa, _starts, go) = setup(burst, false);
+    let aapl = instrument(&AAPL, AssetKind::Equity);
+
+    let (slow, mut slow_rx) = borsa
+        .share_quotes(std::slice::from_ref(&aapl), OverflowPolicy::DropNewest)
+        .await
+        .expect("slow consumer");
+    let (_fast, mut fast_rx) = borsa
+        .share_quotes(std::slice::from_ref(&aapl), OverflowPolicy::Block)
+        .await
+        .expect("fast consumer");
+    go.send(true).unwrap();
+
+    for _ in 0..burst {
+        assert_eq!(next_symbol(&mut fast_rx).await, "AAPL");
+    }
+    let mut delivered = 0;
+    while let Ok(Some(_)) = tokio::time::timeout(Duration::from_millis(100), slow_rx.recv()).await {
+        delivered += 1;
+    }
+    assert!(slow.dropped() > 0, "the slow consumer overflowed");
+    assert_eq!(delivered + slow.dropped(), u64::try_from(burst).unwrap());
+}
+
+#[tokio::test]
+async fn stalled_block_consumer_does_not_hold_back_others() {
+    let burst = 100;
+    let (borsa, _starts, go) = setup(burst, false);
+    let aapl = instrument(&AAPL, AssetKind::Equity);
+
+    let (stalled, mut stalled_rx) = borsa
+        .share_quotes(std::slice::from_ref(&aapl), OverflowPolicy::Block)
+        .await
+        .expect("stalled consumer");
+    let (_live, mut live_rx) = borsa
+        .share_quotes(std::slice::from_ref(&aapl), OverflowPolicy::Block)
+        .await
+        .expect("live consumer");
+    go.send(true).unwrap();
+
+    // Far more updates than the stalled consumer's buffer holds reach the live one.
+    for _ in 0..burst {
+        assert_eq!(next_symbol(&mut live_rx).await, "AAPL");
+    }
+    // The stalled consumer still receives every update once it reads.
+    for _ in 0..burst {
+        assert_eq!(next_symbol(&mut stalled_rx).await, "AAPL");
+    }
+    assert_eq!(stalled.dropped(), 0);
+}
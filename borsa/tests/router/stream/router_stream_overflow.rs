@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use crate::helpers::{AAPL, BurstStreamer, MSFT, MockConnector, candle, instrument, update};
+use borsa::{Borsa, OverflowPolicy};
+use borsa_core::stream::StreamEvent;
+use borsa_core::{AssetKind, BorsaError, CandleUpdate, Interval, QuoteUpdate};
+
+fn key(u: &QuoteUpdate) -> (String, i64) {
+    let symbol = match u.instrument.id() {
+        borsa_core::IdentifierScheme::Security(sec) => sec.symbol.as_str().to_string(),
+        borsa_core::IdentifierScheme::Prediction(_) => unreachable!(),
+    };
+    (symbol, u.ts.timestamp())
+}
+
+fn borsa_with(updates: Vec<QuoteUpdate>, policy: OverflowPolicy) -> Borsa {
+    Borsa::builder()
+        .with_connector(std::sync::Arc::new(BurstStreamer { updates }))
+        .stream_channel_capacity(4)
+        .stream_overflow(policy)
+        .build()
+        .unwrap()
+}
+
+async fn next_events(
+    events: &mut tokio::sync::mpsc::Receiver<StreamEvent>,
+    n: usize,
+) -> Vec<(String, u64)> {
+    let mut out = Vec::with_capacity(n);
+    while out.len() < n {
+        let event = tokio::time::timeout(Duration::from_secs(2), events.recv())
+            .await
+            .expect("event before timeout")
+            .expect("events open");
+        // Lifecycle events share the channel.
+        if let StreamEvent::UpdatesDropped { symbol, total } = event {
+            out.push((symbol.as_str().to_string(), total));
+        }
+    }
+    out
+}
+
+async fn next_keys(
+    rx: &mut tokio::sync::mpsc::Receiver<QuoteUpdate>,
+    n: usize,
+) -> Vec<(String, i64)> {
+    let mut out = Vec::with_capacity(n);
+    for _ in 0..n {
+        let u = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .expect("update before timeout")
+            .expect("stream open");
+        out.push(key(&u));
+    }
+    out
+}
+
+fn owned(items: &[(&str, i64)]) -> Vec<(String, i64)> {
+    items
+        .iter()
+        .map(|(s, ts)| ((*s).to_string(), *ts))
+        .collect()
+}
+
+#[tokio::test]
+async fn drop_oldest_keeps_the_latest_updates() {
+    let borsa = borsa_with(
+        (1..=10).map(|ts| update(&AAPL, ts)).collect(),
+        OverflowPolicy::DropOldest,
+    );
+    let (mut handle, mut rx) = borsa
+        .stream_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    // The consumer reads nothing until the burst has been absorbed.
+    let dropped = next_events(&mut events, 5).await;
+    assert_eq!(
+        dropped,
+        (1..=5).map(|n| ("AAPL".to_string(), n)).collect::<Vec<_>>()
+    );
+
+    // The first update was already handed to the channel; 2..=6 made room for 7..=10.
+    assert_eq!(
+        next_keys(&mut rx, 5).await,
+        owned(&[
+            ("AAPL", 1),
+            ("AAPL", 7),
+            ("AAPL", 8),
+            ("AAPL", 9),
+            ("AAPL", 10)
+        ])
+    );
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn conflate_keeps_the_latest_update_per_symbol() {
+    let borsa = borsa_with(
+        vec![
+            update(&AAPL, 1),
+            update(&MSFT, 2),
+            update(&AAPL, 3),
+            update(&MSFT, 4),
+            update(&AAPL, 5),
+            update(&MSFT, 6),
+        ],
+        OverflowPolicy::Conflate,
+    );
+    let (mut handle, mut rx) = borsa
+        .stream_quotes(&[
+            instrument(&AAPL, AssetKind::Equity),
+            instrument(&MSFT, AssetKind::Equity),
+        ])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    assert_eq!(
+        next_events(&mut events, 3).await,
+        vec![
+            ("MSFT".to_string(), 1),
+            ("AAPL".to_string(), 1),
+            ("MSFT".to_string(), 2),
+        ]
+    );
+    assert_eq!(
+        next_keys(&mut rx, 3).await,
+        owned(&[("AAPL", 1), ("MSFT", 6), ("AAPL", 5)])
+    );
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn conflate_never_replaces_a_final_bar() {
+    let bar = |ts: i64, close: f64, is_final: bool| CandleUpdate {
+        instrument: instrument(&AAPL, AssetKind::Equity),
+        interval: Interval::I1m,
+        candle: candle(ts, close),
+        is_final,
+    };
+    let updates = vec![
+        bar(60, 1.0, false),
+        bar(60, 2.0, false),
+        bar(60, 3.0, true),
+        bar(120, 4.0, false),
+        bar(120, 5.0, false),
+    ];
+    let candles = MockConnector::builder()
+        .name("C")
+        .supports_kind(AssetKind::Equity)
+        .with_candle_stream_updates(updates.clone())
+        .build();
+    let borsa = Borsa::builder()
+        .with_connector(candles)
+        .stream_channel_capacity(4)
+        .stream_overflow(OverflowPolicy::Conflate)
+        .build()
+        .unwrap();
+    let (mut handle, mut rx) = borsa
+        .stream_candles(&[instrument(&AAPL, AssetKind::Equity)], Interval::I1m)
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    // The final bar replaces the pending update of its own bar; the next bar's first
+    // update queues behind it instead of replacing it.
+    assert_eq!(
+        next_events(&mut events, 2).await,
+        vec![("AAPL".to_string(), 1), ("AAPL".to_string(), 2)]
+    );
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        let u = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .expect("update before timeout")
+            .expect("stream open");
+        received.push(u);
+    }
+    assert_eq!(
+        received,
+        [updates[0].clone(), updates[2].clone(), updates[4].clone()]
+    );
+    handle.stop().await;
+}
+
+#[test]
+fn zero_channel_capacity_is_rejected() {
+    let err = Borsa::builder()
+        .with_connector(std::sync::Arc::new(BurstStreamer { updates: vec![] }))
+        .stream_channel_capacity(0)
+        .build()
+        .err()
+        .expect("zero capacity rejected");
+    assert!(matches!(err, BorsaError::InvalidArg(_)));
+}
@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+use crate::helpers::{AAPL, MSFT, instrument, usd};
+use async_trait::async_trait;
+use borsa::{BackoffConfig, Borsa, StalenessConfig, StalenessThresholds, TradingHours};
+use borsa_core::stream::StreamEvent;
+use borsa_core::{
+    AssetKind, BorsaConnector, BorsaError, Exchange, IdentifierScheme, Instrument, QuoteUpdate,
+    RoutingPolicyBuilder, Symbol,
+};
+use chrono::{NaiveTime, TimeZone, Weekday};
+
+/// Sends one update per instrument on start, then one every `every` if set, or nothing more.
+/// `muted` only gets the first update. The session stays open until stopped either way.
+struct TickingStreamer {
+    name: &'static str,
+    every: Option<Duration>,
+    muted: Option<Symbol>,
+    // increasing across sessions so restarted sessions pass the monotonic gate
+    ts: Arc<AtomicI64>,
+}
+
+#[async_trait]
+impl borsa_core::connector::StreamProvider for TickingStreamer {
+    async fn stream_quotes(
+        &self,
+        instruments: &[Instrument],
+    ) -> Result<
+        (
+            borsa_core::stream::StreamHandle,
+            tokio::sync::mpsc::Receiver<QuoteUpdate>,
+        ),
+        BorsaError,
+    > {
+        let (tx, rx) = tokio::sync::mpsc::channel::<QuoteUpdate>(16);
+        let (stop_tx, mut stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let instruments = instruments.to_vec();
+        let every = self.every;
+        let muted = self.muted.clone();
+        let ts = Arc::clone(&self.ts);
+        let join = tokio::spawn(async move {
+            let mut first = true;
+            loop {
+                for inst in &instruments {
+                    let is_muted = match inst.id() {
+                        IdentifierScheme::Security(sec) => muted.as_ref() == Some(&sec.symbol),
+                        IdentifierScheme::Prediction(_) => false,
+                    };
+                    if !first && is_muted {
+                        continue;
+                    }
+                    let u = QuoteUpdate {
+                        instrument: inst.clone(),
+                        price: Some(usd("1.0")),
+                        previous_close: None,
+                        ts: chrono::Utc
+                            .timestamp_opt(ts.fetch_add(1, Ordering::Relaxed) + 1, 0)
+                            .unwrap(),
+                        volume: None,
+                    };
+                    if tx.send(u).await.is_err() {
+                        return;
+                    }
+                }
+                first = false;
+                let Some(every) = every else { break };
+                tokio::select! {
+                    _ = &mut stop_rx => return,
+                    () = tokio::time::sleep(every) => {}
+                }
+            }
+            let _ = stop_rx.await;
+        });
+        Ok((borsa_core::stream::StreamHandle::new(join, stop_tx), rx))
+    }
+}
+
+#[async_trait]
+impl BorsaConnector for TickingStreamer {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+    fn as_stream_provider(&self) -> Option<&dyn borsa_core::connector::StreamProvider> {
+        Some(self)
+    }
+}
+
+fn streamer(name: &'static str, every: Option<Duration>) -> Arc<TickingStreamer> {
+    Arc::new(TickingStreamer {
+        name,
+        every,
+        muted: None,
+        ts: Arc::new(AtomicI64::new(0)),
+    })
+}
+
+fn thresholds(hours: Option<TradingHours>) -> StalenessThresholds {
+    StalenessThresholds {
+        symbol: Some(Duration::from_millis(100)),
+        session: None,
+        hours,
+    }
+}
+
+/// `silent` goes quiet after its first update; `ticking` keeps sending.
+fn borsa_with(hold: Duration, hours: Option<TradingHours>) -> Borsa {
+    borsa_with_staleness(
+        streamer("silent", None),
+        StalenessConfig {
+            kinds: HashMap::from([(AssetKind::Equity, thresholds(hours))]),
+            hold,
+            ..StalenessConfig::default()
+        },
+    )
+}
+
+/// `first` is preferred over `ticking`, which keeps sending.
+fn borsa_with_staleness(first: Arc<TickingStreamer>, staleness: StalenessConfig) -> Borsa {
+    let ticking = streamer("ticking", Some(Duration::from_millis(10)));
+    let policy = RoutingPolicyBuilder::new()
+        .providers_for_kind(AssetKind::Equity, &[first.key(), ticking.key()])
+        .build();
+    Borsa::builder()
+        .with_connector(first)
+        .with_connector(ticking)
+        .routing_policy(policy)
+        .backoff(BackoffConfig {
+            min_backoff_ms: 40,
+            max_backoff_ms: 80,
+            factor: 1,
+            jitter_percent: 0,
+        })
+        .stream_staleness(staleness)
+        .build()
+        .unwrap()
+}
+
+/// Collect routing events until `n` were seen or `wait` passed.
+async fn routing_events(
+    events: &mut tokio::sync::mpsc::Receiver<StreamEvent>,
+    n: usize,
+    wait: Duration,
+) -> Vec<StreamEvent> {
+    let mut out = Vec::new();
+    let deadline = tokio::time::Instant::now() + wait;
+    while out.len() < n {
+        let Ok(event) = tokio::time::timeout_at(deadline, events.recv()).await else {
+            break;
+        };
+        let event = event.expect("events open");
+        if matches!(
+            event,
+            StreamEvent::ProviderStarted { .. }
+                | StreamEvent::Stale { .. }
+                | StreamEvent::Failover { .. }
+        ) {
+            out.push(event);
+        }
+    }
+    out
+}
+
+fn started(provider: &'static str) -> StreamEvent {
+    StreamEvent::ProviderStarted {
+        provider,
+        symbols: vec![AAPL.clone()],
+    }
+}
+
+fn failover(from: &'static str, to: &'static str) -> StreamEvent {
+    StreamEvent::Failover {
+        from,
+        to,
+        symbols: vec![AAPL.clone()],
+    }
+}
+
+#[tokio::test]
+async fn silent_session_fails_over_and_is_held_back() {
+    let borsa = borsa_with(Duration::from_secs(60), None);
+    let (mut handle, mut rx) = borsa
+        .stream_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    // Several backoff ticks pass after the failover without the held provider returning.
+    assert_eq!(
+        routing_events(&mut events, 5, Duration::from_millis(600)).await,
+        vec![
+            started("silent"),
+            StreamEvent::Stale {
+                provider: "silent",
+                symbols: vec![AAPL.clone()],
+            },
+            started("ticking"),
+            failover("silent", "ticking"),
+        ]
+    );
+    let mut received = 0;
+    while received < 5 {
+        tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .expect("update before timeout")
+            .expect("stream open");
+        received += 1;
+    }
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn provider_takes_symbols_back_after_hold() {
+    let borsa = borsa_with(Duration::from_millis(150), None);
+    let (mut handle, _rx) = borsa
+        .stream_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    assert_eq!(
+        routing_events(&mut events, 6, Duration::from_secs(2)).await,
+        vec![
+            started("silent"),
+            StreamEvent::Stale {
+                provider: "silent",
+                symbols: vec![AAPL.clone()],
+            },
+            started("ticking"),
+            failover("silent", "ticking"),
+            started("silent"),
+            failover("ticking", "silent"),
+        ]
+    );
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn closed_hours_do_not_count_as_silence() {
+    let never_open = TradingHours {
+        weekdays: Vec::new(),
+        ..TradingHours::monday_to_friday(
+            chrono_tz::America::New_York,
+            NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+            NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+        )
+    };
+    let borsa = borsa_with(Duration::from_secs(60), Some(never_open));
+    let (mut handle, _rx) = borsa
+        .stream_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    assert_eq!(
+        routing_events(&mut events, 2, Duration::from_millis(400)).await,
+        vec![started("silent")]
+    );
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn exchange_hours_override_kind_hours() {
+    let never_open = TradingHours {
+        weekdays: Vec::new(),
+        ..TradingHours::monday_to_friday(
+            chrono_tz::America::New_York,
+            NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+            NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+        )
+    };
+    let always_open = TradingHours {
+        timezone: chrono_tz::UTC,
+        open: NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+        close: NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+        weekdays: vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ],
+    };
+    let nasdaq = Exchange::try_from_str("NASDAQ").unwrap();
+    let borsa = borsa_with_staleness(
+        streamer("silent", None),
+        StalenessConfig {
+            kinds: HashMap::from([(AssetKind::Equity, thresholds(Some(never_open)))]),
+            exchange_hours: HashMap::from([(nasdaq.clone(), always_open)]),
+            ..StalenessConfig::default()
+        },
+    );
+    let inst = Instrument::from_symbol_and_exchange("AAPL", nasdaq, AssetKind::Equity).unwrap();
+    let (mut handle, _rx) = borsa.stream_quotes(&[inst]).await.expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    // The kind's hours never open, so only the exchange's hours can count the silence.
+    assert_eq!(
+        routing_events(&mut events, 2, Duration::from_secs(1)).await,
+        vec![
+            started("silent"),
+            StreamEvent::Stale {
+                provider: "silent",
+                symbols: vec![AAPL.clone()],
+            },
+        ]
+    );
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn only_stale_symbols_leave_the_provider() {
+    let flaky = Arc::new(TickingStreamer {
+        name: "flaky",
+        every: Some(Duration::from_millis(10)),
+        muted: Some(MSFT.clone()),
+        ts: Arc::new(AtomicI64::new(0)),
+    });
+    let borsa = borsa_with_staleness(
+        flaky,
+        StalenessConfig {
+            kinds: HashMap::from([(AssetKind::Equity, thresholds(None))]),
+            ..StalenessConfig::default()
+        },
+    );
+    let (mut handle, _rx) = borsa
+        .stream_quotes(&[
+            instrument(&AAPL, AssetKind::Equity),
+            instrument(&MSFT, AssetKind::Equity),
+        ])
+        .await
+        .expect("stream started");
+    let mut events = handle.take_events().expect("events receiver");
+
+    let seen = routing_events(&mut events, 6, Duration::from_millis(600)).await;
+    let started_with = |provider: &str| -> Vec<Vec<Symbol>> {
+        seen.iter()
+            .filter_map(|e| match e {
+                StreamEvent::ProviderStarted {
+                    provider: p,
+                    symbols,
+                } if *p == provider => {
+                    let mut symbols = symbols.clone();
+                    symbols.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+                    Some(symbols)
+                }
+                _ => None,
+            })
+            .collect()
+    };
+    assert!(seen.contains(&StreamEvent::Stale {
+        provider: "flaky",
+        symbols: vec![MSFT.clone()],
+    }));
+    assert_eq!(
+        started_with("flaky"),
+        vec![vec![AAPL.clone(), MSFT.clone()], vec![AAPL.clone()]]
+    );
+    assert_eq!(started_with("ticking"), vec![vec![MSFT.clone()]]);
+    handle.stop().await;
+}
+
+#[test]
+fn zero_staleness_threshold_is_rejected() {
+    let err = Borsa::builder()
+        .with_connector(streamer("silent", None))
+        .stream_staleness(StalenessConfig {
+            kinds: HashMap::from([(
+                AssetKind::Equity,
+                StalenessThresholds {
+                    session: Some(Duration::ZERO),
+                    ..StalenessThresholds::default()
+                },
+            )]),
+            ..StalenessConfig::default()
+        })
+        .build()
+        .err()
+        .expect("zero threshold rejected");
+    assert!(matches!(err, BorsaError::InvalidArg(_)));
+}
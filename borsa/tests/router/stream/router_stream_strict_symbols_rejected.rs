@@ -19,6 +19,7 @@ async fn stream_quotes_strict_symbols_rejected_fails_fast() {
                 symbol: Some(AAPL.clone()),
                 kind: Some(AssetKind::Equity),
                 exchange: None,
+                capability: None,
             },
             &[],
             true,
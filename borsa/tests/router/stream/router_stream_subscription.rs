@@ -0,0 +1,213 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::helpers::{AAPL, MSFT, TSLA, instrument, usd};
+use async_trait::async_trait;
+use borsa::Borsa;
+use borsa_core::{
+    AssetKind, BorsaConnector, BorsaError, Instrument, QuoteUpdate, RoutingPolicyBuilder,
+};
+use chrono::TimeZone;
+
+/// Emits one update per subscribed instrument, then stays open until stopped.
+struct RecordingStreamer {
+    starts: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+#[async_trait]
+impl borsa_core::connector::StreamProvider for RecordingStreamer {
+    async fn stream_quotes(
+        &self,
+        instruments: &[Instrument],
+    ) -> Result<
+        (
+            borsa_core::stream::StreamHandle,
+            tokio::sync::mpsc::Receiver<QuoteUpdate>,
+        ),
+        BorsaError,
+    > {
+        self.starts
+            .lock()
+            .unwrap()
+            .push(instruments.iter().map(symbol_of).collect());
+
+        let (tx, rx) = tokio::sync::mpsc::channel::<QuoteUpdate>(16);
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let updates: Vec<QuoteUpdate> = instruments
+            .iter()
+            .enumerate()
+            .map(|(i, inst)| QuoteUpdate {
+                instrument: inst.clone(),
+                price: Some(usd("1.0")),
+                previous_close: None,
+                ts: chrono::Utc
+                    .timestamp_opt(i64::try_from(i).unwrap() + 1, 0)
+                    .unwrap(),
+                volume: None,
+            })
+            .collect();
+        let join = tokio::spawn(async move {
+            for u in updates {
+                if tx.send(u).await.is_err() {
+                    return;
+                }
+            }
+            let _ = stop_rx.await;
+        });
+        Ok((borsa_core::stream::StreamHandle::new(join, stop_tx), rx))
+    }
+}
+
+#[async_trait]
+impl BorsaConnector for RecordingStreamer {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+    fn supports_kind(&self, _kind: AssetKind) -> bool {
+        true
+    }
+    fn as_stream_provider(&self) -> Option<&dyn borsa_core::connector::StreamProvider> {
+        Some(self)
+    }
+}
+
+fn symbol_of(inst: &Instrument) -> String {
+    match inst.id() {
+        borsa_core::IdentifierScheme::Security(sec) => sec.symbol.as_str().to_string(),
+        borsa_core::IdentifierScheme::Prediction(_) => "<non-security>".to_string(),
+    }
+}
+
+fn setup() -> (Borsa, Arc<Mutex<Vec<Vec<String>>>>) {
+    let starts = Arc::new(Mutex::new(Vec::new()));
+    let conn = Arc::new(RecordingStreamer {
+        starts: Arc::clone(&starts),
+    });
+    let borsa = Borsa::builder().with_connector(conn).build().unwrap();
+    (borsa, starts)
+}
+
+async fn next_symbol(rx: &mut tokio::sync::mpsc::Receiver<QuoteUpdate>) -> String {
+    let u = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("update before timeout")
+        .expect("channel open");
+    symbol_of(&u.instrument)
+}
+
+#[tokio::test]
+async fn subscription_add_restarts_session_with_combined_set() {
+    let (borsa, starts) = setup();
+
+    let (sub, mut rx) = borsa
+        .subscribe_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("subscription started");
+    assert_eq!(next_symbol(&mut rx).await, "AAPL");
+
+    sub.add(&[instrument(&MSFT, AssetKind::Equity)])
+        .await
+        .expect("add succeeds");
+
+    let mut seen = Vec::new();
+    while !seen.contains(&"MSFT".to_string()) {
+        seen.push(next_symbol(&mut rx).await);
+    }
+
+    let starts = starts.lock().unwrap().clone();
+    assert_eq!(
+        starts,
+        vec![
+            vec!["AAPL".to_string()],
+            vec!["AAPL".to_string(), "MSFT".to_string()],
+        ],
+        "the running session is replaced by one covering both symbols"
+    );
+}
+
+#[tokio::test]
+async fn subscription_remove_all_keeps_channel_open() {
+    let (borsa, starts) = setup();
+
+    let (sub, mut rx) = borsa
+        .subscribe_quotes(&[
+            instrument(&AAPL, AssetKind::Equity),
+            instrument(&MSFT, AssetKind::Equity),
+        ])
+        .await
+        .expect("subscription started");
+    let _ = next_symbol(&mut rx).await;
+    let _ = next_symbol(&mut rx).await;
+
+    sub.remove(&[instrument(&MSFT, AssetKind::Equity)])
+        .await
+        .expect("remove succeeds");
+    sub.remove(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("removing the last symbol is allowed");
+
+    sub.add(&[instrument(&TSLA, AssetKind::Equity)])
+        .await
+        .expect("add after emptying succeeds");
+
+    let mut seen = Vec::new();
+    while !seen.contains(&"TSLA".to_string()) {
+        seen.push(next_symbol(&mut rx).await);
+    }
+    assert!(!seen.contains(&"MSFT".to_string()));
+
+    let starts = starts.lock().unwrap().clone();
+    assert_eq!(starts.last().unwrap(), &vec!["TSLA".to_string()]);
+    assert!(
+        starts.contains(&vec!["AAPL".to_string()]),
+        "removing MSFT restarts the session with AAPL only"
+    );
+}
+
+#[tokio::test]
+async fn subscription_add_rejected_by_strict_policy_keeps_previous_set() {
+    let starts = Arc::new(Mutex::new(Vec::new()));
+    let conn = Arc::new(RecordingStreamer {
+        starts: Arc::clone(&starts),
+    });
+    let policy = RoutingPolicyBuilder::new()
+        .providers_rule(
+            borsa_core::Selector {
+                symbol: Some(MSFT.clone()),
+                kind: Some(AssetKind::Equity),
+                exchange: None,
+                capability: None,
+            },
+            &[],
+            true,
+        )
+        .build();
+    let borsa = Borsa::builder()
+        .with_connector(conn)
+        .routing_policy(policy)
+        .build()
+        .unwrap();
+
+    let (sub, mut rx) = borsa
+        .subscribe_quotes(&[instrument(&AAPL, AssetKind::Equity)])
+        .await
+        .expect("subscription started");
+    assert_eq!(next_symbol(&mut rx).await, "AAPL");
+
+    let err = sub
+        .add(&[instrument(&MSFT, AssetKind::Equity)])
+        .await
+        .expect_err("strict rule rejects MSFT");
+    match err {
+        BorsaError::StrictSymbolsRejected { rejected } => {
+            assert_eq!(rejected, vec![MSFT.clone()]);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    assert_eq!(
+        starts.lock().unwrap().len(),
+        1,
+        "a rejected add must not restart sessions"
+    );
+}